@@ -3,6 +3,7 @@
 // This is an uber-shader; required control flow is set through uniforms
 
 in vec3 renderColour;
+in float renderAlpha;
 in flat uint sceneLightFragment;
 in flat uint sideViewFragment;
 in flat uint sideViewBorderFragment;
@@ -15,6 +16,10 @@ in flat uint drawingSideViewFragment;
 in flat uint sunFragment;
 in flat uint sunArrowFragment;
 in vec3 fragPos;
+in flat uint logDepthFragment;
+in float logDepthW;
+in flat float depthOffset;
+in flat uint groundPlaneFragment;
 
 out vec4 FragColour;
 
@@ -24,6 +29,23 @@ uniform vec3 borderColour;
 uniform vec3 cameraPos;
 uniform vec3 sunDirection;
 uniform vec3 sunLightColour;
+uniform uint shadowsEnabled;
+uniform uint flatShading;
+uniform float logDepthFarPlane;
+uniform float nearFadeStart;
+uniform float nearFadeEnd;
+uniform uint dither;
+uniform uint anaglyph;
+
+// Thresholds of a 4x4 ordered dither; neighbouring pixels are offset by as different amounts as possible
+const float bayerMatrix[16] = float[16](0.0, 8.0, 2.0, 10.0, 12.0, 4.0, 14.0, 6.0, 3.0, 11.0, 1.0, 9.0, 15.0, 7.0, 13.0, 5.0);
+
+// The offset to add to the colour of the current pixel, in steps of 8-bit colour, between -0.5 and 0.5
+float ditherOffset()
+{
+    ivec2 matrixCoords = ivec2(gl_FragCoord.xy) % 4;
+    return (bayerMatrix[matrixCoords.y * 4 + matrixCoords.x] + 0.5) / 16.0 - 0.5;
+}
 
 float pointInShadow()
 {
@@ -32,8 +54,27 @@ float pointInShadow()
     return (projectionCoords.z - 0.01) < lightViewDepth ? 1.0 : 0.50;
 }
 
+// Check if the current fragment is within the area covered by the shadow map
+bool inShadowMap()
+{
+    vec3 projectionCoords = (lightSpaceVertex.xyz / lightSpaceVertex.w) * 0.5 + 0.5;
+    return all(greaterThanEqual(projectionCoords, vec3(0.0))) && all(lessThanEqual(projectionCoords, vec3(1.0)));
+}
+
 void main()
 {
+    // Spreads the depth precision evenly over distance, rather than concentrating it close to the
+    // camera, so that points far away from the camera do not fight over the same depth
+    if(logDepthFragment == 1)
+    {
+        gl_FragDepth = log2(logDepthW) / log2(logDepthFarPlane + 1.0);
+    }
+    else
+    {
+        gl_FragDepth = gl_FragCoord.z;
+    }
+    gl_FragDepth += depthOffset;
+
     if(sunArrowFragment == 1)
     {
         FragColour = vec4(0.5, 0.5, 0.0, 1.0);
@@ -76,12 +117,45 @@ void main()
         float maxDotSpecular = pow(max(dot(cameraFragVector, halfwayVector), 0.0), 32);
         vec3 specularColour = maxDotSpecular * sunLightColour * renderColour;
 
-        vec3 finalColour = ambientColour + diffuseColour + specularColour;
+        // Flat shading shows the colour of points as given, as the side views do
+        vec3 finalColour = flatShading == 1 ? renderColour : ambientColour + diffuseColour + specularColour;
+
+        // Points close to the camera fade out so that they do not fill the view when the camera is
+        // inside the point cloud. Without fade distances every point is fully visible
+        float nearFade = clamp((distance(cameraPos, fragPos) - nearFadeStart) / max(nearFadeEnd - nearFadeStart, 0.0001), 0.0, 1.0);
+        if(nearFade == 0.0)
+        {
+            discard;
+        }
 
-        FragColour = vec4(finalColour * pointInShadow(), 1.0);
+        // Without shadows the shadow map is not created, so it cannot be sampled
+        float shadowFactor = shadowsEnabled == 1 && flatShading == 0 ? pointInShadow() : 1.0;
+        FragColour = vec4(finalColour * shadowFactor, renderAlpha * nearFade);
     }
     else if(drawingSideViewFragment == 1)
     {
         FragColour = vec4(renderColour, 1.0);
     }
+    else if(groundPlaneFragment == 1)
+    {
+        // Only darkened by the shadows of the points, so that the rest of the ground is an even colour.
+        // The ground can extend past the area the sun casts shadows in
+        float shadowFactor = shadowsEnabled == 1 && inShadowMap() ? pointInShadow() : 1.0;
+        FragColour = vec4(vec3(0.5, 0.5, 0.5) * shadowFactor, 0.35);
+    }
+
+    // Each eye sees only some of the colour channels, so colours are shown by their brightness instead.
+    // Otherwise points without any red, such as the default green points, are not seen by the left eye.
+    // The side views are not seen in 3D, so they keep their colours
+    if(anaglyph == 1 && sideViewFragment == 0 && sideViewBorderFragment == 0 && drawingSideViewFragment == 0)
+    {
+        FragColour.rgb = vec3(dot(FragColour.rgb, vec3(0.299, 0.587, 0.114)));
+    }
+
+    // Breaks up the steps between the 256 levels of each colour channel, which show as bands across
+    // smooth gradients. Fragments of the shadow map only have a depth
+    if(dither == 1 && sceneLightFragment == 0)
+    {
+        FragColour.rgb += vec3(ditherOffset() / 255.0);
+    }
 }
\ No newline at end of file
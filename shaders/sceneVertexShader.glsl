@@ -5,6 +5,9 @@ layout (location = 1) in vec2 texCoords;
 layout (location = 2) in vec3 vertexNormal;
 layout (location = 3) in vec3 pointColour;
 layout (location = 4) in vec3 translation;
+layout (location = 5) in float pointAlpha;
+layout (location = 6) in mat3 pointShape; // Takes up locations 6 to 8
+layout (location = 9) in float pointScale;
 
 // This is an uber-shader; required control flow is set through uniforms
 
@@ -18,10 +21,24 @@ uniform uint drawingGrid;
 uniform uint drawingFromSideView;
 uniform uint drawingSun;
 uniform uint drawingSunArrow;
+uniform uint logDepth;
+uniform uint pointStride;
+uniform uint groundRemoval;
+uniform uint drawingEdges;
+uniform uint drawingSelectionRect;
+uniform uint drawingGroundPlane;
 
 uniform vec3 sunPosition;
 uniform vec3 sunArrowPosition;
 uniform float sunArrowScale;
+uniform float gridDepthOffset;
+uniform float groundThreshold;
+// Height points are lifted by so that points resting on the y = 0 plane are drawn above the grid
+uniform float pointLift;
+uniform vec3 groundPlaneCentre;
+uniform float groundPlaneHalfSize;
+uniform vec3 edgeColour;
+uniform vec3 selectionColour;
 uniform mat4 projViewMatrix;
 uniform mat4 rotationMatrix;
 uniform mat4 lightPerspectiveMatrix;
@@ -31,6 +48,7 @@ out flat uint sunArrowFragment;
 out flat uint sideViewFragment;
 out vec2 textureCoords;
 out vec3 renderColour;
+out float renderAlpha;
 out flat uint sideViewBorderFragment;
 out flat uint sceneLightFragment;
 out vec4 lightSpaceVertex;
@@ -39,10 +57,25 @@ out vec3 fragPos;
 out flat uint gridFragment;
 out flat uint sceneFragment;
 out flat uint drawingSideViewFragment;
+out flat uint logDepthFragment;
+out float logDepthW;
+out flat float depthOffset;
+out flat uint groundPlaneFragment;
+// Only read back through transform feedback, to check the position points are placed at in each pass
+out vec3 capturedPosition;
+
+// The position of the given vertex of a point in the world, before the scene rotation is applied
+vec4 pointWorldPosition(vec3 modelVertex, float lift)
+{
+    vec4 worldSpaceVertex = vec4(modelVertex + translation + cloudTranslation + vec3(0.0, lift, 0.0), 1.0);
+    worldSpaceVertex.y *= reflectVertically;
+    return worldSpaceVertex;
+}
 
 void main()
 {
     renderColour = pointColour;
+    renderAlpha = pointAlpha;
     sideViewFragment = renderSideViews;
     textureCoords = texCoords;
     sideViewBorderFragment = renderSideViewBorder;
@@ -55,6 +88,9 @@ void main()
     drawingSideViewFragment = drawingFromSideView;
     sunFragment = drawingSun;
     sunArrowFragment = drawingSunArrow;
+    depthOffset = 0.0;
+    groundPlaneFragment = drawingGroundPlane;
+    capturedPosition = vec3(0.0);
 
     if(drawingSunArrow == 1)
     {
@@ -67,29 +103,75 @@ void main()
     else if(drawingGrid == 1)
     {
         gl_Position = projViewMatrix * vec4(vertex + translation, 1.0);
+
+        // Pushes the grid lines slightly behind points resting on the grid, so that the two do not
+        // flicker in front of each other
+        depthOffset = gridDepthOffset;
+    }
+    else if(drawingEdges == 1)
+    {
+        // Each end of an edge is at the centre of a point; coloured the same way as the grid
+        gl_Position = projViewMatrix * pointWorldPosition(vec3(0.0), pointLift);
+        renderColour = edgeColour;
+        gridFragment = 1u;
+    }
+    else if(drawingSelectionRect == 1)
+    {
+        // The corners of the selection rectangle are already in Normalized Device Coordinates
+        gl_Position = vec4(vertex.xy, 0.0, 1.0);
+        renderColour = selectionColour;
+        gridFragment = 1u;
+    }
+    else if(drawingGroundPlane == 1)
+    {
+        vec4 worldSpaceVertex = vec4(vertex * vec3(groundPlaneHalfSize, 1.0, groundPlaneHalfSize) + groundPlaneCentre, 1.0);
+        fragPos = worldSpaceVertex.xyz;
+        gl_Position = projViewMatrix * worldSpaceVertex;
+
+        // Points are drawn in the scene higher than they are in the shadow map, so the ground is moved
+        // by the same amount to find the shadows of the points as they are seen
+        lightSpaceVertex = lightPerspectiveMatrix * (worldSpaceVertex - vec4(0.0, pointLift * reflectVertically, 0.0, 0.0));
     }
     else if(renderSideViews == 1 || renderSideViews == 2 || renderSideViewBorder == 1)
     {
         gl_Position = rotationMatrix * vec4(vertex, 1.0);
     }
+    else if(((pointStride > 1u && uint(gl_InstanceID) % pointStride != 0u) || (groundRemoval == 1u && translation.y < groundThreshold))
+            && (drawingSceneLightPerspective == 1 || drawingScene == 1 || drawingFromSideView == 1))
+    {
+        // Points skipped to keep the frame rate up, and points of the ground when it is removed, are
+        // placed outside of the view volume, so that nothing is drawn for them
+        gl_Position = vec4(2.0, 2.0, 2.0, 1.0);
+    }
     else if(drawingSceneLightPerspective == 1)
     {
-        vec4 worldSpaceVertex = vec4(vertex + translation + cloudTranslation, 1.0);
-        worldSpaceVertex.y *= reflectVertically;
-        gl_Position = projViewMatrix * worldSpaceVertex;
+        gl_Position = projViewMatrix * pointWorldPosition(vertex, 0.0);
+        capturedPosition = pointWorldPosition(vec3(0.0), 0.0).xyz;
     }
     else if(drawingScene == 1)
     {
-        vec4 worldSpaceVertex =  vec4(0.05 * vertex + translation + cloudTranslation + vec3(0.0, 0.995, 0.0), 1.0 );
-        worldSpaceVertex.y *= reflectVertically;
+        // A stretched point needs its normals stretched the opposite way to stay perpendicular to its faces
+        normalizedVertexNormal = normalize(transpose(inverse(pointShape)) * vertexNormal);
+        normalizedVertexNormal.y *= reflectVertically;
+        normalizedVertexNormal = vec3(rotationMatrix * vec4(normalizedVertexNormal, 0.0));
+
+        // The scale is uniform, so it does not change the direction of the normals
+        vec3 modelVertex = pointShape * vertex * pointScale;
+        vec4 worldSpaceVertex = pointWorldPosition(modelVertex, pointLift);
         fragPos = worldSpaceVertex.xyz;
         gl_Position = projViewMatrix * worldSpaceVertex;
-        lightSpaceVertex = lightPerspectiveMatrix * vec4(0.05 * vertex + translation + vec3(0.0, 0.995, 0.0), 1.0);
+        lightSpaceVertex = lightPerspectiveMatrix * vec4(modelVertex + translation + vec3(0.0, pointLift, 0.0), 1.0);
+        capturedPosition = pointWorldPosition(vec3(0.0), pointLift).xyz;
     }
     else if(drawingFromSideView == 1)
     {
-        vec4 worldSpaceVertex = vec4(0.05 * vertex + translation + cloudTranslation + vec3(0.0, 0.995, 0.0), 1.0 );
-        worldSpaceVertex.y *= reflectVertically;
-        gl_Position = projViewMatrix * worldSpaceVertex;
+        gl_Position = projViewMatrix * pointWorldPosition(pointShape * vertex * pointScale, pointLift);
+        capturedPosition = pointWorldPosition(vec3(0.0), pointLift).xyz;
     }
+
+    // The shadow map and the view panels keep the regular depth; the shadow test compares against
+    // the regular depth of the shadow map, and the panels are flat
+    bool regularDepthOnly = drawingSceneLightPerspective == 1 || renderSideViews == 1 || renderSideViews == 2 || renderSideViewBorder == 1;
+    logDepthFragment = (logDepth == 1 && !regularDepthOnly) ? 1u : 0u;
+    logDepthW = 1.0 + gl_Position.w;
 }
\ No newline at end of file
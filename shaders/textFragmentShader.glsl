@@ -1,15 +1,24 @@
 #version 430 core
 
 in vec2 textureCoords;
+in vec3 textColour;
 
 out vec4 FragColour;
 
 layout (binding = 0) uniform sampler2D textBitmap;
 
+uniform uint drawingBackground;
+
 void main()
 {
+    if(drawingBackground == 1)
+    {
+        FragColour = vec4(0.0, 0.0, 0.0, 0.75);
+        return;
+    }
+
    // FragColour = vec4(1.0, 0.0, 0.0, 1.0);
-    FragColour = vec4(1.0, 1.0, 1.0, texture(textBitmap, textureCoords).r);
+    FragColour = vec4(textColour, texture(textBitmap, textureCoords).r);
 //    FragColour = vec4(texture(textBitmap, textureCoords).rgb, 1.0);
 //FragColour = texture(textBitmap, textureCoords);
 }
\ No newline at end of file
@@ -1,21 +1,34 @@
 #version 430 core
 
-layout (location = 0) in vec2 aVertex;
-layout (location = 1) in vec2 texCoords;
+// Each character is an instance; the vertex ID selects the corner of the character being processed
+layout (location = 0) in mat4x2 characterCorners; // Takes up locations 0 to 3
+layout (location = 4) in mat4x2 characterTexCoords; // Takes up locations 4 to 7
+layout (location = 8) in vec2 translation;
+layout (location = 9) in vec3 characterColour;
 
 uniform float textScaleX;
 uniform float textScaleY;
 
 uniform mat4 projectionViewMatrix;
-uniform vec2 translation;
+uniform uint drawingBackground;
 
 out vec2 textureCoords;
+out vec3 textColour;
 
 void main()
 {
-    textureCoords = texCoords;
+    textureCoords = characterTexCoords[gl_VertexID];
+    textColour = characterColour;
 
-    vec4 positionVertex = vec4(aVertex, 0.0, 1.0);
+    if(drawingBackground == 1)
+    {
+        // Triangle large enough to cover the entire viewport
+        vec2 corner = vec2((gl_VertexID << 1) & 2, gl_VertexID & 2);
+        gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+        return;
+    }
+
+    vec4 positionVertex = vec4(characterCorners[gl_VertexID], 0.0, 1.0);
     positionVertex.x *= 0.25 * textScaleX;
     positionVertex.y *= 0.25 * textScaleY;
     positionVertex.xy += translation;
@@ -1,14 +1,100 @@
 use std::process::exit;
 use clap::App;
 use clap::{ArgMatches, load_yaml};
+use glfw::Key;
+use nalgebra_glm::{TVec3, vec3};
+use crate::geometry::grid::GridColours;
+use crate::helper_logic::point_cloud_analyzer::STDIN_FILE;
+use crate::ipc_logic::ipc_content_logic::{ClusterBackend, ClusterOutputFormat, PointLimit, PointLimitPolicy};
+use crate::ipc_logic::ipc_receiver::{ParseMode, ParseSettings, PointColumns, UpAxis};
+use crate::rendering::cluster_colour::ClusterPalette;
+use crate::rendering::scene_renderer::VelocityColourMode;
+
+/// The keys that can be chosen to quit the program with. Keys used for other input are left out so
+/// that quitting cannot be mixed up with them
+const EXIT_KEYS: &[(&str, Key)] = &
+[
+    ("escape", Key::Escape), ("pause", Key::Pause), ("scroll_lock", Key::ScrollLock), ("print_screen", Key::PrintScreen),
+    ("menu", Key::Menu), ("kp_enter", Key::KpEnter), ("f13", Key::F13), ("f14", Key::F14), ("f15", Key::F15),
+    ("f16", Key::F16), ("f17", Key::F17), ("f18", Key::F18), ("f19", Key::F19), ("f20", Key::F20), ("f21", Key::F21),
+    ("f22", Key::F22), ("f23", Key::F23), ("f24", Key::F24), ("f25", Key::F25),
+];
 
 /// Holds the result of processing the arguments to the program
 pub struct Args
 {
-    pub initial_data_model: Option<String>,
+    /// The files whose points are concatenated into the initial point cloud
+    pub initial_data_models: Vec<String>,
     pub ipc_files: Vec<IPCFiles>,
     pub display_lidar_pos: bool,
-    pub sleep_duration_ms: u64
+    pub sleep_duration_ms: u64,
+    pub cluster_output_format: ClusterOutputFormat,
+    pub cluster_backend: ClusterBackend,
+    pub buffer_timeout_ns: u64,
+    pub min_epsilon: f32,
+    pub max_epsilon: f32,
+    pub cluster_status_timeout_s: u64,
+    pub units_scale: f32,
+    pub dual_view: bool,
+    pub sphere_culling: bool,
+    pub ipc_handshake: IPCHandshake,
+    pub no_shadows: bool,
+    pub grid_colours: GridColours,
+    pub parse_mode: ParseMode,
+    pub export_file: Option<String>,
+    pub playback_dir: Option<String>,
+    pub playback_frames_per_second: f32,
+    pub alpha_file: Option<String>,
+    pub alpha_to_coverage: bool,
+    pub demo: bool,
+    pub auto_rotate_degrees_per_second: f32,
+    pub overlay_layout_file: Option<String>,
+    pub session_file: String,
+    pub smooth_camera: bool,
+    pub view_export_prefix: String,
+    pub invert_y: bool,
+    pub log_depth: bool,
+    pub covariance_file: Option<String>,
+    pub cluster_displayed: bool,
+    pub cluster_palette: ClusterPalette,
+    pub transparent_background: bool,
+    pub velocity_colours: Option<VelocityColourMode>,
+    pub near_fade: (f32, f32),
+    pub adaptive_quality: bool,
+    pub edges_file: Option<String>,
+    pub cursor_smoothing: usize,
+    pub grid_height: f32,
+    pub json_control: bool,
+    pub shadow_offset: (f32, f32),
+    pub smoothing_factor: f32,
+    pub smoothing_distance: f32,
+    pub dither: bool,
+    pub window_title: String,
+    pub window_icon: Option<String>,
+    pub profile: bool,
+    pub camera_path_file: Option<String>,
+    pub camera_path_record_prefix: Option<String>,
+    pub camera_path_fps: f32,
+    pub compat_buffers: bool,
+    pub grid_depth_offset: f32,
+    pub cluster_export_file: Option<String>,
+    pub sun_elevation: f32,
+    pub sun_arc_degrees_per_second: f32,
+    pub max_points: Option<usize>,
+    pub max_points_policy: PointLimitPolicy,
+    pub ground_plane: bool,
+    pub exit_key: Option<Key>,
+    pub up_axis: UpAxis,
+    pub annotation_labels: Vec<String>,
+    pub point_size_scalar: bool,
+    pub point_size_range: (f32, f32),
+    pub robust_bounds: bool,
+    pub anaglyph: bool,
+    pub eye_separation: f32,
+    pub assets_dir: Option<String>,
+    pub ground_removal: Option<f32>,
+    pub heatmap_decay: f32,
+    pub heatmap_voxel_size: f32
 }
 
 /// Specifies the files used for IPC
@@ -19,6 +105,99 @@ pub struct IPCFiles
     pub data_file_names: String
 }
 
+/// Specifies the contents of the mutex files used to coordinate the IPC
+#[derive(Clone)]
+pub struct IPCHandshake
+{
+    /// Written by the producer once the data file holds a new point cloud
+    pub taken_sentinel: String,
+    /// Written by this program once the data file has been read
+    pub clear_sentinel: String
+}
+
+impl Default for Args
+{
+    /// Creates the arguments used when none are given on the command line. Useful when embedding
+    /// the visualizer, where the arguments are set directly rather than parsed
+    fn default() -> Args
+    {
+        Args
+        {
+            initial_data_models: Vec::new(),
+            ipc_files: vec![],
+            display_lidar_pos: false,
+            sleep_duration_ms: 250,
+            cluster_output_format: ClusterOutputFormat::Index,
+            cluster_backend: ClusterBackend::External,
+            buffer_timeout_ns: 5_000_000,
+            min_epsilon: 0.001,
+            max_epsilon: 10.0,
+            cluster_status_timeout_s: 10,
+            units_scale: 1.0,
+            dual_view: false,
+            sphere_culling: false,
+            ipc_handshake: IPCHandshake{ taken_sentinel: "taken".to_string(), clear_sentinel: "clear".to_string() },
+            no_shadows: false,
+            grid_colours: GridColours::dark_background(),
+            parse_mode: ParseMode::Strict,
+            export_file: None,
+            playback_dir: None,
+            playback_frames_per_second: 10.0,
+            alpha_file: None,
+            alpha_to_coverage: false,
+            demo: false,
+            auto_rotate_degrees_per_second: 10.0,
+            overlay_layout_file: None,
+            session_file: "session.txt".to_string(),
+            smooth_camera: false,
+            view_export_prefix: "view".to_string(),
+            invert_y: false,
+            log_depth: false,
+            covariance_file: None,
+            cluster_displayed: false,
+            cluster_palette: ClusterPalette::Default,
+            transparent_background: false,
+            velocity_colours: None,
+            near_fade: (0.0, 0.0),
+            adaptive_quality: false,
+            edges_file: None,
+            cursor_smoothing: 1,
+            grid_height: 0.0,
+            json_control: false,
+            shadow_offset: (2.0, 4.0),
+            smoothing_factor: 0.5,
+            smoothing_distance: 0.5,
+            dither: false,
+            window_title: "Point Cloud Visualizer".to_string(),
+            window_icon: None,
+            profile: false,
+            camera_path_file: None,
+            camera_path_record_prefix: None,
+            camera_path_fps: 30.0,
+            compat_buffers: false,
+            grid_depth_offset: 0.00002,
+            cluster_export_file: None,
+            sun_elevation: 45.0,
+            sun_arc_degrees_per_second: 15.0,
+            max_points: None,
+            max_points_policy: PointLimitPolicy::Downsample,
+            ground_plane: false,
+            exit_key: Some(Key::Escape),
+            up_axis: UpAxis::Z,
+            annotation_labels: Vec::new(),
+            point_size_scalar: false,
+            point_size_range: (0.5, 2.0),
+            robust_bounds: false,
+            anaglyph: false,
+            eye_separation: 0.065,
+            assets_dir: None,
+            ground_removal: None,
+            heatmap_decay: 1.0,
+            heatmap_voxel_size: 0.5
+        }
+    }
+}
+
 impl Args
 {
     /// Processes the arguments passed into the program
@@ -26,13 +205,7 @@ impl Args
     {
         let yaml = load_yaml!("../arguments.yml");
         let matches = App::from_yaml(yaml).get_matches();
-        let mut args = Args
-        {
-            initial_data_model: None,
-            ipc_files: vec![],
-            display_lidar_pos: false,
-            sleep_duration_ms: 250
-        };
+        let mut args = Args::default();
 
         Args::extract_validate_input(&matches, &mut args);
         args
@@ -45,6 +218,51 @@ impl Args
         !self.ipc_files.is_empty()
     }
 
+    /// Returns if the point cloud is going to be updated, either using IPC or by playing back a
+    /// recorded sequence of point clouds
+    pub fn updating_point_cloud(&self) -> bool
+    {
+        self.using_file_ipc() || self.playback_dir.is_some()
+    }
+
+    /// Returns the limit on the number of points of a point cloud, if one was given
+    pub fn point_limit(&self) -> Option<PointLimit>
+    {
+        self.max_points.map(|x| PointLimit{ max_points: x, policy: self.max_points_policy })
+    }
+
+    /// Returns how the point cloud files are read
+    pub fn parse_settings(&self) -> ParseSettings
+    {
+        ParseSettings{ units_scale: self.units_scale, up_axis: self.up_axis, parse_mode: self.parse_mode, point_columns: self.point_columns() }
+    }
+
+    /// Returns the values written for each point of the point cloud files. Velocities are only read
+    /// when points are coloured by them, and scalars only when points are sized by them
+    pub fn point_columns(&self) -> PointColumns
+    {
+        match (self.velocity_colours.is_some(), self.point_size_scalar)
+        {
+            (false, false) => PointColumns::Position,
+            (true, false) => PointColumns::PositionVelocity,
+            (false, true) => PointColumns::PositionScalar,
+            (true, true) => PointColumns::PositionVelocityScalar,
+        }
+    }
+
+    /// Returns the smallest and largest scale of the points if they are sized by their scalars
+    pub fn point_scale_range(&self) -> Option<(f32, f32)>
+    {
+        if self.point_size_scalar
+        {
+            Some(self.point_size_range)
+        }
+        else
+        {
+            None
+        }
+    }
+
     /// Helper function for the constructor; determines if a static point cloud is being rendered
     /// (provided by initial point cloud file) or a dynamic point cloud (provided by IPC files)
     ///
@@ -54,7 +272,13 @@ impl Args
     {
         // Closure needed to use "?" operator; otherwise compiler will think it applies to extract_validate_input
         let str_to_string = |input: Option<&str>| Some(input?.to_string());
-        args.initial_data_model = str_to_string(matches.value_of("render_initial_point_cloud"));
+        args.initial_data_models = matches.values_of("render_initial_point_cloud").map_or(Vec::new(), |x| x.map(|x| x.to_string()).collect());
+
+        if args.initial_data_models.len() > 1 && args.initial_data_models.iter().any(|x| x == STDIN_FILE)
+        {
+            eprintln!("The initial point cloud can only be read from stdin when it is the only initial point cloud file");
+            exit(-1);
+        }
 
         match (matches.values_of("data_files"), matches.values_of("mutex_files"))
         {
@@ -73,10 +297,10 @@ impl Args
                 }
             _ =>
                 {
-                    if matches.value_of("render_initial_point_cloud").is_none()
+                    if matches.value_of("render_initial_point_cloud").is_none() && matches.value_of("playback_dir").is_none()
                     {
-                        eprintln!("No work specified for the program. Must specify IPC files \
-                        and/or a file containing point cloud data to render");
+                        eprintln!("No work specified for the program. Must specify IPC files, a directory \
+                        of point cloud files to play back and/or a file containing point cloud data to render");
                         exit(-1);
                     }
                 }
@@ -110,5 +334,715 @@ impl Args
                     }
             }
         }
+
+        if let Some(output_format) = matches.value_of("cluster_output_format")
+        {
+            match output_format
+            {
+                "index" => args.cluster_output_format = ClusterOutputFormat::Index,
+                "rgb" => args.cluster_output_format = ClusterOutputFormat::Rgb,
+                _ =>
+                    {
+                        eprintln!("Invalid cluster output format: {}. Must be either \"index\" or \"rgb\"", output_format);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(backend) = matches.value_of("cluster_backend")
+        {
+            match backend
+            {
+                "external" => args.cluster_backend = ClusterBackend::External,
+                "internal" => args.cluster_backend = ClusterBackend::Internal,
+                _ =>
+                    {
+                        eprintln!("Invalid cluster backend: {}. Must be either \"internal\" or \"external\"", backend);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(timeout) = matches.value_of("buffer_timeout")
+        {
+            match timeout.parse::<u64>()
+            {
+                Ok(i) => match i.checked_mul(1_000_000)
+                {
+                    Some(timeout_ns) => args.buffer_timeout_ns = timeout_ns,
+                    None =>
+                        {
+                            eprintln!("The buffer timeout of {}ms is too large", timeout);
+                            exit(-1);
+                        }
+                },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the buffer timeout: {}. Error: {}", timeout, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        for (arg_name, epsilon_limit) in [("min_epsilon", &mut args.min_epsilon), ("max_epsilon", &mut args.max_epsilon)]
+        {
+            if let Some(limit) = matches.value_of(arg_name)
+            {
+                match limit.parse::<f32>()
+                {
+                    Ok(i) if i > 0.0 => *epsilon_limit = i,
+                    Ok(_) =>
+                        {
+                            eprintln!("The {} option must be greater than zero. Got: {}", arg_name, limit);
+                            exit(-1);
+                        },
+                    Err(err) =>
+                        {
+                            eprintln!("Invalid number for the {} option: {}. Error: {}", arg_name, limit, err);
+                            exit(-1);
+                        }
+                }
+            }
+        }
+
+        if args.min_epsilon > args.max_epsilon
+        {
+            eprintln!("The minimum epsilon ({}) cannot be greater than the maximum epsilon ({})", args.min_epsilon, args.max_epsilon);
+            exit(-1);
+        }
+
+        if let Some(timeout) = matches.value_of("cluster_status_timeout")
+        {
+            match timeout.parse::<u64>()
+            {
+                Ok(i) => args.cluster_status_timeout_s = i,
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the cluster status timeout: {}. Error: {}", timeout, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(scale) = matches.value_of("units_scale")
+        {
+            match scale.parse::<f32>()
+            {
+                Ok(i) if i > 0.0 => args.units_scale = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The units scale must be greater than zero. Got: {}", scale);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the units scale: {}. Error: {}", scale, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.dual_view = matches.is_present("dual_view");
+        args.sphere_culling = matches.is_present("sphere_culling");
+        args.no_shadows = matches.is_present("no_shadows");
+
+        if let Some(sentinel) = matches.value_of("ipc_taken_sentinel")
+        {
+            args.ipc_handshake.taken_sentinel = sentinel.to_string();
+        }
+
+        if let Some(sentinel) = matches.value_of("ipc_clear_sentinel")
+        {
+            args.ipc_handshake.clear_sentinel = sentinel.to_string();
+        }
+
+        if args.ipc_handshake.taken_sentinel == args.ipc_handshake.clear_sentinel
+        {
+            eprintln!("The IPC taken and clear sentinels must be different. Both are: {}", args.ipc_handshake.taken_sentinel);
+            exit(-1);
+        }
+
+        for (arg_name, grid_colour) in [("grid_colour", &mut args.grid_colours.line),
+                                        ("grid_x_axis_colour", &mut args.grid_colours.x_axis),
+                                        ("grid_z_axis_colour", &mut args.grid_colours.z_axis)]
+        {
+            if let Some(colour) = matches.value_of(arg_name)
+            {
+                match Args::parse_colour(colour)
+                {
+                    Ok(i) => *grid_colour = i,
+                    Err(err) =>
+                        {
+                            eprintln!("Invalid colour for the {} option: {}. Error: {}", arg_name, colour, err);
+                            exit(-1);
+                        }
+                }
+            }
+        }
+
+        if let Some(parse_mode) = matches.value_of("parse_mode")
+        {
+            match parse_mode
+            {
+                "strict" => args.parse_mode = ParseMode::Strict,
+                "lenient" => args.parse_mode = ParseMode::Lenient,
+                _ =>
+                    {
+                        eprintln!("Invalid parse mode: {}. Must be either \"strict\" or \"lenient\"", parse_mode);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.export_file = str_to_string(matches.value_of("export_on_key"));
+
+        args.playback_dir = str_to_string(matches.value_of("playback_dir"));
+
+        if args.playback_dir.is_some() && args.using_file_ipc()
+        {
+            eprintln!("A playback directory cannot be used together with IPC files");
+            exit(-1);
+        }
+
+        if let Some(speed) = matches.value_of("playback_speed")
+        {
+            match speed.parse::<f32>()
+            {
+                Ok(i) if i > 0.0 => args.playback_frames_per_second = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The playback speed must be greater than zero. Got: {}", speed);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the playback speed: {}. Error: {}", speed, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.alpha_file = str_to_string(matches.value_of("alpha_file"));
+
+        if args.alpha_file.is_some() && args.initial_data_models.is_empty()
+        {
+            eprintln!("An alpha file can only be used with an initial point cloud");
+            exit(-1);
+        }
+
+        args.alpha_to_coverage = matches.is_present("alpha_to_coverage");
+
+        args.demo = matches.is_present("demo");
+
+        if let Some(speed) = matches.value_of("auto_rotate_speed")
+        {
+            match speed.parse::<f32>()
+            {
+                Ok(i) => args.auto_rotate_degrees_per_second = i,
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the auto rotate speed: {}. Error: {}", speed, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.overlay_layout_file = str_to_string(matches.value_of("overlay_layout"));
+
+        if let Some(session_file) = matches.value_of("session_file")
+        {
+            args.session_file = session_file.to_string();
+        }
+
+        args.smooth_camera = matches.is_present("smooth_camera");
+
+        if let Some(view_export_prefix) = matches.value_of("view_export_prefix")
+        {
+            args.view_export_prefix = view_export_prefix.to_string();
+        }
+
+        args.invert_y = matches.is_present("invert_y");
+
+        args.log_depth = matches.is_present("log_depth");
+
+        args.covariance_file = str_to_string(matches.value_of("covariance"));
+
+        if args.covariance_file.is_some() && args.initial_data_models.is_empty()
+        {
+            eprintln!("A covariance file can only be used with an initial point cloud");
+            exit(-1);
+        }
+
+        args.cluster_displayed = matches.is_present("cluster_displayed");
+
+        if let Some(palette) = matches.value_of("palette")
+        {
+            match palette
+            {
+                "default" => args.cluster_palette = ClusterPalette::Default,
+                "okabe_ito" => args.cluster_palette = ClusterPalette::OkabeIto,
+                "tol" => args.cluster_palette = ClusterPalette::Tol,
+                _ =>
+                    {
+                        eprintln!("Invalid palette: {}. Must be one of \"default\", \"okabe_ito\" or \"tol\"", palette);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.transparent_background = matches.is_present("transparent_bg");
+
+        if let Some(velocity_colours) = matches.value_of("velocity_colours")
+        {
+            match velocity_colours
+            {
+                "speed" => args.velocity_colours = Some(VelocityColourMode::Speed),
+                "direction" => args.velocity_colours = Some(VelocityColourMode::Direction),
+                _ =>
+                    {
+                        eprintln!("Invalid velocity colour mode: {}. Must be either \"speed\" or \"direction\"", velocity_colours);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(near_fade) = matches.value_of("near_fade")
+        {
+            match Args::parse_near_fade(near_fade)
+            {
+                Ok(i) => args.near_fade = i,
+                Err(err) =>
+                    {
+                        eprintln!("Invalid near fade distances: {}. Error: {}", near_fade, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.adaptive_quality = matches.is_present("adaptive_quality");
+
+        args.edges_file = str_to_string(matches.value_of("edges"));
+
+        if let Some(cursor_smoothing) = matches.value_of("cursor_smoothing")
+        {
+            match cursor_smoothing.parse::<usize>()
+            {
+                Ok(i) => args.cursor_smoothing = i,
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the cursor smoothing: {}. Error: {}", cursor_smoothing, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(grid_height) = matches.value_of("grid_height")
+        {
+            match grid_height.parse::<f32>()
+            {
+                Ok(i) => args.grid_height = i,
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the grid height: {}. Error: {}", grid_height, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.json_control = matches.is_present("json_control");
+
+        if args.json_control && args.initial_data_models.iter().any(|x| x == STDIN_FILE)
+        {
+            eprintln!("Commands are read from stdin with --json_control, so the initial point cloud cannot be read from stdin");
+            exit(-1);
+        }
+
+        if let Some(shadow_offset) = matches.value_of("shadow_offset")
+        {
+            match Args::parse_shadow_offset(shadow_offset)
+            {
+                Ok(i) => args.shadow_offset = i,
+                Err(err) =>
+                    {
+                        eprintln!("Invalid shadow offset: {}. Error: {}", shadow_offset, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(factor) = matches.value_of("smoothing_factor")
+        {
+            match factor.parse::<f32>()
+            {
+                Ok(i) if i > 0.0 && i <= 1.0 => args.smoothing_factor = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The smoothing factor must be greater than zero and at most one. Got: {}", factor);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the smoothing factor: {}. Error: {}", factor, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(distance) = matches.value_of("smoothing_distance")
+        {
+            match distance.parse::<f32>()
+            {
+                Ok(i) if i > 0.0 => args.smoothing_distance = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The smoothing distance must be greater than zero. Got: {}", distance);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the smoothing distance: {}. Error: {}", distance, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.dither = matches.is_present("dither");
+
+        if let Some(title) = matches.value_of("title")
+        {
+            args.window_title = title.to_string();
+        }
+
+        args.window_icon = str_to_string(matches.value_of("icon"));
+        args.profile = matches.is_present("profile");
+        args.camera_path_file = str_to_string(matches.value_of("camera_path"));
+        args.camera_path_record_prefix = str_to_string(matches.value_of("camera_path_record"));
+
+        if let Some(fps) = matches.value_of("camera_path_fps")
+        {
+            match fps.parse::<f32>()
+            {
+                Ok(i) if i > 0.0 => args.camera_path_fps = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The camera path frames per second must be greater than zero. Got: {}", fps);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the camera path frames per second: {}. Error: {}", fps, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.compat_buffers = matches.is_present("compat_buffers");
+
+        if let Some(grid_depth_offset) = matches.value_of("grid_depth_offset")
+        {
+            match grid_depth_offset.parse::<f32>()
+            {
+                Ok(i) if (0.0..1.0).contains(&i) => args.grid_depth_offset = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The grid depth offset must be at least 0 and less than 1. Got: {}", grid_depth_offset);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the grid depth offset: {}. Error: {}", grid_depth_offset, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.cluster_export_file = str_to_string(matches.value_of("cluster_export"));
+
+        if let Some(elevation) = matches.value_of("sun_elevation")
+        {
+            match elevation.parse::<f32>()
+            {
+                Ok(i) if i > 0.0 && i <= 90.0 => args.sun_elevation = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The sun elevation must be greater than 0 and at most 90 degrees. Got: {}", elevation);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the sun elevation: {}. Error: {}", elevation, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(speed) = matches.value_of("sun_arc_speed")
+        {
+            match speed.parse::<f32>()
+            {
+                Ok(i) => args.sun_arc_degrees_per_second = i,
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the sun arc speed: {}. Error: {}", speed, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(max_points) = matches.value_of("max_points")
+        {
+            match max_points.parse::<usize>()
+            {
+                Ok(i) if i > 0 => args.max_points = Some(i),
+                Ok(_) =>
+                    {
+                        eprintln!("The maximum number of points must be greater than zero");
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the maximum number of points: {}. Error: {}", max_points, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(policy) = matches.value_of("max_points_policy")
+        {
+            match policy
+            {
+                "downsample" => args.max_points_policy = PointLimitPolicy::Downsample,
+                "refuse" => args.max_points_policy = PointLimitPolicy::Refuse,
+                _ =>
+                    {
+                        eprintln!("Invalid maximum points policy: {}. Must be either \"downsample\" or \"refuse\"", policy);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.ground_plane = matches.is_present("ground_plane");
+
+        if let Some(exit_key) = matches.value_of("exit_key")
+        {
+            match EXIT_KEYS.iter().find(|(name, _)| name.eq_ignore_ascii_case(exit_key))
+            {
+                Some((_, key)) => args.exit_key = Some(*key),
+                None =>
+                    {
+                        let names = EXIT_KEYS.iter().map(|(name, _)| *name).collect::<Vec<&str>>().join(", ");
+                        eprintln!("Invalid exit key: {}. Must be one of: {}", exit_key, names);
+                        exit(-1);
+                    }
+            }
+        }
+
+        // Only disables Escape, so that a different quit key can still be chosen for unattended displays
+        if matches.is_present("no_escape_quit") && args.exit_key == Some(Key::Escape)
+        {
+            args.exit_key = None;
+        }
+
+        if let Some(up_axis) = matches.value_of("up_axis")
+        {
+            match up_axis
+            {
+                "z" => args.up_axis = UpAxis::Z,
+                "y" => args.up_axis = UpAxis::Y,
+                _ =>
+                    {
+                        eprintln!("Invalid up axis: {}. Must be either \"z\" or \"y\"", up_axis);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(labels) = matches.value_of("annotation_labels")
+        {
+            args.annotation_labels = labels.split(',').map(|x| x.trim().to_string()).filter(|x| !x.is_empty()).collect();
+        }
+
+        args.point_size_scalar = matches.is_present("point_size_scalar");
+
+        if let Some(point_size_range) = matches.value_of("point_size_range")
+        {
+            match Args::parse_point_size_range(point_size_range)
+            {
+                Ok(i) => args.point_size_range = i,
+                Err(err) =>
+                    {
+                        eprintln!("Invalid point size range: {}. Error: {}", point_size_range, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.robust_bounds = matches.is_present("robust_bounds");
+
+        args.anaglyph = matches.is_present("anaglyph");
+
+        if let Some(eye_separation) = matches.value_of("eye_separation")
+        {
+            match eye_separation.parse::<f32>()
+            {
+                Ok(i) if i > 0.0 => args.eye_separation = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The eye separation must be greater than 0. Got: {}", eye_separation);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the eye separation: {}. Error: {}", eye_separation, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        args.assets_dir = str_to_string(matches.value_of("assets_dir"));
+
+        if let Some(ref assets_dir) = args.assets_dir
+        {
+            if !std::path::Path::new(assets_dir).is_dir()
+            {
+                eprintln!("The assets directory does not exist: {}", assets_dir);
+                exit(-1);
+            }
+        }
+
+        if let Some(ground_removal) = matches.value_of("ground_removal")
+        {
+            match ground_removal.parse::<f32>()
+            {
+                Ok(i) => args.ground_removal = Some(i),
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the ground removal height: {}. Error: {}", ground_removal, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(heatmap_decay) = matches.value_of("heatmap_decay")
+        {
+            match heatmap_decay.parse::<f32>()
+            {
+                Ok(i) if i >= 0.0 => args.heatmap_decay = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The heatmap decay rate must be at least 0. Got: {}", heatmap_decay);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the heatmap decay rate: {}. Error: {}", heatmap_decay, err);
+                        exit(-1);
+                    }
+            }
+        }
+
+        if let Some(heatmap_voxel_size) = matches.value_of("heatmap_voxel_size")
+        {
+            match heatmap_voxel_size.parse::<f32>()
+            {
+                Ok(i) if i > 0.0 => args.heatmap_voxel_size = i,
+                Ok(_) =>
+                    {
+                        eprintln!("The heatmap voxel size must be greater than 0. Got: {}", heatmap_voxel_size);
+                        exit(-1);
+                    },
+                Err(err) =>
+                    {
+                        eprintln!("Invalid number for the heatmap voxel size: {}. Error: {}", heatmap_voxel_size, err);
+                        exit(-1);
+                    }
+            }
+        }
+    }
+
+    /// Parses a colour written as three comma separated numbers between 0 and 1, such as "0.5,0.5,1"
+    ///
+    /// `colour` - the text representation of the colour
+    fn parse_colour(colour: &str) -> Result<TVec3<f32>, String>
+    {
+        let components = colour.split(',').map(|x| x.trim().parse::<f32>()).collect::<Result<Vec<f32>, _>>()
+            .map_err(|x| x.to_string())?;
+
+        if components.len() != 3
+        {
+            return Err(format!("Expected three colour components, found {}", components.len()));
+        }
+
+        if components.iter().any(|x| *x < 0.0 || *x > 1.0)
+        {
+            return Err("Colour components must be between 0 and 1".to_string());
+        }
+
+        Ok(vec3(components[0], components[1], components[2]))
+    }
+
+    /// Parses the distances points start and stop fading at, written as two comma separated numbers
+    /// such as "0.5,2"
+    ///
+    /// `near_fade` - the text representation of the distances
+    fn parse_near_fade(near_fade: &str) -> Result<(f32, f32), String>
+    {
+        let distances = near_fade.split(',').map(|x| x.trim().parse::<f32>()).collect::<Result<Vec<f32>, _>>()
+            .map_err(|x| x.to_string())?;
+
+        if distances.len() != 2
+        {
+            return Err(format!("Expected two distances, found {}", distances.len()));
+        }
+
+        if distances[0] < 0.0 || distances[0] > distances[1]
+        {
+            return Err("The distances cannot be negative, and the first cannot be greater than the second".to_string());
+        }
+
+        Ok((distances[0], distances[1]))
+    }
+
+    /// Parses the depth offsets of the shadow map, written as two comma separated numbers such as "2,4"
+    ///
+    /// `shadow_offset` - the text representation of the offsets
+    fn parse_shadow_offset(shadow_offset: &str) -> Result<(f32, f32), String>
+    {
+        let offsets = shadow_offset.split(',').map(|x| x.trim().parse::<f32>()).collect::<Result<Vec<f32>, _>>()
+            .map_err(|x| x.to_string())?;
+
+        if offsets.len() != 2
+        {
+            return Err(format!("Expected two offsets, found {}", offsets.len()));
+        }
+
+        if offsets.iter().any(|x| *x < 0.0)
+        {
+            return Err("The offsets cannot be negative".to_string());
+        }
+
+        Ok((offsets[0], offsets[1]))
+    }
+
+    /// Parses the smallest and largest scale of points sized by their scalars, written as two comma
+    /// separated numbers such as "0.5,2"
+    ///
+    /// `point_size_range` - the text representation of the scales
+    fn parse_point_size_range(point_size_range: &str) -> Result<(f32, f32), String>
+    {
+        let scales = point_size_range.split(',').map(|x| x.trim().parse::<f32>()).collect::<Result<Vec<f32>, _>>()
+            .map_err(|x| x.to_string())?;
+
+        if scales.len() != 2
+        {
+            return Err(format!("Expected two scales, found {}", scales.len()));
+        }
+
+        if scales[0] <= 0.0 || scales[0] > scales[1]
+        {
+            return Err("The scales must be greater than zero, and the first cannot be greater than the second".to_string());
+        }
+
+        Ok((scales[0], scales[1]))
     }
 }
\ No newline at end of file
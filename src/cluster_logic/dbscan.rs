@@ -0,0 +1,118 @@
+use nalgebra_glm::TVec3;
+use crate::cluster_logic::octree::Octree;
+
+/// Index given to points that do not belong to any cluster. This matches the output of the
+/// external cluster detection program
+pub const NOISE_INDEX: isize = -1;
+
+/// Marker for points that have not yet been visited by the algorithm
+const UNVISITED_INDEX: isize = -2;
+
+/// Finds clusters in the given points using the DBSCAN algorithm. The result holds the cluster index
+/// of each point, with the same ordering as the given points. Points not part of any cluster have
+/// an index of NOISE_INDEX
+///
+/// `points` - the points to find clusters in
+/// `epsilon` - the maximum distance between two points for them to be considered neighbours
+/// `min_num_points` - the minimum number of neighbours (including the point itself) a point requires
+///                     to be considered part of the core of a cluster
+pub fn find_clusters(points: &[TVec3<f32>], epsilon: f32, min_num_points: u32) -> Vec<isize>
+{
+    let octree = Octree::new(points);
+    let mut cluster_indices = vec![UNVISITED_INDEX; points.len()];
+    let mut current_cluster = 0;
+
+    for point_index in 0..points.len()
+    {
+        if cluster_indices[point_index] != UNVISITED_INDEX
+        {
+            continue;
+        }
+
+        let neighbours = octree.find_within_radius(&points[point_index], epsilon);
+        if neighbours.len() < min_num_points as usize
+        {
+            cluster_indices[point_index] = NOISE_INDEX;
+            continue;
+        }
+
+        cluster_indices[point_index] = current_cluster;
+        let mut points_to_expand = neighbours;
+
+        while let Some(neighbour_index) = points_to_expand.pop()
+        {
+            // A point previously thought to be noise is reachable from this cluster, making it a
+            // border point of this cluster. Border points do not expand the cluster further
+            if cluster_indices[neighbour_index] == NOISE_INDEX
+            {
+                cluster_indices[neighbour_index] = current_cluster;
+                continue;
+            }
+
+            if cluster_indices[neighbour_index] != UNVISITED_INDEX
+            {
+                continue;
+            }
+
+            cluster_indices[neighbour_index] = current_cluster;
+
+            let neighbour_neighbours = octree.find_within_radius(&points[neighbour_index], epsilon);
+            if neighbour_neighbours.len() >= min_num_points as usize
+            {
+                points_to_expand.extend(neighbour_neighbours);
+            }
+        }
+
+        current_cluster += 1;
+    }
+
+    cluster_indices
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::cluster_logic::dbscan::{find_clusters, NOISE_INDEX};
+
+    #[test]
+    fn find_separated_clusters()
+    {
+        let mut points = Vec::new();
+        for x in 0..5
+        {
+            points.push(vec3(x as f32 * 0.1, 0.0, 0.0));
+        }
+        for x in 0..5
+        {
+            points.push(vec3(10.0 + x as f32 * 0.1, 0.0, 0.0));
+        }
+        points.push(vec3(5.0, 5.0, 5.0));
+
+        let result = find_clusters(&points, 0.15, 3);
+
+        assert_eq!(11, result.len());
+        assert!(result[0..5].iter().all(|x| *x == 0));
+        assert!(result[5..10].iter().all(|x| *x == 1));
+        assert_eq!(NOISE_INDEX, result[10]);
+    }
+
+    #[test]
+    fn border_point_joins_cluster()
+    {
+        // The last point only has one neighbour, but that neighbour is a core point
+        let points = vec![vec3(0.0, 0.0, 0.0), vec3(0.1, 0.0, 0.0), vec3(0.2, 0.0, 0.0), vec3(0.3, 0.0, 0.0)];
+        let result = find_clusters(&points, 0.15, 3);
+
+        assert_eq!(vec![0, 0, 0, 0], result);
+    }
+
+    #[test]
+    fn all_noise_when_too_few_points()
+    {
+        let points = vec![vec3(0.0, 0.0, 0.0), vec3(0.1, 0.0, 0.0)];
+        let result = find_clusters(&points, 1.0, 3);
+
+        assert_eq!(vec![NOISE_INDEX, NOISE_INDEX], result);
+    }
+}
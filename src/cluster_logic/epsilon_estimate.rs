@@ -0,0 +1,74 @@
+use std::cmp::Ordering;
+use nalgebra_glm::TVec3;
+use crate::cluster_logic::octree::Octree;
+
+/// Suggests an epsilon for the cluster detection using the k-distance heuristic. The distance from
+/// each sampled point to its k-th closest neighbour is sorted; points inside clusters have small
+/// distances while noise has large ones, and the epsilon is taken where the sorted distances start
+/// rising sharply (the "knee" of the curve). Returns None if there are not more than k points
+///
+/// `points` - the points of the point cloud
+/// `k` - the neighbour to find the distance to; the minimum number of points of a cluster
+/// `max_samples` - the maximum number of points to find the k-th neighbour distance of
+pub fn suggest_epsilon(points: &[TVec3<f32>], k: usize, max_samples: usize) -> Option<f32>
+{
+    if points.len() <= k || max_samples == 0
+    {
+        return None;
+    }
+
+    let octree = Octree::new(points);
+    let sample_step = points.len().div_ceil(max_samples);
+
+    let mut distances = (0..points.len()).step_by(sample_step)
+        .filter_map(|x| octree.find_kth_neighbour_distance(x, k))
+        .collect::<Vec<f32>>();
+    distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+    find_knee(&distances)
+}
+
+/// Finds the value where the given increasing values turn from rising slowly to rising quickly. This
+/// is the value furthest below the straight line from the first to the last value, once both the
+/// positions and the values are scaled to be between 0 and 1
+///
+/// `sorted_values` - the values, in increasing order
+fn find_knee(sorted_values: &[f32]) -> Option<f32>
+{
+    let first = *sorted_values.first()?;
+    let last = *sorted_values.last()?;
+
+    if sorted_values.len() < 3 || last - first <= f32::EPSILON
+    {
+        return Some(first);
+    }
+
+    let last_index = (sorted_values.len() - 1) as f32;
+
+    sorted_values.iter()
+        .enumerate()
+        .map(|(index, value)| (index as f32 / last_index - (value - first) / (last - first), *value))
+        .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal))
+        .map(|x| x.1)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::cluster_logic::epsilon_estimate::suggest_epsilon;
+
+    #[test]
+    fn epsilon_separates_clusters_from_noise()
+    {
+        // Two dense grids with points 0.1 apart, and noise with points 3 apart
+        let mut points = (0..1000).map(|x| vec3((x % 10) as f32 * 0.1, (x / 10 % 10) as f32 * 0.1, (x / 100) as f32 * 0.1)).collect::<Vec<_>>();
+        points.extend((0..1000).map(|x| vec3(20.0 + (x % 10) as f32 * 0.1, (x / 10 % 10) as f32 * 0.1, (x / 100) as f32 * 0.1)));
+        points.extend((0..30).map(|x| vec3(-10.0, 5.0, x as f32 * 3.0)));
+
+        let epsilon = suggest_epsilon(&points, 4, 5000).unwrap();
+        assert!((0.1..1.0).contains(&epsilon), "Suggested epsilon: {}", epsilon);
+
+        assert!(suggest_epsilon(&points[0..4], 4, 5000).is_none());
+    }
+}
\ No newline at end of file
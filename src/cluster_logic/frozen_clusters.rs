@@ -0,0 +1,109 @@
+use nalgebra_glm::TVec3;
+use crate::cluster_logic::octree::Octree;
+
+/// Keeps track of the colours of the most recently displayed point cloud so that, when frozen, the
+/// cluster colours can be carried over to updated point clouds without finding clusters again
+pub struct FrozenClusters
+{
+    frozen: bool,
+    points: Vec<TVec3<f32>>,
+    colours: Vec<TVec3<f32>>,
+}
+
+impl FrozenClusters
+{
+    /// Creates a new, unfrozen, state holding the given points
+    ///
+    /// `points` - the points currently displayed
+    /// `colours` - the colours of the points currently displayed
+    pub fn new(points: &[TVec3<f32>], colours: Vec<TVec3<f32>>) -> FrozenClusters
+    {
+        FrozenClusters{ frozen: false, points: points.to_vec(), colours }
+    }
+
+    /// Switches between keeping and discarding cluster colours when the point cloud updates
+    pub fn toggle(&mut self)
+    {
+        self.frozen = !self.frozen;
+    }
+
+    /// Checks if the cluster colours are kept when the point cloud updates
+    pub fn is_frozen(&self) -> bool
+    {
+        self.frozen
+    }
+
+    /// Records the colours given to the currently displayed points, such as after finding clusters
+    ///
+    /// `colours` - the new colours of the displayed points
+    pub fn update_colours(&mut self, colours: &[TVec3<f32>])
+    {
+        self.colours = colours.to_vec();
+    }
+
+    /// Determines the colours for an updated point cloud. If frozen, each point takes the colour of the
+    /// closest point of the previous point cloud; otherwise every point gets the default colour given
+    ///
+    /// `points` - the points of the updated point cloud
+    /// `default_colour` - the colour to use for points if not frozen
+    pub fn colours_for_update(&mut self, points: &[TVec3<f32>], default_colour: TVec3<f32>) -> Vec<TVec3<f32>>
+    {
+        self.colours_for_update_with_defaults(points, vec![default_colour; points.len()])
+    }
+
+    /// Same as colours_for_update, but each point has its own colour to use if not frozen, such as
+    /// when points are coloured by their velocity
+    ///
+    /// `points` - the points of the updated point cloud
+    /// `default_colours` - the colour to use for each point if not frozen
+    pub fn colours_for_update_with_defaults(&mut self, points: &[TVec3<f32>], default_colours: Vec<TVec3<f32>>) -> Vec<TVec3<f32>>
+    {
+        let colours = if self.frozen && !self.points.is_empty() && self.points.len() <= self.colours.len()
+        {
+            let octree = Octree::new(&self.points);
+            points.iter()
+                .zip(default_colours.iter())
+                .map(|(x, default_colour)| octree.find_nearest(x).map_or(*default_colour, |i| self.colours[i]))
+                .collect()
+        }
+        else
+        {
+            default_colours
+        };
+
+        self.points = points.to_vec();
+        self.colours = colours.clone();
+        colours
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::cluster_logic::frozen_clusters::FrozenClusters;
+
+    #[test]
+    fn unfrozen_uses_default_colour()
+    {
+        let mut frozen_clusters = FrozenClusters::new(&[vec3(0.0, 0.0, 0.0)], vec![vec3(1.0, 0.0, 0.0)]);
+        let colours = frozen_clusters.colours_for_update(&[vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0)], vec3(0.0, 1.0, 0.0));
+
+        assert_eq!(vec![vec3(0.0, 1.0, 0.0); 2], colours);
+    }
+
+    #[test]
+    fn frozen_uses_closest_previous_colour()
+    {
+        let mut frozen_clusters = FrozenClusters::new(&[vec3(0.0, 0.0, 0.0), vec3(10.0, 0.0, 0.0)], vec![vec3(0.0, 1.0, 0.0); 2]);
+        frozen_clusters.update_colours(&[vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0)]);
+        frozen_clusters.toggle();
+
+        let colours = frozen_clusters.colours_for_update(&[vec3(9.0, 0.0, 0.0), vec3(0.5, 0.0, 0.0), vec3(11.0, 1.0, 0.0)], vec3(0.0, 1.0, 0.0));
+        assert_eq!(vec![vec3(0.0, 0.0, 1.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0)], colours);
+
+        // Colours carry over to subsequent updates as well
+        let colours = frozen_clusters.colours_for_update(&[vec3(1.0, 0.0, 0.0)], vec3(0.0, 1.0, 0.0));
+        assert_eq!(vec![vec3(1.0, 0.0, 0.0)], colours);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod dbscan;
+pub mod frozen_clusters;
+pub mod octree;
+pub mod spacing_histogram;
+pub mod epsilon_estimate;
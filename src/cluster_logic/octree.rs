@@ -0,0 +1,405 @@
+use std::cmp::Ordering;
+use nalgebra_glm::{TVec3, vec3};
+
+/// Maximum number of points a node holds before it is split into children
+const MAX_POINTS_PER_NODE: usize = 16;
+
+/// Maximum depth of the tree; prevents endless splitting when many points share the same position
+const MAX_DEPTH: u32 = 16;
+
+/// Spatial index over a set of points, allowing fast neighbour queries. The points themselves are
+/// not stored; only their indices into the slice given when constructing the octree
+pub struct Octree
+{
+    nodes: Vec<OctreeNode>,
+    points: Vec<TVec3<f32>>,
+}
+
+/// A single cube of space in the octree
+struct OctreeNode
+{
+    min: TVec3<f32>,
+    max: TVec3<f32>,
+    // Index of the first of the eight children in the node array; children are stored contiguously
+    first_child: Option<usize>,
+    point_indices: Vec<usize>,
+}
+
+impl Octree
+{
+    /// Creates an octree containing the given points
+    ///
+    /// `points` - the points to place into the octree
+    pub fn new(points: &[TVec3<f32>]) -> Octree
+    {
+        let mut min = vec3(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = vec3(f32::MIN, f32::MIN, f32::MIN);
+
+        for point in points
+        {
+            min = nalgebra_glm::min2(&min, point);
+            max = nalgebra_glm::max2(&max, point);
+        }
+
+        if points.is_empty()
+        {
+            min = vec3(0.0, 0.0, 0.0);
+            max = vec3(0.0, 0.0, 0.0);
+        }
+
+        // Nodes are cubes so that the children of a node are also cubes
+        let centre = (min + max) * 0.5;
+        let half_length = (max - min).max() * 0.5 + f32::EPSILON;
+        let half_extent = vec3(half_length, half_length, half_length);
+
+        let mut octree = Octree
+        {
+            nodes: vec![OctreeNode{ min: centre - half_extent, max: centre + half_extent, first_child: None, point_indices: Vec::new() }],
+            points: points.to_vec()
+        };
+
+        for index in 0..points.len()
+        {
+            octree.insert(0, index, 0);
+        }
+
+        octree
+    }
+
+    /// Finds the indices of all points within the given distance of a position, including any point
+    /// located at the position itself
+    ///
+    /// `position` - the position to search around
+    /// `radius` - the maximum distance from the position a point can be to be returned
+    pub fn find_within_radius(&self, position: &TVec3<f32>, radius: f32) -> Vec<usize>
+    {
+        let mut result = Vec::new();
+        let mut nodes_to_visit = vec![0];
+        let radius_squared = radius * radius;
+
+        while let Some(node_index) = nodes_to_visit.pop()
+        {
+            let node = &self.nodes[node_index];
+            if Octree::distance_squared_to_node(node, position) > radius_squared
+            {
+                continue;
+            }
+
+            match node.first_child
+            {
+                Some(first_child) => nodes_to_visit.extend(first_child..first_child + 8),
+                None =>
+                    {
+                        for index in &node.point_indices
+                        {
+                            if nalgebra_glm::distance2(&self.points[*index], position) <= radius_squared
+                            {
+                                result.push(*index);
+                            }
+                        }
+                    }
+            }
+        }
+
+        result
+    }
+
+    /// Finds the index of the point closest to the given position, if there are any points in the octree
+    ///
+    /// `position` - the position to find the closest point to
+    pub fn find_nearest(&self, position: &TVec3<f32>) -> Option<usize>
+    {
+        self.find_nearest_excluding(position, None)
+    }
+
+    /// Finds the index of the point closest to the point at the given index, other than that point
+    /// itself. Points at the same position as the given point are still found
+    ///
+    /// `point_index` - the index of the point to find the neighbour of
+    pub fn find_nearest_neighbour(&self, point_index: usize) -> Option<usize>
+    {
+        self.find_nearest_excluding(&self.points[point_index], Some(point_index))
+    }
+
+    /// Finds the distance from the point at the given index to its k-th closest point, other than the
+    /// point itself. Returns None if there are not enough other points
+    ///
+    /// `point_index` - the index of the point to find the neighbours of
+    /// `k` - which of the neighbours to find the distance to, where 1 is the closest neighbour
+    pub fn find_kth_neighbour_distance(&self, point_index: usize, k: usize) -> Option<f32>
+    {
+        if k == 0
+        {
+            return None;
+        }
+
+        let position = &self.points[point_index];
+
+        // Squared distances of the closest points found so far, in increasing order
+        let mut closest: Vec<f32> = Vec::with_capacity(k + 1);
+        let mut nodes_to_visit = vec![0];
+
+        while let Some(node_index) = nodes_to_visit.pop()
+        {
+            let node = &self.nodes[node_index];
+            if closest.len() == k && Octree::distance_squared_to_node(node, position) > closest[k - 1]
+            {
+                continue;
+            }
+
+            match node.first_child
+            {
+                Some(first_child) => nodes_to_visit.extend(first_child..first_child + 8),
+                None =>
+                    {
+                        for index in node.point_indices.iter().filter(|x| **x != point_index)
+                        {
+                            let distance = nalgebra_glm::distance2(&self.points[*index], position);
+                            if closest.len() < k || distance < closest[k - 1]
+                            {
+                                let insert_index = closest.iter().position(|x| distance < *x).unwrap_or(closest.len());
+                                closest.insert(insert_index, distance);
+                                closest.truncate(k);
+                            }
+                        }
+                    }
+            }
+        }
+
+        if closest.len() == k
+        {
+            Some(closest[k - 1].sqrt())
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Finds the index of the point closest to the given position, skipping the excluded point
+    ///
+    /// `position` - the position to find the closest point to
+    /// `excluded_index` - the index of a point that cannot be returned, if any
+    fn find_nearest_excluding(&self, position: &TVec3<f32>, excluded_index: Option<usize>) -> Option<usize>
+    {
+        let mut closest: Option<(usize, f32)> = None;
+        let mut nodes_to_visit = vec![0];
+
+        while let Some(node_index) = nodes_to_visit.pop()
+        {
+            let node = &self.nodes[node_index];
+            if let Some((_, closest_distance)) = closest
+            {
+                if Octree::distance_squared_to_node(node, position) > closest_distance
+                {
+                    continue;
+                }
+            }
+
+            match node.first_child
+            {
+                Some(first_child) =>
+                    {
+                        // Closest children are visited first (they are pushed last), so that a close point is
+                        // found early and more of the remaining nodes can be skipped
+                        let mut children = (first_child..first_child + 8)
+                            .map(|x| (x, Octree::distance_squared_to_node(&self.nodes[x], position)))
+                            .collect::<Vec<(usize, f32)>>();
+                        children.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+                        nodes_to_visit.extend(children.into_iter().map(|x| x.0));
+                    },
+                None =>
+                    {
+                        for index in node.point_indices.iter().filter(|x| Some(**x) != excluded_index)
+                        {
+                            let distance = nalgebra_glm::distance2(&self.points[*index], position);
+                            if closest.is_none_or(|x| distance < x.1)
+                            {
+                                closest = Some((*index, distance));
+                            }
+                        }
+                    }
+            }
+        }
+
+        closest.map(|x| x.0)
+    }
+
+    /// Inserts the point at the given index into the subtree starting at the given node
+    ///
+    /// `node_index` - the node to insert the point into
+    /// `point_index` - the index of the point to insert
+    /// `depth` - the depth of the node in the tree
+    fn insert(&mut self, node_index: usize, point_index: usize, depth: u32)
+    {
+        if let Some(first_child) = self.nodes[node_index].first_child
+        {
+            let child = first_child + self.child_offset(node_index, &self.points[point_index]);
+            self.insert(child, point_index, depth + 1);
+            return;
+        }
+
+        self.nodes[node_index].point_indices.push(point_index);
+
+        if self.nodes[node_index].point_indices.len() > MAX_POINTS_PER_NODE && depth < MAX_DEPTH
+        {
+            self.split(node_index, depth);
+        }
+    }
+
+    /// Divides a leaf node into eight children and moves its points into them
+    ///
+    /// `node_index` - the node to split
+    /// `depth` - the depth of the node in the tree
+    fn split(&mut self, node_index: usize, depth: u32)
+    {
+        let min = self.nodes[node_index].min;
+        let max = self.nodes[node_index].max;
+        let centre = (min + max) * 0.5;
+        let first_child = self.nodes.len();
+
+        // The bits of the child offset select the upper half of the x, y and z dimension respectively
+        for offset in 0..8
+        {
+            let child_min = vec3
+                (
+                    if offset & 1 == 0 { min.x } else { centre.x },
+                    if offset & 2 == 0 { min.y } else { centre.y },
+                    if offset & 4 == 0 { min.z } else { centre.z }
+                );
+
+            let child_max = vec3
+                (
+                    if offset & 1 == 0 { centre.x } else { max.x },
+                    if offset & 2 == 0 { centre.y } else { max.y },
+                    if offset & 4 == 0 { centre.z } else { max.z }
+                );
+
+            self.nodes.push(OctreeNode{ min: child_min, max: child_max, first_child: None, point_indices: Vec::new() });
+        }
+
+        self.nodes[node_index].first_child = Some(first_child);
+        let point_indices = std::mem::take(&mut self.nodes[node_index].point_indices);
+
+        for point_index in point_indices
+        {
+            self.insert(node_index, point_index, depth);
+        }
+    }
+
+    /// Finds which of the children of a node the given point belongs in
+    ///
+    /// `node_index` - the node whose children are being considered
+    /// `point` - the point to find the child for
+    fn child_offset(&self, node_index: usize, point: &TVec3<f32>) -> usize
+    {
+        let centre = (self.nodes[node_index].min + self.nodes[node_index].max) * 0.5;
+
+        (point.x >= centre.x) as usize |
+            ((point.y >= centre.y) as usize) << 1 |
+            ((point.z >= centre.z) as usize) << 2
+    }
+
+    /// Calculates the squared distance from the position to the closest point of the node's bounds
+    ///
+    /// `node` - the node to find the distance to
+    /// `position` - the position to find the distance from
+    fn distance_squared_to_node(node: &OctreeNode, position: &TVec3<f32>) -> f32
+    {
+        let closest = nalgebra_glm::clamp_vec(position, &node.min, &node.max);
+        nalgebra_glm::distance2(&closest, position)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::cluster_logic::octree::Octree;
+
+    #[test]
+    fn find_points_within_radius()
+    {
+        let mut points = Vec::new();
+        for x in 0..10
+        {
+            for y in 0..10
+            {
+                for z in 0..10
+                {
+                    points.push(vec3(x as f32, y as f32, z as f32));
+                }
+            }
+        }
+
+        let octree = Octree::new(&points);
+        for radius in &[0.5_f32, 1.0, 1.5, 3.0]
+        {
+            let centre = vec3(4.0, 5.0, 6.0);
+            let mut found = octree.find_within_radius(&centre, *radius);
+            found.sort();
+
+            let expected = (0..points.len()).filter(|x| nalgebra_glm::distance(&points[*x], &centre) <= *radius).collect::<Vec<usize>>();
+            assert_eq!(expected, found, "Incorrect points found for radius {}", radius);
+        }
+    }
+
+    #[test]
+    fn identical_points_do_not_split_forever()
+    {
+        let points = vec![vec3(1.0, 1.0, 1.0); 100];
+        let octree = Octree::new(&points);
+
+        assert_eq!(100, octree.find_within_radius(&vec3(1.0, 1.0, 1.0), 0.0).len());
+    }
+
+    #[test]
+    fn find_nearest_point()
+    {
+        let points = (0..200).map(|x| vec3((x % 7) as f32 * 1.5, (x % 11) as f32 * 0.5, x as f32 * 0.1)).collect::<Vec<_>>();
+        let octree = Octree::new(&points);
+
+        for position in &[vec3(0.0, 0.0, 0.0), vec3(3.3, 2.1, 10.05), vec3(-5.0, 20.0, 4.0)]
+        {
+            let expected = (0..points.len())
+                .min_by(|a, b| nalgebra_glm::distance2(&points[*a], position).partial_cmp(&nalgebra_glm::distance2(&points[*b], position)).unwrap())
+                .unwrap();
+            let found = octree.find_nearest(position).unwrap();
+
+            assert_eq!(nalgebra_glm::distance2(&points[expected], position), nalgebra_glm::distance2(&points[found], position));
+        }
+    }
+
+    #[test]
+    fn nearest_neighbour_is_not_itself()
+    {
+        let points = vec![vec3(0.0, 0.0, 0.0), vec3(2.0, 0.0, 0.0), vec3(0.5, 0.0, 0.0), vec3(2.0, 0.0, 0.0)];
+        let octree = Octree::new(&points);
+
+        assert_eq!(Some(2), octree.find_nearest_neighbour(0));
+        assert_eq!(Some(3), octree.find_nearest_neighbour(1));
+        assert_eq!(None, Octree::new(&points[0..1]).find_nearest_neighbour(0));
+    }
+
+    #[test]
+    fn kth_neighbour_distance()
+    {
+        let points = (0..10).map(|x| vec3(x as f32 * x as f32, 0.0, 0.0)).collect::<Vec<_>>();
+        let octree = Octree::new(&points);
+
+        // The closest neighbours of the point at 16 are at 9, 25, 4, 1 and so on
+        assert_eq!(Some(7.0), octree.find_kth_neighbour_distance(4, 1));
+        assert_eq!(Some(9.0), octree.find_kth_neighbour_distance(4, 2));
+        assert_eq!(Some(15.0), octree.find_kth_neighbour_distance(4, 4));
+        assert_eq!(Some(65.0), octree.find_kth_neighbour_distance(4, 9));
+        assert_eq!(None, octree.find_kth_neighbour_distance(4, 10));
+    }
+
+    #[test]
+    fn empty_octree()
+    {
+        let octree = Octree::new(&[]);
+        assert!(octree.find_within_radius(&vec3(0.0, 0.0, 0.0), 10.0).is_empty());
+        assert!(octree.find_nearest(&vec3(0.0, 0.0, 0.0)).is_none());
+    }
+}
@@ -0,0 +1,108 @@
+use std::cmp::Ordering;
+use nalgebra_glm::TVec3;
+use crate::cluster_logic::octree::Octree;
+
+/// Distribution of the distance from points to their nearest neighbour, used to judge how densely
+/// a point cloud is sampled. The median distance is a reasonable starting point for the epsilon of
+/// the cluster detection
+pub struct SpacingHistogram
+{
+    bin_counts: Vec<usize>,
+    bin_width: f32,
+    median: f32,
+    num_sampled: usize,
+}
+
+impl SpacingHistogram
+{
+    /// Finds the nearest neighbour distance of evenly spread out points of the cloud and bins them.
+    /// The bins cover the distances up to the 95th percentile, so that a few isolated points do not
+    /// squash the rest of the histogram; larger distances are counted in the last bin.
+    /// Returns None if there are fewer than two points
+    ///
+    /// `points` - the points of the point cloud
+    /// `max_samples` - the maximum number of points to find the nearest neighbour distance of
+    /// `num_bins` - the number of bins to divide the distances into
+    pub fn new(points: &[TVec3<f32>], max_samples: usize, num_bins: usize) -> Option<SpacingHistogram>
+    {
+        if points.len() < 2 || max_samples == 0 || num_bins == 0
+        {
+            return None;
+        }
+
+        let octree = Octree::new(points);
+        let sample_step = points.len().div_ceil(max_samples);
+
+        let mut distances = (0..points.len()).step_by(sample_step)
+            .filter_map(|x| octree.find_nearest_neighbour(x).map(|neighbour| nalgebra_glm::distance(&points[x], &points[neighbour])))
+            .collect::<Vec<f32>>();
+        distances.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        let median = distances[distances.len() / 2];
+        let binned_range = distances[(distances.len() - 1) * 95 / 100];
+        let bin_width = (binned_range / num_bins as f32).max(f32::EPSILON);
+
+        let mut bin_counts = vec![0; num_bins];
+        for distance in &distances
+        {
+            let bin = ((distance / bin_width) as usize).min(num_bins - 1);
+            bin_counts[bin] += 1;
+        }
+
+        Some(SpacingHistogram{ bin_counts, bin_width, median, num_sampled: distances.len() })
+    }
+
+    /// Get the text representation of the histogram, one line per bin. Each line holds the range of
+    /// the bin followed by a bar whose length is relative to the fullest bin
+    ///
+    /// `bar_length` - the length of the bar of the fullest bin
+    pub fn to_string_rows(&self, bar_length: usize) -> Vec<String>
+    {
+        let max_count = self.bin_counts.iter().copied().max().unwrap_or(0).max(1);
+        let last_bin = self.bin_counts.len() - 1;
+
+        self.bin_counts.iter().enumerate()
+            .map(|(index, count)|
+                {
+                    let range = if index == last_bin
+                    {
+                        format!("> {:.3}", index as f32 * self.bin_width)
+                    }
+                    else
+                    {
+                        format!("{:.3}-{:.3}", index as f32 * self.bin_width, (index + 1) as f32 * self.bin_width)
+                    };
+
+                    format!("{:<12} {}", range, "=".repeat(count * bar_length / max_count))
+                })
+            .collect()
+    }
+
+    /// Get the text describing the histogram as a whole
+    pub fn to_string_summary(&self) -> String
+    {
+        format!("NN distance of {} points. Median: {:.3}", self.num_sampled, self.median)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::cluster_logic::spacing_histogram::SpacingHistogram;
+
+    #[test]
+    fn regular_grid_has_uniform_spacing()
+    {
+        let points = (0..1000).map(|x| vec3((x % 10) as f32 * 0.5, (x / 10 % 10) as f32 * 0.5, (x / 100) as f32 * 0.5)).collect::<Vec<_>>();
+        let histogram = SpacingHistogram::new(&points, 100, 4).unwrap();
+
+        assert_eq!(0.5, histogram.median);
+        assert_eq!(100, histogram.num_sampled);
+        // Every distance is the largest binned distance, so all of them land in the last bin
+        assert_eq!(vec![0, 0, 0, 100], histogram.bin_counts);
+        assert_eq!("> 0.375      ==========", histogram.to_string_rows(10)[3]);
+
+        assert!(SpacingHistogram::new(&points[0..1], 100, 4).is_none());
+    }
+}
\ No newline at end of file
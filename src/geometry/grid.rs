@@ -2,6 +2,33 @@ use std::mem::size_of;
 use nalgebra_glm::{TVec2, TVec3, vec2, vec3};
 use crate::geometry::geometry_trait::RenderableGeometry;
 
+/// The colours used to render the grid
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GridColours
+{
+    /// Colour of the lines that are not on an axis
+    pub line: TVec3<f32>,
+    /// Colour of the line on the positive x-axis; the negative side uses half of this colour
+    pub x_axis: TVec3<f32>,
+    /// Colour of the line on the positive z-axis; the negative side uses half of this colour
+    pub z_axis: TVec3<f32>,
+}
+
+impl GridColours
+{
+    /// Colours that stand out against a dark background
+    pub fn dark_background() -> GridColours
+    {
+        GridColours{ line: vec3(0.25, 0.25, 0.25), x_axis: vec3(0.8, 0.0, 0.0), z_axis: vec3(0.0, 0.0, 0.8) }
+    }
+
+    /// Colours that stand out against a light background
+    pub fn light_background() -> GridColours
+    {
+        GridColours{ line: vec3(0.6, 0.6, 0.6), x_axis: vec3(0.9, 0.2, 0.2), z_axis: vec3(0.2, 0.2, 0.9) }
+    }
+}
+
 /// Represents the world grid in the scene to give a sense of scale to the pointcloud
 pub struct Grid
 {
@@ -9,6 +36,7 @@ pub struct Grid
     translations: Vec<TVec3<f32>>,
     colours: Vec<TVec3<f32>>,
     num_lines: i32,
+    height: f32,
     tex_coords: Vec<TVec2<f32>>,
     normals: Vec<TVec3<f32>>,
     indices: Vec<u32>
@@ -63,7 +91,8 @@ impl Grid
     ///
     /// `num_lines_per_dimension` - number of grid lines to extend in the x, y and z directions.
     ///                             Each grid line is separated by one world unit
-    pub fn new(num_lines_per_dimension: i32) -> Grid
+    /// `grid_colours` - the colours of the grid lines
+    pub fn new(num_lines_per_dimension: i32, grid_colours: GridColours) -> Grid
     {
         // Some really large value; unlikely point cloud will extend beyond this
         let max_offset_from_origin = 10_000_f32;
@@ -94,49 +123,92 @@ impl Grid
         // X-dimension lines
 
         let mut translations = vec![vec3(0.0, 0.0, 0.0)];
-        let mut colours = vec![vec3(0.4, 0.0, 0.0)];
 
         for x in (-starting_value..starting_value).into_iter().filter(|x| *x != 0)
         {
             translations.push(vec3(0.0, 0.0, x as f32));
-            colours.push(vec3(0.25_f32, 0.25, 0.25));
         }
 
         translations.push(vec3(0.0, 0.0, 0.0));
-        colours.push(vec3(0.8, 0.0, 0.0));
 
         for x in (-starting_value..starting_value).into_iter().filter(|x| *x != 0)
         {
             translations.push(vec3(0.0, 0.0, x as f32));
-            colours.push(vec3(0.25_f32, 0.25, 0.25));
         }
 
         // Z-dimension lines
 
         translations.push(vec3(0.0, 0.0, 0.0));
-        colours.push(vec3(0.0, 0.0, 0.4));
 
         for z in (-starting_value..starting_value).into_iter().filter(|x| *x != 0)
         {
             translations.push(vec3(z as f32, 0.0, 0.0));
-            colours.push(vec3(0.25_f32, 0.25, 0.25));
         }
 
         translations.push(vec3(0.0, 0.0, 0.0));
-        colours.push(vec3(0.0, 0.0, 0.8));
 
         for z in (-starting_value..starting_value).into_iter().filter(|x| *x != 0)
         {
             translations.push(vec3(z as f32, 0.0, 0.0));
-            colours.push(vec3(0.25_f32, 0.25, 0.25));
         }
 
+        let colours = Grid::create_colours(num_lines_per_dimension, &grid_colours);
+
         // Not used but provided so that indexing into layouts used by this model (the vertex layout)
         // is consistent for all non-instanced layouts
         let normals = vec![vec3(0.0, 0.0, 0.0); vertices.len()];
         let tex_coords = vec![vec2(0.0, 0.0); vertices.len()];
 
-        Grid{ vertices, translations, colours, num_lines: num_lines_per_dimension, normals, tex_coords, indices: Vec::new() }
+        Grid{ vertices, translations, colours, num_lines: num_lines_per_dimension, height: 0.0, normals, tex_coords, indices: Vec::new() }
+    }
+
+    /// Changes the colours of the grid lines. The grid has to be uploaded again for the change to be seen
+    ///
+    /// `grid_colours` - the new colours of the grid lines
+    pub fn set_colours(&mut self, grid_colours: GridColours)
+    {
+        self.colours = Grid::create_colours(self.num_lines, &grid_colours);
+    }
+
+    /// Moves the grid vertically, so that it can be lined up with a ground that is not at a height of
+    /// zero. The grid has to be uploaded again for the change to be seen
+    ///
+    /// `height` - the height of the plane the grid lies in
+    pub fn set_height(&mut self, height: f32)
+    {
+        self.height = height;
+
+        for translation in self.translations.iter_mut()
+        {
+            translation.y = height;
+        }
+    }
+
+    /// Get the height of the plane the grid lies in
+    pub fn get_height(&self) -> f32
+    {
+        self.height
+    }
+
+    /// Creates the colour of each grid line instance, in the same order as the grid line translations
+    ///
+    /// `num_lines_per_dimension` - number of grid lines to extend in the x, y and z directions
+    /// `grid_colours` - the colours of the grid lines
+    fn create_colours(num_lines_per_dimension: i32, grid_colours: &GridColours) -> Vec<TVec3<f32>>
+    {
+        // The line directly on the axis uses a dimmer colour on the negative side of the axis
+        let num_lines_per_side = (-(num_lines_per_dimension / 2)..num_lines_per_dimension / 2).filter(|x| *x != 0).count();
+        let mut colours = Vec::new();
+
+        for axis_colour in &[grid_colours.x_axis, grid_colours.z_axis]
+        {
+            colours.push(axis_colour * 0.5);
+            colours.extend(std::iter::repeat_n(grid_colours.line, num_lines_per_side));
+            colours.push(*axis_colour);
+            colours.extend(std::iter::repeat_n(grid_colours.line, num_lines_per_side));
+        }
+
+        colours
     }
 
     /// Get the instance translations for the grid lines
@@ -156,4 +228,41 @@ impl Grid
     {
         self.num_lines
     }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::geometry::grid::{Grid, GridColours};
+
+    #[test]
+    fn colours_match_translations()
+    {
+        let grid_colours = GridColours{ line: vec3(0.1, 0.1, 0.1), x_axis: vec3(1.0, 0.0, 0.0), z_axis: vec3(0.0, 0.0, 1.0) };
+        let mut grid = Grid::new(10, grid_colours);
+
+        assert_eq!(grid.get_translations().len(), grid.get_colours().len());
+        assert_eq!(vec3(0.5, 0.0, 0.0), grid.get_colours()[0]);
+        assert_eq!(vec3(0.1, 0.1, 0.1), grid.get_colours()[1]);
+
+        grid.set_colours(GridColours::dark_background());
+        assert_eq!(grid.get_translations().len(), grid.get_colours().len());
+        assert_eq!(vec3(0.4, 0.0, 0.0), grid.get_colours()[0]);
+    }
+
+    #[test]
+    fn height_moves_every_line()
+    {
+        let mut grid = Grid::new(10, GridColours::dark_background());
+        let translations = grid.get_translations().clone();
+
+        grid.set_height(-2.5);
+        assert_eq!(-2.5, grid.get_height());
+
+        for (original, moved) in translations.iter().zip(grid.get_translations().iter())
+        {
+            assert_eq!(vec3(original.x, -2.5, original.z), *moved);
+        }
+    }
 }
\ No newline at end of file
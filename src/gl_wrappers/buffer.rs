@@ -1,9 +1,9 @@
-use std::ffi::c_void;
+use std::ffi::{c_void, CStr};
 use std::fmt::Debug;
-use std::mem::size_of;
-use std::process::exit;
+use std::mem::{size_of, size_of_val};
 use std::ptr::{copy_nonoverlapping, null};
 use gl::types::GLsync;
+use crate::helper_logic::status_output::print_status;
 use crate::gl_wrappers::vao::VAO;
 
 /// Represents a buffer storage object on the GPU. It supports fast uploads by using a round-robin
@@ -11,11 +11,75 @@ use crate::gl_wrappers::vao::VAO;
 pub struct Buffer
 {
     buffers: Vec<u32>,
+    // Both are empty when uploading with glBufferSubData
     pointers: Vec<*mut c_void>,
     fences: Vec<GLsync>,
     current_buffer_index: usize,
     number_buffers: usize,
     buffer_type: BufferType,
+    upload_mode: UploadMode,
+}
+
+/// Specifies how data is uploaded to buffers
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UploadMode
+{
+    /// The buffers stay mapped and data is copied into them directly. The value is the amount of time in
+    /// nanoseconds to wait for a buffer to become free when writing to it
+    PersistentMapped(u64),
+    /// Data is uploaded with glBufferSubData, which the driver synchronizes. Slower, but works on drivers
+    /// that do not support persistently mapped buffers or have bugs in them
+    BufferSubData,
+}
+
+impl UploadMode
+{
+    /// Selects how data is uploaded to buffers. Persistently mapped buffers are used unless compatible
+    /// buffers are requested or the OpenGL context that was created does not support them, which requires
+    /// OpenGL 4.4 or the buffer storage extension. Must be called after the OpenGL context is created
+    ///
+    /// `compat_buffers` - true if glBufferSubData should be used regardless of what the context supports
+    /// `timeout_ns` - the amount of time in nanoseconds to wait for a persistently mapped buffer to become free
+    pub fn select(compat_buffers: bool, timeout_ns: u64) -> UploadMode
+    {
+        if compat_buffers
+        {
+            return UploadMode::BufferSubData;
+        }
+
+        let (mut major_version, mut minor_version) = (0, 0);
+        unsafe
+            {
+                gl::GetIntegerv(gl::MAJOR_VERSION, &mut major_version);
+                gl::GetIntegerv(gl::MINOR_VERSION, &mut minor_version);
+            }
+
+        if (major_version, minor_version) >= (4, 4) || UploadMode::buffer_storage_extension_supported()
+        {
+            return UploadMode::PersistentMapped(timeout_ns);
+        }
+
+        print_status(format!("OpenGL {}.{} does not support persistently mapped buffers; uploading with glBufferSubData instead", major_version, minor_version));
+        UploadMode::BufferSubData
+    }
+
+    /// Checks if the OpenGL context supports the extension providing persistently mapped buffers
+    fn buffer_storage_extension_supported() -> bool
+    {
+        let mut num_extensions = 0;
+        unsafe{ gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut num_extensions); }
+
+        (0..num_extensions).any(|x|
+            {
+                let extension = unsafe{ gl::GetStringi(gl::EXTENSIONS, x as u32) };
+                if extension.is_null()
+                {
+                    return false;
+                }
+
+                unsafe{ CStr::from_ptr(extension as *const _) }.to_string_lossy() == "GL_ARB_buffer_storage"
+            })
+    }
 }
 
 type BindingPoint = u32;
@@ -37,7 +101,8 @@ impl Buffer
     /// `number_buffers` - the number of buffers to use in the round-robin upload. The total vRAM used
     ///                     by the buffer is size_buffer_bytes * number_buffers
     /// 'buffer_type' - the type of buffer to create
-    pub fn new(vao: &VAO, size_buffer_bytes: isize, number_buffers: usize, buffer_type: BufferType) -> Buffer
+    /// `upload_mode` - how data is uploaded to the buffer
+    pub fn new(vao: &VAO, size_buffer_bytes: isize, number_buffers: usize, buffer_type: BufferType, upload_mode: UploadMode) -> Buffer
     {
         let mut buffers = Vec::new();
         let mut pointers = Vec::new();
@@ -46,6 +111,21 @@ impl Buffer
         for _ in 0..number_buffers
         {
             let mut buffer: u32 = 0;
+
+            if upload_mode == UploadMode::BufferSubData
+            {
+                // Created through a binding rather than directly, as older drivers are the reason for this mode
+                unsafe
+                    {
+                        gl::GenBuffers(1, &mut buffer);
+                        gl::BindBuffer(gl::COPY_WRITE_BUFFER, buffer);
+                        gl::BufferData(gl::COPY_WRITE_BUFFER, size_buffer_bytes, null(), gl::DYNAMIC_DRAW);
+                    }
+
+                buffers.push(buffer);
+                continue;
+            }
+
             let buffer_flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
             let map_flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
 
@@ -62,40 +142,40 @@ impl Buffer
                 }
         }
 
-        let mut buffer = Buffer{ buffers, pointers, fences, current_buffer_index: 0, number_buffers, buffer_type };
+        let mut buffer = Buffer{ buffers, pointers, fences, current_buffer_index: 0, number_buffers, buffer_type, upload_mode };
         buffer.update_binding(vao);
         buffer
     }
 
-    /// Write data to the buffer. If after waiting for the timeout provided the buffer is not free
-    /// to be written to, an error aborts the program
+    /// Write data to the buffer. If after waiting for the timeout provided in the constructor the buffer
+    /// is not free to be written to, an error is returned and nothing is written
     ///
     /// `data` - information to write to the buffer
     /// `vao' - the vao that the buffer is a part of
-    /// `timeout` - the amount of time in nanoseconds to wait for the buffer to become free
-    pub fn write_data<T: Debug>(&mut self, data: &Vec<T>, vao: &VAO, timeout: u64)
+    pub fn write_data<T: Debug>(&mut self, data: &[T], vao: &VAO) -> Result<(), String>
     {
-        self.write_data_offset(data, vao, timeout, 0);
+        self.write_data_offset(data, vao, 0)
     }
 
-    /// Write data to the buffer at an offset. If after waiting for the timeout provided the buffer is not free
-    /// to be written to, an error aborts the program
+    /// Write data to the buffer at an offset. If after waiting for the timeout provided in the constructor
+    /// the buffer is not free to be written to, an error is returned and nothing is written
     ///
     /// `data` - information to write to the buffer
     /// `vao' - the vao that the buffer is a part of
-    /// `timeout` - the amount of time in nanoseconds to wait for the buffer to become free
     /// 'offset_bytes' - the offset into the buffer to write data to
-    pub fn write_data_offset<T: Debug>(&mut self, data: &[T], vao: &VAO, timeout: u64, offset_bytes: isize)
+    pub fn write_data_offset<T: Debug>(&mut self, data: &[T], vao: &VAO, offset_bytes: isize) -> Result<(), String>
     {
         let number_elements_offset = (offset_bytes as usize / size_of::<T>()) as isize;
 
-        self.current_buffer_index = (self.current_buffer_index + 1) % self.number_buffers;
-        self.wait_for_buffer(timeout);
-        unsafe
-            {
-                copy_nonoverlapping(data.as_ptr(), (self.pointers[self.current_buffer_index] as *mut T).offset(number_elements_offset), data.len());
-            }
+        // Only move onto the next buffer once it is known to be free, so that a failed write leaves
+        // the buffer in the same state as before the write
+        let next_buffer_index = (self.current_buffer_index + 1) % self.number_buffers;
+        self.wait_for_buffer(next_buffer_index)?;
+        self.current_buffer_index = next_buffer_index;
+
+        self.copy_to_current_buffer(data, number_elements_offset);
         self.update_binding(vao);
+        Ok(())
     }
 
     /// Updates the buffer with the provided data without changing the binding of the vao (ie use the same
@@ -106,16 +186,26 @@ impl Buffer
     pub fn write_data_no_wait_no_binding<T: Debug>(&mut self, data: &Vec<T>, offset_bytes: isize)
     {
         let number_elements_offset = (offset_bytes as usize / size_of::<T>()) as isize;
-        unsafe
-            {
-                copy_nonoverlapping(data.as_ptr(), (self.pointers[self.current_buffer_index] as *mut T).offset(number_elements_offset), data.len());
-            }
+        self.copy_to_current_buffer(data, number_elements_offset);
+    }
+
+    /// Waits for the buffer written to next to become free, returning an error if it does not become free
+    /// within the timeout given in the constructor. Writing to the buffer afterwards does not wait again
+    pub fn wait_for_next_buffer(&self) -> Result<(), String>
+    {
+        self.wait_for_buffer((self.current_buffer_index + 1) % self.number_buffers)
     }
 
     /// Updates the fence for the buffer object. This MUST be called after drawing operations that use
     /// the buffer are called
     pub fn update_fence(&mut self)
     {
+        // The driver keeps track of when buffers written with glBufferSubData are in use
+        if self.upload_mode == UploadMode::BufferSubData
+        {
+            return;
+        }
+
         unsafe
             {
                 gl::DeleteSync(self.fences[self.current_buffer_index]);
@@ -123,48 +213,81 @@ impl Buffer
             }
     }
 
-    /// Waits for the buffer to become available
+    /// Waits for the buffer to become available, returning an error if it does not become available
+    /// within the timeout given in the constructor
     ///
-    /// `timeout` - the amount of time in nanoseconds to wait for the buffer to become free
-    fn wait_for_buffer(&mut self, timeout: u64)
+    /// `buffer_index` - the index of the buffer in the round-robin to wait for
+    fn wait_for_buffer(&self, buffer_index: usize) -> Result<(), String>
     {
+        let timeout = match self.upload_mode
+        {
+            UploadMode::PersistentMapped(i) => i,
+            UploadMode::BufferSubData => return Ok(())
+        };
+
         unsafe
             {
                 // First try without any flushing and no timeout, in case buffer is already free
-                let wait_result =  gl::ClientWaitSync(self.fences[self.current_buffer_index], 0, 0);
+                let wait_result =  gl::ClientWaitSync(self.fences[buffer_index], 0, 0);
                 if wait_result == gl::ALREADY_SIGNALED || wait_result == gl::CONDITION_SATISFIED
                 {
-                    return;
+                    return Ok(());
                 }
 
                 // Buffer is not free, wait for the specified amount of time
-                let wait_result = gl::ClientWaitSync(self.fences[self.current_buffer_index], 0, timeout);
+                let wait_result = gl::ClientWaitSync(self.fences[buffer_index], 0, timeout);
                 if wait_result == gl::ALREADY_SIGNALED || wait_result == gl::CONDITION_SATISFIED
                 {
-                    return;
+                    return Ok(());
                 }
 
                 // Buffer is still not free, hint to driver to make the buffer free by flushing commands, and wait again
-                let wait_result = gl::ClientWaitSync(self.fences[self.current_buffer_index], gl::SYNC_FLUSH_COMMANDS_BIT, timeout);
+                let wait_result = gl::ClientWaitSync(self.fences[buffer_index], gl::SYNC_FLUSH_COMMANDS_BIT, timeout);
                 if wait_result == gl::ALREADY_SIGNALED || wait_result == gl::CONDITION_SATISFIED
                 {
-                    return;
+                    return Ok(());
                 }
 
                 // One last final attempt- execute all OpenGL commands issued earlier and then wait for the buffer to be free
                 gl::Flush();
-                let wait_result = gl::ClientWaitSync(self.fences[self.current_buffer_index], gl::SYNC_FLUSH_COMMANDS_BIT, timeout);
+                let wait_result = gl::ClientWaitSync(self.fences[buffer_index], gl::SYNC_FLUSH_COMMANDS_BIT, timeout);
                 if wait_result == gl::ALREADY_SIGNALED || wait_result == gl::CONDITION_SATISFIED
                 {
-                    return;
+                    return Ok(());
                 }
 
-                // TODO: Show error window to user
-                eprintln!("Failed to wait for buffer: {}, {}", wait_result== gl::TIMEOUT_EXPIRED, wait_result== gl::WAIT_FAILED);
-                exit(-1);
+                if wait_result == gl::TIMEOUT_EXPIRED
+                {
+                    Err(format!("Timed out after {}ns waiting for buffer to become free", timeout))
+                }
+                else
+                {
+                    Err("Failed to wait for buffer to become free".to_string())
+                }
             }
     }
 
+    /// Copies the data into the current buffer
+    ///
+    /// `data` - the data to copy
+    /// `number_elements_offset` - the offset into the buffer to copy to, in elements of the data
+    fn copy_to_current_buffer<T>(&self, data: &[T], number_elements_offset: isize)
+    {
+        match self.upload_mode
+        {
+            UploadMode::PersistentMapped(_) => unsafe
+                {
+                    copy_nonoverlapping(data.as_ptr(), (self.pointers[self.current_buffer_index] as *mut T).offset(number_elements_offset), data.len());
+                },
+            UploadMode::BufferSubData => unsafe
+                {
+                    gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.buffers[self.current_buffer_index]);
+                    gl::BufferSubData(gl::COPY_WRITE_BUFFER, number_elements_offset * size_of::<T>() as isize,
+                                      size_of_val(data) as isize, data.as_ptr() as *const c_void);
+                }
+        }
+    }
+
     /// Updates the binding of the VAO with the new buffer to render from
     ///
     /// 'vao' - the vao to update the binding of
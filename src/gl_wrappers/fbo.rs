@@ -1,3 +1,4 @@
+use std::ffi::c_void;
 use nalgebra_glm::{TMat4, TVec4, vec4};
 use crate::rendering::camera::{Camera, CameraType};
 
@@ -8,6 +9,7 @@ pub struct FBO
     fbo: u32,
     texture: u32,
     texture_dimensions: (i32, i32),
+    texture_type: TextureType,
     binding_point: u32,
     camera: Camera
 }
@@ -72,7 +74,7 @@ impl FBO
 
         let camera = Camera::new(camera_type);
 
-        Ok(FBO { fbo, texture, texture_dimensions, binding_point, camera })
+        Ok(FBO { fbo, texture, texture_dimensions, texture_type, binding_point, camera })
     }
 
     /// Get a mutable reference to the camera the FBO uses for rendering
@@ -117,6 +119,61 @@ impl FBO
                 gl::BindTextureUnit(self.binding_point, self.texture);
             }
     }
+
+    /// Get the dimensions of the texture the FBO writes to
+    pub fn get_texture_dimensions(&self) -> (i32, i32)
+    {
+        self.texture_dimensions
+    }
+
+    /// Reads back the contents of the FBO's texture as RGBA bytes, with the rows ordered from the bottom
+    /// of the texture to the top. A depth texture is returned as grayscale, with the depth range held
+    /// by the texture stretched to the full range of colours
+    pub fn read_texture(&self) -> Vec<u8>
+    {
+        let num_pixels = (self.texture_dimensions.0 * self.texture_dimensions.1) as usize;
+
+        match self.texture_type
+        {
+            TextureType::RGB8 =>
+                {
+                    let mut pixels = vec![0_u8; num_pixels * 4];
+                    unsafe
+                        {
+                            gl::GetTextureImage(self.texture, 0, gl::RGBA, gl::UNSIGNED_BYTE, pixels.len() as i32, pixels.as_mut_ptr() as *mut c_void);
+                        }
+
+                    pixels
+                },
+            TextureType::DepthComponent =>
+                {
+                    let mut depths = vec![0.0_f32; num_pixels];
+                    unsafe
+                        {
+                            gl::GetTextureImage(self.texture, 0, gl::DEPTH_COMPONENT, gl::FLOAT, (depths.len() * std::mem::size_of::<f32>()) as i32,
+                                                depths.as_mut_ptr() as *mut c_void);
+                        }
+
+                    depth_to_grayscale(&depths)
+                }
+        }
+    }
+}
+
+/// Converts depth values to RGBA grayscale bytes. Depths are usually bunched together, so the range
+/// of depths present is stretched to the full range of colours to make the result readable
+///
+/// `depths` - the depth of each pixel
+fn depth_to_grayscale(depths: &[f32]) -> Vec<u8>
+{
+    let min_depth = depths.iter().copied().fold(f32::MAX, f32::min);
+    let max_depth = depths.iter().copied().fold(f32::MIN, f32::max);
+    let depth_range = (max_depth - min_depth).max(f32::EPSILON);
+
+    depths.iter()
+        .map(|x| ((x - min_depth) / depth_range * 255.0).round() as u8)
+        .flat_map(|x| vec![x, x, x, 255])
+        .collect()
 }
 
 impl Drop for FBO
@@ -1,4 +1,5 @@
 pub mod buffer;
 pub mod fbo;
 pub mod shader_program_creation;
+pub mod transform_feedback;
 pub mod vao;
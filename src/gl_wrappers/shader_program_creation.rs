@@ -4,7 +4,7 @@ use std::io::Read;
 use std::path::PathBuf;
 use std::process::exit;
 use std::ptr::{null, null_mut};
-use nalgebra_glm::{TMat4, TVec2, TVec3};
+use nalgebra_glm::{TMat4, TVec3};
 
 /// Abstraction of a shader program
 pub struct ShaderProgram
@@ -42,9 +42,23 @@ impl ShaderProgram
     ///
     /// `shaders` - information to create shaders used in the creation of the shader program
     pub fn new(shaders: Vec<ShaderInitInfo>) -> ShaderProgram
+    {
+        ShaderProgram::with_feedback_varyings(shaders, &[])
+    }
+
+    /// Creates a new shader program whose given vertex shader outputs can be captured with transform
+    /// feedback. The outputs are written interleaved in the order given
+    ///
+    /// `shaders` - information to create shaders used in the creation of the shader program
+    /// `feedback_varyings` - the names of the vertex shader outputs to capture
+    pub fn with_feedback_varyings(shaders: Vec<ShaderInitInfo>, feedback_varyings: &[&str]) -> ShaderProgram
     {
         ShaderProgram::check_validate_shader_info(&shaders);
 
+        // Kept alive until the program is linked, as only pointers to the names are given to OpenGL
+        let feedback_varyings = feedback_varyings.iter().map(|x| CString::new(*x).unwrap()).collect::<Vec<CString>>();
+        let feedback_varying_ptrs = feedback_varyings.iter().map(|x| x.as_ptr()).collect::<Vec<_>>();
+
         let mut created_shaders = Vec::new();
         let shader_program: u32;
         unsafe
@@ -58,6 +72,12 @@ impl ShaderProgram
                     created_shaders.push(shader);
                 }
 
+                // Has to be specified before linking for the outputs to be captured
+                if !feedback_varying_ptrs.is_empty()
+                {
+                    gl::TransformFeedbackVaryings(shader_program, feedback_varying_ptrs.len() as i32, feedback_varying_ptrs.as_ptr(), gl::INTERLEAVED_ATTRIBS);
+                }
+
                 gl::LinkProgram(shader_program);
 
                 for x in created_shaders
@@ -120,18 +140,6 @@ impl ShaderProgram
             }
     }
 
-    /// Uploads the given 2d vector of floats to the uniform of the specified name
-    ///
-    /// `uniform_name` - name of the uniform to upload the 2d vector of floats to
-    /// `data` - 2d vector of floats to upload to the uniform
-    pub fn write_vec2<A: AsRef<str>>(&self, uniform_name: A, data: &TVec2<f32>)
-    {
-        unsafe
-            {
-                gl::Uniform2fv(self.get_uniform_location(uniform_name.as_ref()), 1, data.as_ptr());
-            }
-    }
-
     /// Uploads the given matrix of floats to the uniform of the specified name
     ///
     /// `uniform_name` - name of the uniform to upload the matrix to
@@ -0,0 +1,99 @@
+use std::ffi::c_void;
+use std::mem::size_of;
+use nalgebra_glm::{TVec3, vec3};
+
+/// Captures a vec3 output of the vertex shader for every vertex drawn, such as to read back the
+/// positions the shader computed. The shader program must have been linked with the captured output
+/// as its only transform feedback varying
+pub struct TransformFeedback
+{
+    transform_feedback: u32,
+    buffer: u32,
+    query: u32,
+    max_num_vertices: usize,
+}
+
+impl TransformFeedback
+{
+    /// Creates a transform feedback object along with the buffer it writes to
+    ///
+    /// `max_num_vertices` - the most vertices that can be captured at once. Vertices drawn past this
+    ///                      amount are not captured
+    pub fn new(max_num_vertices: usize) -> TransformFeedback
+    {
+        let mut transform_feedback: u32 = 0;
+        let mut buffer: u32 = 0;
+        let mut query: u32 = 0;
+
+        unsafe
+            {
+                gl::CreateTransformFeedbacks(1, &mut transform_feedback);
+                gl::CreateBuffers(1, &mut buffer);
+                gl::CreateQueries(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN, 1, &mut query);
+
+                // Only written by the GPU and read back with glGetNamedBufferSubData, so no flags are needed
+                gl::NamedBufferStorage(buffer, (max_num_vertices.max(1) * size_of::<TVec3<f32>>()) as isize, std::ptr::null(), 0);
+                gl::TransformFeedbackBufferBase(transform_feedback, 0, buffer);
+            }
+
+        TransformFeedback{ transform_feedback, buffer, query, max_num_vertices }
+    }
+
+    /// Get the most vertices that can be captured at once
+    pub fn max_num_vertices(&self) -> usize
+    {
+        self.max_num_vertices
+    }
+
+    /// Captures the vertices drawn as points by the given draw calls, returning the captured output of
+    /// each vertex in the order they were drawn. Nothing is rasterized while capturing
+    ///
+    /// `draw` - issues the draw calls to capture. Only points can be drawn
+    pub fn capture_points<F: FnOnce()>(&self, draw: F) -> Vec<TVec3<f32>>
+    {
+        unsafe
+            {
+                gl::Enable(gl::RASTERIZER_DISCARD);
+                gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, self.transform_feedback);
+                gl::BeginQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN, self.query);
+                gl::BeginTransformFeedback(gl::POINTS);
+            }
+
+        draw();
+
+        let mut num_written: u32 = 0;
+
+        unsafe
+            {
+                gl::EndTransformFeedback();
+                gl::EndQuery(gl::TRANSFORM_FEEDBACK_PRIMITIVES_WRITTEN);
+                gl::BindTransformFeedback(gl::TRANSFORM_FEEDBACK, 0);
+                gl::Disable(gl::RASTERIZER_DISCARD);
+
+                // Waits for the draw calls to finish, which is fine as capturing is only done on request
+                gl::GetQueryObjectuiv(self.query, gl::QUERY_RESULT, &mut num_written);
+            }
+
+        let mut captured = vec![vec3(0.0, 0.0, 0.0); (num_written as usize).min(self.max_num_vertices)];
+
+        unsafe
+            {
+                gl::GetNamedBufferSubData(self.buffer, 0, (captured.len() * size_of::<TVec3<f32>>()) as isize, captured.as_mut_ptr() as *mut c_void);
+            }
+
+        captured
+    }
+}
+
+impl Drop for TransformFeedback
+{
+    fn drop(&mut self)
+    {
+        unsafe
+            {
+                gl::DeleteTransformFeedbacks(1, &self.transform_feedback);
+                gl::DeleteBuffers(1, &self.buffer);
+                gl::DeleteQueries(1, &self.query);
+            }
+    }
+}
\ No newline at end of file
@@ -47,6 +47,33 @@ impl VAO
             }
     }
 
+    /// Enables or disables reading an index from its buffer. A disabled index has the same value for
+    /// every vertex. The VAO must have been binded before calling this function
+    pub fn set_index_enabled(&self, index: u32, enabled: bool)
+    {
+        unsafe
+            {
+                if enabled
+                {
+                    gl::EnableVertexAttribArray(index);
+                }
+                else
+                {
+                    gl::DisableVertexAttribArray(index);
+                }
+            }
+    }
+
+    /// Specify which buffer binding an index reads from. By default an index reads from the binding
+    /// with the same number. The VAO must have been binded before calling this function
+    pub fn specify_index_binding(&self, index: u32, binding_index: u32)
+    {
+        unsafe
+            {
+                gl::VertexAttribBinding(index, binding_index);
+            }
+    }
+
     /// Specify what portion of a buffer to use for rendering
     pub fn update_vertex_buffer_binding(&self, index: u32, buffer: u32, offset: isize, stride: i32)
     {
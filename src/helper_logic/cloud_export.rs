@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::Path;
+use nalgebra_glm::{TVec3, vec3};
+use crate::ipc_logic::ipc_receiver::UpAxis;
+
+/// Writes the given point cloud to a file. A file ending in ".ply" is written as an ASCII PLY file
+/// with the colour of each point; otherwise the points are written in the same "x|y|z|" layout as the
+/// point cloud files read by the program. Either way the points are written in the coordinates of
+/// the files read by the program, so an exported pipe file can be read again without changes
+///
+/// `file_name` - the file to write the point cloud to
+/// `translations` - the positions of the points, as rendered
+/// `colours` - the colour of each point, as rendered
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the files read by the program
+pub fn export_point_cloud(file_name: &String, translations: &[TVec3<f32>], colours: &[TVec3<f32>], units_scale: f32, up_axis: UpAxis) -> Result<(), String>
+{
+    let is_ply = Path::new(file_name).extension().is_some_and(|x| x.eq_ignore_ascii_case("ply"));
+
+    let contents = if is_ply
+    {
+        format_ply(translations, colours, units_scale, up_axis)
+    }
+    else
+    {
+        format_pipe_text(translations, units_scale, up_axis)
+    };
+
+    fs::write(file_name, contents).map_err(|x| x.to_string())
+}
+
+/// Writes each point to a file with the index of the cluster it is part of, as lines of "x y z cluster".
+/// Points not part of any cluster have a cluster of -1. The points are written in the coordinates
+/// of the files read by the program
+///
+/// `file_name` - the file to write the cluster assignments to
+/// `translations` - the positions of the points, as rendered
+/// `cluster_indices` - the cluster index of each point
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the files read by the program
+pub fn export_cluster_assignments(file_name: &String, translations: &[TVec3<f32>], cluster_indices: &[isize], units_scale: f32, up_axis: UpAxis) -> Result<(), String>
+{
+    if translations.len() != cluster_indices.len()
+    {
+        return Err(format!("Got {} cluster indices for {} points", cluster_indices.len(), translations.len()));
+    }
+
+    fs::write(file_name, format_cluster_assignments(translations, cluster_indices, units_scale, up_axis)).map_err(|x| x.to_string())
+}
+
+/// Get the name of the file the points inside the camera's view are written to: the export file
+/// with "_frustum" added before its extension, so that both exports can be kept
+///
+/// `file_name` - the file the whole point cloud is exported to
+pub fn frustum_export_file_name(file_name: &String) -> String
+{
+    let path = Path::new(file_name);
+
+    match (path.file_stem(), path.extension())
+    {
+        (Some(stem), Some(extension)) => path.with_file_name(format!("{}_frustum.{}", stem.to_string_lossy(), extension.to_string_lossy()))
+                                             .to_string_lossy().into_owned(),
+        _ => format!("{}_frustum", file_name)
+    }
+}
+
+/// Creates the text of a point cloud file in the layout read by the program
+///
+/// `translations` - the positions of the points, as rendered
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the files read by the program
+fn format_pipe_text(translations: &[TVec3<f32>], units_scale: f32, up_axis: UpAxis) -> String
+{
+    // Reading a point cloud file can swap the y and z components, so they are swapped back here
+    translations.iter()
+        .map(|x| up_axis.file_from_world(x) / units_scale)
+        .map(|x| format!("{}|{}|{}|", x.x, x.y, x.z))
+        .collect()
+}
+
+/// Creates the text of a cluster assignment file, with a line per point
+///
+/// `translations` - the positions of the points, as rendered
+/// `cluster_indices` - the cluster index of each point
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the files read by the program
+fn format_cluster_assignments(translations: &[TVec3<f32>], cluster_indices: &[isize], units_scale: f32, up_axis: UpAxis) -> String
+{
+    translations.iter().zip(cluster_indices)
+        .map(|(x, cluster_index)| (up_axis.file_from_world(x) / units_scale, cluster_index))
+        .map(|(x, cluster_index)| format!("{} {} {} {}\n", x.x, x.y, x.z, cluster_index))
+        .collect()
+}
+
+/// Creates the text of an ASCII PLY file holding the points and their colours
+///
+/// `translations` - the positions of the points, as rendered
+/// `colours` - the colour of each point, as rendered. Points without a colour are written as white
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the files read by the program
+fn format_ply(translations: &[TVec3<f32>], colours: &[TVec3<f32>], units_scale: f32, up_axis: UpAxis) -> String
+{
+    let mut contents = format!("ply\nformat ascii 1.0\nelement vertex {}\n\
+                                property float x\nproperty float y\nproperty float z\n\
+                                property uchar red\nproperty uchar green\nproperty uchar blue\nend_header\n", translations.len());
+
+    let to_byte = |x: f32| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    for (index, translation) in translations.iter().enumerate()
+    {
+        let colour = colours.get(index).copied().unwrap_or(vec3(1.0, 1.0, 1.0));
+        let position = up_axis.file_from_world(translation) / units_scale;
+
+        contents += &format!("{} {} {} {} {} {}\n", position.x, position.y, position.z,
+                             to_byte(colour.x), to_byte(colour.y), to_byte(colour.z));
+    }
+
+    contents
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::helper_logic::cloud_export::{format_cluster_assignments, format_pipe_text, format_ply, frustum_export_file_name};
+    use crate::ipc_logic::ipc_receiver::{IPCContributor, ParseMode, UpAxis};
+
+    #[test]
+    fn pipe_text_reads_back_the_same()
+    {
+        let translations = vec![vec3(1.0, 2.0, 3.0), vec3(-4.0, 5.5, 6.0)];
+        for up_axis in &[UpAxis::Z, UpAxis::Y]
+        {
+            let contents = format_pipe_text(&translations, 0.5, *up_axis);
+            assert_eq!(translations, IPCContributor::parse_read_data(&contents, 0.5, *up_axis, ParseMode::Strict).unwrap());
+        }
+    }
+
+    #[test]
+    fn ply_has_a_line_per_point()
+    {
+        let contents = format_ply(&[vec3(1.0, 2.0, 3.0)], &[vec3(1.0, 0.0, 0.5)], 1.0, UpAxis::Z);
+
+        assert!(contents.contains("element vertex 1\n"));
+        assert!(contents.ends_with("end_header\n1 3 2 255 0 128\n"));
+    }
+
+    #[test]
+    fn cluster_assignments_have_a_line_per_point()
+    {
+        let contents = format_cluster_assignments(&[vec3(1.0, 2.0, 3.0), vec3(4.0, 5.0, 6.0)], &[2, -1], 0.5, UpAxis::Z);
+
+        assert_eq!("2 6 4 2\n8 12 10 -1\n", contents);
+    }
+    #[test]
+    fn frustum_file_keeps_extension()
+    {
+        assert_eq!("scans/cloud_frustum.ply", frustum_export_file_name(&"scans/cloud.ply".to_string()));
+        assert_eq!("cloud_frustum", frustum_export_file_name(&"cloud".to_string()));
+    }
+}
\ No newline at end of file
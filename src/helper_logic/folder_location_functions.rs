@@ -1,26 +1,61 @@
 use std::env;
 use std::path::PathBuf;
 use std::process::exit;
+use std::sync::RwLock;
 
 // The DevelopmentFlag indicates that this program is being run
 // from the project folder. If this is not set, it is assumed
 // that the program executable is being launched. This means
 // that all required folders should be in the same folder
-// as the executable
+// as the executable. Both are overridden by an assets folder
+// given with --assets_dir
 
-/// Get the location of the folder holding the bitmap font atlas
-pub fn get_text_folder() -> PathBuf
+/// The folder holding the assets, if one was given. Shared so that every asset is found the same way
+static ASSETS_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Sets the folder holding the shaders, text rendering files, point models and cluster program, in
+/// the same layout as beside the executable of a release. Must be called before any of them are loaded
+///
+/// `assets_dir` - the folder holding the assets, or None to find them using the DevelopmentFlag
+pub fn set_assets_dir(assets_dir: Option<PathBuf>)
 {
-    if env::var("DevelopmentFlag").is_ok()
+    if let Ok(mut i) = ASSETS_DIR.write()
     {
-        get_root_project_folder().join("text_rendering")
+        *i = assets_dir;
     }
-    else
+}
+
+/// Get the folder given with set_assets_dir, if any
+fn get_assets_dir() -> Option<PathBuf>
+{
+    ASSETS_DIR.read().ok().and_then(|x| x.clone())
+}
+
+/// Check if the assets are in the project folder, which is the case when running from the project
+/// source without an assets folder given
+fn running_from_project() -> bool
+{
+    get_assets_dir().is_none() && env::var("DevelopmentFlag").is_ok()
+}
+
+/// Get the folder the assets are found relative to: the given assets folder, the project folder or
+/// the working directory, in that order
+fn get_assets_base_folder() -> PathBuf
+{
+    match get_assets_dir()
     {
-        PathBuf::new().join("text_rendering")
+        Some(i) => i,
+        None if running_from_project() => get_root_project_folder(),
+        None => PathBuf::new()
     }
 }
 
+/// Get the location of the folder holding the bitmap font atlas
+pub fn get_text_folder() -> PathBuf
+{
+    get_assets_base_folder().join("text_rendering")
+}
+
 /// Get the location of the folder containing the cluster
 /// detection program
 pub fn get_cluster_program_location() -> PathBuf
@@ -29,7 +64,7 @@ pub fn get_cluster_program_location() -> PathBuf
     // If running a release build, then it will have been packaged specifically for Linux or Windows,
     // hence why if no DevelopmentFlag is set, the same location is returned
 
-    if env::var("DevelopmentFlag").is_ok()
+    if running_from_project()
     {
         if cfg!(windows)
         {
@@ -46,34 +81,20 @@ pub fn get_cluster_program_location() -> PathBuf
     }
     else
     {
-        PathBuf::new().join("ClusterDetectionExe/ClusterDetectionExe")
+        get_assets_base_folder().join("ClusterDetectionExe/ClusterDetectionExe")
     }
 }
 
 /// Get the location of the shaders folder
 pub fn get_shaders_folder() -> PathBuf
 {
-    if env::var("DevelopmentFlag").is_ok()
-    {
-        get_root_project_folder().join("shaders")
-    }
-    else
-    {
-        PathBuf::new().join("shaders")
-    }
+    get_assets_base_folder().join("shaders")
 }
 
 /// Get the location of the point models folder
 pub fn get_point_models_folder() -> PathBuf
 {
-    if env::var("DevelopmentFlag").is_ok()
-    {
-        get_root_project_folder().join("point_models")
-    }
-    else
-    {
-        PathBuf::new().join("point_models")
-    }
+    get_assets_base_folder().join("point_models")
 }
 
 /// Get the location of hte folder holding the models
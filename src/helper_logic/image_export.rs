@@ -0,0 +1,151 @@
+use std::fs;
+
+/// Writes the given pixels to a PNG file. The image data is stored without compression, which keeps
+/// the writer small at the cost of larger files
+///
+/// `file_name` - the file to write the image to
+/// `dimensions` - the width and height of the image
+/// `rgba` - the pixels of the image, four bytes per pixel, with the rows ordered from the bottom of
+///          the image to the top as they are read from OpenGL
+pub fn write_png(file_name: &String, dimensions: (i32, i32), rgba: &[u8]) -> Result<(), String>
+{
+    fs::write(file_name, encode_png(dimensions, rgba)?).map_err(|x| x.to_string())
+}
+
+/// Creates the contents of a PNG file holding the given pixels
+///
+/// `dimensions` - the width and height of the image
+/// `rgba` - the pixels of the image, with the rows ordered from the bottom of the image to the top
+fn encode_png(dimensions: (i32, i32), rgba: &[u8]) -> Result<Vec<u8>, String>
+{
+    let (width, height) = (dimensions.0.max(0) as usize, dimensions.1.max(0) as usize);
+    if rgba.len() != width * height * 4
+    {
+        return Err(format!("Expected {} bytes for a {}x{} image, got {}", width * height * 4, width, height, rgba.len()));
+    }
+
+    // Each row starts with the filter type, which is always "none"
+    let mut scanlines = Vec::with_capacity((width * 4 + 1) * height);
+    for row in rgba.chunks(width * 4).rev()
+    {
+        scanlines.push(0);
+        scanlines.extend_from_slice(row);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(width as u32).to_be_bytes());
+    header.extend_from_slice(&(height as u32).to_be_bytes());
+    // Bit depth of 8, RGBA colour type, default compression, filtering and no interlacing
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib_stored(&scanlines));
+    write_chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+/// Appends a PNG chunk, with its length and checksum, to the given file contents
+///
+/// `png` - the contents of the PNG file written so far
+/// `chunk_type` - the four letter type of the chunk
+/// `data` - the data held by the chunk
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8])
+{
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let checked_start = png.len();
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+
+    let crc = crc32(&png[checked_start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// Wraps the given data in a zlib stream made of uncompressed deflate blocks
+///
+/// `data` - the data to wrap
+fn zlib_stored(data: &[u8]) -> Vec<u8>
+{
+    // Largest amount of data an uncompressed deflate block can hold
+    const MAX_BLOCK_LENGTH: usize = 65535;
+
+    let mut stream = vec![0x78, 0x01];
+    let mut blocks = data.chunks(MAX_BLOCK_LENGTH).peekable();
+
+    if blocks.peek().is_none()
+    {
+        stream.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+
+    while let Some(block) = blocks.next()
+    {
+        let is_final = blocks.peek().is_none();
+        let length = block.len() as u16;
+
+        stream.push(if is_final { 1 } else { 0 });
+        stream.extend_from_slice(&length.to_le_bytes());
+        stream.extend_from_slice(&(!length).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+/// Calculates the CRC-32 checksum used by PNG chunks
+///
+/// `data` - the data to calculate the checksum of
+fn crc32(data: &[u8]) -> u32
+{
+    let mut crc = 0xFFFF_FFFF_u32;
+    for byte in data
+    {
+        crc ^= *byte as u32;
+        for _ in 0..8
+        {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+
+    !crc
+}
+
+/// Calculates the Adler-32 checksum that ends a zlib stream
+///
+/// `data` - the data to calculate the checksum of
+fn adler32(data: &[u8]) -> u32
+{
+    let (mut a, mut b) = (1_u32, 0_u32);
+    for byte in data
+    {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::helper_logic::image_export::{adler32, crc32, encode_png};
+
+    #[test]
+    fn png_holds_flipped_rows()
+    {
+        // The bottom row is red and the top row is blue
+        let rgba = [255, 0, 0, 255, 0, 0, 255, 255];
+        let png = encode_png((1, 2), &rgba).unwrap();
+
+        assert_eq!(&[0x89, b'P', b'N', b'G'], &png[0..4]);
+        assert_eq!(0xAE42_6082, crc32(b"IEND"));
+        assert_eq!(0x11E6_0398, adler32(b"Wikipedia"));
+
+        // The image data comes after the signature (8 bytes), the header chunk (25 bytes), the length
+        // and type of the data chunk (8 bytes) and the zlib and block headers (7 bytes)
+        assert_eq!(&[0, 0, 0, 255, 255, 0, 255, 0, 0, 255], &png[48..58]);
+
+        assert!(encode_png((2, 2), &rgba).is_err());
+    }
+}
\ No newline at end of file
@@ -1,18 +1,40 @@
-use std::sync::mpsc::{Receiver, sync_channel, SyncSender};
+use std::path::PathBuf;
+use std::process::exit;
+use std::sync::mpsc::{channel, Receiver, sync_channel, SyncSender, TryRecvError};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use glfw::OpenGlProfileHint;
-use nalgebra_glm::{TMat4, TVec3, vec3};
-use crate::args_parser;
+use nalgebra_glm::{TMat4, TVec3, vec2, vec3};
 use crate::args_parser::Args;
-use crate::rendering::scene_renderer::{SceneRenderer, ModelId};
+use crate::cluster_logic::frozen_clusters::FrozenClusters;
+use crate::cluster_logic::spacing_histogram::SpacingHistogram;
+use crate::geometry::grid::GridColours;
+use crate::gl_wrappers::buffer::UploadMode;
+use crate::rendering::scene_renderer::{SceneRenderer, ModelId, InitialPointAttributes, VelocityColours, default_point_colour, scalar_point_scales};
 use crate::rendering::camera::{Camera, CameraType, PerspectiveParam};
-use crate::ipc_logic::ipc_content_logic::ClusterInformation;
-use crate::ipc_logic::ipc_receiver::{IPCContributor, SendContents};
-use crate::helper_logic::point_cloud_analyzer::InitialCloudAnalyzer;
+use crate::rendering::gpu_profiler::GpuProfiler;
+use crate::rendering::cluster_colour::ClusterColour;
+use crate::ipc_logic::ipc_content_logic::{ClusterAssignments, ClusterInformation, ClusterSummary, PointFilter};
+use crate::ipc_logic::ipc_receiver::{IPCContributor, PointColumns, SendContents};
+use crate::ipc_logic::json_control::{ControlCommand, launch_json_control_reader};
+use crate::ipc_logic::playback::Playback;
+use crate::helper_logic::folder_location_functions::set_assets_dir;
+use crate::helper_logic::status_output::set_status_to_stderr;
+use crate::helper_logic::overlay_layout::OverlayLayout;
+use crate::helper_logic::session::Session;
+use crate::helper_logic::point_cloud_analyzer::{InitialCloudAnalyzer, covariance_box_transform, read_edges, read_point_alphas, read_point_covariances};
 use crate::rendering::text_rendering::TextRendering;
 use crate::rendering::view_fbo::ViewFBO;
+use crate::view_logic::adaptive_quality::AdaptiveQuality;
+use crate::view_logic::auto_rotate::AutoRotate;
+use crate::view_logic::annotations::Annotations;
+use crate::view_logic::box_selection::BoxSelection;
+use crate::view_logic::camera_path::CameraPath;
+use crate::view_logic::point_smoothing::PointSmoothing;
+use crate::view_logic::update_heatmap::UpdateHeatmap;
+use crate::view_logic::pose_history::PoseHistory;
+use crate::view_logic::sun_arc::SunArc;
 use crate::view_logic::view_selection::ViewSelection;
 use crate::window::RenderWindow;
 
@@ -35,8 +57,20 @@ pub struct PointCloudData
     pub time_since_update: Instant,
     pub pause_updating: bool,
     pub cluster_result_text: String,
+    pub cluster_result_time: Instant,
+    pub cluster_program_disabled: bool,
+    pub cluster_parameter_note: Option<(String, Instant)>,
+    pub reload_note: Option<(String, Instant)>,
+    pub frozen_clusters: FrozenClusters,
+    pub point_smoothing: PointSmoothing,
+    pub update_heatmap: UpdateHeatmap,
+    pub point_filter: Option<PointFilter>,
     pub num_points_cloud: usize,
     pub cluster_information: ClusterInformation,
+    pub epsilon_suggestion: Option<f32>,
+    pub velocity_legend: Option<String>,
+    pub last_cluster_summary: Option<ClusterSummary>,
+    pub last_cluster_assignments: Option<ClusterAssignments>,
 }
 
 /// Holds all of the variables required for updating
@@ -47,6 +81,8 @@ pub struct PointCloudUpdate
     pub cluster_for_most_recent: bool,
     pub sender: SyncSender<Result<SendContents, String>>,
     pub receiver: Receiver<Result<SendContents, String>>,
+    pub playback: Option<Playback>,
+    pub control_receiver: Option<Receiver<Result<ControlCommand, String>>>,
     quit_ipc_thread: Arc<Mutex<bool>>
 }
 
@@ -56,14 +92,46 @@ pub struct RenderData
 {
     pub buffer_groups: SceneRenderer,
     pub cube_model_id: ModelId,
+    pub sun_model_id: ModelId,
+    pub sun_arrow_model_id: ModelId,
+    pub plane_model_id: ModelId,
+    pub ground_plane_model_id: ModelId,
     pub render_window: RenderWindow,
     pub camera: Camera,
+    pub second_camera: Option<Camera>,
+    pub second_camera_active: bool,
     pub translation_matrix: TMat4<f32>,
     pub view_selection: ViewSelection,
     pub view_fbos: ViewFBO,
     pub text_renderer: TextRendering,
     pub cloud_translation: TVec3<f32>,
     pub add_lidar_pos: bool,
+    pub show_grid_labels: bool,
+    pub help_page: Option<usize>,
+    pub overlay_layout: OverlayLayout,
+    pub shadows_enabled: bool,
+    pub show_sun_frustum: bool,
+    pub shadow_offset: (f32, f32),
+    pub x_ray: bool,
+    pub flat_shading: bool,
+    pub anaglyph: bool,
+    pub near_fade: (f32, f32),
+    pub ground_removal: bool,
+    pub ground_threshold: f32,
+    pub grid_colour_presets: Vec<GridColours>,
+    pub grid_colour_preset_index: usize,
+    pub pose_history: PoseHistory,
+    pub right_view_out_of_frame: bool,
+    pub top_view_out_of_frame: bool,
+    pub auto_rotate: AutoRotate,
+    pub invert_look_y: bool,
+    pub spacing_histogram: Option<SpacingHistogram>,
+    pub adaptive_quality: Option<AdaptiveQuality>,
+    pub gpu_profiler: Option<GpuProfiler>,
+    pub camera_path: Option<CameraPath>,
+    pub box_selection: BoxSelection,
+    pub annotations: Annotations,
+    last_frame_time: Instant,
     reflect_vertically: i32,
 }
 
@@ -71,15 +139,35 @@ impl ProgramVariables
 {
     /// Creates the all of the program variables required for the
     /// program to run
-    pub fn new() -> ProgramVariables
+    ///
+    /// `args` - the arguments the program was launched with
+    pub fn new(args: Args) -> ProgramVariables
     {
-        let args = args_parser::Args::parse_args();
-        let point_analyzer = InitialCloudAnalyzer::new(&args.initial_data_model, args.display_lidar_pos);
+        // Shaders and the font atlas are loaded as soon as the window and text renderer are created
+        set_assets_dir(args.assets_dir.as_ref().map(PathBuf::from));
+        set_status_to_stderr(args.json_control);
+
+        // The window is created before reading the initial point cloud so that the progress of reading
+        // a large point cloud can be shown
+        let mut render_window = create_window((1280, 720), args.window_title.clone(), args.window_icon.as_ref(), args.alpha_to_coverage, args.transparent_background,
+                                              args.compat_buffers);
+        let upload_mode = UploadMode::select(args.compat_buffers, args.buffer_timeout_ns);
+        let mut text_renderer = TextRendering::new(render_window.get_window_dimensions(), upload_mode);
+        let mut point_analyzer = load_initial_point_cloud(&args, &mut render_window, &mut text_renderer);
+        if let Err(err) = args.point_limit().map_or(Ok(()), |x| point_analyzer.limit_points(x))
+        {
+            eprintln!("{}", err);
+            exit(-1);
+        }
+
+        point_analyzer.set_use_robust_bounds(args.robust_bounds);
+
+        let velocity_colours = args.velocity_colours.map(|x| VelocityColours::new(point_analyzer.get_initial_velocities(), x));
 
         let mut program_variables = ProgramVariables
         {
-            render_data: RenderData::new(&point_analyzer),
-            point_cloud_data: PointCloudData::new(&args, &point_analyzer),
+            render_data: RenderData::new(&args, &point_analyzer, velocity_colours.as_ref(), render_window, text_renderer, upload_mode),
+            point_cloud_data: PointCloudData::new(&args, &point_analyzer, velocity_colours.as_ref()),
             point_cloud_update: PointCloudUpdate::new(&args),
             args,
             point_analyzer,
@@ -89,8 +177,9 @@ impl ProgramVariables
         // If the point cloud is being updated, then the the cameras will be
         // centred after the first update of the point cloud. This is because logically
         // an initial point cloud will not be provided if the point clouds
-        // are going to be updated
-        if !program_variables.args.using_file_ipc()
+        // are going to be updated. Without either, the points are given later by an
+        // embedding program, which centres the cameras when it does so
+        if !program_variables.args.updating_point_cloud() && !program_variables.args.initial_data_models.is_empty()
         {
             program_variables.centre_views(program_variables.args.display_lidar_pos);
         }
@@ -103,31 +192,160 @@ impl ProgramVariables
     {
         if !self.have_centred_views
         {
-            self.point_analyzer = InitialCloudAnalyzer::new(&Some(self.point_cloud_update.current_content_file.clone()), displaying_lidar_pos);
+            // The initial point cloud files, including stdin which can only be read once, are already
+            // analyzed. Without a file there is nothing to read, such as when points are given by an
+            // embedding program
+            let content_file = &self.point_cloud_update.current_content_file;
+            if !self.args.initial_data_models.contains(content_file) && !content_file.is_empty()
+            {
+                self.replace_point_analyzer(InitialCloudAnalyzer::new(std::slice::from_ref(content_file), displaying_lidar_pos, self.args.parse_settings()));
+            }
+
+            self.position_views_around_cloud();
+        }
+
+        self.have_centred_views = true;
+    }
+
+    /// Centres the camera views on the given points, unless they have already been centred
+    ///
+    /// `points` - the points of the point cloud
+    pub fn centre_views_on_points(&mut self, points: &[TVec3<f32>])
+    {
+        if !self.have_centred_views
+        {
+            self.replace_point_analyzer(InitialCloudAnalyzer::analyze_points(points.to_vec(), false));
+            self.position_views_around_cloud();
+        }
+
+        self.have_centred_views = true;
+    }
 
-            let mut right_pos = self.point_analyzer.get_centre();
-            right_pos -= self.render_data.view_fbos.get_right_fbo().get_camera().get_direction() * self.point_analyzer.get_max_length();
+    /// Centres the camera views on the given points, even if they have already been centred
+    ///
+    /// `points` - the points of the point cloud
+    pub fn recentre_views_on_points(&mut self, points: &[TVec3<f32>])
+    {
+        self.have_centred_views = false;
+        self.centre_views_on_points(points);
+    }
 
-            let mut top_pos = self.point_analyzer.get_centre();
-            top_pos -= self.render_data.view_fbos.get_top_fbo().get_camera().get_direction() * self.point_analyzer.get_max_length();
+    /// Switches between centring the views on all of the points and on the points that are not outliers,
+    /// then recentres the views. Returns true if the outliers are now ignored
+    pub fn toggle_robust_bounds(&mut self) -> bool
+    {
+        let use_robust_bounds = !self.point_analyzer.is_using_robust_bounds();
+        self.point_analyzer.set_use_robust_bounds(use_robust_bounds);
+        self.position_views_around_cloud();
+        use_robust_bounds
+    }
 
-            // The values of "3" were provided as based off of different point
-            // clouds provided, it provided a good offset for the cameras. Worst case
-            // the user moves the camera to a desired location
+    /// Replaces the point analyzer with one for a different point cloud, keeping whether the outliers
+    /// of the point cloud are ignored
+    ///
+    /// `point_analyzer` - the analyzer of the new point cloud
+    fn replace_point_analyzer(&mut self, mut point_analyzer: InitialCloudAnalyzer)
+    {
+        point_analyzer.set_use_robust_bounds(self.point_analyzer.is_using_robust_bounds());
+        self.point_analyzer = point_analyzer;
+    }
 
-            let mut sun_pos = self.point_analyzer.get_centre();
-            sun_pos -= self.render_data.view_fbos.get_sun_fbo().get_sun_direction() * 3.0;
+    /// Places the main camera, the side views and the sun around the point cloud held by the point analyzer
+    fn position_views_around_cloud(&mut self)
+    {
+        // The values of "3" were provided as based off of different point
+        // clouds provided, it provided a good offset for the cameras. Worst case
+        // the user moves the camera to a desired location
+
+        let mut sun_pos = self.point_analyzer.get_centre();
+        sun_pos -= self.render_data.view_fbos.get_sun_fbo().get_sun_direction() * 3.0;
+
+        let camera_distance = 3.0;
+        let mut main_camera_pos = self.point_analyzer.get_centre();
+        main_camera_pos -= self.render_data.camera.get_direction() * camera_distance;
 
-            let mut main_camera_pos = self.point_analyzer.get_centre();
-            main_camera_pos -= self.render_data.camera.get_direction() * 3.0;
+        // Otherwise the far side of large point clouds is cut off. Extra room is left so that the
+        // camera can be moved away from the point cloud without it being cut off straight away
+        let cloud_far_distance = camera_distance + self.point_analyzer.get_max_length();
+        let far_plane = self.render_data.camera.get_far_plane().max(cloud_far_distance * 1.5);
 
-            self.render_data.camera.set_camera_pos(main_camera_pos);
-            self.render_data.view_fbos.hard_set_light_pos(sun_pos, self.point_analyzer.get_centre());
-            self.render_data.view_fbos.hard_set_right_view_pos(right_pos);
-            self.render_data.view_fbos.hard_set_top_view_pos(top_pos);
+        self.render_data.camera.set_camera_pos(main_camera_pos);
+        self.render_data.camera.set_far_plane(far_plane);
+        if let Some(second_camera) = &mut self.render_data.second_camera
+        {
+            second_camera.set_camera_pos(main_camera_pos);
+            second_camera.set_far_plane(far_plane);
         }
+        self.render_data.view_fbos.hard_set_light_pos(sun_pos, self.point_analyzer.get_centre());
+        self.render_data.view_fbos.centre_right_view(self.point_analyzer.get_centre(), self.point_analyzer.get_max_length());
+        self.render_data.view_fbos.centre_top_view(self.point_analyzer.get_centre(), self.point_analyzer.get_max_length());
+    }
+
+    /// Writes the camera and view positions, cluster parameters and display toggles to the given file
+    ///
+    /// `session_file` - the file to write the session to
+    pub fn save_session(&self, session_file: &String) -> Result<(), String>
+    {
+        let view_fbos = &self.render_data.view_fbos;
+
+        let session = Session
+        {
+            main_camera: self.render_data.camera.get_pose(),
+            top_camera: view_fbos.get_top_fbo().get_camera().get_pose(),
+            right_camera: view_fbos.get_right_fbo().get_camera().get_pose(),
+            sun_position: view_fbos.get_sun_fbo().get_sun_position(),
+            sun_look_at: view_fbos.get_sun_fbo().look_at_position(),
+            epsilon: self.point_cloud_data.cluster_information.epsilon,
+            min_num_points: self.point_cloud_data.cluster_information.min_num_points,
+            grid_colour_preset_index: self.render_data.grid_colour_preset_index,
+            cloud_translation: self.render_data.cloud_translation,
+            reflect_vertically: self.render_data.get_reflect_vertically() == -1,
+            show_grid_labels: self.render_data.show_grid_labels,
+            shadows_enabled: self.render_data.shadows_enabled,
+            add_lidar_pos: self.render_data.add_lidar_pos,
+        };
+
+        session.save(session_file)
+    }
+
+    /// Restores a session written by save_session. The views are no longer centred automatically
+    /// afterwards, as that would move them away from the restored positions
+    ///
+    /// `session_file` - the file to read the session from
+    pub fn load_session(&mut self, session_file: &String) -> Result<(), String>
+    {
+        let session = Session::load(session_file)?;
+
+        if session.grid_colour_preset_index >= self.render_data.grid_colour_presets.len()
+        {
+            return Err(format!("Invalid grid colour preset in the session: {}", session.grid_colour_preset_index));
+        }
+
+        self.render_data.camera.set_pose(session.main_camera);
+        self.render_data.view_fbos.set_top_view_pose(session.top_camera);
+        self.render_data.view_fbos.set_right_view_pose(session.right_camera);
+        self.render_data.view_fbos.hard_set_light_pos(session.sun_position, session.sun_look_at);
+
+        let cluster_information = &mut self.point_cloud_data.cluster_information;
+        cluster_information.epsilon = session.epsilon.max(cluster_information.min_epsilon).min(cluster_information.max_epsilon);
+        cluster_information.min_num_points = session.min_num_points.max(1);
+        self.point_cloud_update.cluster_for_most_recent = false;
+
+        self.render_data.grid_colour_preset_index = session.grid_colour_preset_index;
+        self.render_data.buffer_groups.set_grid_colours(self.render_data.grid_colour_presets[session.grid_colour_preset_index])?;
+
+        self.render_data.cloud_translation = session.cloud_translation;
+        if session.reflect_vertically != (self.render_data.get_reflect_vertically() == -1)
+        {
+            self.render_data.reflect_y_axis();
+        }
+
+        self.render_data.show_grid_labels = session.show_grid_labels;
+        self.render_data.shadows_enabled = session.shadows_enabled;
+        self.render_data.add_lidar_pos = session.add_lidar_pos;
 
         self.have_centred_views = true;
+        Ok(())
     }
 }
 
@@ -135,29 +353,174 @@ impl RenderData
 {
     /// Creates the variables required to perform rendering operations in the scene
     ///
+    /// `args` - the arguments passed to the program upon launching it
     /// `point_analyzer` - information about the inital point cloud (if none is provided,
     ///                     the InitialCloudAnalyzer will take that into account
-    fn new(point_analyzer: &InitialCloudAnalyzer) -> RenderData
+    /// `velocity_colours` - the colours of the initial point cloud, if coloured by velocity
+    /// `render_window` - the window being rendered to
+    /// `text_renderer` - the text renderer for the render window
+    /// `upload_mode` - how data is uploaded to the buffers of the scene
+    fn new(args: &Args, point_analyzer: &InitialCloudAnalyzer, velocity_colours: Option<&VelocityColours>, render_window: RenderWindow,
+           text_renderer: TextRendering, upload_mode: UploadMode) -> RenderData
     {
-        let render_window = create_window((1280, 720), "Point Cloud Visualizer".to_string());
-        let (buffer_groups, cube_model_id) = SceneRenderer::setup_scene_renderer(point_analyzer);
+        let initial_alphas = match &args.alpha_file
+        {
+            Some(alpha_file) => match read_point_alphas(alpha_file, point_analyzer.get_initial_points().len())
+            {
+                Ok(i) => Some(i),
+                Err(err) =>
+                    {
+                        eprintln!("{}", err);
+                        exit(-1);
+                    }
+            },
+            None => None
+        };
+
+        // Covariances are in the units of the point cloud file, so the boxes are scaled the same way as
+        // the points. Points without any variance are kept at a tenth of the default point size
+        let initial_shapes = match &args.covariance_file
+        {
+            Some(covariance_file) => match read_point_covariances(covariance_file, point_analyzer.get_initial_points().len())
+            {
+                Ok(i) => Some(i.iter().map(|x| covariance_box_transform(x, 0.005 / args.units_scale) * args.units_scale).collect::<Vec<_>>()),
+                Err(err) =>
+                    {
+                        eprintln!("{}", err);
+                        exit(-1);
+                    }
+            },
+            None => None
+        };
+
+        let initial_scales = args.point_scale_range().map(|x| scalar_point_scales(point_analyzer.get_initial_scalars(), x));
+
+        let edges = match &args.edges_file
+        {
+            Some(edges_file) => match read_edges(edges_file)
+            {
+                Ok(i) => i,
+                Err(err) =>
+                    {
+                        eprintln!("{}", err);
+                        exit(-1);
+                    }
+            },
+            None => Vec::new()
+        };
+
+        let overlay_layout = match &args.overlay_layout_file
+        {
+            Some(layout_file) => match OverlayLayout::from_file(layout_file)
+            {
+                Ok(i) => i,
+                Err(err) =>
+                    {
+                        eprintln!("{}", err);
+                        exit(-1);
+                    }
+            },
+            None => OverlayLayout::new()
+        };
+
+        let camera_path = match &args.camera_path_file
+        {
+            Some(path_file) => match CameraPath::from_file(path_file)
+            {
+                Ok(i) => Some(i),
+                Err(err) =>
+                    {
+                        eprintln!("{}", err);
+                        exit(-1);
+                    }
+            },
+            None => None
+        };
+
+        let initial_attributes = InitialPointAttributes
+        {
+            colours: velocity_colours.map(|x| x.colours.as_slice()),
+            alphas: initial_alphas.as_deref(),
+            shapes: initial_shapes.as_deref(),
+            scales: initial_scales.as_deref(),
+        };
+
+        let (mut buffer_groups, model_ids) = SceneRenderer::setup_scene_renderer(point_analyzer, initial_attributes, &edges, upload_mode, args.grid_colours);
+
+        if let Err(err) = buffer_groups.set_grid_height(args.grid_height)
+        {
+            eprintln!("Failed to upload the grid height: {}", err);
+        }
+
+        if args.ground_plane
+        {
+            buffer_groups.toggle_model_visibility(model_ids.ground_plane);
+        }
+
+        let mut view_fbos = ViewFBO::new(&render_window, SunArc::new(args.sun_elevation, args.sun_arc_degrees_per_second));
+        view_fbos.set_invert_look_y(args.invert_y);
 
         RenderData
         {
             buffer_groups,
-            cube_model_id,
-            text_renderer: TextRendering::new(render_window.get_window_dimensions()),
-            camera: setup_default_camera(&render_window),
-            view_fbos: ViewFBO::new(&render_window),
+            cube_model_id: model_ids.cube,
+            sun_model_id: model_ids.sun,
+            sun_arrow_model_id: model_ids.sun_arrow,
+            plane_model_id: model_ids.plane,
+            ground_plane_model_id: model_ids.ground_plane,
+            text_renderer,
+            camera: setup_default_camera(&render_window, args.dual_view, args.smooth_camera, args.invert_y, args.cursor_smoothing),
+            second_camera: if args.dual_view { Some(setup_default_camera(&render_window, true, args.smooth_camera, args.invert_y, args.cursor_smoothing)) } else { None },
+            second_camera_active: false,
+            view_fbos,
             render_window,
             translation_matrix: setup_translation_matrix(),
             view_selection: ViewSelection::new(),
             cloud_translation: vec3(0.0, 0.0, 0.0),
             reflect_vertically: 1,
-            add_lidar_pos: false
+            add_lidar_pos: false,
+            show_grid_labels: false,
+            help_page: None,
+            overlay_layout,
+            shadows_enabled: !args.no_shadows,
+            show_sun_frustum: false,
+            shadow_offset: args.shadow_offset,
+            x_ray: false,
+            flat_shading: false,
+            anaglyph: args.anaglyph,
+            near_fade: args.near_fade,
+            ground_removal: args.ground_removal.is_some(),
+            // Without a height given, a little above zero removes the ground of point clouds resting on it
+            ground_threshold: args.ground_removal.unwrap_or(0.1),
+            // The colours given when launching the program are the first preset
+            grid_colour_presets: vec![args.grid_colours, GridColours::dark_background(), GridColours::light_background()],
+            grid_colour_preset_index: 0,
+            pose_history: PoseHistory::new(20),
+            right_view_out_of_frame: false,
+            top_view_out_of_frame: false,
+            auto_rotate: AutoRotate::new(args.demo, args.auto_rotate_degrees_per_second),
+            invert_look_y: args.invert_y,
+            spacing_histogram: None,
+            // Frames taking longer than this no longer feel interactive
+            adaptive_quality: if args.adaptive_quality { Some(AdaptiveQuality::new(1.0 / 30.0)) } else { None },
+            gpu_profiler: if args.profile { Some(GpuProfiler::new()) } else { None },
+            camera_path,
+            box_selection: BoxSelection::new(),
+            annotations: Annotations::new(args.annotation_labels.clone()),
+            last_frame_time: Instant::now()
         }
     }
 
+    /// Get the seconds since this function was last called, which is once per frame. Limited to a
+    /// tenth of a second so that a stalled frame does not make smoothly moving cameras jump
+    pub fn update_frame_time(&mut self) -> f32
+    {
+        let now = Instant::now();
+        let delta_time = now.duration_since(self.last_frame_time).as_secs_f32().min(0.1);
+        self.last_frame_time = now;
+        delta_time
+    }
+
     pub fn get_reflect_vertically(&self) -> i32
     {
         self.reflect_vertically
@@ -176,23 +539,39 @@ impl PointCloudUpdate
     /// `args` - the arguments passed to the program upon launching it
     fn new(args: &Args) -> PointCloudUpdate
     {
+        let (sender, receiver) = sync_channel(1);
+        let quit_ipc_thread = Arc::new(Mutex::new(false));
+
+        let playback = match args.playback_dir
+        {
+            Some(ref i) =>
+                {
+                    match Playback::new(i, args.playback_frames_per_second, sender.clone(), args.parse_settings())
+                    {
+                        Ok(i) => Some(i),
+                        Err(err) =>
+                            {
+                                eprintln!("Failed to play back the point clouds in {}: {}", i, err);
+                                exit(-1);
+                            }
+                    }
+                },
+            None => None
+        };
+
         // The initial file is the one containing the initial point cloud
         // or the first file used for updating the point cloud
-        let current_content_file = if let Some(ref i) = args.initial_data_model
-        {
-            i.clone()
-        }
-        else
+        let current_content_file = match (args.initial_data_models.first(), &playback)
         {
-            args.ipc_files[0].data_file_names.clone()
+            (Some(i), _) => i.clone(),
+            (None, Some(i)) => i.get_current_frame_file().clone(),
+            (None, None) => args.ipc_files.first().map(|x| x.data_file_names.clone()).unwrap_or_default()
         };
 
-        let (sender, receiver) = sync_channel(1);
-        let quit_ipc_thread = Arc::new(Mutex::new(false));
-
         if args.using_file_ipc()
         {
-            launch_ipc_contributor(IPCContributor::new(args.ipc_files.clone(), sender.clone(), args.sleep_duration_ms), quit_ipc_thread.clone());
+            launch_ipc_contributor(IPCContributor::new(args.ipc_files.clone(), sender.clone(), args.sleep_duration_ms, args.parse_settings(),
+                                                        args.ipc_handshake.clone()), quit_ipc_thread.clone());
         }
 
         PointCloudUpdate
@@ -201,6 +580,8 @@ impl PointCloudUpdate
             cluster_for_most_recent: false,
             sender,
             receiver,
+            playback,
+            control_receiver: if args.json_control { Some(launch_json_control_reader()) } else { None },
             quit_ipc_thread,
         }
     }
@@ -223,40 +604,143 @@ impl PointCloudData
     /// `args` - the arguments passed into the program when launching it
     /// `point_analyzer` - information about the initial point cloud (if none is provided,
     ///                     the InitialCloudAnalyzer will take that into account
-    fn new(args: &Args, point_analyzer: &InitialCloudAnalyzer) -> PointCloudData
+    /// `velocity_colours` - the colours of the initial point cloud, if coloured by velocity
+    fn new(args: &Args, point_analyzer: &InitialCloudAnalyzer, velocity_colours: Option<&VelocityColours>) -> PointCloudData
     {
         let cluster_information = ClusterInformation
         {
             output_file: "clusterDetectionResult.txt".to_string(),
-            epsilon: 0.05,
-            min_num_points: 20
+            output_format: args.cluster_output_format,
+            backend: args.cluster_backend,
+            epsilon: 0.05_f32.max(args.min_epsilon).min(args.max_epsilon),
+            min_epsilon: args.min_epsilon,
+            max_epsilon: args.max_epsilon,
+            units_scale: args.units_scale,
+            up_axis: args.up_axis,
+            parse_mode: args.parse_mode,
+            min_num_points: 20,
+            // The cluster program reads three values per point, so it is not given files holding velocities
+            // or scalars. It reads a single file, so it is not given one of several files making up the point cloud
+            cluster_displayed: args.cluster_displayed || args.point_columns() != PointColumns::Position || args.initial_data_models.len() > 1,
+            cluster_colour: ClusterColour::new(args.cluster_palette)
         };
 
         PointCloudData
         {
             time_since_update: Instant::now(),
-            pause_updating: false || args.initial_data_model.is_some(),
+            pause_updating: !args.initial_data_models.is_empty(),
             cluster_result_text: "Cluster program status: No Error".to_string(),
+            cluster_result_time: Instant::now(),
+            cluster_program_disabled: false,
+            cluster_parameter_note: None,
+            reload_note: None,
+            frozen_clusters: FrozenClusters::new(point_analyzer.get_initial_points(), velocity_colours.map_or(vec![default_point_colour(); point_analyzer.get_initial_points().len()], |x| x.colours.clone())),
+            point_smoothing: PointSmoothing::new(args.smoothing_factor, args.smoothing_distance),
+            update_heatmap: UpdateHeatmap::new(args.heatmap_voxel_size, args.heatmap_decay),
+            point_filter: None,
             num_points_cloud: point_analyzer.get_initial_points().len(),
             cluster_information,
+            epsilon_suggestion: None,
+            velocity_legend: velocity_colours.map(|x| x.to_string_legend()),
+            last_cluster_summary: None,
+            last_cluster_assignments: None,
             position: point_analyzer.get_initial_lidar_pos()
         }
     }
 }
 
+/// Messages sent from the thread reading the initial point cloud
+enum LoadingProgress
+{
+    PointsRead(usize),
+    Done(InitialCloudAnalyzer),
+}
+
+/// Reads the initial point cloud on a separate thread, showing how many points have been read in the
+/// window until the point cloud has been completely read
+///
+/// `args` - the arguments passed to the program upon launching it
+/// `render_window` - the window to show the progress in
+/// `text_renderer` - the text renderer for the render window
+fn load_initial_point_cloud(args: &Args, render_window: &mut RenderWindow, text_renderer: &mut TextRendering) -> InitialCloudAnalyzer
+{
+    // Nothing to read, so there is no reason to show progress
+    if args.initial_data_models.is_empty()
+    {
+        return InitialCloudAnalyzer::new(&args.initial_data_models, args.display_lidar_pos, args.parse_settings());
+    }
+
+    let (sender, receiver) = channel();
+    let initial_data_models = args.initial_data_models.clone();
+    let display_lidar_pos = args.display_lidar_pos;
+    let parse_settings = args.parse_settings();
+
+    thread::spawn(move ||
+        {
+            let point_analyzer = InitialCloudAnalyzer::new_with_progress(&initial_data_models, display_lidar_pos, parse_settings,
+                                                                         |x| { let _ = sender.send(LoadingProgress::PointsRead(x)); });
+            let _ = sender.send(LoadingProgress::Done(point_analyzer));
+        });
+
+    let mut points_read = 0;
+
+    loop
+    {
+        render_window.poll_events();
+        if render_window.should_close()
+        {
+            exit(0);
+        }
+
+        loop
+        {
+            match receiver.try_recv()
+            {
+                Ok(LoadingProgress::PointsRead(i)) => points_read = i,
+                Ok(LoadingProgress::Done(point_analyzer)) => return point_analyzer,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) =>
+                    {
+                        eprintln!("Failed to read the initial point cloud");
+                        exit(-1);
+                    }
+            }
+        }
+
+        unsafe
+            {
+                gl::Viewport(0, 0, render_window.get_window_dimensions().0, render_window.get_window_dimensions().1);
+                gl::ClearColor(0.15, 0.15, 0.15, 1.0);
+                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+            }
+
+        text_renderer.update_window_dimensions(render_window.get_window_dimensions());
+        text_renderer.buffer_text_for_rendering(format!("Loading... {} points", points_read), vec2(0.4, 0.5), 40);
+        text_renderer.render_buffered_text();
+        render_window.swap_buffers();
+
+        // No need to redraw any faster than the progress could reasonably be read
+        thread::sleep(Duration::from_millis(16));
+    }
+}
+
 /// Creates a window of the given size and title
 ///
 /// `window_size` - the size the window should have
 /// `window_title` - the title the created window should have
-pub fn create_window(window_size: (u32, u32), window_tile: String) -> RenderWindow
+/// `window_icon` - the PNG file to use as the icon of the window, if any
+/// `multisampling` - true if the window should be multisampled, which alpha to coverage requires
+/// `alpha_channel` - true if the window should store the alpha of what is rendered
+/// `compat_buffers` - true if data is uploaded with glBufferSubData, which does not need OpenGL 4.4
+pub fn create_window(window_size: (u32, u32), window_tile: String, window_icon: Option<&String>, multisampling: bool, alpha_channel: bool,
+                     compat_buffers: bool) -> RenderWindow
 {
-    let window_hints = if cfg!(debug_assertions)
+    let mut window_hints = if cfg!(debug_assertions)
     {
         vec!
         [
             // Only have debug mode if the program as a whole is compiled in debug mode
             glfw::WindowHint::OpenGlDebugContext(true),
-            glfw::WindowHint::ContextVersion(4, 5),
             glfw::WindowHint::OpenGlProfile(OpenGlProfileHint::Core)
         ]
     }
@@ -264,16 +748,32 @@ pub fn create_window(window_size: (u32, u32), window_tile: String) -> RenderWind
     {
         vec!
         [
-            glfw::WindowHint::ContextVersion(4, 5),
             glfw::WindowHint::OpenGlProfile(OpenGlProfileHint::Core)
         ]
     };
 
+    // OpenGL 4.3 is the oldest version the shaders are written for. It is enough for compatible buffers,
+    // and is otherwise only used if the driver cannot create a 4.5 context; which buffers are used is
+    // then decided by probing the version that was created
+    let context_versions: &[(u32, u32)] = if compat_buffers { &[(4, 3)] } else { &[(4, 5), (4, 3)] };
+
+    if multisampling
+    {
+        window_hints.push(glfw::WindowHint::Samples(Some(4)));
+    }
+
+    if alpha_channel
+    {
+        window_hints.push(glfw::WindowHint::AlphaBits(Some(8)));
+    }
+
     let render_window = RenderWindow::new
         (
             window_size,
             window_tile,
+            window_icon,
             window_hints,
+            context_versions,
         );
 
     // These are known to be needed later in the program
@@ -281,6 +781,11 @@ pub fn create_window(window_size: (u32, u32), window_tile: String) -> RenderWind
         {
             gl::Enable(gl::DEPTH_TEST);
             gl::Enable(gl::STENCIL_TEST);
+
+            if multisampling
+            {
+                gl::Enable(gl::MULTISAMPLE);
+            }
         }
 
     render_window
@@ -290,17 +795,35 @@ pub fn create_window(window_size: (u32, u32), window_tile: String) -> RenderWind
 /// updated to be centred around the point cloud
 ///
 /// `render_window` - the window being used for rendering
-pub fn setup_default_camera(render_window: &RenderWindow) -> Camera
+/// `dual_view` - true if the camera renders to half of the scene
+/// `smooth_movement` - true if the camera should speed up and slow down when moved
+/// `invert_look_y` - true if the vertical rotation of the camera should be inverted
+pub fn setup_default_camera(render_window: &RenderWindow, dual_view: bool, smooth_movement: bool, invert_look_y: bool, cursor_smoothing: usize) -> Camera
 {
-    Camera::new(CameraType::Perspective(PerspectiveParam
+    // Keeps the scene from looking horizontally squashed when it is split between two cameras
+    let window_dimensions = if dual_view
+    {
+        (render_window.get_window_dimensions().0 / 2, render_window.get_window_dimensions().1)
+    }
+    else
     {
-        window_dimensions: render_window.get_window_dimensions(),
+        render_window.get_window_dimensions()
+    };
+
+    let mut camera = Camera::new(CameraType::Perspective(PerspectiveParam
+    {
+        window_dimensions,
         near_plane: 0.1,
         far_plane: 100.0,
         position: vec3(0.0, 0.0, 0.0),
         direction: vec3(1.0, 0.0, 0.0),
         up: vec3(0.0, 1.0, 0.0),
-    }))
+    }));
+
+    camera.set_smooth_movement(smooth_movement);
+    camera.set_invert_look_y(invert_look_y);
+    camera.set_cursor_smoothing(cursor_smoothing);
+    camera
 }
 
 /// Creates the matrix used
@@ -0,0 +1,86 @@
+/// Describes what a key, or a combination of keys, does
+pub struct KeyBinding
+{
+    pub keys: &'static str,
+    pub action: &'static str,
+}
+
+/// All of the input the program responds to, in the order shown in the help overlay. When input
+/// handling is added or changed, this table has to be updated as well
+pub const KEY_BINDINGS: &[KeyBinding] = &
+[
+    KeyBinding{ keys: "W A S D Q E", action: "Move the main camera, or the selected view" },
+    KeyBinding{ keys: "Middle mouse", action: "Hold and move the cursor to rotate the main camera" },
+    KeyBinding{ keys: "Ctrl + move", action: "Move and rotate the camera ten times slower, for precise positioning" },
+    KeyBinding{ keys: "K", action: "Toggle orbiting the camera around the point cloud centre" },
+    KeyBinding{ keys: "Right click", action: "Orbit the camera around the clicked point" },
+    KeyBinding{ keys: "'", action: "Pin an annotation to the point under the cursor (--annotation_labels)" },
+    KeyBinding{ keys: "Shift + '", action: "Remove all annotations" },
+    KeyBinding{ keys: "Left drag", action: "Select the points inside the dragged out rectangle; click to deselect" },
+    KeyBinding{ keys: "Y", action: "Invert the vertical rotation of the cameras (--invert_y)" },
+    KeyBinding{ keys: "T", action: "Toggle automatically orbiting the camera (--demo)" },
+    KeyBinding{ keys: "Left click", action: "Select or deselect a side view" },
+    KeyBinding{ keys: "Ctrl + Tab", action: "Select the next view: main, top, right, sun, sun look at" },
+    KeyBinding{ keys: "=", action: "Make the selected side view mirror the main camera, or stop mirroring it" },
+    KeyBinding{ keys: "Home", action: "Recentre the selected side view, or those out of frame" },
+    KeyBinding{ keys: "Shift + Home", action: "Switch the selected view between a perspective and an orthographic projection" },
+    KeyBinding{ keys: "Click scene", action: "Select the half of the scene to control (--dual_view)" },
+    KeyBinding{ keys: "1 2 3", action: "Scroll the sun look at position along x, y or z" },
+    KeyBinding{ keys: "Shift + scroll", action: "Scroll the sun look at position along the sun direction" },
+    KeyBinding{ keys: "U", action: "Reset the sun to above the point cloud" },
+    KeyBinding{ keys: "4 5 6 7 8 9", action: "Place the sun along +x, -x, +y, -y, +z or -z of the point cloud" },
+    KeyBinding{ keys: "- Shift + -", action: "Step the sun forward or back along its arc (--sun_elevation)" },
+    KeyBinding{ keys: "0", action: "Start or stop sweeping the sun along its arc (--sun_arc_speed)" },
+    KeyBinding{ keys: "Ctrl + Z", action: "Undo the last main camera movement" },
+    KeyBinding{ keys: "Z X", action: "Decrease or increase the cluster epsilon" },
+    KeyBinding{ keys: "M", action: "Suggest an epsilon from the point spacing, or reject the suggestion" },
+    KeyBinding{ keys: "Enter", action: "Apply the suggested epsilon" },
+    KeyBinding{ keys: "V B", action: "Decrease or increase the minimum points of a cluster" },
+    KeyBinding{ keys: "C", action: "Find clusters in the paused point cloud" },
+    KeyBinding{ keys: "N", action: "Toggle the nearest neighbour distance histogram" },
+    KeyBinding{ keys: "P", action: "Pause or resume point cloud updates" },
+    KeyBinding{ keys: "Shift + P", action: "Toggle colouring points by how often their region changes (--heatmap_decay)" },
+    KeyBinding{ keys: "Space", action: "Play or pause the playback (--playback_dir)" },
+    KeyBinding{ keys: "Left Right", action: "Step the playback back or forward a frame" },
+    KeyBinding{ keys: "Down Up", action: "Seek the playback back or forward a tenth" },
+    KeyBinding{ keys: "F", action: "Freeze the cluster colours" },
+    KeyBinding{ keys: "Shift + F", action: "Toggle drawing points in their exact colours, without lighting" },
+    KeyBinding{ keys: ";", action: "Toggle smoothing point positions over updates (--smoothing_factor)" },
+    KeyBinding{ keys: "R", action: "Re-enable the cluster program after it was not found" },
+    KeyBinding{ keys: "Ctrl + R", action: "Reload the point cloud file and recentre the views" },
+    KeyBinding{ keys: "`", action: "Toggle ignoring outliers when centring the views (--robust_bounds)" },
+    KeyBinding{ keys: "Backspace", action: "Clear the cluster program status" },
+    KeyBinding{ keys: "F10", action: "Print the parameters and cluster sizes of the last clusters found" },
+    KeyBinding{ keys: "Shift + F10", action: "Export the cluster of each point of the last clusters found (--cluster_export)" },
+    KeyBinding{ keys: "F1 F2", action: "Move the point cloud along the x-axis" },
+    KeyBinding{ keys: "F3 F4", action: "Move the point cloud along the z-axis" },
+    KeyBinding{ keys: "F5 F6", action: "Move the point cloud along the y-axis" },
+    KeyBinding{ keys: "F7", action: "Reflect the point cloud vertically" },
+    KeyBinding{ keys: "Tab", action: "Add the lidar position to the shown positions" },
+    KeyBinding{ keys: "G", action: "Toggle the grid labels" },
+    KeyBinding{ keys: "Shift + G", action: "Toggle hiding the points below the ground height (--ground_removal)" },
+    KeyBinding{ keys: "Shift + PageUp PageDown", action: "Raise or lower the ground height" },
+    KeyBinding{ keys: "I", action: "Toggle the overlay text and compass (--overlay_layout)" },
+    KeyBinding{ keys: "H", action: "Toggle shadows" },
+    KeyBinding{ keys: "Shift + H", action: "Toggle drawing the volume the sun casts shadows in" },
+    KeyBinding{ keys: "Insert", action: "Toggle drawing the sun" },
+    KeyBinding{ keys: "Delete", action: "Toggle drawing the arrow at the sun look at position" },
+    KeyBinding{ keys: "End", action: "Toggle drawing the side views and the shadow map" },
+    KeyBinding{ keys: "\\", action: "Toggle the ground plane under the point cloud (--ground_plane)" },
+    KeyBinding{ keys: ", .", action: "Decrease or increase the slope offset of the shadows (--shadow_offset)" },
+    KeyBinding{ keys: "Shift + , .", action: "Decrease or increase the constant offset of the shadows" },
+    KeyBinding{ keys: "[ ]", action: "Decrease or increase the distance points are fully faded in at (--near_fade)" },
+    KeyBinding{ keys: "Shift + [ ]", action: "Decrease or increase the distance points closer than are hidden" },
+    KeyBinding{ keys: "J", action: "Toggle seeing points hidden behind other points" },
+    KeyBinding{ keys: "Shift + J", action: "Toggle red and cyan anaglyph 3D (--anaglyph, --eye_separation)" },
+    KeyBinding{ keys: "L", action: "Cycle the grid colours" },
+    KeyBinding{ keys: "PageUp PageDown", action: "Raise or lower the grid (--grid_height)" },
+    KeyBinding{ keys: "F8 F9", action: "Save or restore the session (--session_file)" },
+    KeyBinding{ keys: "O", action: "Export the displayed point cloud (--export_on_key)" },
+    KeyBinding{ keys: "Shift + O", action: "Export the points inside the view of the camera" },
+    KeyBinding{ keys: "F11", action: "Start or stop moving the camera along the camera path (--camera_path)" },
+    KeyBinding{ keys: "F12", action: "Export the main and side views as PNGs (--view_export_prefix)" },
+    KeyBinding{ keys: "Shift + F12", action: "Capture and export the point positions computed by the vertex shader" },
+    KeyBinding{ keys: "/", action: "Show the next page of this help, or hide it after the last page" },
+    KeyBinding{ keys: "Escape", action: "Quit (--exit_key, --no_escape_quit)" },
+];
\ No newline at end of file
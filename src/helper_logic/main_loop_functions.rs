@@ -1,15 +1,44 @@
+use std::path::Path;
 use std::time::Instant;
 use glfw::{Action, Key, MouseButton};
-use nalgebra_glm::{TVec3, vec2, vec3};
-use crate::helper_logic::initialization_functions::RenderData;
-use crate::rendering::scene_renderer::{SceneRenderer, ModelId, UploadInformation};
+use nalgebra_glm::{distance, TVec2, TVec3, vec2, vec3};
+use crate::cluster_logic::epsilon_estimate::suggest_epsilon;
+use crate::cluster_logic::frozen_clusters::FrozenClusters;
+use crate::cluster_logic::spacing_histogram::SpacingHistogram;
+use crate::helper_logic::cloud_export::{export_cluster_assignments, export_point_cloud, frustum_export_file_name};
+use crate::helper_logic::image_export::write_png;
+use crate::helper_logic::initialization_functions::{ProgramVariables, RenderData};
+use crate::helper_logic::key_bindings::KEY_BINDINGS;
+use crate::helper_logic::overlay_layout::{OverlayElement, OverlayLayout};
+use crate::helper_logic::status_output::print_status;
+use crate::rendering::scene_renderer::{SceneRenderer, ModelId, UploadInformation, default_point_colour, selection_colour};
 use crate::rendering::camera::Camera;
-use crate::ipc_logic::ipc_content_logic::{ClusterInformation, IPCProcessingArgs, IPCUpdateResult, launch_cluster_program, process_ipc_content, read_cluster_output_file};
+use crate::rendering::draw_functions::OutsideParam;
+use crate::rendering::gpu_profiler::{GpuProfiler, ProfileSection, profile_section};
+use crate::rendering::position_capture::{expected_point_positions, max_deviation, POINT_LIFT};
+use crate::ipc_logic::playback::Playback;
+use crate::ipc_logic::ipc_receiver::UpAxis;
+use crate::ipc_logic::ipc_content_logic::{ClusterAssignments, ClusterBackend, ClusterInformation, ClusterProgramError, ClusterSummary, IPCProcessingArgs, IPCUpdateResult, find_clusters_internally, launch_cluster_program, process_ipc_content, read_cluster_output_file};
 use crate::rendering::text_rendering::TextRendering;
 use crate::rendering::view_fbo::ViewFBO;
+use crate::view_logic::annotations::Annotations;
+use crate::view_logic::box_selection::points_in_rect;
+use crate::view_logic::compass::{compass_labels, facing_axis};
+use crate::view_logic::point_picking::pick_point;
+use crate::view_logic::point_smoothing::PointSmoothing;
+use crate::view_logic::update_heatmap::UpdateHeatmap;
+use crate::view_logic::pose_history::PoseHistory;
 use crate::view_logic::view_selection::ViewSelection;
+use crate::view_port_constants::{SCENE_VIEWPORT_HEIGHT_OFFSET, ViewLayout};
 use crate::window::RenderWindow;
 
+/// How far the sun is placed from the point cloud when moved around it; the same distance it is placed
+/// at when the views are centred
+const SUN_DISTANCE: f32 = 3.0;
+
+/// How many key bindings fit on one page of the help overlay, with each row being a fixed height
+const HELP_ROWS_PER_PAGE: usize = 22;
+
 /// Required parameters to write program information
 /// to the window
 pub struct TextWriteParam<'a>
@@ -17,14 +46,42 @@ pub struct TextWriteParam<'a>
     pub text_renderer: &'a mut TextRendering,
     pub view_fbos: &'a ViewFBO,
     pub camera: &'a mut Camera,
+    pub second_camera: Option<&'a Camera>,
     pub time_update: &'a Instant,
     pub render_window: &'a RenderWindow,
     pub num_points: usize,
+    pub content_file: &'a str,
     pub cluster_result_text: &'a str,
     pub epsilon: f32,
+    pub cluster_parameter_note: &'a Option<(String, Instant)>,
+    pub reload_note: &'a Option<(String, Instant)>,
     pub min_num_points: u32,
     pub lidar_pos: Option<TVec3<f32>>,
-    pub add_lidar_pos: bool
+    pub add_lidar_pos: bool,
+    pub clusters_frozen: bool,
+    pub points_smoothed: bool,
+    pub updates_paused: Option<bool>,
+    pub show_grid_labels: bool,
+    pub help_page: Option<usize>,
+    pub overlay_layout: &'a OverlayLayout,
+    pub units_scale: f32,
+    pub cloud_warning: Option<&'a str>,
+    pub playback_position: Option<String>,
+    pub right_view_out_of_frame: bool,
+    pub top_view_out_of_frame: bool,
+    pub sun_look_at_selected: bool,
+    pub spacing_histogram: Option<&'a SpacingHistogram>,
+    pub epsilon_suggestion: Option<f32>,
+    pub velocity_legend: Option<&'a str>,
+    pub near_fade: (f32, f32),
+    pub quality_text: Option<String>,
+    pub grid_height: f32,
+    pub ground_threshold: Option<f32>,
+    pub shadow_offset: Option<(f32, f32)>,
+    pub gpu_profiler: Option<&'a GpuProfiler>,
+    pub camera_path_progress: Option<String>,
+    pub selected_points: Option<String>,
+    pub annotations: &'a Annotations
 }
 
 /// Required parameters to process a new update
@@ -33,9 +90,11 @@ pub struct HandleIPCUpdate<'a>
 {
     pub ipc_args: IPCProcessingArgs<'a>,
     pub lidar_pos: &'a mut Option<TVec3<f32>>,
+    pub velocity_legend: &'a mut Option<String>,
     pub num_cloud_points: &'a mut usize,
     pub time_since_update: &'a mut Instant,
     pub cluster_result_text: &'a mut String,
+    pub cluster_result_time: &'a mut Instant,
     pub current_content_file: &'a mut String
 }
 
@@ -46,10 +105,28 @@ pub struct HandleClusterUpdate<'a>
     pub buffer_update_content: &'a ClusterInformation,
     pub cube_model_id: ModelId,
     pub cluster_result_text: &'a mut String,
-    pub current_content_file: &'a mut String
+    pub cluster_result_time: &'a mut Instant,
+    pub current_content_file: &'a mut String,
+    pub cluster_program_disabled: &'a mut bool,
+    pub frozen_clusters: &'a mut FrozenClusters,
+    pub last_cluster_summary: &'a mut Option<ClusterSummary>,
+    pub last_cluster_assignments: &'a mut Option<ClusterAssignments>,
+    /// The height below which the displayed points are left out of the clustering, if the ground is removed
+    pub ground_threshold: Option<f32>
+}
+
+/// Places the side views for the current size of the window, so that they keep their aspect ratio
+/// when the window is resized
+///
+/// `render_variables` - the variables holding the side views and the window
+pub fn update_view_layout(render_variables: &mut RenderData)
+{
+    let layout = ViewLayout::new(render_variables.render_window.get_window_dimensions());
+    render_variables.view_selection.update_layout(&layout);
 }
 
-/// Checks if any of the views of the scene have been selected
+/// Checks if any of the views of the scene have been selected, either by clicking on them or by
+/// cycling through them with Control + Tab
 ///
 /// `view_selection` - struct containing all of the scene views
 /// `fbos` - struct containing the scene view's FBOs
@@ -57,259 +134,2051 @@ pub struct HandleClusterUpdate<'a>
 /// `render_window` - the window being rendered to
 pub fn check_for_view_selection(view_selection: &mut ViewSelection, fbos: &mut ViewFBO, camera: &mut Camera, render_window: &RenderWindow)
 {
-    if render_window.get_cursor_button_history().iter().find(|x| **x == (MouseButton::Button1, Action::Press)).is_some()
+    let clicked = render_window.get_cursor_button_history().iter().find(|x| **x == (MouseButton::Button1, Action::Press)).is_some();
+    let cycled = render_window.is_control_down() && render_window.get_key_input().iter().find(|x| **x == (Key::Tab, Action::Press)).is_some();
+
+    if clicked || cycled
     {
-        view_selection.update_view_selection(render_window);
+        if clicked
+        {
+            view_selection.update_view_selection(render_window);
+        }
+        else
+        {
+            view_selection.select_next_view();
+        }
 
         // Depending on the state of the program, the movement of a camera
         // can still occur even after a different view is selected. This
         // fixes that. These are also placed here as left-clicking should
-        // terminate existing camera movement, regardless of where left-click is
+        // terminate existing camera movement, regardless of where left-click is.
+        // The same applies to selecting the next view with Control + Tab
         fbos.reset_movement_key_status();
         camera.clear_movement_key();
     }
 }
 
-/// Updates the position of the camera based off of the input of the user
+/// Selects which half of the scene user input applies to when the scene is split between two cameras.
+/// The camera that is used is the one whose half of the scene was last clicked on
 ///
-/// `view_selection` - struct that handles the state of what view is selected
-/// `fbos` - struct containing the scene view's FBOs
-/// `camera` - the main scene camea
-/// `render_window` - the window being rendered to
-pub fn update_camera_movement(view_selection: &mut ViewSelection, fbos: &mut ViewFBO, camera: &mut Camera, render_window: &RenderWindow)
+/// `render_variables` - the variables holding the scene cameras
+pub fn check_active_camera(render_variables: &mut RenderData)
 {
-    if view_selection.is_any_view_selected()
+    let second_camera = match &mut render_variables.second_camera
+    {
+        Some(i) => i,
+        None => return
+    };
+
+    let clicked = render_variables.render_window.get_cursor_button_history().iter()
+        .find(|x| **x == (MouseButton::Button1, Action::Press) || **x == (MouseButton::Button2, Action::Press) ||
+               **x == (MouseButton::Button3, Action::Press)).is_some();
+    if !clicked
     {
-        fbos.update_camera_movement(view_selection, render_window);
+        return;
     }
-    else
+
+    // The cursor position has its origin in the top left of the window, whereas the scene viewport
+    // is placed starting from the bottom of the window
+    let (cursor_x, cursor_y) = render_variables.render_window.get_latest_cursor_pos();
+    let window_dimensions = render_variables.render_window.get_window_dimensions();
+    let scene_width = window_dimensions.0 as f32 * ViewLayout::new(window_dimensions).scene_width;
+    let scene_height = window_dimensions.1 as f32 * (1.0 - SCENE_VIEWPORT_HEIGHT_OFFSET);
+
+    if cursor_x < 0 || cursor_x as f32 > scene_width || cursor_y < 0 || cursor_y as f32 > scene_height
     {
-        Camera::update_camera_movement(&render_window, camera);
-        Camera::update_camera_rotation(&render_window, camera);
+        return;
     }
-}
 
-/// Reflects the point cloud along the y-axis
-pub fn reflect_point_cloud(render_variables: &mut RenderData)
-{
-    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F7, Action::Press)).is_some()
+    let second_camera_active = cursor_x as f32 >= scene_width / 2.0;
+    if second_camera_active != render_variables.second_camera_active
     {
-        render_variables.reflect_y_axis();
+        // Otherwise the camera that is no longer active keeps moving with no way of stopping it
+        render_variables.camera.clear_movement_key();
+        render_variables.camera.set_rotation_button_status(false);
+        second_camera.clear_movement_key();
+        second_camera.set_rotation_button_status(false);
     }
+
+    render_variables.second_camera_active = second_camera_active;
 }
 
-/// Toggles adding the lidar to the various camera positions text representations. This does not move
-/// the cameras physically
-pub fn add_lidar_pos(render_variables: &mut RenderData)
+/// Toggles orbiting the active scene camera around the centre of the point cloud. Right clicking the
+/// scene makes the camera orbit around the point under the cursor instead
+///
+/// `render_variables` - the variables holding the scene cameras and the rendered point cloud
+/// `cloud_centre` - the centre of the point cloud, as read from the point cloud file
+pub fn update_orbit_pivot(render_variables: &mut RenderData, cloud_centre: TVec3<f32>)
 {
-    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::Tab, Action::Press)).is_some()
+    let toggled = render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::K, Action::Press)).is_some();
+    let picked = render_variables.render_window.get_cursor_button_history().iter().find(|x| **x == (MouseButton::Button2, Action::Press)).is_some();
+    if !toggled && !picked
     {
-        render_variables.add_lidar_pos = !render_variables.add_lidar_pos;
+        return;
     }
-}
 
-/// Handles changes to parameters passed into the cluster detection algorithm
-pub fn update_cluster_information(cluster_information: &mut ClusterInformation, cluster_for_most_recent: &mut bool, render_window: &RenderWindow)
-{
-    if render_window.get_key_input().iter().find(|x| **x == (Key::Z, Action::Press)).is_some() ||
-        render_window.get_key_input().iter().find(|x| **x == (Key::Z, Action::Repeat)).is_some()
+    let reflect_vertically = render_variables.get_reflect_vertically();
+    let cloud_translation = render_variables.cloud_translation;
+    let to_world = |point: TVec3<f32>| cloud_point_to_world(point, cloud_translation, reflect_vertically);
+
+    let dual_view = render_variables.second_camera.is_some();
+    let second_camera_active = render_variables.second_camera_active;
+    let camera = select_active_camera(&mut render_variables.camera, &mut render_variables.second_camera, second_camera_active);
+
+    if toggled
     {
-        cluster_information.epsilon = (cluster_information.epsilon - 0.05).max(0.0);
-        *cluster_for_most_recent = false;
+        let pivot = if camera.get_orbit_pivot().is_some() { None } else { Some(to_world(cloud_centre)) };
+        camera.set_orbit_pivot(pivot);
     }
 
-    if render_window.get_key_input().iter().find(|x| **x == (Key::X, Action::Press)).is_some() ||
-        render_window.get_key_input().iter().find(|x| **x == (Key::X, Action::Repeat)).is_some()
+    if !picked
     {
-        cluster_information.epsilon += 0.05;
-        *cluster_for_most_recent = false;
+        return;
     }
 
-    if render_window.get_key_input().iter().find(|x| **x == (Key::V, Action::Press)).is_some() ||
-        render_window.get_key_input().iter().find(|x| **x == (Key::V, Action::Repeat)).is_some()
+    let cursor_pos = render_variables.render_window.get_latest_cursor_pos();
+    if !cursor_in_scene(&render_variables.render_window, cursor_pos)
     {
-        cluster_information.min_num_points = (cluster_information.min_num_points - 1).max(1);
-        *cluster_for_most_recent = false;
+        return;
     }
 
-    if render_window.get_key_input().iter().find(|x| **x == (Key::B, Action::Press)).is_some() ||
-        render_window.get_key_input().iter().find(|x| **x == (Key::B, Action::Repeat)).is_some()
+    let ndc = scene_cursor_ndc(&render_variables.render_window, cursor_pos, dual_view, second_camera_active);
+    let (ray_origin, ray_direction) = camera.screen_ray(ndc);
+    let (cloud_points, _) = render_variables.buffer_groups.get_instance_information(render_variables.cube_model_id);
+
+    if let Some(point) = pick_point(ray_origin, ray_direction, cloud_points.iter().map(|x| to_world(*x)))
     {
-        cluster_information.min_num_points += 1;
-        *cluster_for_most_recent = false;
+        camera.set_orbit_pivot(Some(point));
     }
 }
 
-/// Updates the point cloud based off of the update provided the IPC mechanism
+/// Pins an annotation to the point under the cursor when the apostrophe key is pressed, or removes all
+/// of the annotations when it is pressed with Shift held
 ///
-/// `args` - struct containing the variables required to handle an IPC update
-pub fn update_point_cloud(args: HandleIPCUpdate)
+/// `render_variables` - the variables holding the scene cameras and the rendered point cloud
+pub fn update_annotations(render_variables: &mut RenderData)
 {
-    // This reads the update of the IPC and then launches the cluster program (at this point it is the
-    // same effect as the "update_static_point_cloud_clusters" function below
-    match process_ipc_content(args.ipc_args)
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::Apostrophe, Action::Press)).is_none()
     {
-        IPCUpdateResult::Success(i) =>
-            {
-                if let Some(new_lidar_file) = i.updated_lidar_file
-                {
-                    *args.time_since_update = Instant::now();
-                    *args.current_content_file = new_lidar_file;
-                }
+        return;
+    }
 
-                if let Some(num_points) = i.num_points
-                {
-                    *args.num_cloud_points = num_points;
-                }
+    if render_variables.render_window.is_shift_down()
+    {
+        render_variables.annotations.clear();
+        return;
+    }
 
-                *args.lidar_pos = i.lidar_pos;
-                *args.cluster_result_text = i.cluster_error_message;
+    let cursor_pos = render_variables.render_window.get_latest_cursor_pos();
+    if !cursor_in_scene(&render_variables.render_window, cursor_pos)
+    {
+        return;
+    }
+
+    let reflect_vertically = render_variables.get_reflect_vertically();
+    let cloud_translation = render_variables.cloud_translation;
+
+    let dual_view = render_variables.second_camera.is_some();
+    let second_camera_active = render_variables.second_camera_active;
+    let camera = select_active_camera(&mut render_variables.camera, &mut render_variables.second_camera, second_camera_active);
+
+    let ndc = scene_cursor_ndc(&render_variables.render_window, cursor_pos, dual_view, second_camera_active);
+    let (ray_origin, ray_direction) = camera.screen_ray(ndc);
+    let (cloud_points, _) = render_variables.buffer_groups.get_instance_information(render_variables.cube_model_id);
+
+    match pick_point(ray_origin, ray_direction, cloud_points.iter().map(|x| cloud_point_to_world(*x, cloud_translation, reflect_vertically)))
+    {
+        Some(point) =>
+            {
+                let label = render_variables.annotations.add(point);
+                print_status(format!("Added annotation {} at {:.3}, {:.3}, {:.3}", label, point.x, point.y, point.z));
             },
-        IPCUpdateResult::Error(err) => *args.cluster_result_text = err,
-        IPCUpdateResult::NoChange => {}
+        None => print_status("No point under the cursor to annotate")
     }
 }
 
-/// Updates the point cloud clusters for a point cloud
+/// Selects the points inside the rectangle dragged out over the scene with the left mouse button by
+/// highlighting them. Clicking the scene without dragging deselects the points
 ///
-/// `args` - struct holding the variables required to update a point cloud's clusters
-pub fn update_point_cloud_clusters(args: HandleClusterUpdate)
+/// `render_variables` - the variables holding the scene cameras and the rendered point cloud
+pub fn update_box_selection(render_variables: &mut RenderData)
 {
-    match launch_cluster_program(args.buffer_update_content, args.current_content_file)
+    let (translations, colours) = render_variables.buffer_groups.get_instance_information(render_variables.cube_model_id);
+    render_variables.box_selection.discard_if_replaced(colours);
+
+    let cursor_pos = render_variables.render_window.get_latest_cursor_pos();
+    let button_history = render_variables.render_window.get_cursor_button_history();
+
+    if button_history.iter().find(|x| **x == (MouseButton::Button1, Action::Press)).is_some() && cursor_in_scene(&render_variables.render_window, cursor_pos)
+    {
+        render_variables.box_selection.start_drag(vec2(cursor_pos.0 as f32, cursor_pos.1 as f32));
+    }
+
+    if button_history.iter().find(|x| **x == (MouseButton::Button1, Action::Release)).is_none() || !render_variables.box_selection.is_dragging()
     {
-        Ok(_) =>
+        return;
+    }
+
+    let new_colours = match render_variables.box_selection.finish_drag(vec2(cursor_pos.0 as f32, cursor_pos.1 as f32))
+    {
+        Some((corner, opposite_corner)) =>
             {
-                match read_cluster_output_file(args.buffer_update_content)
+                let reflect_vertically = render_variables.get_reflect_vertically();
+                let cloud_translation = render_variables.cloud_translation;
+                let dual_view = render_variables.second_camera.is_some();
+                let second_camera_active = render_variables.second_camera_active;
+
+                let camera = match &render_variables.second_camera
                 {
-                    Ok(colours) =>
-                        {
-                            args.buffer_groups.upload_instance_information(vec![UploadInformation
-                            {
-                                model_id: args.cube_model_id,
-                                instance_translations: None,
-                                instance_colours: Some(&colours)
-                            }]);
-
-                            *args.cluster_result_text = "Cluster program status: No Error".to_string();
-                        },
-                    Err(err) => *args.cluster_result_text = err,
-                }
-            }
-        Err(err) => *args.cluster_result_text = err,
+                    Some(i) if second_camera_active => i,
+                    _ => &render_variables.camera
+                };
+
+                // The rectangle is in the part of the scene of the active camera, which is where the points are projected to
+                let to_ndc = |x: TVec2<f32>| scene_cursor_ndc(&render_variables.render_window, (x.x as i32, x.y as i32), dual_view, second_camera_active);
+                let screen_positions = translations.iter().map(|x| camera.world_to_screen(&cloud_point_to_world(*x, cloud_translation, reflect_vertically)));
+
+                let selected = points_in_rect(to_ndc(corner), to_ndc(opposite_corner), screen_positions);
+                Some(render_variables.box_selection.select(selected, colours, selection_colour()))
+            },
+        None => render_variables.box_selection.clear()
+    };
+
+    if let Some(new_colours) = new_colours
+    {
+        let upload_result = render_variables.buffer_groups.upload_instance_information(vec![UploadInformation
+        {
+            model_id: render_variables.cube_model_id,
+            instance_translations: None,
+            instance_colours: Some(&new_colours),
+            instance_alphas: None,
+            instance_shapes: None,
+            instance_scales: None
+        }]);
+
+        if let Err(err) = upload_result
+        {
+            eprintln!("Failed to upload the colours of the selected points: {}", err);
+        }
     }
 }
 
-/// Determines if the window should be closed due to the input of the user
+/// Get the corners of the rectangle being dragged out to select points, in Normalized Device
+/// Coordinates of the whole scene
 ///
-/// `render_window` - the window being rendered
-pub fn check_window_close(render_window: &mut RenderWindow)
+/// `render_variables` - the variables holding the selection and the window
+pub fn selection_rect_ndc(render_variables: &RenderData) -> Option<(TVec2<f32>, TVec2<f32>)>
+{
+    let cursor_pos = render_variables.render_window.get_latest_cursor_pos();
+    let to_ndc = |x: TVec2<f32>| scene_cursor_ndc(&render_variables.render_window, (x.x as i32, x.y as i32), false, false);
+
+    render_variables.box_selection.drag_rect(vec2(cursor_pos.0 as f32, cursor_pos.1 as f32))
+        .map(|(corner, opposite_corner)| (to_ndc(corner), to_ndc(opposite_corner)))
+}
+
+/// Check if the cursor is over the scene, rather than over the side views or the information text
+///
+/// `render_window` - the window being rendered to
+/// `cursor_pos` - the position of the cursor, in window coordinates
+fn cursor_in_scene(render_window: &RenderWindow, cursor_pos: (i32, i32)) -> bool
+{
+    let window_dimensions = render_window.get_window_dimensions();
+    let scene_width = window_dimensions.0 as f32 * ViewLayout::new(window_dimensions).scene_width;
+    let scene_height = window_dimensions.1 as f32 * (1.0 - SCENE_VIEWPORT_HEIGHT_OFFSET);
+
+    cursor_pos.0 >= 0 && cursor_pos.0 as f32 <= scene_width && cursor_pos.1 >= 0 && cursor_pos.1 as f32 <= scene_height
+}
+
+/// Converts a cursor position to Normalized Device Coordinates of the part of the scene a camera
+/// renders to
+///
+/// `render_window` - the window being rendered to
+/// `cursor_pos` - the position of the cursor, in window coordinates
+/// `dual_view` - true if the scene is split between two cameras
+/// `second_camera_active` - true if the coordinates are for the half of the second camera
+fn scene_cursor_ndc(render_window: &RenderWindow, cursor_pos: (i32, i32), dual_view: bool, second_camera_active: bool) -> TVec2<f32>
 {
-    if render_window.get_key_input().iter().find(|x| **x == (Key::Escape, Action::Press)).is_some()
+    // The cursor position has its origin in the top left of the window, whereas the scene viewport
+    // is placed starting from the bottom of the window and is as tall as the window
+    let window_dimensions = render_window.get_window_dimensions();
+    let scene_width = window_dimensions.0 as f32 * ViewLayout::new(window_dimensions).scene_width;
+
+    let (viewport_x, viewport_width) = match (dual_view, second_camera_active)
     {
-        render_window.set_window_should_close(true);
-    }
+        (true, true) => (scene_width / 2.0, scene_width / 2.0),
+        (true, false) => (0.0, scene_width / 2.0),
+        _ => (0.0, scene_width)
+    };
+
+    let window_height = window_dimensions.1 as f32;
+    vec2((cursor_pos.0 as f32 - viewport_x) / viewport_width * 2.0 - 1.0,
+         (window_height - cursor_pos.1 as f32 - window_height * SCENE_VIEWPORT_HEIGHT_OFFSET) / window_height * 2.0 - 1.0)
 }
 
-/// Updates the position of the point cloud from where it was originally placed
+/// Toggles slowly orbiting the main camera around the point cloud with the T key, and rotates the
+/// camera if it is turned on. The camera orbits around its orbit pivot if it has one, otherwise around
+/// the centre of the point cloud. Any user input suspends the rotation for a few seconds
 ///
-/// `render_variables` - struct holding the required variables for rendering
-pub fn change_point_cloud_position(render_variables: &mut RenderData)
+/// `render_variables` - variables required to render the scene
+/// `cloud_centre` - the centre of the point cloud, as read from the point cloud file
+pub fn update_auto_rotate(render_variables: &mut RenderData, cloud_centre: TVec3<f32>)
 {
-    let move_amount = 0.05;
+    let render_window = &render_variables.render_window;
+    if render_window.get_key_input().iter().find(|x| **x == (Key::T, Action::Press)).is_some()
+    {
+        render_variables.auto_rotate.toggle();
+    }
 
-    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F2, Action::Press)).is_some() ||
-        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F2, Action::Repeat)).is_some()
+    // Turning the rotation on should not suspend it straight away
+    let now = Instant::now();
+    let user_input = render_window.get_key_input().iter().any(|x| x.0 != Key::T) || !render_window.get_cursor_button_history().is_empty()
+        || !render_window.get_scroll_history().is_empty() || render_variables.camera.is_moving();
+
+    if user_input
     {
-        render_variables.cloud_translation.x += move_amount;
+        render_variables.auto_rotate.suspend(now);
     }
 
-    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F1, Action::Press)).is_some() ||
-        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F1, Action::Repeat)).is_some()
+    if let Some(degrees) = render_variables.auto_rotate.angle_since_last_update(now)
     {
-        render_variables.cloud_translation.x -= move_amount;
+        let pivot = render_variables.camera.get_orbit_pivot()
+            .unwrap_or(cloud_point_to_world(cloud_centre, render_variables.cloud_translation, render_variables.get_reflect_vertically()));
+
+        render_variables.camera.orbit_around(pivot, degrees);
     }
+}
 
-    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F4, Action::Press)).is_some() ||
-        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F4, Action::Repeat)).is_some()
+/// Checks whether the point cloud can be seen in the top and right views, and recentres a side view
+/// on the point cloud when the Home key is pressed. The selected side view is recentred; if neither
+/// is selected, every side view that cannot see the point cloud is recentred
+///
+/// `render_variables` - variables required to render the scene
+/// `cloud_centre` - the centre of the point cloud, as read from the point cloud file
+/// `cloud_max_length` - the largest extent of the point cloud
+pub fn check_side_views_framing(render_variables: &mut RenderData, cloud_centre: TVec3<f32>, cloud_max_length: f32)
+{
+    let reflect_vertically = render_variables.get_reflect_vertically();
+    let cloud_translation = render_variables.cloud_translation;
+
+    if !render_variables.render_window.is_shift_down() && render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::Home, Action::Press)).is_some()
     {
-        render_variables.cloud_translation.z += move_amount;
+        let centre = cloud_point_to_world(cloud_centre, cloud_translation, reflect_vertically);
+        let selection = &render_variables.view_selection;
+        let no_side_view_selected = !selection.get_right_view_selected() && !selection.get_top_view_selected();
+
+        if selection.get_right_view_selected() || (no_side_view_selected && render_variables.right_view_out_of_frame)
+        {
+            render_variables.view_fbos.centre_right_view(centre, cloud_max_length);
+        }
+
+        if selection.get_top_view_selected() || (no_side_view_selected && render_variables.top_view_out_of_frame)
+        {
+            render_variables.view_fbos.centre_top_view(centre, cloud_max_length);
+        }
     }
 
-    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F3, Action::Press)).is_some() ||
-        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F3, Action::Repeat)).is_some()
+    let (cloud_points, _) = render_variables.buffer_groups.get_instance_information(render_variables.cube_model_id);
+
+    // Without any points there is nothing that could be out of frame
+    if cloud_points.is_empty()
     {
-        render_variables.cloud_translation.z -= move_amount;
+        render_variables.right_view_out_of_frame = false;
+        render_variables.top_view_out_of_frame = false;
+        return;
     }
 
-    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F6, Action::Press)).is_some() ||
-        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F6, Action::Repeat)).is_some()
+    let world_points = || cloud_points.iter().map(|x| cloud_point_to_world(*x, cloud_translation, reflect_vertically));
+
+    render_variables.right_view_out_of_frame = !render_variables.view_fbos.get_right_fbo().get_camera().any_position_in_view(world_points());
+    render_variables.top_view_out_of_frame = !render_variables.view_fbos.get_top_fbo().get_camera().any_position_in_view(world_points());
+}
+
+/// Switches the selected view between a perspective and an orthographic projection when Shift + Home
+/// is pressed. The orthographic projection is sized to show the point cloud at about the size the
+/// perspective projection did
+///
+/// `render_variables` - variables required to render the scene
+/// `cloud_centre` - the centre of the point cloud, as read from the point cloud file
+pub fn check_toggle_view_projection(render_variables: &mut RenderData, cloud_centre: TVec3<f32>)
+{
+    if !render_variables.render_window.is_shift_down() || render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::Home, Action::Press)).is_none()
     {
-        render_variables.cloud_translation.y += move_amount;
+        return;
     }
 
-    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F5, Action::Press)).is_some() ||
-        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F5, Action::Repeat)).is_some()
+    let centre = cloud_point_to_world(cloud_centre, render_variables.cloud_translation, render_variables.get_reflect_vertically());
+
+    if let Some(camera) = render_variables.view_fbos.get_mut_selected_camera(&render_variables.view_selection)
     {
-        render_variables.cloud_translation.y -= move_amount;
+        if camera.is_perspective()
+        {
+            camera.set_orthographic(distance(&camera.get_position(), &centre));
+        }
+        else
+        {
+            camera.set_perspective();
+        }
+
+        print_status(format!("Selected view projection: {}", if camera.is_perspective() { "perspective" } else { "orthographic" }));
     }
 }
 
-/// Updates the given variable to indicate if updates to the point cloud should be paused
+/// Returns the scene camera that user input should be applied to
 ///
-/// `pause_updating` - variable holding whether or not to update the point cloud
-/// `render_window` - the window being rendered to
-pub fn check_pause_updates(pause_updating: &mut bool, render_window: &RenderWindow)
+/// `camera` - the main scene camera
+/// `second_camera` - the camera rendering the second half of the scene, if the scene is split
+/// `second_camera_active` - true if user input applies to the second camera
+pub fn select_active_camera<'a>(camera: &'a mut Camera, second_camera: &'a mut Option<Camera>, second_camera_active: bool) -> &'a mut Camera
 {
-    if render_window.get_key_input().iter().find(|x| **x == (Key::P, Action::Press)).is_some()
+    match second_camera
     {
-        *pause_updating = !*pause_updating;
+        Some(i) if second_camera_active => i,
+        _ => camera
     }
 }
 
-/// Writes the information about the scene to the window
+/// Updates the position of the camera based off of the input of the user. The Equal key makes the
+/// selected side view mirror the camera, and the side views mirroring the camera are moved with it
 ///
-/// `param` - the variables required to render scene information text
-pub fn write_scene_info(param: TextWriteParam)
+/// `view_selection` - struct that handles the state of what view is selected
+/// `fbos` - struct containing the scene view's FBOs
+/// `camera` - the main scene camea
+/// `pose_history` - the history of the camera's poses, if movements of the camera can be undone
+/// `render_window` - the window being rendered to
+/// `delta_time` - the seconds since the previous frame
+pub fn update_camera_movement(view_selection: &mut ViewSelection, fbos: &mut ViewFBO, camera: &mut Camera,
+                              pose_history: Option<&mut PoseHistory>, render_window: &RenderWindow, delta_time: f32)
 {
-    param.text_renderer.update_window_dimensions(param.render_window.get_window_dimensions());
-    param.text_renderer.buffer_text_for_rendering(format!("NP: {:.2}", (param.num_points as f32 / 1000.0)), vec2(0.025, 0.15), 30);
-
-    if param.time_update.elapsed().as_secs() < 10
+    if view_selection.is_any_view_selected()
     {
-        param.text_renderer.buffer_text_for_rendering(format!("TU:  {:.2}s", (param.time_update.elapsed().as_millis() as f32 / 1000.0)), vec2(0.025, 0.1), 30);
+        fbos.update_camera_movement(view_selection, render_window, delta_time);
     }
     else
     {
-        param.text_renderer.buffer_text_for_rendering("TU: > 10s", vec2(0.025, 0.1), 30);
+        Camera::update_camera_movement(render_window, camera, delta_time);
+        Camera::update_camera_rotation(&render_window, camera);
     }
 
-    let lidar_pos = match (param.add_lidar_pos, param.lidar_pos)
+    if render_window.get_key_input().iter().find(|x| **x == (Key::Equal, Action::Press)).is_some()
     {
-        (true, Some(i)) => i,
-        _ => vec3(0.0, 0.0, 0.0)
-    };
+        fbos.toggle_mirror_main_camera(view_selection);
+    }
 
-    param.text_renderer.buffer_text_for_rendering("MP:  ".to_string() + &param.camera.to_string_pos(lidar_pos), vec2(0.2, 0.15), 30);
-    param.text_renderer.buffer_text_for_rendering("MD: ".to_string() + &param.camera.to_string_direction(), vec2(0.2, 0.1), 30);
-    param.text_renderer.buffer_text_for_rendering(param.cluster_result_text, vec2(0.025, 0.025), 80);
-    param.text_renderer.buffer_text_for_rendering("Epsilon: ".to_string() + &format!("{:.2}", param.epsilon), vec2(0.715, 0.025), 15);
-    param.text_renderer.buffer_text_for_rendering("Min points: ".to_string() + &param.min_num_points.to_string(), vec2(0.85, 0.025), 15);
-    param.view_fbos.buffer_write_fbo_information(param.text_renderer, lidar_pos);
+    fbos.mirror_main_camera(camera);
 
-    let lidar_pos_text = if let Some(lidar_pos) = param.lidar_pos
+    if let Some(pose_history) = pose_history
     {
-        let mut string = format!("LP: {:.1}   {:.1}   {:.1}", lidar_pos.x, lidar_pos.y, lidar_pos.z);
-        if param.add_lidar_pos
+        if render_window.is_control_down() && render_window.get_key_input().iter().find(|x| **x == (Key::Z, Action::Press)).is_some()
         {
-            string += " (Added)";
+            if let Some(pose) = pose_history.undo()
+            {
+                camera.set_pose(pose);
+            }
+        }
+        else
+        {
+            pose_history.record(camera.get_pose(), camera.is_moving());
         }
-        string
     }
-    else
+}
+
+/// Reflects the point cloud along the y-axis
+pub fn reflect_point_cloud(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F7, Action::Press)).is_some()
     {
-        "LP: N/A".to_string()
-    };
-    param.text_renderer.buffer_text_for_rendering(lidar_pos_text, vec2(0.025, 0.2), 30);
+        render_variables.reflect_y_axis();
+    }
+}
+
+/// Toggles adding the lidar to the various camera positions text representations. This does not move
+/// the cameras physically
+pub fn add_lidar_pos(render_variables: &mut RenderData)
+{
+    // Control + Tab selects the next view instead
+    if !render_variables.render_window.is_control_down() && render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::Tab, Action::Press)).is_some()
+    {
+        render_variables.add_lidar_pos = !render_variables.add_lidar_pos;
+    }
+}
+
+/// Toggles rendering the world coordinates of the major grid lines
+pub fn toggle_grid_labels(render_variables: &mut RenderData)
+{
+    // Shift + G toggles removing the ground instead
+    if !render_variables.render_window.is_shift_down() &&
+        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::G, Action::Press)).is_some()
+    {
+        render_variables.show_grid_labels = !render_variables.show_grid_labels;
+    }
+}
+
+/// Shows the help overlay listing all of the input the program responds to, advances it to its
+/// next page, or hides it after its last page
+pub fn toggle_help(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::Slash, Action::Press)).is_some()
+    {
+        render_variables.help_page = next_help_page(render_variables.help_page);
+    }
+}
 
-    param.text_renderer.render_buffered_text();
+/// Get the help page shown after the given one, or None if the help is hidden afterwards
+///
+/// `help_page` - the currently shown help page, if any
+fn next_help_page(help_page: Option<usize>) -> Option<usize>
+{
+    match help_page
+    {
+        None => Some(0),
+        Some(page) => Some(page + 1).filter(|x| *x < num_help_pages())
+    }
+}
+
+/// Get the number of pages needed to list all of the key bindings in the help overlay
+fn num_help_pages() -> usize
+{
+    KEY_BINDINGS.len().div_ceil(HELP_ROWS_PER_PAGE)
+}
+
+/// Toggles hiding the text overlay elements whose placement is given by the overlay layout
+pub fn toggle_overlay(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::I, Action::Press)).is_some()
+    {
+        render_variables.overlay_layout.toggle_hidden();
+    }
+}
+
+/// Toggles rendering the shadows of the point cloud, or with Shift held, toggles drawing the view
+/// frustum of the sun that the shadows are found in
+pub fn toggle_shadows(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::H, Action::Press)).is_some()
+    {
+        if render_variables.render_window.is_shift_down()
+        {
+            render_variables.show_sun_frustum = !render_variables.show_sun_frustum;
+        }
+        else
+        {
+            render_variables.shadows_enabled = !render_variables.shadows_enabled;
+        }
+    }
+}
+
+/// Hides or shows the models drawn around the point cloud, for a cleaner view of the points. Insert
+/// toggles the sun, Delete toggles the arrow at the sun look at position, End toggles the view planes
+/// showing the side views and the shadow map and Backslash toggles the ground plane under the point cloud
+pub fn toggle_model_visibility(render_variables: &mut RenderData)
+{
+    let key_inputs = render_variables.render_window.get_key_input();
+
+    for (key, model_id) in [(Key::Insert, render_variables.sun_model_id), (Key::Delete, render_variables.sun_arrow_model_id), (Key::End, render_variables.plane_model_id),
+        (Key::Backslash, render_variables.ground_plane_model_id)].iter()
+    {
+        if key_inputs.iter().find(|x| **x == (*key, Action::Press)).is_some()
+        {
+            render_variables.buffer_groups.toggle_model_visibility(*model_id);
+        }
+    }
+}
+
+/// Moves the sun to look at the point cloud from directly above, which is the default lighting, or
+/// from any of the six directions along the world axes
+///
+/// `render_variables` - the variables holding the sun and the key input
+/// `cloud_centre` - the centre of the point cloud
+pub fn snap_sun_position(render_variables: &mut RenderData, cloud_centre: TVec3<f32>)
+{
+    let snap_directions =
+    [
+        (Key::U, vec3(0.0, 1.0, 0.0)),
+        (Key::Num4, vec3(1.0, 0.0, 0.0)),
+        (Key::Num5, vec3(-1.0, 0.0, 0.0)),
+        (Key::Num6, vec3(0.0, 1.0, 0.0)),
+        (Key::Num7, vec3(0.0, -1.0, 0.0)),
+        (Key::Num8, vec3(0.0, 0.0, 1.0)),
+        (Key::Num9, vec3(0.0, 0.0, -1.0)),
+    ];
+
+    let key_input = render_variables.render_window.get_key_input();
+    if let Some((_, direction)) = snap_directions.iter().find(|(key, _)| key_input.contains(&(*key, Action::Press)))
+    {
+        render_variables.view_fbos.hard_set_light_pos(cloud_centre + direction * SUN_DISTANCE, cloud_centre);
+    }
+}
+
+/// Moves the sun along an arc around the point cloud at a fixed elevation, for studying how shadows
+/// change over a day. The minus key steps the sun forward along the arc, or back with Shift held, and
+/// the 0 key starts or stops sweeping the sun along the arc over time
+///
+/// `render_variables` - the variables holding the sun and the key input
+/// `cloud_centre` - the centre of the point cloud
+pub fn update_sun_arc(render_variables: &mut RenderData, cloud_centre: TVec3<f32>)
+{
+    let step_degrees = 15.0;
+
+    let render_window = &render_variables.render_window;
+    let sun = render_variables.view_fbos.get_mut_sun_fbo();
+
+    let mut moved = false;
+    if render_window.get_key_input().iter().find(|x| **x == (Key::Minus, Action::Press) || **x == (Key::Minus, Action::Repeat)).is_some()
+    {
+        sun.get_mut_arc().step(if render_window.is_shift_down() { -step_degrees } else { step_degrees });
+        moved = true;
+    }
+
+    if render_window.get_key_input().iter().find(|x| **x == (Key::Num0, Action::Press)).is_some()
+    {
+        sun.get_mut_arc().toggle_sweep();
+        moved = true;
+    }
+
+    // Always advanced, so that time before a sweep starts is not swept through
+    moved |= sun.get_mut_arc().advance(Instant::now());
+
+    if moved && sun.get_arc().is_active()
+    {
+        sun.place_on_arc(cloud_centre, SUN_DISTANCE);
+    }
+}
+
+/// Toggles drawing all of the points of the point cloud, including those hidden behind other points
+pub fn toggle_x_ray(render_variables: &mut RenderData)
+{
+    if !render_variables.render_window.is_shift_down() && render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::J, Action::Press)).is_some()
+    {
+        render_variables.x_ray = !render_variables.x_ray;
+    }
+}
+
+/// Toggles showing the main view in red and cyan anaglyph 3D, to be seen with red and cyan glasses
+pub fn toggle_anaglyph(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.is_shift_down() && render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::J, Action::Press)).is_some()
+    {
+        render_variables.anaglyph = !render_variables.anaglyph;
+    }
+}
+
+/// Toggles drawing the points of the main view in their colour as given, without lighting or shadows,
+/// so that colours such as those of clusters are seen exactly
+pub fn toggle_flat_shading(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.is_shift_down() && render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F, Action::Press)).is_some()
+    {
+        render_variables.flat_shading = !render_variables.flat_shading;
+    }
+}
+
+/// Records how long the last frame took, so that fewer points are drawn while frames are slow when
+/// adaptive quality is enabled
+///
+/// `render_variables` - struct holding the adaptive quality
+/// `delta_time` - how long the last frame took, in seconds
+pub fn update_adaptive_quality(render_variables: &mut RenderData, delta_time: f32)
+{
+    if let Some(adaptive_quality) = &mut render_variables.adaptive_quality
+    {
+        adaptive_quality.record_frame_time(delta_time);
+    }
+}
+
+/// Changes the distances from the camera between which points fade in. The right bracket moves the
+/// distance points are fully visible at further away, and the left bracket moves it closer. With Shift
+/// held, the distance points start being visible at is moved instead
+pub fn update_near_fade(render_variables: &mut RenderData)
+{
+    let key_pressed = |key: Key| render_variables.render_window.get_key_input().iter()
+        .find(|x| **x == (key, Action::Press) || **x == (key, Action::Repeat)).is_some();
+
+    let change = if key_pressed(Key::RightBracket)
+    {
+        0.25
+    }
+    else if key_pressed(Key::LeftBracket)
+    {
+        -0.25
+    }
+    else
+    {
+        return;
+    };
+
+    let (start, end) = &mut render_variables.near_fade;
+    if render_variables.render_window.is_shift_down()
+    {
+        *start = (*start + change).max(0.0).min(*end);
+    }
+    else
+    {
+        *end = (*end + change).max(*start);
+    }
+}
+
+/// Changes how far the depths of the shadow map are pushed away from the sun, to remove shadow acne
+/// without detaching shadows from the points casting them. The period increases the offset scaled by
+/// the slope of the faces and the comma decreases it. With Shift held, the constant offset is changed instead
+pub fn update_shadow_offset(render_variables: &mut RenderData)
+{
+    let key_pressed = |key: Key| render_variables.render_window.get_key_input().iter()
+        .find(|x| **x == (key, Action::Press) || **x == (key, Action::Repeat)).is_some();
+
+    let direction = if key_pressed(Key::Period)
+    {
+        1.0
+    }
+    else if key_pressed(Key::Comma)
+    {
+        -1.0
+    }
+    else
+    {
+        return;
+    };
+
+    let (factor, units) = &mut render_variables.shadow_offset;
+    if render_variables.render_window.is_shift_down()
+    {
+        *units = (*units + direction).max(0.0);
+    }
+    else
+    {
+        *factor = (*factor + direction * 0.25).max(0.0);
+    }
+}
+
+/// Shows a histogram of the distance between points and their nearest neighbour, or hides it. The
+/// histogram is calculated from the point cloud at the time it is shown
+pub fn toggle_spacing_histogram(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::N, Action::Press)).is_some()
+    {
+        if render_variables.spacing_histogram.is_some()
+        {
+            render_variables.spacing_histogram = None;
+            return;
+        }
+
+        // Enough points for a representative histogram, while staying quick to calculate
+        let max_samples = 5000;
+        let num_bins = 10;
+
+        let (translations, _) = render_variables.buffer_groups.get_instance_information(render_variables.cube_model_id);
+        render_variables.spacing_histogram = SpacingHistogram::new(translations, max_samples, num_bins);
+
+        if render_variables.spacing_histogram.is_none()
+        {
+            eprintln!("At least two points are needed to show the nearest neighbour distances");
+        }
+    }
+}
+
+/// Inverts the vertical rotation of all of the cameras, or restores it
+pub fn toggle_invert_look_y(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::Y, Action::Press)).is_some()
+    {
+        render_variables.invert_look_y = !render_variables.invert_look_y;
+
+        render_variables.camera.set_invert_look_y(render_variables.invert_look_y);
+        if let Some(second_camera) = render_variables.second_camera.as_mut()
+        {
+            second_camera.set_invert_look_y(render_variables.invert_look_y);
+        }
+        render_variables.view_fbos.set_invert_look_y(render_variables.invert_look_y);
+    }
+}
+
+/// Changes the colours of the world grid to the next preset
+pub fn cycle_grid_colours(render_variables: &mut RenderData)
+{
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::L, Action::Press)).is_some()
+    {
+        render_variables.grid_colour_preset_index = (render_variables.grid_colour_preset_index + 1) % render_variables.grid_colour_presets.len();
+
+        if let Err(err) = render_variables.buffer_groups.set_grid_colours(render_variables.grid_colour_presets[render_variables.grid_colour_preset_index])
+        {
+            eprintln!("Failed to upload the grid colours: {}", err);
+        }
+    }
+}
+
+/// Moves the world grid up with Page Up and down with Page Down, to line it up with the ground of
+/// the point cloud
+pub fn update_grid_height(render_variables: &mut RenderData)
+{
+    // With Shift held, the height the ground is removed below is changed instead
+    if render_variables.render_window.is_shift_down()
+    {
+        return;
+    }
+
+    let key_pressed = |key: Key| render_variables.render_window.get_key_input().iter()
+        .find(|x| **x == (key, Action::Press) || **x == (key, Action::Repeat)).is_some();
+
+    let change = if key_pressed(Key::PageUp)
+    {
+        0.1
+    }
+    else if key_pressed(Key::PageDown)
+    {
+        -0.1
+    }
+    else
+    {
+        return;
+    };
+
+    let grid_height = render_variables.buffer_groups.get_grid_height() + change;
+    if let Err(err) = render_variables.buffer_groups.set_grid_height(grid_height)
+    {
+        eprintln!("Failed to upload the grid height: {}", err);
+    }
+}
+
+/// Toggles hiding the points below the ground threshold with Shift + G, and raises or lowers the
+/// threshold with Shift + Page Up and Shift + Page Down
+pub fn update_ground_removal(render_variables: &mut RenderData)
+{
+    if !render_variables.render_window.is_shift_down()
+    {
+        return;
+    }
+
+    let key_pressed = |key: Key| render_variables.render_window.get_key_input().iter()
+        .find(|x| **x == (key, Action::Press) || **x == (key, Action::Repeat)).is_some();
+
+    let toggle_pressed = render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::G, Action::Press)).is_some();
+    let raise_pressed = key_pressed(Key::PageUp);
+    let lower_pressed = key_pressed(Key::PageDown);
+
+    if toggle_pressed
+    {
+        render_variables.ground_removal = !render_variables.ground_removal;
+    }
+
+    if raise_pressed
+    {
+        render_variables.ground_threshold += 0.1;
+    }
+    else if lower_pressed
+    {
+        render_variables.ground_threshold -= 0.1;
+    }
+}
+
+/// Handles changes to parameters passed into the cluster detection algorithm
+///
+/// `cluster_information` - the parameters for the cluster detection
+/// `cluster_for_most_recent` - variable holding if clusters were found for the current parameters
+/// `cluster_parameter_note` - set to a message, and the time it was set, if a parameter reaches its limit
+/// `render_window` - the window being rendered to
+pub fn update_cluster_information(cluster_information: &mut ClusterInformation, cluster_for_most_recent: &mut bool,
+                                  cluster_parameter_note: &mut Option<(String, Instant)>, render_window: &RenderWindow)
+{
+    // Control + Z undoes camera movements instead
+    if !render_window.is_control_down() &&
+        (render_window.get_key_input().iter().find(|x| **x == (Key::Z, Action::Press)).is_some() ||
+        render_window.get_key_input().iter().find(|x| **x == (Key::Z, Action::Repeat)).is_some())
+    {
+        cluster_information.epsilon -= 0.05;
+        if cluster_information.epsilon <= cluster_information.min_epsilon
+        {
+            cluster_information.epsilon = cluster_information.min_epsilon;
+            *cluster_parameter_note = Some((format!("Epsilon at minimum ({})", cluster_information.min_epsilon), Instant::now()));
+        }
+        *cluster_for_most_recent = false;
+    }
+
+    if render_window.get_key_input().iter().find(|x| **x == (Key::X, Action::Press)).is_some() ||
+        render_window.get_key_input().iter().find(|x| **x == (Key::X, Action::Repeat)).is_some()
+    {
+        cluster_information.epsilon += 0.05;
+        if cluster_information.epsilon >= cluster_information.max_epsilon
+        {
+            cluster_information.epsilon = cluster_information.max_epsilon;
+            *cluster_parameter_note = Some((format!("Epsilon at maximum ({})", cluster_information.max_epsilon), Instant::now()));
+        }
+        *cluster_for_most_recent = false;
+    }
+
+    if render_window.get_key_input().iter().find(|x| **x == (Key::V, Action::Press)).is_some() ||
+        render_window.get_key_input().iter().find(|x| **x == (Key::V, Action::Repeat)).is_some()
+    {
+        cluster_information.min_num_points = (cluster_information.min_num_points - 1).max(1);
+        *cluster_for_most_recent = false;
+    }
+
+    if render_window.get_key_input().iter().find(|x| **x == (Key::B, Action::Press)).is_some() ||
+        render_window.get_key_input().iter().find(|x| **x == (Key::B, Action::Repeat)).is_some()
+    {
+        cluster_information.min_num_points += 1;
+        *cluster_for_most_recent = false;
+    }
+}
+
+/// Suggests an epsilon for the cluster detection from the distances between the displayed points and
+/// their neighbours, and applies or discards the suggestion. The suggestion is shown until either
+/// is done
+///
+/// `render_variables` - the variables used for rendering, holding the displayed points
+/// `cluster_information` - the parameters for the cluster detection
+/// `epsilon_suggestion` - the epsilon waiting to be applied or discarded
+/// `cluster_for_most_recent` - variable holding if clusters were found for the current parameters
+pub fn check_suggest_epsilon(render_variables: &RenderData, cluster_information: &mut ClusterInformation,
+                             epsilon_suggestion: &mut Option<f32>, cluster_for_most_recent: &mut bool)
+{
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::M, Action::Press)).is_some()
+    {
+        if epsilon_suggestion.is_some()
+        {
+            *epsilon_suggestion = None;
+            return;
+        }
+
+        // Same sample size as the nearest neighbour histogram, which is quick enough to not stall a frame
+        let max_samples = 5000;
+
+        let (translations, _) = render_variables.buffer_groups.get_instance_information(render_variables.cube_model_id);
+        *epsilon_suggestion = suggest_epsilon(translations, cluster_information.min_num_points as usize, max_samples);
+
+        if epsilon_suggestion.is_none()
+        {
+            eprintln!("More than {} points are needed to suggest an epsilon", cluster_information.min_num_points);
+        }
+    }
+
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::Enter, Action::Press)).is_some()
+    {
+        if let Some(suggestion) = epsilon_suggestion.take()
+        {
+            cluster_information.epsilon = suggestion.max(cluster_information.min_epsilon).min(cluster_information.max_epsilon);
+            *cluster_for_most_recent = false;
+        }
+    }
+}
+
+/// Updates the point cloud based off of the update provided the IPC mechanism
+///
+/// `args` - struct containing the variables required to handle an IPC update
+pub fn update_point_cloud(args: HandleIPCUpdate)
+{
+    // This reads the update of the IPC and then launches the cluster program (at this point it is the
+    // same effect as the "update_static_point_cloud_clusters" function below
+    match process_ipc_content(args.ipc_args)
+    {
+        IPCUpdateResult::Success(i) =>
+            {
+                if let Some(new_lidar_file) = i.updated_lidar_file
+                {
+                    *args.time_since_update = Instant::now();
+                    *args.current_content_file = new_lidar_file;
+                }
+
+                if let Some(num_points) = i.num_points
+                {
+                    *args.num_cloud_points = num_points;
+                }
+
+                *args.lidar_pos = i.lidar_pos;
+                *args.velocity_legend = i.velocity_legend;
+                *args.cluster_result_text = i.cluster_error_message;
+                *args.cluster_result_time = Instant::now();
+            },
+        IPCUpdateResult::Error(err) =>
+            {
+                *args.cluster_result_text = err;
+                *args.cluster_result_time = Instant::now();
+            },
+        IPCUpdateResult::NoChange => {}
+    }
+}
+
+/// Updates the point cloud clusters for a point cloud, returning if clusters were found. If the external
+/// cluster program was previously found to be missing, no clustering is attempted with it
+///
+/// `args` - struct holding the variables required to update a point cloud's clusters
+pub fn update_point_cloud_clusters(args: HandleClusterUpdate) -> bool
+{
+    // The displayed points are written in the layout of a point cloud file, so that the cluster
+    // program reads them the same way as the file they came from. Points of the removed ground are
+    // left out, so that clusters only form from the points above it
+    let (content_file, kept_points) = if args.buffer_update_content.cluster_displayed
+    {
+        let displayed_file = std::env::temp_dir().join("point_cloud_visualizer_displayed.txt").to_string_lossy().to_string();
+        let (translations, _) = args.buffer_groups.get_instance_information(args.cube_model_id);
+
+        let kept_points = args.ground_threshold.map(|threshold| translations.iter().map(|x| x.y >= threshold).collect::<Vec<bool>>());
+        let clustered_points = match &kept_points
+        {
+            Some(kept_points) => translations.iter().zip(kept_points).filter(|(_, x)| **x).map(|(x, _)| *x).collect(),
+            None => translations.to_vec()
+        };
+
+        if let Err(err) = export_point_cloud(&displayed_file, &clustered_points, &[], args.buffer_update_content.units_scale, args.buffer_update_content.up_axis)
+        {
+            *args.cluster_result_text = "Failed to write the displayed points for cluster detection: ".to_string() + &err;
+            *args.cluster_result_time = Instant::now();
+            return false;
+        }
+
+        (displayed_file, kept_points)
+    }
+    else
+    {
+        (args.current_content_file.clone(), None)
+    };
+
+    let cluster_result = match args.buffer_update_content.backend
+    {
+        ClusterBackend::External =>
+            {
+                if *args.cluster_program_disabled
+                {
+                    return false;
+                }
+
+                match launch_cluster_program(args.buffer_update_content, &content_file)
+                {
+                    Ok(_) => read_cluster_output_file(args.buffer_update_content),
+                    Err(ClusterProgramError::NotFound(err)) =>
+                        {
+                            *args.cluster_program_disabled = true;
+                            Err(err)
+                        },
+                    Err(ClusterProgramError::Failed(err)) => Err(err),
+                }
+            },
+        ClusterBackend::Internal => find_clusters_internally(args.buffer_update_content, &content_file)
+    };
+
+    let (clusters_found, cluster_result_text) = match cluster_result
+    {
+        Ok(cluster_result) =>
+            {
+                let cluster_result = match &kept_points
+                {
+                    Some(kept_points) => cluster_result.include_removed_points(kept_points, default_point_colour()),
+                    None => cluster_result
+                };
+
+                *args.last_cluster_summary = Some(ClusterSummary::new(args.buffer_update_content, &cluster_result));
+
+                let upload_result = args.buffer_groups.upload_instance_information(vec![UploadInformation
+                {
+                    model_id: args.cube_model_id,
+                    instance_translations: None,
+                    instance_colours: Some(&cluster_result.colours),
+                    instance_alphas: None,
+                    instance_shapes: None,
+                    instance_scales: None
+                }]);
+
+                match upload_result
+                {
+                    Ok(_) =>
+                        {
+                            args.frozen_clusters.update_colours(&cluster_result.colours);
+
+                            let (points, _) = args.buffer_groups.get_instance_information(args.cube_model_id);
+                            *args.last_cluster_assignments = Some(ClusterAssignments{ points: points.to_vec(), cluster_indices: cluster_result.cluster_indices });
+                            (true, "Cluster program status: No Error".to_string())
+                        },
+                    Err(err) => (false, "Failed to upload cluster colours: ".to_string() + &err)
+                }
+            },
+        Err(err) => (false, err),
+    };
+    *args.cluster_result_text = cluster_result_text;
+    *args.cluster_result_time = Instant::now();
+    clusters_found
+}
+
+/// Prints the parameters and the cluster sizes of the most recent cluster detection to stdout as CSV
+/// lines, so that they can be copied into a record of the results
+///
+/// `last_cluster_summary` - the record of the most recent cluster detection
+/// `render_window` - the window being rendered to
+pub fn check_print_cluster_summary(last_cluster_summary: &Option<ClusterSummary>, render_window: &RenderWindow)
+{
+    if render_window.is_shift_down() || render_window.get_key_input().iter().find(|x| **x == (Key::F10, Action::Press)).is_none()
+    {
+        return;
+    }
+
+    match last_cluster_summary
+    {
+        Some(summary) =>
+            {
+                for line in summary.to_csv_lines()
+                {
+                    print_status(line);
+                }
+            },
+        None => eprintln!("No clusters have been found yet, press C to find clusters")
+    }
+}
+
+/// Writes each point of the most recent cluster detection with the index of its cluster to the
+/// cluster export file when Shift + F10 is pressed
+///
+/// `last_cluster_assignments` - the cluster of each point of the most recent cluster detection
+/// `export_file` - the file to write the cluster assignments to, if one was given
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the point cloud files
+/// `render_window` - the window being rendered to
+pub fn check_export_cluster_assignments(last_cluster_assignments: &Option<ClusterAssignments>, export_file: &Option<String>, units_scale: f32, up_axis: UpAxis,
+                                        render_window: &RenderWindow)
+{
+    if !render_window.is_shift_down() || render_window.get_key_input().iter().find(|x| **x == (Key::F10, Action::Press)).is_none()
+    {
+        return;
+    }
+
+    let file = match export_file
+    {
+        Some(i) => i,
+        None =>
+            {
+                eprintln!("No file to export the cluster assignments to. Specify one with --cluster_export");
+                return;
+            }
+    };
+
+    match last_cluster_assignments
+    {
+        Some(assignments) =>
+            {
+                match export_cluster_assignments(file, &assignments.points, &assignments.cluster_indices, units_scale, up_axis)
+                {
+                    Ok(_) => print_status(format!("Exported the clusters of {} points to {}", assignments.points.len(), file)),
+                    Err(err) => eprintln!("Failed to export the cluster assignments to {}: {}", file, err)
+                }
+            },
+        None => eprintln!("No clusters have been found yet, press C to find clusters")
+    }
+}
+
+/// Allows clustering to be attempted again after it was disabled due to the cluster program
+/// not being found
+///
+/// `cluster_program_disabled` - variable holding whether clustering is disabled
+/// `cluster_result_text` - the text displaying the status of the cluster program
+/// `cluster_result_time` - the time the status of the cluster program was last set
+/// `render_window` - the window being rendered to
+pub fn check_cluster_program_retry(cluster_program_disabled: &mut bool, cluster_result_text: &mut String,
+                                   cluster_result_time: &mut Instant, render_window: &RenderWindow)
+{
+    // Control + R reloads the point cloud file instead
+    if *cluster_program_disabled && !render_window.is_control_down() && render_window.get_key_input().iter().find(|x| **x == (Key::R, Action::Press)).is_some()
+    {
+        *cluster_program_disabled = false;
+        *cluster_result_text = "Cluster program status: Re-enabled, press C to retry".to_string();
+        *cluster_result_time = Instant::now();
+    }
+}
+
+/// Resets the status of the cluster program back to no error, either when requested by the user or
+/// when the status has been displayed for longer than the given timeout. While clustering is disabled
+/// the status is reset to a reminder of that instead, as clustering does nothing until it is re-enabled
+///
+/// `cluster_result_text` - the text displaying the status of the cluster program
+/// `cluster_result_time` - the time the status of the cluster program was last set
+/// `cluster_program_disabled` - whether clustering is disabled due to the cluster program not being found
+/// `timeout_s` - seconds after which the status is cleared. A value of 0 never clears the status automatically
+/// `render_window` - the window being rendered to
+pub fn check_clear_cluster_result(cluster_result_text: &mut String, cluster_result_time: &mut Instant, cluster_program_disabled: bool,
+                                  timeout_s: u64, render_window: &RenderWindow)
+{
+    let dismissed = render_window.get_key_input().iter().find(|x| **x == (Key::Backspace, Action::Press)).is_some();
+    let timed_out = timeout_s != 0 && cluster_result_time.elapsed().as_secs() >= timeout_s;
+
+    if dismissed || timed_out
+    {
+        *cluster_result_text = if cluster_program_disabled
+        {
+            "Cluster program status: Disabled, press R to retry".to_string()
+        }
+        else
+        {
+            "Cluster program status: No Error".to_string()
+        };
+        *cluster_result_time = Instant::now();
+    }
+}
+
+/// Determines if the window should be closed due to the input of the user. Closing the window
+/// itself is always possible, even with no quit key
+///
+/// `render_window` - the window being rendered
+/// `exit_key` - the key that quits the program, if any
+pub fn check_window_close(render_window: &mut RenderWindow, exit_key: Option<Key>)
+{
+    let exit_key = match exit_key
+    {
+        Some(i) => i,
+        None => return,
+    };
+
+    if render_window.get_key_input().iter().find(|x| **x == (exit_key, Action::Press)).is_some()
+    {
+        render_window.set_window_should_close(true);
+    }
+}
+
+/// Updates the position of the point cloud from where it was originally placed
+///
+/// `render_variables` - struct holding the required variables for rendering
+pub fn change_point_cloud_position(render_variables: &mut RenderData)
+{
+    let move_amount = 0.05;
+
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F2, Action::Press)).is_some() ||
+        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F2, Action::Repeat)).is_some()
+    {
+        render_variables.cloud_translation.x += move_amount;
+    }
+
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F1, Action::Press)).is_some() ||
+        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F1, Action::Repeat)).is_some()
+    {
+        render_variables.cloud_translation.x -= move_amount;
+    }
+
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F4, Action::Press)).is_some() ||
+        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F4, Action::Repeat)).is_some()
+    {
+        render_variables.cloud_translation.z += move_amount;
+    }
+
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F3, Action::Press)).is_some() ||
+        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F3, Action::Repeat)).is_some()
+    {
+        render_variables.cloud_translation.z -= move_amount;
+    }
+
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F6, Action::Press)).is_some() ||
+        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F6, Action::Repeat)).is_some()
+    {
+        render_variables.cloud_translation.y += move_amount;
+    }
+
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F5, Action::Press)).is_some() ||
+        render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F5, Action::Repeat)).is_some()
+    {
+        render_variables.cloud_translation.y -= move_amount;
+    }
+}
+
+/// Toggles keeping the current cluster colours when the point cloud is updated
+///
+/// `frozen_clusters` - the state holding the cluster colours to keep
+/// `render_window` - the window being rendered to
+pub fn check_freeze_clusters(frozen_clusters: &mut FrozenClusters, render_window: &RenderWindow)
+{
+    // Shift + F toggles flat shading instead
+    if !render_window.is_shift_down() && render_window.get_key_input().iter().find(|x| **x == (Key::F, Action::Press)).is_some()
+    {
+        frozen_clusters.toggle();
+    }
+}
+
+/// Toggles displaying the positions of points averaged over the recent point clouds
+///
+/// `point_smoothing` - the state holding the averaged positions
+/// `render_window` - the window being rendered to
+pub fn check_toggle_point_smoothing(point_smoothing: &mut PointSmoothing, render_window: &RenderWindow)
+{
+    if render_window.get_key_input().iter().find(|x| **x == (Key::Semicolon, Action::Press)).is_some()
+    {
+        point_smoothing.toggle();
+    }
+}
+
+/// Toggles colouring the points of updated point clouds by how often the regions around them changed
+///
+/// `update_heatmap` - the state holding the recent changes of each region
+/// `render_window` - the window being rendered to
+pub fn check_toggle_update_heatmap(update_heatmap: &mut UpdateHeatmap, render_window: &RenderWindow)
+{
+    if render_window.is_shift_down() && render_window.get_key_input().iter().find(|x| **x == (Key::P, Action::Press)).is_some()
+    {
+        update_heatmap.toggle();
+    }
+}
+
+/// Handles the playback controls and advances the playback of recorded point clouds, if any
+///
+/// `playback` - the playback of the recorded point clouds, if one was requested
+/// `render_window` - the window being rendered to
+pub fn update_playback(playback: &mut Option<Playback>, render_window: &RenderWindow)
+{
+    if let Some(playback) = playback
+    {
+        playback.update(render_window);
+    }
+}
+
+/// Writes the point cloud currently being rendered to the export file when the export key is pressed
+///
+/// `render_variables` - struct holding the required variables for rendering
+/// `export_file` - the file to write the point cloud to, if one was given
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the point cloud files
+pub fn check_export_point_cloud(render_variables: &RenderData, export_file: &Option<String>, units_scale: f32, up_axis: UpAxis)
+{
+    if !render_variables.render_window.is_shift_down() && render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::O, Action::Press)).is_some()
+    {
+        match export_file
+        {
+            Some(file) =>
+                {
+                    let (translations, colours) = render_variables.buffer_groups.get_instance_information(render_variables.cube_model_id);
+                    match export_point_cloud(file, translations, colours, units_scale, up_axis)
+                    {
+                        Ok(_) => print_status(format!("Exported {} points to {}", translations.len(), file)),
+                        Err(err) => eprintln!("Failed to export the point cloud to {}: {}", file, err)
+                    }
+                },
+            None => eprintln!("No file to export the point cloud to. Specify one with --export_on_key")
+        }
+    }
+}
+
+/// Writes the points of the rendered point cloud that are inside the view of the active scene camera
+/// when Shift + O is pressed, so that part of a point cloud can be cut out by moving the camera.
+/// The points are written to the export file with "_frustum" added to its name
+///
+/// `render_variables` - struct holding the required variables for rendering
+/// `export_file` - the file the whole point cloud is written to, if one was given
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the point cloud files
+pub fn check_export_frustum_point_cloud(render_variables: &RenderData, export_file: &Option<String>, units_scale: f32, up_axis: UpAxis)
+{
+    if !render_variables.render_window.is_shift_down() || render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::O, Action::Press)).is_none()
+    {
+        return;
+    }
+
+    let file = match export_file
+    {
+        Some(i) => frustum_export_file_name(i),
+        None =>
+            {
+                eprintln!("No file to export the point cloud to. Specify one with --export_on_key");
+                return;
+            }
+    };
+
+    let camera = match &render_variables.second_camera
+    {
+        Some(i) if render_variables.second_camera_active => i,
+        _ => &render_variables.camera
+    };
+
+    let frustum_planes = camera.extract_frustum_planes();
+    let reflect_vertically = render_variables.get_reflect_vertically();
+
+    let (translations, colours) = render_variables.buffer_groups.get_instance_information(render_variables.cube_model_id);
+    let mut inside_translations = Vec::new();
+    let mut inside_colours = Vec::new();
+
+    for (index, translation) in translations.iter().enumerate()
+    {
+        let world_pos = cloud_point_to_world(*translation, render_variables.cloud_translation, reflect_vertically);
+        if frustum_planes.iter().all(|plane| plane.x * world_pos.x + plane.y * world_pos.y + plane.z * world_pos.z + plane.w >= 0.0)
+        {
+            inside_translations.push(*translation);
+
+            if let Some(colour) = colours.get(index)
+            {
+                inside_colours.push(*colour);
+            }
+        }
+    }
+
+    match export_point_cloud(&file, &inside_translations, &inside_colours, units_scale, up_axis)
+    {
+        Ok(_) => print_status(format!("Exported {} of {} points inside the view to {}", inside_translations.len(), translations.len(), file)),
+        Err(err) => eprintln!("Failed to export the point cloud to {}: {}", file, err)
+    }
+}
+
+/// Starts or stops moving the main camera along the camera path with the F11 key, and moves the camera
+/// along the path while it plays. When recording, the path is moved along by one recorded frame per
+/// frame so that the recording plays back at the right speed regardless of how long frames take
+///
+/// `render_variables` - struct holding the required variables for rendering
+/// `recording_fps` - the frames per second of the recording, if the frames are recorded
+/// `delta_time` - the seconds since the previous frame
+pub fn update_camera_path(render_variables: &mut RenderData, recording_fps: Option<f32>, delta_time: f32)
+{
+    let camera_path = match &mut render_variables.camera_path
+    {
+        Some(i) => i,
+        None => return
+    };
+
+    if render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F11, Action::Press)).is_some()
+    {
+        camera_path.toggle();
+    }
+
+    if let Some(pose) = camera_path.advance(recording_fps.map_or(delta_time, |x| 1.0 / x))
+    {
+        render_variables.camera.set_pose(pose);
+    }
+}
+
+/// Writes the window to a PNG file if the camera was moved along the camera path this frame. The
+/// files are numbered by the frame of the recording. Called after everything has been rendered,
+/// before the buffers are swapped
+///
+/// `render_variables` - struct holding the required variables for rendering
+/// `file_prefix` - the start of the name of each written file
+/// `fps` - the frames per second of the recording
+pub fn record_camera_path_frame(render_variables: &RenderData, file_prefix: &String, fps: f32)
+{
+    let camera_path = match &render_variables.camera_path
+    {
+        Some(i) if i.moved_camera() => i,
+        _ => return
+    };
+
+    let frame_file = format!("{}_{:05}.png", file_prefix, (camera_path.get_time() * fps).round() as usize);
+    let (dimensions, pixels) = render_variables.render_window.read_pixels();
+
+    if let Err(err) = write_png(&frame_file, dimensions, &pixels)
+    {
+        eprintln!("Failed to write the camera path frame {}: {}", frame_file, err);
+    }
+}
+
+/// Writes the main view, as rendered so far this frame, and the top, right and sun views to PNG files
+/// when F12 is pressed. Called after everything has been rendered, before the buffers are swapped
+///
+/// `render_variables` - struct holding the required variables for rendering
+/// `file_prefix` - the start of the name of each written file
+pub fn check_export_views(render_variables: &RenderData, file_prefix: &String)
+{
+    if !render_variables.render_window.is_shift_down() && render_variables.render_window.get_key_input().iter().find(|x| **x == (Key::F12, Action::Press)).is_some()
+    {
+        let main_view_file = format!("{}_main.png", file_prefix);
+        let (dimensions, pixels) = render_variables.render_window.read_pixels();
+
+        let result = write_png(&main_view_file, dimensions, &pixels)
+            .and_then(|_| render_variables.view_fbos.export_views(file_prefix))
+            .map(|x| std::iter::once(main_view_file).chain(x).collect::<Vec<String>>());
+
+        match result
+        {
+            Ok(files) => print_status(format!("Exported the views to {}", files.join(", "))),
+            Err(err) => eprintln!("Failed to export the views: {}", err)
+        }
+    }
+}
+
+/// Reads back where the vertex shader places the points in the shadow, side view and scene passes
+/// when Shift + F12 is pressed, and compares them to where they are expected to be. The positions of
+/// each pass are written to a separate point cloud file, named by appending the pass to the given prefix
+///
+/// `buffer_groups` - the scene renderer drawing the points
+/// `cube_model_id` - the model the points are drawn with
+/// `render_window` - the window being rendered to
+/// `outside_param` - the variables the scene was rendered with
+/// `file_prefix` - the start of the name of each written file
+/// `units_scale` - the scale that was applied to the points when they were read
+/// `up_axis` - which axis points up in the files read by the program
+pub fn check_capture_point_positions(buffer_groups: &mut SceneRenderer, cube_model_id: ModelId, render_window: &RenderWindow, outside_param: &OutsideParam,
+                                     file_prefix: &String, units_scale: f32, up_axis: UpAxis)
+{
+    if !render_window.is_shift_down() || render_window.get_key_input().iter().find(|x| **x == (Key::F12, Action::Press)).is_none()
+    {
+        return;
+    }
+
+    for (pass, captured) in buffer_groups.capture_point_positions(cube_model_id, outside_param)
+    {
+        let (translations, _) = buffer_groups.get_instance_information(cube_model_id);
+        let expected = expected_point_positions(pass, translations, outside_param.cloud_translation, outside_param.reflect_vertical);
+
+        match max_deviation(&captured, &expected)
+        {
+            Some(deviation) => print_status(format!("{} pass: {} positions captured, largest deviation from the expected positions {:.6}", pass.name(), captured.len(), deviation)),
+            None => eprintln!("{} pass: captured {} positions, but expected {}", pass.name(), captured.len(), expected.len())
+        }
+
+        let file_name = format!("{}_{}_positions.txt", file_prefix, pass.name());
+        match export_point_cloud(&file_name, &captured, &[], units_scale, up_axis)
+        {
+            Ok(()) => print_status(format!("Wrote the captured positions to {}", file_name)),
+            Err(err) => eprintln!("Failed to write the captured positions: {}", err)
+        }
+    }
+}
+
+/// Saves the session to the session file when F8 is pressed, and restores it when F9 is pressed
+///
+/// `program_variables` - the variables of the program that make up the session
+pub fn check_session_keys(program_variables: &mut ProgramVariables)
+{
+    let key_pressed = |key: Key| program_variables.render_data.render_window.get_key_input().iter().find(|x| **x == (key, Action::Press)).is_some();
+    let (save, load) = (key_pressed(Key::F8), key_pressed(Key::F9));
+    let session_file = program_variables.args.session_file.clone();
+
+    if save
+    {
+        match program_variables.save_session(&session_file)
+        {
+            Ok(_) => print_status(format!("Saved the session to {}", session_file)),
+            Err(err) => eprintln!("Failed to save the session to {}: {}", session_file, err)
+        }
+    }
+
+    if load
+    {
+        match program_variables.load_session(&session_file)
+        {
+            Ok(_) => print_status(format!("Loaded the session from {}", session_file)),
+            Err(err) => eprintln!("Failed to load the session from {}: {}", session_file, err)
+        }
+    }
+}
+
+/// Updates the given variable to indicate if updates to the point cloud should be paused
+///
+/// `pause_updating` - variable holding whether or not to update the point cloud
+/// `render_window` - the window being rendered to
+pub fn check_pause_updates(pause_updating: &mut bool, render_window: &RenderWindow)
+{
+    // Shift + P toggles the update heatmap instead
+    if !render_window.is_shift_down() && render_window.get_key_input().iter().find(|x| **x == (Key::P, Action::Press)).is_some()
+    {
+        *pause_updating = !*pause_updating;
+    }
+}
+
+/// Formats a count for display, shortened with a suffix chosen by its magnitude, such as "850",
+/// "340k" or "1.2M". Up to three significant digits are kept, without trailing zeros
+///
+/// `count` - the count to format
+pub fn format_count(count: usize) -> String
+{
+    const SUFFIXES: [&str; 4] = ["", "k", "M", "B"];
+
+    let mut value = count as f64;
+    let mut suffix_index = 0;
+
+    // Values that would round up to 1000 are written with the next suffix instead
+    while value >= 999.5 && suffix_index < SUFFIXES.len() - 1
+    {
+        value /= 1000.0;
+        suffix_index += 1;
+    }
+
+    if suffix_index == 0
+    {
+        return count.to_string();
+    }
+
+    let decimals = if value < 9.995 { 2 } else if value < 99.95 { 1 } else { 0 };
+    let digits = format!("{:.*}", decimals, value);
+    let digits = if digits.contains('.') { digits.trim_end_matches('0').trim_end_matches('.') } else { &digits };
+
+    format!("{}{}", digits, SUFFIXES[suffix_index])
+}
+
+/// Shortens a file path to at most the given number of characters so that it fits in the overlay.
+/// Paths that fit are kept as they are, and longer ones are shortened to the file stem. Stems that
+/// still do not fit keep their end, as that is usually where the frame number of a sequence of files is
+///
+/// `file` - the path of the file
+/// `max_num_char` - the most characters the shortened path can have
+pub fn shorten_file_name(file: &str, max_num_char: usize) -> String
+{
+    if file.chars().count() <= max_num_char
+    {
+        return file.to_string();
+    }
+
+    let stem = Path::new(file).file_stem().map_or(file.to_string(), |x| x.to_string_lossy().to_string());
+    let num_stem_chars = stem.chars().count();
+    if num_stem_chars <= max_num_char
+    {
+        return stem;
+    }
+
+    let num_kept_chars = max_num_char.saturating_sub(3);
+    "...".to_string() + &stem.chars().skip(num_stem_chars - num_kept_chars).collect::<String>()
+}
+
+/// Writes the information about the scene to the window
+///
+/// `param` - the variables required to render scene information text
+pub fn write_scene_info(param: TextWriteParam)
+{
+    param.text_renderer.update_window_dimensions(param.render_window.get_window_dimensions());
+
+    // The help replaces the rest of the text, as the two would overlap
+    if let Some(help_page) = param.help_page
+    {
+        write_help(param.text_renderer, help_page);
+        return;
+    }
+
+    let layout = param.overlay_layout;
+
+    if let Some(position) = layout.get_position(OverlayElement::PointCount)
+    {
+        param.text_renderer.buffer_text_for_rendering(format!("Points: {}", format_count(param.num_points)), position, 30);
+    }
+
+    // Lets the files of an IPC sequence or a playback be told apart
+    if let Some(position) = layout.get_position(OverlayElement::FileName)
+    {
+        if !param.content_file.is_empty()
+        {
+            param.text_renderer.buffer_text_for_rendering("File: ".to_string() + &shorten_file_name(param.content_file, 40), position, 46);
+        }
+    }
+
+    if let Some(position) = layout.get_position(OverlayElement::UpdateTime)
+    {
+        if param.time_update.elapsed().as_secs() < 10
+        {
+            param.text_renderer.buffer_text_for_rendering(format!("TU:  {:.2}s", (param.time_update.elapsed().as_millis() as f32 / 1000.0)), position, 30);
+        }
+        else
+        {
+            param.text_renderer.buffer_text_for_rendering("TU: > 10s", position, 30);
+        }
+    }
+
+    let lidar_pos = match (param.add_lidar_pos, param.lidar_pos)
+    {
+        (true, Some(i)) => i,
+        _ => vec3(0.0, 0.0, 0.0)
+    };
+
+    // The second camera's text is placed above and below the main camera's text
+    if let Some(position) = layout.get_position(OverlayElement::CameraPosition)
+    {
+        param.text_renderer.buffer_text_for_rendering("MP:  ".to_string() + &param.camera.to_string_pos(lidar_pos), position, 30);
+
+        if let Some(second_camera) = param.second_camera
+        {
+            param.text_renderer.buffer_text_for_rendering("MP2: ".to_string() + &second_camera.to_string_pos(lidar_pos), position + vec2(0.0, 0.05), 30);
+        }
+    }
+
+    if let Some(position) = layout.get_position(OverlayElement::CameraDirection)
+    {
+        param.text_renderer.buffer_text_for_rendering("MD: ".to_string() + &param.camera.to_string_direction(), position, 30);
+
+        if let Some(second_camera) = param.second_camera
+        {
+            param.text_renderer.buffer_text_for_rendering("MD2: ".to_string() + &second_camera.to_string_direction(), position - vec2(0.0, 0.0375), 30);
+        }
+    }
+
+    if let Some(position) = layout.get_position(OverlayElement::ClusterText)
+    {
+        param.text_renderer.buffer_text_for_rendering(param.cluster_result_text, position, 80);
+    }
+
+    if let Some(position) = layout.get_position(OverlayElement::Epsilon)
+    {
+        param.text_renderer.buffer_text_for_rendering("Epsilon: ".to_string() + &format!("{:.3}", param.epsilon), position, 15);
+    }
+
+    // Only shown briefly; long enough to explain why a parameter stopped changing. Placed above
+    // the epsilon, even if the epsilon itself is not shown
+    if let Some((note, time_set)) = param.cluster_parameter_note
+    {
+        if time_set.elapsed().as_secs() < 2
+        {
+            let epsilon_position = layout.get_position(OverlayElement::Epsilon).unwrap_or(vec2(0.715, 0.025));
+            param.text_renderer.buffer_text_for_rendering(note, epsilon_position + vec2(0.0, 0.0375), 30);
+        }
+    }
+
+    // Long enough to read the file name or the reason reloading failed
+    if let Some((note, time_set)) = param.reload_note
+    {
+        if time_set.elapsed().as_secs() < 5
+        {
+            param.text_renderer.buffer_text_for_rendering(note, vec2(0.475, 0.3), 60);
+        }
+    }
+
+    // Shown until accepted or rejected, next to the epsilon it would replace
+    if let Some(suggestion) = param.epsilon_suggestion
+    {
+        let epsilon_position = layout.get_position(OverlayElement::Epsilon).unwrap_or(vec2(0.715, 0.025));
+        param.text_renderer.buffer_text_for_rendering(format!("Suggested epsilon: {:.3} (Enter accepts, M rejects)", suggestion),
+                                                      epsilon_position + vec2(0.0, 0.075), 50);
+    }
+
+    if let Some(position) = layout.get_position(OverlayElement::MinPoints)
+    {
+        param.text_renderer.buffer_text_for_rendering("Min points: ".to_string() + &param.min_num_points.to_string(), position, 15);
+    }
+
+    if param.near_fade.1 > 0.0
+    {
+        param.text_renderer.buffer_text_for_rendering(format!("NF: {:.2} - {:.2}", param.near_fade.0, param.near_fade.1), vec2(0.025, 0.25), 30);
+    }
+
+    if let Some((factor, units)) = param.shadow_offset
+    {
+        param.text_renderer.buffer_text_for_rendering(format!("SO: {:.2}, {:.1}", factor, units), vec2(0.025, 0.35), 30);
+    }
+
+    if let Some(quality_text) = param.quality_text
+    {
+        param.text_renderer.buffer_text_for_rendering(quality_text, vec2(0.025, 0.3), 45);
+    }
+
+    if let (Some(position), Some(velocity_legend)) = (layout.get_position(OverlayElement::VelocityLegend), param.velocity_legend)
+    {
+        param.text_renderer.buffer_text_for_rendering(velocity_legend, position, 60);
+    }
+
+    if let Some(position) = layout.get_position(OverlayElement::Compass)
+    {
+        write_compass(param.text_renderer, param.camera.get_yaw(), position);
+    }
+
+    if let Some(spacing_histogram) = param.spacing_histogram
+    {
+        param.text_renderer.buffer_text_for_rendering(spacing_histogram.to_string_summary(), vec2(0.2, 0.93), 50);
+
+        for (index, row) in spacing_histogram.to_string_rows(30).iter().enumerate()
+        {
+            param.text_renderer.buffer_text_for_rendering(row, vec2(0.2, 0.89 - index as f32 * 0.035), 45);
+        }
+    }
+
+    if let Some(gpu_profiler) = param.gpu_profiler
+    {
+        for (index, row) in gpu_profiler.to_string_rows().iter().enumerate()
+        {
+            param.text_renderer.buffer_text_for_rendering(row, vec2(0.55, 0.93 - index as f32 * 0.035), 30);
+        }
+    }
+
+    param.view_fbos.buffer_write_fbo_information(param.text_renderer, lidar_pos);
+
+    if param.sun_look_at_selected
+    {
+        param.text_renderer.buffer_text_for_rendering(param.view_fbos.get_sun_fbo().to_string_scroll_axis(), vec2(0.75, 0.25), 40);
+    }
+
+    if param.view_fbos.get_sun_fbo().get_arc().is_active()
+    {
+        param.text_renderer.buffer_text_for_rendering(param.view_fbos.get_sun_fbo().get_arc().to_string_angles(), vec2(0.75, 0.3), 40);
+    }
+
+    let lidar_pos_text = if let Some(lidar_pos) = param.lidar_pos
+    {
+        let mut string = format!("LP: {:.1}   {:.1}   {:.1}", lidar_pos.x, lidar_pos.y, lidar_pos.z);
+        if param.add_lidar_pos
+        {
+            string += " (Added)";
+        }
+        string
+    }
+    else
+    {
+        "LP: N/A".to_string()
+    };
+    param.text_renderer.buffer_text_for_rendering(lidar_pos_text, vec2(0.025, 0.2), 30);
+
+    param.text_renderer.buffer_text_for_rendering(format!("Units scale: {}", param.units_scale), vec2(0.75, 0.2), 30);
+
+    if param.grid_height != 0.0
+    {
+        param.text_renderer.buffer_text_for_rendering(format!("Grid height: {:.2}", param.grid_height), vec2(0.75, 0.15), 30);
+    }
+
+    if let Some(ground_threshold) = param.ground_threshold
+    {
+        param.text_renderer.buffer_text_for_rendering(format!("Ground below: {:.2}", ground_threshold), vec2(0.75, 0.1), 30);
+    }
+
+    if param.clusters_frozen
+    {
+        param.text_renderer.buffer_text_for_rendering("Clusters frozen", vec2(0.475, 0.2), 30);
+    }
+
+    if param.points_smoothed
+    {
+        param.text_renderer.buffer_text_for_rendering("Points smoothed", vec2(0.475, 0.25), 30);
+    }
+
+    // Only point clouds that are updated can be paused; red stands out from the rest of the text
+    match param.updates_paused
+    {
+        Some(true) => param.text_renderer.buffer_coloured_text_for_rendering("PAUSED", vec2(0.475, 0.05), 10, vec3(1.0, 0.2, 0.2)),
+        Some(false) => param.text_renderer.buffer_coloured_text_for_rendering("LIVE", vec2(0.475, 0.05), 10, vec3(0.2, 1.0, 0.2)),
+        None => {}
+    }
+
+    if let Some(camera_path_progress) = param.camera_path_progress
+    {
+        param.text_renderer.buffer_text_for_rendering(camera_path_progress, vec2(0.475, 0.35), 40);
+    }
+
+    if let Some(selected_points) = param.selected_points
+    {
+        param.text_renderer.buffer_coloured_text_for_rendering(selected_points, vec2(0.475, 0.4), 30, selection_colour());
+    }
+
+    if let Some(playback_position) = param.playback_position
+    {
+        param.text_renderer.buffer_text_for_rendering(playback_position, vec2(0.475, 0.1), 40);
+    }
+
+    if let Some(cloud_warning) = param.cloud_warning
+    {
+        param.text_renderer.buffer_text_for_rendering(cloud_warning, vec2(0.475, 0.15), 60);
+    }
+
+    // Placed in the bottom left corner of the respective view
+    if param.right_view_out_of_frame
+    {
+        param.text_renderer.buffer_text_for_rendering("Out of frame (Home to recentre)", vec2(0.7, 0.365), 35);
+    }
+
+    if param.top_view_out_of_frame
+    {
+        param.text_renderer.buffer_text_for_rendering("Out of frame (Home to recentre)", vec2(0.7, 0.69), 35);
+    }
+
+    if param.view_fbos.is_right_view_mirrored()
+    {
+        param.text_renderer.buffer_text_for_rendering("Mirrors main view (=)", vec2(0.7, 0.33), 35);
+    }
+
+    if param.view_fbos.is_top_view_mirrored()
+    {
+        param.text_renderer.buffer_text_for_rendering("Mirrors main view (=)", vec2(0.7, 0.655), 35);
+    }
+
+    if param.show_grid_labels
+    {
+        let scene_width = ViewLayout::new(param.render_window.get_window_dimensions()).scene_width;
+
+        match param.second_camera
+        {
+            Some(second_camera) =>
+                {
+                    write_grid_labels(param.text_renderer, param.camera, param.grid_height, 0.0, scene_width / 2.0);
+                    write_grid_labels(param.text_renderer, second_camera, param.grid_height, scene_width / 2.0, scene_width / 2.0);
+                },
+            None => write_grid_labels(param.text_renderer, param.camera, param.grid_height, 0.0, scene_width)
+        }
+    }
+
+    if !param.annotations.is_empty()
+    {
+        let scene_width = ViewLayout::new(param.render_window.get_window_dimensions()).scene_width;
+
+        match param.second_camera
+        {
+            Some(second_camera) =>
+                {
+                    write_annotations(param.text_renderer, param.annotations, param.camera, 0.0, scene_width / 2.0);
+                    write_annotations(param.text_renderer, param.annotations, second_camera, scene_width / 2.0, scene_width / 2.0);
+                },
+            None => write_annotations(param.text_renderer, param.annotations, param.camera, 0.0, scene_width)
+        }
+    }
+
+    let text_renderer = param.text_renderer;
+    profile_section(param.gpu_profiler, ProfileSection::Text, || text_renderer.render_buffered_text());
+}
+
+/// Writes the list of all of the input the program responds to over a darkened scene
+///
+/// `text_renderer` - the text renderer used to write the help
+/// `help_page` - the page of the key bindings to write
+fn write_help(text_renderer: &mut TextRendering, help_page: usize)
+{
+    text_renderer.dim_background();
+
+    let page_bindings = KEY_BINDINGS.iter().skip(help_page * HELP_ROWS_PER_PAGE).take(HELP_ROWS_PER_PAGE);
+    for (index, key_binding) in page_bindings.enumerate()
+    {
+        let y_position = 0.95 - index as f32 * 0.04;
+        text_renderer.buffer_text_for_rendering(key_binding.keys, vec2(0.025, y_position), 20);
+        text_renderer.buffer_text_for_rendering(key_binding.action, vec2(0.2, y_position), key_binding.action.len());
+    }
+
+    let page_text = format!("Page {} of {}, press / for the next page", help_page + 1, num_help_pages());
+    text_renderer.buffer_text_for_rendering(page_text, vec2(0.025, 0.03), 60);
+
+    text_renderer.render_buffered_text();
+}
+
+/// Writes a top-down compass showing which way the main camera faces relative to the world axes.
+/// The camera always faces the top of the compass
+///
+/// `text_renderer` - the text renderer used to write the compass
+/// `yaw` - the horizontal rotation of the main camera, in degrees
+/// `centre` - the centre of the compass, in window coordinates
+fn write_compass(text_renderer: &mut TextRendering, yaw: f32, centre: TVec2<f32>)
+{
+    // Taller than it is wide so that the compass appears round in a typical widescreen window
+    let radius = vec2(0.035, 0.06);
+    // Text is positioned from its bottom left, so labels are shifted to be centred on their position
+    let label_centring = vec2(0.008, 0.01);
+
+    text_renderer.buffer_text_for_rendering("o", centre - label_centring, 1);
+
+    for (label, offset) in compass_labels(yaw).iter()
+    {
+        text_renderer.buffer_text_for_rendering(label, centre + offset.component_mul(&radius) - label_centring, 2);
+    }
+
+    text_renderer.buffer_text_for_rendering(format!("Facing {}", facing_axis(yaw)), centre - vec2(0.03, radius.y + 0.045), 10);
+}
+
+/// Writes the world coordinates of the major grid line intersections closest to the camera
+///
+/// `text_renderer` - the text renderer used to write the labels
+/// `camera` - the camera used to render the main scene
+/// `grid_height` - the height of the plane the grid lies in
+/// `viewport_start_x` - where the camera's viewport starts horizontally, as a fraction of the window width
+/// `viewport_width` - the width of the camera's viewport, as a fraction of the window width
+fn write_grid_labels(text_renderer: &mut TextRendering, camera: &Camera, grid_height: f32, viewport_start_x: f32, viewport_width: f32)
+{
+    // Every tenth grid line is labelled, and only a few around the camera, as the text renderer
+    // has a limited number of characters it can render each frame
+    let major_line_spacing = 10;
+    let num_labels_per_side = 3;
+    // The grid only extends this far from the origin; see the grid created in the SceneRenderer
+    let grid_extent = 250;
+
+    let camera_pos = camera.get_position();
+    let closest_x = (camera_pos.x / major_line_spacing as f32).round() as i32 * major_line_spacing;
+    let closest_z = (camera_pos.z / major_line_spacing as f32).round() as i32 * major_line_spacing;
+
+    for x_offset in -num_labels_per_side..=num_labels_per_side
+    {
+        for z_offset in -num_labels_per_side..=num_labels_per_side
+        {
+            let x = closest_x + x_offset * major_line_spacing;
+            let z = closest_z + z_offset * major_line_spacing;
+
+            if x.abs() > grid_extent || z.abs() > grid_extent
+            {
+                continue;
+            }
+
+            let window_pos = match camera.world_to_screen(&vec3(x as f32, grid_height, z as f32)).and_then(|i| scene_ndc_to_window(i, viewport_start_x, viewport_width))
+            {
+                Some(i) => i,
+                None => continue
+            };
+
+            text_renderer.buffer_text_for_rendering(format!("{},{}", x, z), window_pos, 10);
+        }
+    }
+}
+
+/// Writes the text of each annotation at the position it is pinned to. Annotations behind the camera
+/// or outside of its view are not written
+///
+/// `text_renderer` - the text renderer used to write the annotations
+/// `annotations` - the annotations to write
+/// `camera` - the camera used to render the scene the annotations are written over
+/// `viewport_start_x` - where the camera's viewport starts horizontally, as a fraction of the window width
+/// `viewport_width` - the width of the camera's viewport, as a fraction of the window width
+fn write_annotations(text_renderer: &mut TextRendering, annotations: &Annotations, camera: &Camera, viewport_start_x: f32, viewport_width: f32)
+{
+    for (ndc, label) in annotations.on_screen(|x| camera.world_to_screen(x))
+    {
+        if let Some(window_pos) = scene_ndc_to_window(ndc, viewport_start_x, viewport_width)
+        {
+            text_renderer.buffer_coloured_text_for_rendering(label, window_pos, 30, vec3(1.0, 0.85, 0.3));
+        }
+    }
+}
+
+/// Converts a position in the Normalized Device Coordinates of a scene camera to where text is placed
+/// in the window. None is returned for positions above the top of the window
+///
+/// `ndc` - the position, relative to the scene viewport of the camera
+/// `viewport_start_x` - where the camera's viewport starts horizontally, as a fraction of the window width
+/// `viewport_width` - the width of the camera's viewport, as a fraction of the window width
+fn scene_ndc_to_window(ndc: TVec2<f32>, viewport_start_x: f32, viewport_width: f32) -> Option<TVec2<f32>>
+{
+    // The text renderer positions text relative to the entire window, whereas the NDC are
+    // relative to the scene viewport
+    let window_x = viewport_start_x + (ndc.x + 1.0) * 0.5 * viewport_width;
+    let window_y = SCENE_VIEWPORT_HEIGHT_OFFSET + (ndc.y + 1.0) * 0.5;
+
+    // The scene viewport extends past the top of the window
+    if window_y > 1.0 { None } else { Some(vec2(window_x, window_y)) }
+}
+
+/// Converts a point of the point cloud to where it is placed in the scene. Matches where the points
+/// are placed in the scene vertex shader
+///
+/// `point` - the point, as stored in the instance buffer
+/// `cloud_translation` - the translation applied to the point cloud
+/// `reflect_vertically` - -1 if the point cloud is reflected vertically, otherwise 1
+fn cloud_point_to_world(point: TVec3<f32>, cloud_translation: TVec3<f32>, reflect_vertically: i32) -> TVec3<f32>
+{
+    let mut world_pos = point + cloud_translation + vec3(0.0, POINT_LIFT, 0.0);
+    world_pos.y *= reflect_vertically as f32;
+    world_pos
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::helper_logic::key_bindings::KEY_BINDINGS;
+    use crate::helper_logic::main_loop_functions::{format_count, next_help_page, num_help_pages, shorten_file_name, HELP_ROWS_PER_PAGE};
+
+    #[test]
+    fn counts_formatted_by_magnitude()
+    {
+        assert_eq!("0", format_count(0));
+        assert_eq!("850", format_count(850));
+        assert_eq!("999", format_count(999));
+        assert_eq!("1k", format_count(1000));
+        assert_eq!("1.23k", format_count(1234));
+        assert_eq!("340k", format_count(340_000));
+        assert_eq!("999k", format_count(999_499));
+        assert_eq!("1M", format_count(999_500));
+        assert_eq!("1.2M", format_count(1_200_000));
+        assert_eq!("2.5B", format_count(2_500_000_000));
+    }
+
+    #[test]
+    fn help_pages_cycle_then_hide()
+    {
+        assert!(num_help_pages() * HELP_ROWS_PER_PAGE >= KEY_BINDINGS.len());
+        assert!((num_help_pages() - 1) * HELP_ROWS_PER_PAGE < KEY_BINDINGS.len());
+
+        let mut help_page = next_help_page(None);
+        for page in 0..num_help_pages()
+        {
+            assert_eq!(Some(page), help_page);
+            help_page = next_help_page(help_page);
+        }
+        assert_eq!(None, help_page);
+    }
+
+    #[test]
+    fn long_file_names_shortened()
+    {
+        assert_eq!("frames/frame_12.txt", shorten_file_name("frames/frame_12.txt", 20));
+        assert_eq!("frame_12", shorten_file_name("/home/user/recordings/frames/frame_12.txt", 20));
+        assert_eq!("...ing_frame_12", shorten_file_name("/home/user/long_recording_frame_12.txt", 15));
+    }
 }
\ No newline at end of file
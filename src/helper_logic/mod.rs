@@ -1,4 +1,10 @@
+pub mod cloud_export;
 pub mod folder_location_functions;
+pub mod image_export;
 pub mod initialization_functions;
+pub mod key_bindings;
 pub mod main_loop_functions;
+pub mod overlay_layout;
 pub mod point_cloud_analyzer;
+pub mod session;
+pub mod status_output;
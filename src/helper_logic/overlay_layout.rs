@@ -0,0 +1,197 @@
+use std::fs;
+use nalgebra_glm::{TVec2, vec2};
+
+/// The text shown on top of the scene whose placement can be configured
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OverlayElement
+{
+    PointCount,
+    UpdateTime,
+    CameraPosition,
+    CameraDirection,
+    ClusterText,
+    Epsilon,
+    MinPoints,
+    Compass,
+    VelocityLegend,
+    FileName,
+}
+
+impl OverlayElement
+{
+    /// All of the configurable elements, in the order they are stored in the layout
+    pub const ALL: [OverlayElement; 10] =
+    [
+        OverlayElement::PointCount,
+        OverlayElement::UpdateTime,
+        OverlayElement::CameraPosition,
+        OverlayElement::CameraDirection,
+        OverlayElement::ClusterText,
+        OverlayElement::Epsilon,
+        OverlayElement::MinPoints,
+        OverlayElement::Compass,
+        OverlayElement::VelocityLegend,
+        OverlayElement::FileName,
+    ];
+
+    /// Get the name used for the element in a layout file
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            OverlayElement::PointCount => "point_count",
+            OverlayElement::UpdateTime => "update_time",
+            OverlayElement::CameraPosition => "camera_position",
+            OverlayElement::CameraDirection => "camera_direction",
+            OverlayElement::ClusterText => "cluster_text",
+            OverlayElement::Epsilon => "epsilon",
+            OverlayElement::MinPoints => "min_points",
+            OverlayElement::Compass => "compass",
+            OverlayElement::VelocityLegend => "velocity_legend",
+            OverlayElement::FileName => "file_name",
+        }
+    }
+}
+
+/// Where an element is placed, in window coordinates (0 to 1, starting from the bottom left of the
+/// window), and whether it is shown at all
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OverlayPlacement
+{
+    pub position: TVec2<f32>,
+    pub visible: bool,
+}
+
+/// Holds the placement of each configurable element of the text overlay
+pub struct OverlayLayout
+{
+    placements: [OverlayPlacement; 10],
+    hidden: bool,
+}
+
+impl OverlayLayout
+{
+    /// Creates the layout used when no layout file is given
+    pub fn new() -> OverlayLayout
+    {
+        let placement = |x: f32, y: f32| OverlayPlacement{ position: vec2(x, y), visible: true };
+
+        OverlayLayout
+        {
+            placements:
+            [
+                placement(0.025, 0.15),
+                placement(0.025, 0.1),
+                placement(0.2, 0.15),
+                placement(0.2, 0.1),
+                placement(0.025, 0.025),
+                placement(0.715, 0.025),
+                placement(0.85, 0.025),
+                placement(0.06, 0.88),
+                placement(0.5, 0.2),
+                placement(0.025, 0.4),
+            ],
+            hidden: false,
+        }
+    }
+
+    /// Reads a layout file. Each line places one element and is written as "name x y", optionally
+    /// followed by "hidden" to not show the element. Elements not in the file keep their default
+    /// placement. Empty lines and lines starting with '#' are ignored
+    ///
+    /// `layout_file` - the file containing the layout
+    pub fn from_file(layout_file: &String) -> Result<OverlayLayout, String>
+    {
+        let content = fs::read_to_string(layout_file).map_err(|x| format!("Failed to read overlay layout file {}: {}", layout_file, x))?;
+        OverlayLayout::parse(&content)
+    }
+
+    /// Get the position of the given element, or None if the element should not be shown
+    ///
+    /// `element` - the element to get the position of
+    pub fn get_position(&self, element: OverlayElement) -> Option<TVec2<f32>>
+    {
+        let placement = self.placements[element as usize];
+        if self.hidden || !placement.visible
+        {
+            return None;
+        }
+
+        Some(placement.position)
+    }
+
+    /// Hides all of the configurable elements, or shows the ones the layout has as visible again
+    pub fn toggle_hidden(&mut self)
+    {
+        self.hidden = !self.hidden;
+    }
+
+    /// Parses the contents of a layout file
+    ///
+    /// `content` - the contents of the layout file
+    fn parse(content: &str) -> Result<OverlayLayout, String>
+    {
+        let mut layout = OverlayLayout::new();
+
+        for (line_number, line) in content.lines().enumerate().map(|(index, x)| (index + 1, x.trim()))
+        {
+            if line.is_empty() || line.starts_with('#')
+            {
+                continue;
+            }
+
+            let words = line.split_whitespace().collect::<Vec<&str>>();
+            if words.len() != 3 && !(words.len() == 4 && words[3] == "hidden")
+            {
+                return Err(format!("Line {} of the overlay layout must be \"name x y\", optionally followed by \"hidden\". Got: {}", line_number, line));
+            }
+
+            let element = match OverlayElement::ALL.iter().find(|x| x.name() == words[0])
+            {
+                Some(i) => *i,
+                None => return Err(format!("Unknown overlay element on line {}: {}", line_number, words[0]))
+            };
+
+            let parse_coordinate = |coordinate: &str| match coordinate.parse::<f32>()
+            {
+                Ok(i) if (0.0..=1.0).contains(&i) => Ok(i),
+                Ok(_) => Err(format!("Coordinate on line {} of the overlay layout is not between 0 and 1: {}", line_number, coordinate)),
+                Err(err) => Err(format!("Invalid coordinate on line {} of the overlay layout: {}. Error: {}", line_number, coordinate, err))
+            };
+
+            layout.placements[element as usize] = OverlayPlacement
+            {
+                position: vec2(parse_coordinate(words[1])?, parse_coordinate(words[2])?),
+                visible: words.len() == 3,
+            };
+        }
+
+        Ok(layout)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec2;
+    use crate::helper_logic::overlay_layout::{OverlayElement, OverlayLayout};
+
+    #[test]
+    fn layout_overrides_only_given_elements()
+    {
+        let layout = OverlayLayout::parse("# Moved for a small window\npoint_count 0.5 0.5\n\nepsilon 0.1 0.2 hidden\n").unwrap();
+
+        assert_eq!(Some(vec2(0.5, 0.5)), layout.get_position(OverlayElement::PointCount));
+        assert_eq!(None, layout.get_position(OverlayElement::Epsilon));
+        assert_eq!(OverlayLayout::new().get_position(OverlayElement::MinPoints), layout.get_position(OverlayElement::MinPoints));
+    }
+
+    #[test]
+    fn invalid_layout_lines_rejected()
+    {
+        assert!(OverlayLayout::parse("frame_rate 0.5 0.5").is_err());
+        assert!(OverlayLayout::parse("point_count 0.5").is_err());
+        assert!(OverlayLayout::parse("point_count 0.5 1.5").is_err());
+        assert!(OverlayLayout::parse("point_count 0.5 0.5 shown").is_err());
+    }
+}
\ No newline at end of file
@@ -1,8 +1,14 @@
+use std::cmp::Ordering;
 use std::fs::File;
 use std::io::Read;
 use std::process::exit;
-use nalgebra_glm::{TVec3, vec3};
-use crate::ipc_logic::ipc_receiver::IPCContributor;
+use nalgebra_glm::{TMat3, TVec3, vec3};
+use crate::helper_logic::status_output::print_status;
+use crate::ipc_logic::ipc_content_logic::PointLimit;
+use crate::ipc_logic::ipc_receiver::{IPCContributor, ParsedPoints, ParseSettings};
+
+/// The name given instead of a file to read the initial point cloud from stdin
+pub const STDIN_FILE: &str = "-";
 
 /// Holds information about the initial point cloud. This needed to render the initial point cloud
 /// (when a static point cloud is being rendered) and to centre the cameras (both scene and views)
@@ -10,107 +16,332 @@ pub struct InitialCloudAnalyzer
 {
     initial_pos: Option<TVec3<f32>>,
     default_points: Vec<TVec3<f32>>,
-    centre: TVec3<f32>,
-    max_length: f32,
+    initial_velocities: Vec<TVec3<f32>>,
+    initial_scalars: Vec<f32>,
+    absolute_bounds: CloudBounds,
+    robust_bounds: CloudBounds,
+    use_robust_bounds: bool,
+    bounding_sphere: BoundingSphere,
+    warning: Option<String>,
 }
 
-impl InitialCloudAnalyzer
+/// Length used in place of the extent of a point cloud that has none. Matches the distance the main
+/// camera is placed from the centre of the point cloud
+const DEFAULT_MAX_LENGTH: f32 = 3.0;
+
+/// The percentiles of the points along each axis that bound the point cloud when its outliers are ignored
+pub const ROBUST_PERCENTILES: (f32, f32) = (2.0, 98.0);
+
+/// The centre of a point cloud and its largest extent along an axis
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CloudBounds
+{
+    pub centre: TVec3<f32>,
+    pub max_length: f32,
+}
+
+impl CloudBounds
 {
-    /// Reads the point cloud in the given file and extracts analytics from it. If no file is given,
-    /// then an empty point cloud is assumed
+    /// Creates the bounds of the box with the given extremes. A box without any extent uses a default
+    /// length so that cameras are not placed on the point cloud
     ///
-    /// `initial_point_position` - file specifying the points of a point cloud
-    pub fn new(initial_point_positions: &Option<String>, displaying_lidar_pos: bool) -> InitialCloudAnalyzer
+    /// `min` - the smallest coordinates in each dimension
+    /// `max` - the largest coordinates in each dimension
+    fn from_extremes(min: TVec3<f32>, max: TVec3<f32>) -> CloudBounds
     {
-        match initial_point_positions
+        let max_length = nalgebra_glm::comp_max(&(max - min).abs());
+        CloudBounds{ centre: (min + max) / 2.0, max_length: if max_length == 0.0 { DEFAULT_MAX_LENGTH } else { max_length } }
+    }
+
+    /// Creates the bounds of the points between the given percentiles along each axis, so that a few
+    /// points far away from the rest do not affect them
+    ///
+    /// `points` - the points of the point cloud; must not be empty
+    /// `percentiles` - the lower and upper percentiles, between 0 and 100
+    fn from_percentiles(points: &[TVec3<f32>], percentiles: (f32, f32)) -> CloudBounds
+    {
+        let mut min = vec3(0.0, 0.0, 0.0);
+        let mut max = vec3(0.0, 0.0, 0.0);
+        let mut values = Vec::with_capacity(points.len());
+
+        for axis in 0..3
         {
-            Some(i) =>
+            values.clear();
+            values.extend(points.iter().map(|x| x[axis]));
+
+            // Selecting a value is linear, rather than sorting all of them, so that this remains quick
+            // for large point clouds
+            let mut select_percentile = |percentile: f32|
                 {
-                    let mut file = match File::open(&i)
-                    {
-                        Ok(i) => i,
-                        Err(err) =>
-                            {
-                                eprintln!("Failed to open file: {}, with error: {}", i, err.to_string());
-                                exit(-1);
-                            }
-                    };
-                    let mut file_contents = String::new();
-                    if let Err(err) = file.read_to_string(&mut file_contents)
-                    {
-                        if cfg!(debug_assertions)
-                        {
-                            println!("Failed to read initial point cloud file: {}", err.to_string());
-                        }
-                    }
-
-                    let initial_points = IPCContributor::parse_read_data(&file_contents).unwrap();
-
-                    // Find extremes of point cloud in each dimension
-                    let mut min_x = f32::MAX;
-                    let mut max_x = f32::MIN;
-                    let mut min_z = f32::MAX;
-                    let mut max_z = f32::MIN;
-                    let mut min_y = f32::MAX;
-                    let mut max_y = f32::MIN;
-
-                    let starting_index = if displaying_lidar_pos
-                    {
-                        1
-                    }
-                    else
-                    {
-                        0
-                    };
-
-                    for point in &initial_points[starting_index..]
-                    {
-                        min_x = min_x.min(point.x);
-                        max_x = max_x.max(point.x);
-
-                        min_z = min_z.min(point.z);
-                        max_z = max_z.max(point.z);
-
-                        min_y = min_y.min(point.y);
-                        max_y = max_y.max(point.y);
-                    }
-
-                    let centre = vec3((min_x + max_x) / 2.0, (min_y + max_y) / 2.0, (min_z + max_z) / 2.0);
-                    let max_length = (max_x - min_x).abs()
-                                         .max((max_y - min_y).abs())
-                                         .max((max_z - min_z).abs());
-
-                    let initial_pos = if displaying_lidar_pos
-                    {
-                        Some(initial_points[0])
-                    }
-                    else
-                    {
-                        None
-                    };
-
-                    InitialCloudAnalyzer { default_points: initial_points, centre, max_length, initial_pos }
-                },
-            None => InitialCloudAnalyzer { default_points: vec![], centre: vec3(0.0, 0.0, 0.0), max_length: 0.0, initial_pos: None }
+                    let index = ((values.len() - 1) as f32 * percentile / 100.0).round() as usize;
+                    *values.select_nth_unstable_by(index, |a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal)).1
+                };
+
+            min[axis] = select_percentile(percentiles.0);
+            max[axis] = select_percentile(percentiles.1);
+        }
+
+        CloudBounds::from_extremes(min, max)
+    }
+}
+
+/// A sphere that encloses all of the points of a point cloud
+#[derive(Copy, Clone, Debug)]
+pub struct BoundingSphere
+{
+    pub centre: TVec3<f32>,
+    pub radius: f32,
+}
+
+impl BoundingSphere
+{
+    /// Creates the smallest sphere enclosing the box with the given extremes
+    ///
+    /// `min` - the smallest coordinates in each dimension
+    /// `max` - the largest coordinates in each dimension
+    pub fn from_extremes(min: TVec3<f32>, max: TVec3<f32>) -> BoundingSphere
+    {
+        BoundingSphere{ centre: (min + max) / 2.0, radius: nalgebra_glm::distance(&min, &max) / 2.0 }
+    }
+}
+
+impl InitialCloudAnalyzer
+{
+    /// Reads the given files and extracts analytics of the point cloud they make up together. If no
+    /// files are given, then an empty point cloud is assumed
+    ///
+    /// `initial_point_positions` - files specifying the points of a point cloud, which are concatenated
+    /// `displaying_lidar_pos` - true if the first point in each file is the lidar position. Ignored for
+    ///                          files starting with a line giving the lidar position
+    /// `parse_settings` - how the files are read
+    pub fn new(initial_point_positions: &[String], displaying_lidar_pos: bool, parse_settings: ParseSettings) -> InitialCloudAnalyzer
+    {
+        InitialCloudAnalyzer::new_with_progress(initial_point_positions, displaying_lidar_pos, parse_settings, |_| {})
+    }
+
+    /// Same as new, but periodically reports how many points have been read so far
+    ///
+    /// `initial_point_positions` - files specifying the points of a point cloud, which are concatenated
+    /// `displaying_lidar_pos` - true if the first point in each file is the lidar position. Ignored for
+    ///                          files starting with a line giving the lidar position
+    /// `parse_settings` - how the files are read
+    /// `progress` - called with the number of points read so far, over all of the files
+    pub fn new_with_progress<F: Fn(usize)>(initial_point_positions: &[String], displaying_lidar_pos: bool, parse_settings: ParseSettings,
+                                           progress: F) -> InitialCloudAnalyzer
+    {
+        if initial_point_positions.is_empty()
+        {
+            let bounding_sphere = BoundingSphere{ centre: vec3(0.0, 0.0, 0.0), radius: 0.0 };
+            let bounds = CloudBounds{ centre: vec3(0.0, 0.0, 0.0), max_length: DEFAULT_MAX_LENGTH };
+            return InitialCloudAnalyzer { default_points: vec![], initial_velocities: vec![], initial_scalars: vec![], absolute_bounds: bounds, robust_bounds: bounds,
+                                          use_robust_bounds: false, initial_pos: None, bounding_sphere, warning: None };
+        }
+
+        let mut header_lidar_pos = None;
+        let mut initial_points = Vec::new();
+        let mut initial_velocities = Vec::new();
+        let mut initial_scalars = Vec::new();
+
+        for (index, file) in initial_point_positions.iter().enumerate()
+        {
+            let points_read = initial_points.len();
+            let (file_lidar_pos, (points, velocities, scalars)) = read_point_cloud_file(file, parse_settings, |x| progress(points_read + x));
+
+            // The lidar position of the first file is the lidar position of the whole point cloud. The
+            // lidar positions written as the first point of the other files are not part of the point cloud
+            let skipped_points = if index == 0
+            {
+                header_lidar_pos = file_lidar_pos;
+                0
+            }
+            else if file_lidar_pos.is_none() && displaying_lidar_pos
+            {
+                1
+            }
+            else
+            {
+                0
+            };
+
+            initial_points.extend(points.into_iter().skip(skipped_points));
+            initial_velocities.extend(velocities.into_iter().skip(skipped_points));
+            initial_scalars.extend(scalars.into_iter().skip(skipped_points));
+        }
+
+        let mut analyzer = match header_lidar_pos
+        {
+            Some(lidar_pos) => InitialCloudAnalyzer::analyze_points_with_lidar(initial_points, Some(lidar_pos), 0),
+            None => InitialCloudAnalyzer::analyze_points(initial_points, displaying_lidar_pos)
+        };
+        analyzer.initial_velocities = initial_velocities;
+        analyzer.initial_scalars = initial_scalars;
+        analyzer
+    }
+
+    /// Extracts the analytics of the given point cloud. Clouds without any extent (empty or with all
+    /// points at the same location) use a default length so that cameras are not placed on the cloud
+    ///
+    /// `initial_points` - the points of the point cloud
+    /// `displaying_lidar_pos` - true if the first point is the lidar position
+    pub fn analyze_points(initial_points: Vec<TVec3<f32>>, displaying_lidar_pos: bool) -> InitialCloudAnalyzer
+    {
+        if displaying_lidar_pos
+        {
+            let initial_pos = initial_points.first().copied();
+            InitialCloudAnalyzer::analyze_points_with_lidar(initial_points, initial_pos, 1)
+        }
+        else
+        {
+            InitialCloudAnalyzer::analyze_points_with_lidar(initial_points, None, 0)
         }
     }
 
+    /// Extracts the analytics of the given point cloud, whose lidar position is already known
+    ///
+    /// `initial_points` - the points of the point cloud
+    /// `initial_pos` - the lidar position, if any
+    /// `starting_index` - the index of the first point that is part of the point cloud rather than
+    ///                    the lidar position
+    fn analyze_points_with_lidar(initial_points: Vec<TVec3<f32>>, initial_pos: Option<TVec3<f32>>, starting_index: usize) -> InitialCloudAnalyzer
+    {
+        let cloud_points = initial_points.get(starting_index..).unwrap_or(&[]);
+
+        if cloud_points.is_empty()
+        {
+            // Without points the extremes would remain at their sentinel values; the lidar is the only
+            // position known to be of interest
+            let centre = initial_pos.unwrap_or(vec3(0.0, 0.0, 0.0));
+            let bounding_sphere = BoundingSphere{ centre, radius: 0.0 };
+            let bounds = CloudBounds{ centre, max_length: DEFAULT_MAX_LENGTH };
+            return InitialCloudAnalyzer { default_points: initial_points, initial_velocities: vec![], initial_scalars: vec![], absolute_bounds: bounds, robust_bounds: bounds,
+                                          use_robust_bounds: false, initial_pos, bounding_sphere, warning: Some("Point cloud is empty".to_string()) };
+        }
+
+        // Find extremes of point cloud in each dimension
+        let mut min_x = f32::MAX;
+        let mut max_x = f32::MIN;
+        let mut min_z = f32::MAX;
+        let mut max_z = f32::MIN;
+        let mut min_y = f32::MAX;
+        let mut max_y = f32::MIN;
+
+        for point in cloud_points
+        {
+            min_x = min_x.min(point.x);
+            max_x = max_x.max(point.x);
+
+            min_z = min_z.min(point.z);
+            max_z = max_z.max(point.z);
+
+            min_y = min_y.min(point.y);
+            max_y = max_y.max(point.y);
+        }
+
+        let absolute_bounds = CloudBounds::from_extremes(vec3(min_x, min_y, min_z), vec3(max_x, max_y, max_z));
+        let robust_bounds = CloudBounds::from_percentiles(cloud_points, ROBUST_PERCENTILES);
+
+        let mut warning = None;
+
+        if max_x == min_x && max_y == min_y && max_z == min_z
+        {
+            warning = Some(if cloud_points.len() == 1
+            {
+                "Point cloud has a single point".to_string()
+            }
+            else
+            {
+                "All points of the point cloud are at the same position".to_string()
+            });
+        }
+
+        let bounding_sphere = BoundingSphere::from_extremes(vec3(min_x, min_y, min_z), vec3(max_x, max_y, max_z));
+
+        InitialCloudAnalyzer { default_points: initial_points, initial_velocities: vec![], initial_scalars: vec![], absolute_bounds, robust_bounds, use_robust_bounds: false,
+                               initial_pos, bounding_sphere, warning }
+    }
+
     /// Get the points of the initial point cloud
     pub fn get_initial_points(&self) -> &Vec<TVec3<f32>>
     {
         &self.default_points
     }
 
-    /// Get the centre of the initial point cloud
+    /// Get the velocity of each point of the initial point cloud; empty if the velocities were not read
+    pub fn get_initial_velocities(&self) -> &Vec<TVec3<f32>>
+    {
+        &self.initial_velocities
+    }
+
+    /// Get the scalar of each point of the initial point cloud; empty if the scalars were not read
+    pub fn get_initial_scalars(&self) -> &Vec<f32>
+    {
+        &self.initial_scalars
+    }
+
+    /// Get the centre of the initial point cloud, from the robust bounds if they are used
     pub fn get_centre(&self) -> TVec3<f32>
     {
-        self.centre
+        self.get_bounds().centre
     }
 
-    /// Get the maximum length of the initial point cloud
+    /// Get the maximum length of the initial point cloud, from the robust bounds if they are used
     pub fn get_max_length(&self) -> f32
     {
-        self.max_length
+        self.get_bounds().max_length
+    }
+
+    /// Get the bounds the views are centred on: the robust bounds if they are used, otherwise the
+    /// absolute bounds
+    pub fn get_bounds(&self) -> CloudBounds
+    {
+        if self.use_robust_bounds
+        {
+            self.robust_bounds
+        }
+        else
+        {
+            self.absolute_bounds
+        }
+    }
+
+    /// Get the bounds of all of the points of the initial point cloud
+    pub fn get_absolute_bounds(&self) -> CloudBounds
+    {
+        self.absolute_bounds
+    }
+
+    /// Get the bounds of the initial point cloud ignoring its outliers, which are the points outside
+    /// of the ROBUST_PERCENTILES along any axis
+    pub fn get_robust_bounds(&self) -> CloudBounds
+    {
+        self.robust_bounds
+    }
+
+    /// Sets whether the centre and maximum length are those of the robust bounds. The bounding sphere
+    /// always encloses all of the points
+    ///
+    /// `use_robust_bounds` - true to ignore the outliers of the point cloud
+    pub fn set_use_robust_bounds(&mut self, use_robust_bounds: bool)
+    {
+        self.use_robust_bounds = use_robust_bounds;
+    }
+
+    /// Check if the centre and maximum length are those of the robust bounds
+    pub fn is_using_robust_bounds(&self) -> bool
+    {
+        self.use_robust_bounds
+    }
+
+    /// Get the sphere enclosing the initial point cloud
+    pub fn get_bounding_sphere(&self) -> BoundingSphere
+    {
+        self.bounding_sphere
+    }
+
+    /// Get the warning about the initial point cloud that should be shown to the user, if any
+    pub fn get_warning(&self) -> Option<&str>
+    {
+        self.warning.as_deref()
     }
 
     /// Get the initial position of the lidar, if any
@@ -118,4 +349,335 @@ impl InitialCloudAnalyzer
     {
         self.initial_pos
     }
+
+    /// Applies the given limit to the points of the initial point cloud. The centre and extent of the
+    /// point cloud remain those of all of its points. A warning is shown if points were dropped
+    ///
+    /// `point_limit` - the largest number of points to keep, and what to do with larger point clouds
+    pub fn limit_points(&mut self, point_limit: PointLimit) -> Result<(), String>
+    {
+        let num_points = self.default_points.len();
+        let (points, kept_indices) = point_limit.apply(std::mem::take(&mut self.default_points), (0..num_points).collect())?;
+
+        if points.len() < num_points
+        {
+            self.warning = Some(PointLimit::to_string_downsampled(num_points, points.len()));
+        }
+
+        self.default_points = points;
+        self.initial_velocities = kept_indices.iter().filter_map(|x| self.initial_velocities.get(*x).copied()).collect();
+        self.initial_scalars = kept_indices.iter().filter_map(|x| self.initial_scalars.get(*x).copied()).collect();
+        Ok(())
+    }
+}
+
+/// Reads the lidar position given by the header line, the points and the velocities and scalars of the
+/// points of a point cloud file, or of stdin. Exits the program with a message naming the file if it cannot be read
+///
+/// `file` - the point cloud file to read, or STDIN_FILE to read from stdin
+/// `parse_settings` - how the file is read
+/// `progress` - called with the number of points of the file read so far
+fn read_point_cloud_file<F: Fn(usize)>(file: &String, parse_settings: ParseSettings, progress: F) -> (Option<TVec3<f32>>, ParsedPoints)
+{
+    let mut file_contents = String::new();
+    let read_result = if file == STDIN_FILE
+    {
+        std::io::stdin().read_to_string(&mut file_contents)
+    }
+    else
+    {
+        let mut opened_file = match File::open(file)
+        {
+            Ok(i) => i,
+            Err(err) =>
+                {
+                    eprintln!("Failed to open file: {}, with error: {}", file, err);
+                    exit(-1);
+                }
+        };
+        opened_file.read_to_string(&mut file_contents)
+    };
+
+    if let Err(err) = read_result
+    {
+        if cfg!(debug_assertions)
+        {
+            print_status(format!("Failed to read initial point cloud file {}: {}", file, err));
+        }
+    }
+
+    let (header_lidar_pos, point_contents) = match IPCContributor::split_lidar_header(&file_contents, parse_settings.units_scale, parse_settings.up_axis)
+    {
+        Ok(i) => i,
+        Err(err) =>
+            {
+                eprintln!("Invalid lidar position in {}: {}", file, err);
+                exit(-1);
+            }
+    };
+
+    if let Some(warning) = IPCContributor::incomplete_vertex_warning(point_contents, parse_settings.point_columns)
+    {
+        eprintln!("{}: {}", file, warning);
+    }
+
+    match IPCContributor::parse_points_with_progress(point_contents, parse_settings, progress)
+    {
+        Ok(parsed_points) => (header_lidar_pos, parsed_points),
+        Err(err) =>
+            {
+                eprintln!("Failed to read the points of {}: {}", file, err);
+                exit(-1);
+            }
+    }
+}
+
+/// Reads the alpha of each point of a point cloud from the given file
+///
+/// `alpha_file` - file with an alpha between 0 and 1 for each point, separated by "|"
+/// `num_points` - the number of points in the point cloud
+pub fn read_point_alphas(alpha_file: &String, num_points: usize) -> Result<Vec<f32>, String>
+{
+    let content = std::fs::read_to_string(alpha_file).map_err(|x| format!("Failed to read alpha file {}: {}", alpha_file, x))?;
+    parse_point_alphas(&content, num_points)
+}
+
+/// Parses the alphas of the points of a point cloud
+///
+/// `content` - an alpha between 0 and 1 for each point, separated by "|"
+/// `num_points` - the number of points in the point cloud
+fn parse_point_alphas(content: &str, num_points: usize) -> Result<Vec<f32>, String>
+{
+    let alphas = content.split('|')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .enumerate()
+        .map(|(index, x)| match x.parse::<f32>()
+        {
+            Ok(i) if (0.0..=1.0).contains(&i) => Ok(i),
+            Ok(i) => Err(format!("Alpha of point {} is not between 0 and 1: {}", index, i)),
+            Err(err) => Err(format!("Failed to parse alpha of point {} having value {}. Error: {}", index, x, err))
+        })
+        .collect::<Result<Vec<f32>, String>>()?;
+
+    if alphas.len() != num_points
+    {
+        return Err(format!("Expected an alpha for each of the {} points, but found {}", num_points, alphas.len()));
+    }
+
+    Ok(alphas)
+}
+
+/// Reads the 3x3 covariance of each point of a point cloud from the given file
+///
+/// `covariance_file` - file with the nine values of the rows of each point's covariance, separated by "|"
+/// `num_points` - the number of points in the point cloud
+pub fn read_point_covariances(covariance_file: &String, num_points: usize) -> Result<Vec<TMat3<f32>>, String>
+{
+    let content = std::fs::read_to_string(covariance_file).map_err(|x| format!("Failed to read covariance file {}: {}", covariance_file, x))?;
+    parse_point_covariances(&content, num_points)
+}
+
+/// Parses the covariances of the points of a point cloud
+///
+/// `content` - the nine values of the rows of each point's covariance, separated by "|"
+/// `num_points` - the number of points in the point cloud
+fn parse_point_covariances(content: &str, num_points: usize) -> Result<Vec<TMat3<f32>>, String>
+{
+    let values = content.split('|')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .enumerate()
+        .map(|(index, x)| x.parse::<f32>()
+            .map_err(|err| format!("Failed to parse covariance value {} of point {} having value {}. Error: {}", index % 9, index / 9, x, err)))
+        .collect::<Result<Vec<f32>, String>>()?;
+
+    if values.len() != num_points * 9
+    {
+        return Err(format!("Expected nine covariance values for each of the {} points, but found {}", num_points, values.len()));
+    }
+
+    Ok(values.chunks(9).map(TMat3::from_row_slice).collect())
+}
+
+/// Reads the pairs of points to draw lines between from the given file
+///
+/// `edges_file` - file with the indices of the two points of each edge, separated by "|"
+pub fn read_edges(edges_file: &String) -> Result<Vec<[u32; 2]>, String>
+{
+    let content = std::fs::read_to_string(edges_file).map_err(|x| format!("Failed to read edges file {}: {}", edges_file, x))?;
+    parse_edges(&content)
+}
+
+/// Parses the pairs of points to draw lines between. The points are given by their index in the
+/// point cloud, so that edges can be given once for point clouds that are updated
+///
+/// `content` - the indices of the two points of each edge, separated by "|"
+fn parse_edges(content: &str) -> Result<Vec<[u32; 2]>, String>
+{
+    let indices = content.split('|')
+        .map(|x| x.trim())
+        .filter(|x| !x.is_empty())
+        .enumerate()
+        .map(|(index, x)| x.parse::<u32>()
+            .map_err(|err| format!("Failed to parse point index {} of edge {} having value {}. Error: {}", index % 2, index / 2, x, err)))
+        .collect::<Result<Vec<u32>, String>>()?;
+
+    if indices.len() % 2 != 0
+    {
+        return Err(format!("Expected two point indices for each edge, but found {} indices", indices.len()));
+    }
+
+    Ok(indices.chunks(2).map(|x| [x[0], x[1]]).collect())
+}
+
+/// Finds the transformation that turns a cube spanning -1 to 1 into a box spanning one standard
+/// deviation of the given covariance along each of its eigenvectors. The covariance is assumed to be
+/// symmetric; negative variances, which can only come from rounding errors, are treated as zero.
+/// A minimum size keeps the transformation invertible, which is required to transform the normals
+///
+/// `covariance` - the covariance of a point
+/// `min_half_size` - the smallest distance from the centre of the box to any of its faces
+pub fn covariance_box_transform(covariance: &TMat3<f32>, min_half_size: f32) -> TMat3<f32>
+{
+    let eigen = covariance.symmetric_eigen();
+    let half_sizes = eigen.eigenvalues.map(|x| x.max(0.0).sqrt().max(min_half_size));
+
+    eigen.eigenvectors * TMat3::from_diagonal(&half_sizes)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::fs;
+    use nalgebra_glm::{TMat3, vec3};
+    use crate::helper_logic::point_cloud_analyzer::{BoundingSphere, CloudBounds, InitialCloudAnalyzer, DEFAULT_MAX_LENGTH, parse_point_alphas, parse_point_covariances, covariance_box_transform, parse_edges};
+    use crate::args_parser::Args;
+
+    fn analyze_file(file_name: &str, contents: &str) -> InitialCloudAnalyzer
+    {
+        let file_path = std::env::temp_dir().join(file_name);
+        fs::write(&file_path, contents).unwrap();
+
+        let analyzer = InitialCloudAnalyzer::new(&[file_path.to_str().unwrap().to_string()], false, Args::default().parse_settings());
+        let _ = fs::remove_file(&file_path);
+        analyzer
+    }
+
+    #[test]
+    fn bounding_sphere_encloses_extremes()
+    {
+        let bounding_sphere = BoundingSphere::from_extremes(vec3(-1.0, 0.0, 2.0), vec3(1.0, 4.0, 6.0));
+
+        assert_eq!(vec3(0.0, 2.0, 4.0), bounding_sphere.centre);
+        assert_eq!(3.0, bounding_sphere.radius);
+    }
+    #[test]
+    fn empty_cloud_uses_default_length()
+    {
+        let analyzer = analyze_file("point_cloud_analyzer_empty.txt", "");
+
+        assert!(analyzer.get_initial_points().is_empty());
+        assert_eq!(vec3(0.0, 0.0, 0.0), analyzer.get_centre());
+        assert_eq!(DEFAULT_MAX_LENGTH, analyzer.get_max_length());
+        assert_eq!(0.0, analyzer.get_bounding_sphere().radius);
+        assert!(analyzer.get_warning().is_some());
+    }
+
+    #[test]
+    fn single_point_cloud_uses_default_length()
+    {
+        let analyzer = analyze_file("point_cloud_analyzer_single.txt", "1|2|2|");
+
+        assert_eq!(1, analyzer.get_initial_points().len());
+        assert_eq!(vec3(1.0, 2.0, 2.0), analyzer.get_centre());
+        assert_eq!(DEFAULT_MAX_LENGTH, analyzer.get_max_length());
+        assert!(analyzer.get_warning().is_some());
+    }
+
+    #[test]
+    fn files_concatenated_with_combined_extents()
+    {
+        let files = [("point_cloud_analyzer_first.txt", "0|0|0|1|1|1|"), ("point_cloud_analyzer_second.txt", "9|9|9|4|5|6|")];
+        let file_paths = files.iter()
+            .map(|(name, contents)|
+                {
+                    let file_path = std::env::temp_dir().join(name);
+                    fs::write(&file_path, contents).unwrap();
+                    file_path.to_str().unwrap().to_string()
+                })
+            .collect::<Vec<_>>();
+
+        // The first point of each file is a lidar position; only the one of the first file is kept
+        let analyzer = InitialCloudAnalyzer::new(&file_paths, true, Args::default().parse_settings());
+        file_paths.iter().for_each(|x| { let _ = fs::remove_file(x); });
+
+        // Files are written with the z coordinate before the y coordinate
+        assert_eq!(&vec![vec3(0.0, 0.0, 0.0), vec3(1.0, 1.0, 1.0), vec3(4.0, 6.0, 5.0)], analyzer.get_initial_points());
+        assert_eq!(Some(vec3(0.0, 0.0, 0.0)), analyzer.get_initial_lidar_pos());
+        assert_eq!(vec3(2.5, 3.5, 3.0), analyzer.get_centre());
+        assert_eq!(5.0, analyzer.get_max_length());
+    }
+
+    #[test]
+    fn robust_bounds_ignore_outliers()
+    {
+        // A single far away point among a hundred points spread along the x-axis
+        let mut points = (0..100).map(|x| vec3(x as f32 / 99.0, 0.0, 0.0)).collect::<Vec<_>>();
+        points.push(vec3(1000.0, 0.0, 0.0));
+
+        let mut analyzer = InitialCloudAnalyzer::analyze_points(points, false);
+        assert_eq!(CloudBounds{ centre: vec3(500.0, 0.0, 0.0), max_length: 1000.0 }, analyzer.get_absolute_bounds());
+
+        let robust_bounds = analyzer.get_robust_bounds();
+        assert!((robust_bounds.centre.x - 0.5).abs() < 0.05, "Robust centre is {}", robust_bounds.centre.x);
+        assert!(robust_bounds.max_length < 1.0, "Robust length is {}", robust_bounds.max_length);
+
+        assert_eq!(analyzer.get_absolute_bounds().centre, analyzer.get_centre());
+        analyzer.set_use_robust_bounds(true);
+        assert_eq!(robust_bounds.centre, analyzer.get_centre());
+        assert_eq!(robust_bounds.max_length, analyzer.get_max_length());
+    }
+
+    #[test]
+    fn alphas_match_number_of_points()
+    {
+        assert_eq!(vec![0.5, 1.0, 0.0], parse_point_alphas("0.5|1|0|", 3).unwrap());
+        assert!(parse_point_alphas("0.5|1|", 3).is_err());
+        assert!(parse_point_alphas("0.5|2|0|", 3).is_err());
+    }
+
+    #[test]
+    fn edges_are_pairs_of_indices()
+    {
+        assert_eq!(vec![[0, 1], [1, 5]], parse_edges("0|1|\n1|5|").unwrap());
+        assert!(parse_edges("0|1|2|").is_err());
+        assert!(parse_edges("0|-1|").is_err());
+    }
+
+    #[test]
+    fn covariance_box_follows_eigenvectors()
+    {
+        let covariances = parse_point_covariances("4|0|0|0|1|0|0|0|0|", 1).unwrap();
+        assert!(parse_point_covariances("4|0|0|0|1|0|0|0|", 1).is_err());
+
+        let transform = covariance_box_transform(&covariances[0], 0.01);
+
+        // The corners of the cube end up one deviation from the centre along each axis, with the
+        // zero variance along z kept at the minimum size
+        let corner = transform * vec3(1.0, 1.0, 1.0);
+        assert!((corner.x.abs() - 2.0).abs() < 0.001);
+        assert!((corner.y.abs() - 1.0).abs() < 0.001);
+        assert!((corner.z.abs() - 0.01).abs() < 0.001);
+
+        // A rotated covariance is stretched the most along the diagonal, by the root of its largest eigenvalue
+        let rotated = TMat3::new(2.5, 1.5, 0.0, 1.5, 2.5, 0.0, 0.0, 0.0, 1.0);
+        let transform = covariance_box_transform(&rotated, 0.01);
+        let longest_axis = (0..3).map(|x| transform.column(x).into_owned())
+            .max_by(|a, b| a.norm().partial_cmp(&b.norm()).unwrap())
+            .unwrap();
+
+        assert!((longest_axis.norm() - 2.0).abs() < 0.001);
+        assert!((longest_axis.x.abs() - longest_axis.y.abs()).abs() < 0.001);
+    }
 }
\ No newline at end of file
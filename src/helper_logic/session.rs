@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fs;
+use nalgebra_glm::{TVec3, vec3};
+use crate::view_logic::pose_history::CameraPose;
+
+/// The adjustments made to the scene that can be saved to a file, so that they do not have to be
+/// redone when the program is started again on the same point cloud
+#[derive(Clone, Debug, PartialEq)]
+pub struct Session
+{
+    pub main_camera: CameraPose,
+    pub top_camera: CameraPose,
+    pub right_camera: CameraPose,
+    pub sun_position: TVec3<f32>,
+    pub sun_look_at: TVec3<f32>,
+    pub epsilon: f32,
+    pub min_num_points: u32,
+    pub grid_colour_preset_index: usize,
+    pub cloud_translation: TVec3<f32>,
+    pub reflect_vertically: bool,
+    pub show_grid_labels: bool,
+    pub shadows_enabled: bool,
+    pub add_lidar_pos: bool,
+}
+
+impl Session
+{
+    /// Writes the session to the given file. Each value is written on its own line as its name
+    /// followed by the value, with vectors written as their three components
+    ///
+    /// `session_file` - the file to write the session to
+    pub fn save(&self, session_file: &String) -> Result<(), String>
+    {
+        fs::write(session_file, self.to_file_contents()).map_err(|x| x.to_string())
+    }
+
+    /// Reads a session previously written by save
+    ///
+    /// `session_file` - the file to read the session from
+    pub fn load(session_file: &String) -> Result<Session, String>
+    {
+        let content = fs::read_to_string(session_file).map_err(|x| x.to_string())?;
+        Session::parse(&content)
+    }
+
+    /// Creates the text written to a session file
+    fn to_file_contents(&self) -> String
+    {
+        let vector = |x: &TVec3<f32>| format!("{} {} {}", x.x, x.y, x.z);
+
+        let lines =
+        [
+            format!("main_camera_position {}", vector(&self.main_camera.position)),
+            format!("main_camera_direction {}", vector(&self.main_camera.direction)),
+            format!("top_camera_position {}", vector(&self.top_camera.position)),
+            format!("top_camera_direction {}", vector(&self.top_camera.direction)),
+            format!("right_camera_position {}", vector(&self.right_camera.position)),
+            format!("right_camera_direction {}", vector(&self.right_camera.direction)),
+            format!("sun_position {}", vector(&self.sun_position)),
+            format!("sun_look_at {}", vector(&self.sun_look_at)),
+            format!("epsilon {}", self.epsilon),
+            format!("min_num_points {}", self.min_num_points),
+            format!("grid_colour_preset {}", self.grid_colour_preset_index),
+            format!("cloud_translation {}", vector(&self.cloud_translation)),
+            format!("reflect_vertically {}", self.reflect_vertically),
+            format!("show_grid_labels {}", self.show_grid_labels),
+            format!("shadows_enabled {}", self.shadows_enabled),
+            format!("add_lidar_pos {}", self.add_lidar_pos),
+        ];
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Parses the text of a session file. Every value written by save must be present
+    ///
+    /// `content` - the contents of the session file
+    fn parse(content: &str) -> Result<Session, String>
+    {
+        let values = content.lines()
+            .filter_map(|x|
+                {
+                    let mut words = x.splitn(2, ' ');
+                    Some((words.next()?, words.next()?))
+                })
+            .collect::<HashMap<&str, &str>>();
+
+        let value = |name: &str| values.get(name).map(|x| x.trim()).ok_or_else(|| format!("Session is missing the value {}", name));
+        let parse_error = |name: &str, value: &str| format!("Invalid value for {} in the session: {}", name, value);
+
+        let vector = |name: &str| -> Result<TVec3<f32>, String>
+            {
+                let text = value(name)?;
+                let components = text.split_whitespace().map(|x| x.parse::<f32>()).collect::<Result<Vec<f32>, _>>()
+                    .map_err(|_| parse_error(name, text))?;
+
+                match components.as_slice()
+                {
+                    [x, y, z] => Ok(vec3(*x, *y, *z)),
+                    _ => Err(parse_error(name, text))
+                }
+            };
+
+        let number = |name: &str| -> Result<f32, String>
+            {
+                let text = value(name)?;
+                text.parse::<f32>().map_err(|_| parse_error(name, text))
+            };
+
+        let integer = |name: &str| -> Result<usize, String>
+            {
+                let text = value(name)?;
+                text.parse::<usize>().map_err(|_| parse_error(name, text))
+            };
+
+        let boolean = |name: &str| -> Result<bool, String>
+            {
+                let text = value(name)?;
+                text.parse::<bool>().map_err(|_| parse_error(name, text))
+            };
+
+        Ok(Session
+        {
+            main_camera: CameraPose{ position: vector("main_camera_position")?, direction: vector("main_camera_direction")? },
+            top_camera: CameraPose{ position: vector("top_camera_position")?, direction: vector("top_camera_direction")? },
+            right_camera: CameraPose{ position: vector("right_camera_position")?, direction: vector("right_camera_direction")? },
+            sun_position: vector("sun_position")?,
+            sun_look_at: vector("sun_look_at")?,
+            epsilon: number("epsilon")?,
+            min_num_points: integer("min_num_points")? as u32,
+            grid_colour_preset_index: integer("grid_colour_preset")?,
+            cloud_translation: vector("cloud_translation")?,
+            reflect_vertically: boolean("reflect_vertically")?,
+            show_grid_labels: boolean("show_grid_labels")?,
+            shadows_enabled: boolean("shadows_enabled")?,
+            add_lidar_pos: boolean("add_lidar_pos")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::helper_logic::session::Session;
+    use crate::view_logic::pose_history::CameraPose;
+
+    #[test]
+    fn session_reads_back_the_same()
+    {
+        let session = Session
+        {
+            main_camera: CameraPose{ position: vec3(1.0, 2.0, 3.0), direction: vec3(0.0, 0.0, -1.0) },
+            top_camera: CameraPose{ position: vec3(0.0, 5.5, 0.0), direction: vec3(0.0, -1.0, 0.0) },
+            right_camera: CameraPose{ position: vec3(0.0, 0.0, 4.25), direction: vec3(0.0, 0.0, -1.0) },
+            sun_position: vec3(-3.0, 3.0, 0.5),
+            sun_look_at: vec3(0.0, 0.0, 0.0),
+            epsilon: 0.35,
+            min_num_points: 12,
+            grid_colour_preset_index: 2,
+            cloud_translation: vec3(0.1, -0.2, 0.0),
+            reflect_vertically: true,
+            show_grid_labels: false,
+            shadows_enabled: true,
+            add_lidar_pos: false,
+        };
+
+        assert_eq!(session, Session::parse(&session.to_file_contents()).unwrap());
+
+        let missing_value = session.to_file_contents().replace("epsilon 0.35\n", "");
+        assert!(Session::parse(&missing_value).is_err());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether status messages are written to stderr instead of stdout. Shared so that every part of
+/// the program writes its status messages to the same place
+static STATUS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Sets where status messages are written. With --json_control, stdout only holds the acknowledgements
+/// of the commands, so that they can be read without having to pick them out from other messages.
+/// Should be called before the window is created, as creating it writes status messages
+///
+/// `to_stderr` - true if status messages should be written to stderr
+pub fn set_status_to_stderr(to_stderr: bool)
+{
+    STATUS_TO_STDERR.store(to_stderr, Ordering::Relaxed);
+}
+
+/// Writes a line about what the program is doing, such as what was exported, to stdout, or to stderr
+/// if stdout is reserved for something else
+///
+/// `message` - the line to write
+pub fn print_status<A: AsRef<str>>(message: A)
+{
+    if STATUS_TO_STDERR.load(Ordering::Relaxed)
+    {
+        eprintln!("{}", message.as_ref());
+    }
+    else
+    {
+        println!("{}", message.as_ref());
+    }
+}
\ No newline at end of file
@@ -3,28 +3,216 @@ use std::io::{BufReader, Read};
 use std::process::Command;
 use std::str::FromStr;
 use std::sync::mpsc::Receiver;
-use lazy_static::lazy_static;
-use nalgebra_glm::TVec3;
-use crate::rendering::scene_renderer::{SceneRenderer, ModelId, UploadInformation, default_point_colour};
+use nalgebra_glm::{TVec3, vec3};
+use crate::rendering::scene_renderer::{SceneRenderer, ModelId, UploadInformation, VelocityColourMode, VelocityColours, default_point_colour, scalar_point_scales};
 use crate::rendering::cluster_colour::ClusterColour;
 use crate::helper_logic::folder_location_functions::get_cluster_program_location;
-use crate::ipc_logic::ipc_receiver::SendContents;
-
-// This is static so that it does need to be recalculated everytime the point cloud is updated though
-// IPC. It could be passed in as a parameter, but the this variable is only used in one place and
-// the structure holding program variables (and its substructures) are already big
-lazy_static!
-{
-    static ref CLUSTER_COLOUR: ClusterColour = ClusterColour::new();
-}
+use crate::cluster_logic::dbscan;
+use crate::cluster_logic::frozen_clusters::FrozenClusters;
+use crate::view_logic::point_smoothing::PointSmoothing;
+use crate::view_logic::update_heatmap::UpdateHeatmap;
+use crate::ipc_logic::ipc_receiver::{IPCContributor, ParseMode, SendContents, UpAxis};
 
 /// Holds required variables to perform cluster detection and read its results
 #[derive(Clone)]
 pub struct ClusterInformation
 {
     pub output_file: String,
+    pub output_format: ClusterOutputFormat,
+    pub backend: ClusterBackend,
+    pub epsilon: f32,
+    pub min_epsilon: f32,
+    pub max_epsilon: f32,
+    pub min_num_points: u32,
+    /// Scale applied to the point cloud files; epsilon is in the scaled units
+    pub units_scale: f32,
+    /// Which axis points up in the point cloud files
+    pub up_axis: UpAxis,
+    /// How values in the point cloud files that are not numbers are handled
+    pub parse_mode: ParseMode,
+    /// Clusters are found in the displayed points rather than in the point cloud file
+    pub cluster_displayed: bool,
+    /// The colours given to the clusters that are found
+    pub cluster_colour: ClusterColour,
+}
+
+/// The possible layouts of the file written by the cluster detection program
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClusterOutputFormat
+{
+    /// Whitespace separated cluster indices, one per point (-1 is noise)
+    Index,
+    /// One "r g b" triplet of floats per line, one line per point
+    Rgb,
+}
+
+/// The possible implementations used to find clusters in the point cloud
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClusterBackend
+{
+    /// Clusters are found by the program in the ClusterDetectionExe folder
+    External,
+    /// Clusters are found within this program
+    Internal,
+}
+
+/// The result of the cluster detection
+pub struct ClusterResult
+{
+    /// The colour of each point, indicating the cluster it is part of
+    pub colours: Vec<TVec3<f32>>,
+    /// The number of points in each cluster
+    pub cluster_sizes: Vec<usize>,
+    /// The number of points not part of any cluster
+    pub num_noise: usize,
+    /// The cluster index of each point; -1 for points not part of any cluster
+    pub cluster_indices: Vec<isize>,
+}
+
+impl ClusterResult
+{
+    /// Expands the result of clustering only some of the points to all of the points. The points left
+    /// out are given the colour passed in and are not part of any cluster, without counting as noise
+    ///
+    /// `kept` - if each point was among the points that were clustered
+    /// `removed_colour` - the colour given to the points left out
+    pub fn include_removed_points(self, kept: &[bool], removed_colour: TVec3<f32>) -> ClusterResult
+    {
+        let mut colours = self.colours.into_iter();
+        let mut cluster_indices = self.cluster_indices.into_iter();
+
+        let (colours, cluster_indices) = kept.iter()
+            .map(|x|
+                {
+                    if *x
+                    {
+                        (colours.next().unwrap_or(removed_colour), cluster_indices.next().unwrap_or(-1))
+                    }
+                    else
+                    {
+                        (removed_colour, -1)
+                    }
+                })
+            .unzip();
+
+        ClusterResult{ colours, cluster_sizes: self.cluster_sizes, num_noise: self.num_noise, cluster_indices }
+    }
+}
+
+/// Record of the most recent cluster detection, kept so that it can be printed
+pub struct ClusterSummary
+{
     pub epsilon: f32,
     pub min_num_points: u32,
+    pub cluster_sizes: Vec<usize>,
+    pub num_noise: usize,
+}
+
+impl ClusterSummary
+{
+    /// Creates the record of a cluster detection
+    ///
+    /// `cluster_information` - the parameters the clusters were found with
+    /// `cluster_result` - the clusters that were found
+    pub fn new(cluster_information: &ClusterInformation, cluster_result: &ClusterResult) -> ClusterSummary
+    {
+        ClusterSummary
+        {
+            epsilon: cluster_information.epsilon,
+            min_num_points: cluster_information.min_num_points,
+            cluster_sizes: cluster_result.cluster_sizes.clone(),
+            num_noise: cluster_result.num_noise
+        }
+    }
+
+    /// Get the record as CSV lines: a header and a line holding the parameters and the number of
+    /// clusters, followed by a header and a line for the size of each cluster
+    pub fn to_csv_lines(&self) -> Vec<String>
+    {
+        let mut lines = vec!
+        [
+            "epsilon,min_num_points,num_clusters,num_noise".to_string(),
+            format!("{},{},{},{}", self.epsilon, self.min_num_points, self.cluster_sizes.len(), self.num_noise),
+            "cluster,num_points".to_string()
+        ];
+
+        lines.extend(self.cluster_sizes.iter().enumerate().map(|(index, size)| format!("{},{}", index, size)));
+        lines
+    }
+}
+
+/// The cluster of each point of the most recent cluster detection, kept so that it can be exported
+pub struct ClusterAssignments
+{
+    /// The positions of the points the clusters were found in, as rendered
+    pub points: Vec<TVec3<f32>>,
+    /// The cluster index of each point; -1 for points not part of any cluster
+    pub cluster_indices: Vec<isize>,
+}
+
+/// Decides whether a received point is kept, such as to only show points within a range of the lidar
+pub type PointFilter = Box<dyn Fn(&TVec3<f32>) -> bool>;
+
+/// A borrowed point filter, used while the received points are filtered
+pub type PointFilterRef<'a> = &'a dyn Fn(&TVec3<f32>) -> bool;
+
+/// The points that are kept, along with the values given for those points
+pub type KeptPoints<T> = (Vec<TVec3<f32>>, Vec<T>);
+
+/// What happens to a point cloud with more points than the point limit
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PointLimitPolicy
+{
+    /// Every n-th point is kept, so that the point cloud keeps its shape with fewer points
+    Downsample,
+    /// The whole point cloud is rejected
+    Refuse,
+}
+
+/// Limits the number of points of a point cloud, so that accidentally giving an enormous point cloud
+/// does not exhaust memory or hang the program
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointLimit
+{
+    pub max_points: usize,
+    pub policy: PointLimitPolicy,
+}
+
+impl PointLimit
+{
+    /// Applies the limit to the given points, returning the points that are kept along with the values
+    /// given for those points. An error is returned if the point cloud is refused
+    ///
+    /// `points` - the points of the point cloud
+    /// `values` - a value for each point, such as its velocity; empty if there are none
+    pub fn apply<T: Copy>(&self, points: Vec<TVec3<f32>>, values: Vec<T>) -> Result<KeptPoints<T>, String>
+    {
+        if points.len() <= self.max_points
+        {
+            return Ok((points, values));
+        }
+
+        match self.policy
+        {
+            PointLimitPolicy::Downsample =>
+                {
+                    // Rounded up, so that no more than the maximum number of points are kept
+                    let stride = points.len().div_ceil(self.max_points);
+                    let values = if values.len() == points.len() { values.into_iter().step_by(stride).collect() } else { values };
+                    Ok((points.into_iter().step_by(stride).collect(), values))
+                },
+            PointLimitPolicy::Refuse => Err(format!("Point cloud of {} points refused; the maximum is {} (--max_points)", points.len(), self.max_points))
+        }
+    }
+
+    /// Get the text telling that a point cloud was downsampled
+    ///
+    /// `num_points` - the number of points of the point cloud
+    /// `num_kept` - the number of points kept after downsampling
+    pub fn to_string_downsampled(num_points: usize, num_kept: usize) -> String
+    {
+        format!("Point cloud downsampled from {} to {} points (--max_points)", num_points, num_kept)
+    }
 }
 
 /// Holds required variables to perform a multi-threaded IPC update
@@ -34,7 +222,15 @@ pub struct IPCProcessingArgs<'a>
     pub buffer_group: &'a mut SceneRenderer,
     pub point_model_id: ModelId,
     pub cluster_information: &'a ClusterInformation,
+    pub frozen_clusters: &'a mut FrozenClusters,
+    pub point_smoothing: &'a mut PointSmoothing,
+    pub update_heatmap: &'a mut UpdateHeatmap,
+    pub point_filter: Option<PointFilterRef<'a>>,
     pub display_lidar_pos: bool,
+    pub velocity_colour_mode: Option<VelocityColourMode>,
+    pub point_limit: Option<PointLimit>,
+    /// The smallest and largest scale of the points if they are sized by their scalars
+    pub point_scale_range: Option<(f32, f32)>,
 }
 
 /// Holds information about the result of updating the point cloud
@@ -43,6 +239,7 @@ pub struct UploadResult
     pub updated_lidar_file: Option<String>,
     pub num_points: Option<usize>,
     pub lidar_pos: Option<TVec3<f32>>,
+    pub velocity_legend: Option<String>,
     pub cluster_error_message: String
 }
 
@@ -67,33 +264,90 @@ pub fn process_ipc_content(ipc_args: IPCProcessingArgs) -> IPCUpdateResult
                 {
                     Ok(i) =>
                         {
-                            // If lidar pos is in the content file, the first data point is the lidar
-                            // position. Thus the number of point cloud points instances is one less
-                            //  than the number of points in the data file
-                            let (num_instances, lidar_pos) = if ipc_args.display_lidar_pos
+                            // A lidar position given by the header line of the content file is used
+                            // over the first point convention. Otherwise, if lidar pos is in the content
+                            // file, the first data point is the lidar position. Thus the number of point
+                            // cloud points instances is one less than the number of points in the data file
+                            let (num_instances, lidar_pos) = match i.lidar_pos
                             {
-                                (i.points.len() - 1, Some(i.points[0]))
-                            }
-                            else
-                            {
-                                (i.points.len(), None)
+                                Some(lidar_pos) => (i.points.len(), Some(lidar_pos)),
+                                None if ipc_args.display_lidar_pos => (i.points.len() - 1, Some(i.points[0])),
+                                None => (i.points.len(), None)
                             };
 
+                            // The indices of the kept points are tracked so that the velocities and scalars of
+                            // those points can be kept as well
                             let starting_index = i.points.len() - num_instances;
+                            let (kept_points, kept_indices) = filter_points(&i.points[starting_index..], &(starting_index..i.points.len()).collect::<Vec<usize>>(),
+                                                                            ipc_args.point_filter);
 
-                            ipc_args.buffer_group.upload_instance_information(vec![UploadInformation
+                            let num_received_points = kept_points.len();
+                            let (kept_points, kept_indices) = match ipc_args.point_limit
+                            {
+                                Some(point_limit) => match point_limit.apply(kept_points, kept_indices)
+                                {
+                                    Ok(i) => i,
+                                    Err(err) => return IPCUpdateResult::Error(err)
+                                },
+                                None => (kept_points, kept_indices)
+                            };
+
+                            let kept_velocities = kept_indices.iter().filter_map(|x| i.velocities.get(*x).copied()).collect::<Vec<TVec3<f32>>>();
+                            let scales = ipc_args.point_scale_range.map(|scale_range|
+                                {
+                                    let kept_scalars = kept_indices.iter().filter_map(|x| i.scalars.get(*x).copied()).collect::<Vec<f32>>();
+                                    scalar_point_scales(&kept_scalars, scale_range)
+                                });
+
+                            let points = ipc_args.point_smoothing.smooth(&kept_points);
+                            let velocity_colours = ipc_args.velocity_colour_mode
+                                .map(|x| VelocityColours::new(&kept_velocities, x));
+
+                            // The heatmap is shown over every other colouring while it is enabled
+                            let heatmap_colours = ipc_args.update_heatmap.record_update(&points);
+                            let colours = match (heatmap_colours, &velocity_colours)
+                            {
+                                (Some(heatmap_colours), _) => heatmap_colours,
+                                (None, Some(velocity_colours)) => ipc_args.frozen_clusters.colours_for_update_with_defaults(&points, velocity_colours.colours.clone()),
+                                (None, None) => ipc_args.frozen_clusters.colours_for_update(&points, default_point_colour())
+                            };
+
+                            let upload_result = ipc_args.buffer_group.upload_instance_information(vec![UploadInformation
                             {
                                 model_id: ipc_args.point_model_id,
-                                instance_colours: Some(&vec![default_point_colour(); num_instances]),
-                                instance_translations: Some(&i.points[starting_index..]),
+                                instance_colours: Some(&colours),
+                                instance_translations: Some(&points),
+                                instance_alphas: None,
+                                instance_shapes: None,
+                                instance_scales: scales.as_deref(),
                             }]);
 
+                            if let Err(err) = upload_result
+                            {
+                                return IPCUpdateResult::Error(format!("Failed to upload updated point cloud: {}", err));
+                            }
+
                             return IPCUpdateResult::Success(UploadResult
                             {
                                 updated_lidar_file: Some(i.file_name),
                                 lidar_pos,
-                                num_points: Some(num_instances),
-                                cluster_error_message: "Cluster program status: No Error".to_string()
+                                num_points: Some(points.len()),
+                                velocity_legend: if ipc_args.update_heatmap.is_enabled()
+                                {
+                                    Some(ipc_args.update_heatmap.to_string_legend())
+                                }
+                                else
+                                {
+                                    velocity_colours.map(|x| x.to_string_legend())
+                                },
+                                cluster_error_message: if kept_points.len() < num_received_points
+                                {
+                                    PointLimit::to_string_downsampled(num_received_points, kept_points.len())
+                                }
+                                else
+                                {
+                                    "Cluster program status: No Error".to_string()
+                                }
                             });
                         }
                     Err(err) =>  return IPCUpdateResult::Error(format!("Error parsing updated data: {}", err))
@@ -111,16 +365,53 @@ pub fn process_ipc_content(ipc_args: IPCProcessingArgs) -> IPCUpdateResult
     IPCUpdateResult::NoChange
 }
 
+/// Keeps the points that the given filter accepts, along with the values given for those points.
+/// Without a filter every point is kept
+///
+/// `points` - the points to filter
+/// `values` - a value for each point, such as its velocity or colour; empty if there are none
+/// `point_filter` - decides whether a point is kept, if any
+pub fn filter_points<T: Copy>(points: &[TVec3<f32>], values: &[T], point_filter: Option<PointFilterRef>) -> KeptPoints<T>
+{
+    let point_filter = match point_filter
+    {
+        Some(i) => i,
+        None => return (points.to_vec(), values.to_vec())
+    };
+
+    let kept_indices = (0..points.len()).filter(|x| point_filter(&points[*x])).collect::<Vec<usize>>();
+
+    (kept_indices.iter().map(|x| points[*x]).collect(), kept_indices.iter().filter_map(|x| values.get(*x).copied()).collect())
+}
+
+/// The ways launching the cluster detection program can fail
+pub enum ClusterProgramError
+{
+    /// The cluster detection program does not exist; further attempts will fail the same way
+    NotFound(String),
+    /// The cluster detection program exists but did not run successfully
+    Failed(String),
+}
+
 /// Launches the cluster program to find clusters in the point cloud
 ///
 /// `cluster_information` - parameters for the cluster detection program
 /// `content_file` - the file that contains the point cloud for the cluster detection
-pub fn launch_cluster_program(cluster_information: &ClusterInformation, content_file: &String) -> Result<(), String>
+pub fn launch_cluster_program(cluster_information: &ClusterInformation, content_file: &String) -> Result<(), ClusterProgramError>
 {
-    let cluster_output = Command::new(get_cluster_program_location())
+    let cluster_program = get_cluster_program_location();
+
+    // Checked explicitly as otherwise the error from Command is an opaque OS error message
+    if !cluster_program.is_file()
+    {
+        return Err(ClusterProgramError::NotFound(format!("Cluster binary not found at {}; clustering disabled", cluster_program.display())));
+    }
+
+    let cluster_output = Command::new(cluster_program)
         .arg(content_file)
         .arg(&cluster_information.output_file)
-        .arg(cluster_information.epsilon.to_string())
+        // The cluster program reads the unscaled point cloud file
+        .arg((cluster_information.epsilon / cluster_information.units_scale).to_string())
         .arg(cluster_information.min_num_points.to_string())
         .output();
 
@@ -134,30 +425,51 @@ pub fn launch_cluster_program(cluster_information: &ClusterInformation, content_
                         {
                             if code == -1
                             {
-                                return Err("Error running cluster program :".to_string() + &String::from_utf8_lossy(&i.stderr));
+                                return Err(ClusterProgramError::Failed("Error running cluster program :".to_string() + &String::from_utf8_lossy(&i.stderr)));
                             }
                         },
                     None =>
                         {
-                            return Err("Failed to get result of cluster detection program".to_string());
+                            // No exit code means the program was terminated by a signal (ie it crashed)
+                            return Err(ClusterProgramError::Failed("Cluster detection program terminated unexpectedly".to_string()));
                         }
                 }
             },
         Err(err) =>
             {
-                return Err("Error with cluster detection program: ".to_string() + &err.to_string());
+                return Err(ClusterProgramError::Failed("Error with cluster detection program: ".to_string() + &err.to_string()));
             }
     }
 
     Ok(())
 }
 
-/// Reads the result of the cluster detection and returns a vector of colours indicating the clusters
-/// visually. An index of 0 in the return result corresponds to the first point in the point cloud file
-/// passed to the cluster detection program.
+/// Finds clusters in the point cloud without using the external cluster program. The result has the
+/// same layout as the one given by read_cluster_output_file
+///
+/// `cluster_information` - parameters for the cluster detection
+/// `content_file` - the file that contains the point cloud for the cluster detection
+pub fn find_clusters_internally(cluster_information: &ClusterInformation, content_file: &String) -> Result<ClusterResult, String>
+{
+    let mut file_contents = String::new();
+    let read_result = File::open(content_file).and_then(|mut x| x.read_to_string(&mut file_contents));
+    if let Err(err) = read_result
+    {
+        return Err("Error reading point cloud for cluster detection: ".to_string() + &err.to_string());
+    }
+
+    let points = IPCContributor::parse_read_data(&file_contents, cluster_information.units_scale, cluster_information.up_axis, cluster_information.parse_mode)?;
+
+    let cluster_indices = dbscan::find_clusters(&points, cluster_information.epsilon, cluster_information.min_num_points);
+    Ok(cluster_result_from_indices(&cluster_indices, &cluster_information.cluster_colour))
+}
+
+/// Reads the result of the cluster detection, holding the colours indicating the clusters visually.
+/// An index of 0 in the colours corresponds to the first point in the point cloud file passed to the
+/// cluster detection program.
 ///
 /// `cluster_information` - the variable holding the location of the file holding the cluster detection result
-pub fn read_cluster_output_file(cluster_information: &ClusterInformation) -> Result<Vec<TVec3<f32>>, String>
+pub fn read_cluster_output_file(cluster_information: &ClusterInformation) -> Result<ClusterResult, String>
 {
     let file = match File::open(&cluster_information.output_file)
     {
@@ -172,24 +484,249 @@ pub fn read_cluster_output_file(cluster_information: &ClusterInformation) -> Res
         return Err("Failed to read cluster result file".to_string());
     }
 
-    let mut colours = Vec::new();
+    parse_cluster_output(&file_contents, cluster_information.output_format, &cluster_information.cluster_colour)
+}
 
-    for x in file_contents.split_whitespace()
+/// Converts the contents of a cluster detection result file into the colour of each point. With the
+/// Rgb format, each distinct colour other than the noise colour is counted as a cluster
+///
+/// `file_contents` - the contents of the cluster detection result file
+/// `output_format` - how the contents of the file are laid out
+/// `cluster_colour` - the colours given to the cluster indices
+pub fn parse_cluster_output(file_contents: &str, output_format: ClusterOutputFormat, cluster_colour: &ClusterColour) -> Result<ClusterResult, String>
+{
+    match output_format
     {
-        let cluster_index = match isize::from_str(x)
-        {
-            Ok(i) => i,
-            Err(err) =>
+        ClusterOutputFormat::Index =>
+            {
+                let mut cluster_indices = Vec::new();
+
+                for x in file_contents.split_whitespace()
+                {
+                    let cluster_index = match isize::from_str(x)
+                    {
+                        Ok(i) => i,
+                        Err(err) =>
+                            {
+                                if cfg!(debug_assertions)
+                                {
+                                    eprintln!("Could not convert {} to an integer: {}", x, err);
+                                }
+                                -1
+                            }
+                    };
+                    cluster_indices.push(cluster_index);
+                }
+
+                Ok(cluster_result_from_indices(&cluster_indices, cluster_colour))
+            },
+        ClusterOutputFormat::Rgb =>
+            {
+                let mut colours = Vec::new();
+
+                // Blank lines (such as a trailing newline) do not correspond to a point
+                for (line_number, line) in file_contents.lines().enumerate().filter(|(_, x)| !x.trim().is_empty())
                 {
-                    if cfg!(debug_assertions)
+                    let components = line.split_whitespace().collect::<Vec<&str>>();
+                    if components.len() != 3
+                    {
+                        return Err(format!("Expected three colour components on line {} of cluster result file, found {}", line_number + 1, components.len()));
+                    }
+
+                    let mut colour = [0.0_f32; 3];
+                    for (index, component) in components.iter().enumerate()
                     {
-                        eprintln!("Could not convert {} to an integer: {}", x, err);
+                        colour[index] = match f32::from_str(component)
+                        {
+                            Ok(i) => i,
+                            Err(err) => return Err(format!("Invalid colour component {} on line {} of cluster result file: {}", component, line_number + 1, err))
+                        };
                     }
-                    -1
+
+                    colours.push(vec3(colour[0], colour[1], colour[2]));
                 }
-        };
-        colours.push(CLUSTER_COLOUR.get_colour((cluster_index + 1) as usize));
+
+                let noise_colour = cluster_colour.get_colour(0);
+                let mut cluster_colours: Vec<TVec3<f32>> = Vec::new();
+                let mut cluster_sizes = Vec::new();
+                let mut cluster_indices = Vec::with_capacity(colours.len());
+                let mut num_noise = 0;
+
+                // Clusters are numbered in the order their colours first appear
+                for colour in colours.iter()
+                {
+                    if *colour == noise_colour
+                    {
+                        num_noise += 1;
+                        cluster_indices.push(-1);
+                    }
+                    else
+                    {
+                        match cluster_colours.iter().position(|x| x == colour)
+                        {
+                            Some(index) =>
+                                {
+                                    cluster_sizes[index] += 1;
+                                    cluster_indices.push(index as isize);
+                                },
+                            None =>
+                                {
+                                    cluster_indices.push(cluster_colours.len() as isize);
+                                    cluster_colours.push(*colour);
+                                    cluster_sizes.push(1);
+                                }
+                        }
+                    }
+                }
+
+                Ok(ClusterResult{ colours, cluster_sizes, num_noise, cluster_indices })
+            }
     }
+}
+
+/// Creates the cluster result from the cluster index of each point, where negative indices are noise
+///
+/// `cluster_indices` - the cluster index of each point
+/// `cluster_colour` - the colours given to the cluster indices
+fn cluster_result_from_indices(cluster_indices: &[isize], cluster_colour: &ClusterColour) -> ClusterResult
+{
+    let mut cluster_sizes = vec![0; cluster_indices.iter().max().map_or(0, |x| (*x + 1).max(0) as usize)];
+    let mut num_noise = 0;
 
-    Ok(colours)
+    for cluster_index in cluster_indices
+    {
+        if *cluster_index < 0
+        {
+            num_noise += 1;
+        }
+        else
+        {
+            cluster_sizes[*cluster_index as usize] += 1;
+        }
+    }
+
+    ClusterResult
+    {
+        colours: cluster_indices.iter().map(|x| cluster_colour.get_colour(((*x).max(-1) + 1) as usize)).collect(),
+        cluster_sizes,
+        num_noise,
+        cluster_indices: cluster_indices.iter().map(|x| (*x).max(-1)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::{TVec3, vec3};
+    use crate::ipc_logic::ipc_content_logic::{ClusterOutputFormat, ClusterResult, ClusterSummary, PointLimit, PointLimitPolicy, filter_points, parse_cluster_output};
+    use crate::rendering::cluster_colour::{ClusterColour, ClusterPalette};
+
+    #[test]
+    fn parse_index_output()
+    {
+        let cluster_colour = ClusterColour::new(ClusterPalette::Default);
+        match parse_cluster_output("0 1\t-1 \n2\n0", ClusterOutputFormat::Index, &cluster_colour)
+        {
+            Ok(i) =>
+                {
+                    assert_eq!(5, i.colours.len(), "Incorrect number of colours");
+                    assert_eq!(cluster_colour.get_colour(1), i.colours[0]);
+                    assert_eq!(cluster_colour.get_colour(2), i.colours[1]);
+                    assert_eq!(cluster_colour.get_colour(0), i.colours[2]);
+                    assert_eq!(cluster_colour.get_colour(3), i.colours[3]);
+
+                    assert_eq!(vec![2, 1, 1], i.cluster_sizes);
+                    assert_eq!(1, i.num_noise);
+                    assert_eq!(vec![0, 1, -1, 2, 0], i.cluster_indices);
+                },
+            Err(_) => panic!("Failed to parse cluster indices")
+        }
+    }
+
+    #[test]
+    fn parse_rgb_output()
+    {
+        match parse_cluster_output("0.5 0 1\n 0.25\t0.75   0.0 \r\n\n1 1 1\n", ClusterOutputFormat::Rgb, &ClusterColour::new(ClusterPalette::Default))
+        {
+            Ok(i) =>
+                {
+                    assert_eq!(vec![1, 1, 1], i.cluster_sizes);
+                    assert_eq!(vec![0, 1, 2], i.cluster_indices);
+
+                    let i = i.colours;
+                    assert_eq!(3, i.len(), "Incorrect number of colours");
+
+                    assert_eq!(0.5, i[0].x);
+                    assert_eq!(0.0, i[0].y);
+                    assert_eq!(1.0, i[0].z);
+
+                    assert_eq!(0.25, i[1].x);
+                    assert_eq!(0.75, i[1].y);
+                    assert_eq!(0.0, i[1].z);
+
+                    assert_eq!(1.0, i[2].x);
+                    assert_eq!(1.0, i[2].y);
+                    assert_eq!(1.0, i[2].z);
+                },
+            Err(_) => panic!("Failed to parse cluster colours")
+        }
+    }
+
+    #[test]
+    fn summary_as_csv()
+    {
+        let summary = ClusterSummary{ epsilon: 0.5, min_num_points: 4, cluster_sizes: vec![10, 3], num_noise: 2 };
+        assert_eq!(vec!["epsilon,min_num_points,num_clusters,num_noise", "0.5,4,2,2", "cluster,num_points", "0,10", "1,3"], summary.to_csv_lines());
+    }
+
+    #[test]
+    fn removed_points_outside_clusters()
+    {
+        let removed_colour = vec3(0.5, 0.5, 0.5);
+        let cluster_result = ClusterResult{ colours: vec![vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)], cluster_sizes: vec![1, 1], num_noise: 0, cluster_indices: vec![0, 1] };
+        let cluster_result = cluster_result.include_removed_points(&[false, true, false, true], removed_colour);
+
+        assert_eq!(vec![removed_colour, vec3(1.0, 0.0, 0.0), removed_colour, vec3(0.0, 1.0, 0.0)], cluster_result.colours);
+        assert_eq!(vec![-1, 0, -1, 1], cluster_result.cluster_indices);
+        assert_eq!(vec![1, 1], cluster_result.cluster_sizes);
+        assert_eq!(0, cluster_result.num_noise);
+    }
+
+    #[test]
+    fn parse_rgb_output_missing_component()
+    {
+        assert!(parse_cluster_output("0.5 0 1\n0.5 0\n", ClusterOutputFormat::Rgb, &ClusterColour::new(ClusterPalette::Default)).is_err());
+        assert!(parse_cluster_output("0.5 0 a\n", ClusterOutputFormat::Rgb, &ClusterColour::new(ClusterPalette::Default)).is_err());
+    }
+
+    #[test]
+    fn filter_keeps_accepted_points_and_values()
+    {
+        let points = vec![vec3(1.0, 0.0, 0.0), vec3(5.0, 0.0, 0.0), vec3(2.0, 0.0, 0.0)];
+        let velocities = vec![vec3(0.0, 1.0, 0.0), vec3(0.0, 2.0, 0.0), vec3(0.0, 3.0, 0.0)];
+        let within_range = |x: &TVec3<f32>| x.norm() < 3.0;
+
+        assert_eq!((vec![points[0], points[2]], vec![velocities[0], velocities[2]]), filter_points(&points, &velocities, Some(&within_range)));
+        assert_eq!((vec![points[0], points[2]], Vec::<TVec3<f32>>::new()), filter_points::<TVec3<f32>>(&points, &[], Some(&within_range)));
+        assert_eq!((points.clone(), velocities.clone()), filter_points(&points, &velocities, None));
+    }
+
+    #[test]
+    fn point_limit_downsamples_or_refuses()
+    {
+        let points = (0..7).map(|x| vec3(x as f32, 0.0, 0.0)).collect::<Vec<TVec3<f32>>>();
+        let velocities = (0..7).collect::<Vec<i32>>();
+
+        let downsample = PointLimit{ max_points: 3, policy: PointLimitPolicy::Downsample };
+        let (kept_points, kept_velocities) = downsample.apply(points.clone(), velocities.clone()).unwrap();
+        assert_eq!(vec![points[0], points[3], points[6]], kept_points);
+        assert_eq!(vec![0, 3, 6], kept_velocities);
+
+        // Values that are not given for every point are left alone
+        assert!(downsample.apply(points.clone(), Vec::<i32>::new()).unwrap().1.is_empty());
+
+        let refuse = PointLimit{ max_points: 7, policy: PointLimitPolicy::Refuse };
+        assert_eq!(points.len(), refuse.apply(points.clone(), velocities.clone()).unwrap().0.len());
+        assert!(PointLimit{ max_points: 6, ..refuse }.apply(points, velocities).is_err());
+    }
 }
\ No newline at end of file
@@ -1,13 +1,26 @@
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
 use std::fs::File;
+use std::hash::{Hash, Hasher};
 use std::io::{Read, Write};
 use std::iter::FromIterator;
 use std::str::FromStr;
 use std::sync::mpsc::SyncSender;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
 use std::thread::sleep;
 use std::time::Duration;
 use nalgebra_glm::{TVec3, vec3};
-use crate::args_parser::IPCFiles;
+use crate::args_parser::{IPCFiles, IPCHandshake};
+use crate::helper_logic::status_output::print_status;
+
+/// Files shorter than this many bytes per thread are parsed on fewer threads, as starting a thread
+/// takes longer than parsing a small file
+const MIN_CHUNK_LENGTH: usize = 1 << 20;
+
+/// Starts the optional first line of a point cloud file that gives the lidar position
+const LIDAR_HEADER: &str = "LIDAR";
 
 /// Monitors the files used for updating the point cloud for any updated point cloud data
 pub struct IPCContributor
@@ -15,13 +28,130 @@ pub struct IPCContributor
     files: Vec<IPCFiles>,
     file_index: usize,
     sender: SyncSender<Result<SendContents, String>>,
-    sleep_duration_ms: u64
+    sleep_duration_ms: u64,
+    parse_settings: ParseSettings,
+    handshake: IPCHandshake,
+    // The hash of the last data file warned about, so that a producer writing the same malformed
+    // file repeatedly does not repeat the warning
+    last_warned_file: Option<u64>
+}
+
+/// How a point cloud file with values that are not numbers is handled
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ParseMode
+{
+    /// The whole point cloud is rejected, so that corrupted files are noticed
+    Strict,
+    /// Only the points with a value that is not a number are skipped, with a warning printed for each
+    Lenient,
+}
+
+/// The values given for each point of a point cloud file, in the order they are written
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PointColumns
+{
+    /// The position of the point, written as x|y|z|
+    Position,
+    /// The position of the point followed by its velocity, written as x|y|z|vx|vy|vz|
+    PositionVelocity,
+    /// The position of the point followed by a scalar, such as its intensity, written as x|y|z|s|
+    PositionScalar,
+    /// The position of the point followed by its velocity and a scalar, written as x|y|z|vx|vy|vz|s|
+    PositionVelocityScalar,
+}
+
+/// Which axis points up in a point cloud file. The program itself has the y-axis pointing up
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum UpAxis
+{
+    /// The z-axis points up, as is common for lidar data. The y and z values are swapped when the file is read or written
+    Z,
+    /// The y-axis points up, the same as the program, so values are read and written as they are
+    Y,
+}
+
+impl UpAxis
+{
+    /// Converts a position or velocity written in a file to the coordinates of the program
+    ///
+    /// `values` - the x, y and z values as written in the file
+    pub fn world_from_file(&self, values: [f32; 3]) -> TVec3<f32>
+    {
+        match self
+        {
+            UpAxis::Z => vec3(values[0], values[2], values[1]),
+            UpAxis::Y => vec3(values[0], values[1], values[2]),
+        }
+    }
+
+    /// Converts a position in the coordinates of the program to the values written in a file
+    ///
+    /// `position` - the position to write
+    pub fn file_from_world(&self, position: &TVec3<f32>) -> TVec3<f32>
+    {
+        match self
+        {
+            UpAxis::Z => vec3(position.x, position.z, position.y),
+            UpAxis::Y => *position,
+        }
+    }
 }
 
+impl PointColumns
+{
+    /// Get the number of values written for each point
+    pub fn num_values(&self) -> usize
+    {
+        match self
+        {
+            PointColumns::Position => 3,
+            PointColumns::PositionVelocity => 6,
+            PointColumns::PositionScalar => 4,
+            PointColumns::PositionVelocityScalar => 7,
+        }
+    }
+
+    /// Check if the velocity of each point is written
+    pub fn has_velocity(&self) -> bool
+    {
+        *self == PointColumns::PositionVelocity || *self == PointColumns::PositionVelocityScalar
+    }
+
+    /// Check if a scalar is written for each point. It is the last value of each point
+    pub fn has_scalar(&self) -> bool
+    {
+        *self == PointColumns::PositionScalar || *self == PointColumns::PositionVelocityScalar
+    }
+}
+
+/// How the values of a point cloud file are read and converted to the coordinates of the program
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParseSettings
+{
+    /// The value each coordinate and velocity is multiplied by, such as to convert the point cloud to meters
+    pub units_scale: f32,
+    /// Which axis of the file points up
+    pub up_axis: UpAxis,
+    /// How values that are not numbers are handled
+    pub parse_mode: ParseMode,
+    /// The values written for each point
+    pub point_columns: PointColumns,
+}
+
+/// The points of a point cloud file, the velocity of each point and the scalar of each point. The
+/// velocities and scalars are empty if the file does not hold them
+pub type ParsedPoints = (Vec<TVec3<f32>>, Vec<TVec3<f32>>, Vec<f32>);
+
 /// The result of reading the output of the updated point cloud file
 pub struct SendContents
 {
     pub points: Vec<TVec3<f32>>,
+    /// The velocity of each point; empty if the file does not hold velocities
+    pub velocities: Vec<TVec3<f32>>,
+    /// The scalar of each point; empty if the file does not hold scalars
+    pub scalars: Vec<f32>,
+    /// The lidar position given by the header line of the file, if the file has one
+    pub lidar_pos: Option<TVec3<f32>>,
     pub file_name: String,
 }
 
@@ -32,9 +162,13 @@ impl IPCContributor
     /// `ipc_files` - the files used for IPC
     /// `sender` - the variable used to send to the rest of the program (this variable runs in its own
     ///             thread) that new point cloud data is available
-    pub fn new(ipc_files: Vec<IPCFiles>, sender: SyncSender<Result<SendContents, String>>, sleep_duration_ms: u64) -> IPCContributor
+    /// `sleep_duration_ms` - the time to wait between checks for updated point cloud data
+    /// `parse_settings` - how the updated point cloud data is read
+    /// `handshake` - the contents of the mutex files used to coordinate with the producer of the point cloud data
+    pub fn new(ipc_files: Vec<IPCFiles>, sender: SyncSender<Result<SendContents, String>>, sleep_duration_ms: u64,
+               parse_settings: ParseSettings, handshake: IPCHandshake) -> IPCContributor
     {
-        IPCContributor{ files: ipc_files, file_index: 0, sender, sleep_duration_ms }
+        IPCContributor{ files: ipc_files, file_index: 0, sender, sleep_duration_ms, parse_settings, handshake, last_warned_file: None }
     }
 
     /// Monitors the IPC files for updated point cloud data
@@ -63,12 +197,12 @@ impl IPCContributor
             {
                 if cfg!(debug_assertions)
                 {
-                    println!("Failed to read mutex file: {}", err.to_string());
+                    print_status(format!("Failed to read mutex file: {}", err));
                 }
 
                 return;
             }
-            if mutex_file_contents == "taken"
+            if mutex_file_contents.trim_end() == self.handshake.taken_sentinel
             {
                 break;
             }
@@ -78,95 +212,384 @@ impl IPCContributor
             sleep(Duration::from_millis(self.sleep_duration_ms));
         }
 
-        let mut point_cloud_data = String::new();
+        // The data file can disappear between the mutex file being checked and the data file being read,
+        // such as if the producer removed it. That is reported the same way as a malformed point cloud
+        let data_file_name = self.files[self.file_index].data_file_names.clone();
+        let read_result = fs::read_to_string(&data_file_name)
+            .map_err(|x| format!("Failed to read point cloud file {}: {}", data_file_name, x));
+
+        // Indicate file can now be used for further point cloud updates. This is done even if the data
+        // file could not be read, so that the producer writes it again rather than it being read repeatedly
+        if let Err(err) = IPCContributor::write_mutex_file(&self.files[self.file_index].mutex_file_names, &self.handshake.clear_sentinel)
         {
-            let mut point_cloud_file = File::open(&self.files[self.file_index].data_file_names).unwrap();
-            if let Err(err) = point_cloud_file.read_to_string(&mut point_cloud_data)
-            {
-                if cfg!(debug_assertions)
+            // The mutex file still indicates the data file is ready, so reading it is tried again later
+            self.send_contents(Err(format!("Failed to write to mutex file {}: {}", self.files[self.file_index].mutex_file_names, err)));
+            sleep(Duration::from_millis(self.sleep_duration_ms));
+            return;
+        }
+
+        if let Ok(read_content) = &read_result
+        {
+            self.warn_incomplete_vertex(read_content, &data_file_name);
+        }
+
+        let contents = read_result
+            .and_then(|x| IPCContributor::parse_contents(&x, data_file_name, self.parse_settings));
+        self.send_contents(contents);
+
+        self.file_index = (self.file_index + 1) % self.files.len();
+    }
+
+    /// Prints a warning if the last point of the data file is incomplete, unless the data file is the
+    /// same as the last one warned about
+    ///
+    /// `read_content` - the contents of the data file
+    /// `data_file_name` - the name of the data file
+    fn warn_incomplete_vertex(&mut self, read_content: &str, data_file_name: &str)
+    {
+        let warning = match IPCContributor::incomplete_vertex_warning(read_content, self.parse_settings.point_columns)
+        {
+            Some(i) => i,
+            None =>
                 {
-                    println!("Failed to read point cloud file: {}", err.to_string());
+                    self.last_warned_file = None;
+                    return;
                 }
+        };
 
-                return;
-            }
+        let mut hasher = DefaultHasher::new();
+        read_content.hash(&mut hasher);
+        let file_hash = hasher.finish();
+
+        if self.last_warned_file != Some(file_hash)
+        {
+            eprintln!("{}: {}", data_file_name, warning);
+            self.last_warned_file = Some(file_hash);
         }
+    }
 
-        // Indicate file can now be used for further point cloud updates
+    /// Sends the result of reading a data file to the rest of the program, where errors are shown in
+    /// the overlay
+    ///
+    /// `contents` - the points read from the data file, or why they could not be read
+    fn send_contents(&self, contents: Result<SendContents, String>)
+    {
+        if let Err(err) = self.sender.send(contents)
         {
-            let mut mutex_file = File::create(&self.files[self.file_index].mutex_file_names).unwrap();
-            if let Err(err) = mutex_file.write(b"clear")
-            {
-               panic!("Failed to write to mutex file: {}", err.to_string());
-            }
+            panic!("Failed to send the result of reading the point cloud file: {}", err);
         }
+    }
 
-        let send_result = match IPCContributor::parse_read_data(&point_cloud_data)
+    /// Replaces the contents of the given mutex file. The contents are written to a temporary file that
+    /// is then renamed to the mutex file, so that the producer never reads a partially written mutex file
+    ///
+    /// `mutex_file_name` - the mutex file to write to
+    /// `contents` - the contents to write to the mutex file
+    fn write_mutex_file(mutex_file_name: &String, contents: &String) -> Result<(), String>
+    {
+        let temp_file_name = mutex_file_name.clone() + ".tmp";
+
+        {
+            let mut temp_file = File::create(&temp_file_name).map_err(|x| x.to_string())?;
+            temp_file.write_all(contents.as_bytes()).map_err(|x| x.to_string())?;
+        }
+
+        fs::rename(&temp_file_name, mutex_file_name).map_err(|x| x.to_string())
+    }
+
+    /// Parses a point cloud file into the contents sent to the rest of the program: the lidar position
+    /// from the header line, if there is one, followed by the points
+    ///
+    /// `read_content` - the file containing updated point cloud data
+    /// `file_name` - the name of the file that was read
+    /// `parse_settings` - how the values of the file are read
+    pub fn parse_contents(read_content: &str, file_name: String, parse_settings: ParseSettings) -> Result<SendContents, String>
+    {
+        let (lidar_pos, point_content) = IPCContributor::split_lidar_header(read_content, parse_settings.units_scale, parse_settings.up_axis)?;
+        let (points, velocities, scalars) = IPCContributor::parse_points(point_content, parse_settings)?;
+
+        Ok(SendContents{ points, velocities, scalars, lidar_pos, file_name })
+    }
+
+    /// Checks if the last point of a point cloud file is missing some of its values, which happens if
+    /// the file was written only partly. Such a point is dropped when the file is parsed; the returned
+    /// warning says so
+    ///
+    /// `read_content` - the point cloud file, with or without the header line giving the lidar position
+    /// `point_columns` - the values written for each point
+    pub fn incomplete_vertex_warning(read_content: &str, point_columns: PointColumns) -> Option<String>
+    {
+        let num_values = point_columns.num_values();
+        let trimmed_content = read_content.trim_end().trim_end_matches('|');
+        if trimmed_content.is_empty()
         {
-            Ok(points) => self.sender.send(Ok(SendContents{ points, file_name: self.files[self.file_index].data_file_names.clone() })),
-            Err(err) => self.sender.send(Err(err))
+            return None;
+        }
+
+        // The header line does not contain a separator, so it only adds to the first value
+        let num_written_values = trimmed_content.split('|').count();
+        if num_written_values.is_multiple_of(num_values)
+        {
+            return None;
+        }
+
+        Some(format!("Incomplete last vertex, did not receive {} components to form a vertex. New vertex count: {}",
+                     num_values, IPCContributor::round_number_down(num_written_values, num_values) / num_values))
+    }
+
+    /// Separates the optional header line giving the lidar position, written as "LIDAR x y z", from
+    /// the points of a point cloud file. The lidar position is converted the same way as the points.
+    /// Returns the lidar position, if the file has the header, and the rest of the file
+    ///
+    /// `read_content` - the file containing updated point cloud data
+    /// `units_scale` - the value each coordinate is multiplied by, such as to convert the point cloud to meters
+    /// `up_axis` - which axis of the file points up
+    pub fn split_lidar_header(read_content: &str, units_scale: f32, up_axis: UpAxis) -> Result<(Option<TVec3<f32>>, &str), String>
+    {
+        let trimmed_content = read_content.trim_start();
+        if !trimmed_content.starts_with(LIDAR_HEADER)
+        {
+            return Ok((None, read_content));
+        }
+
+        let (header, point_content) = match trimmed_content.find('\n')
+        {
+            Some(i) => (&trimmed_content[..i], &trimmed_content[i + 1..]),
+            None => (trimmed_content, "")
         };
 
-        if let Err(err) = send_result
+        let values = header[LIDAR_HEADER.len()..].split_whitespace()
+            .map(|x| f32::from_str(x).map_err(|err| format!("Failed to parse lidar position {}. Error: {}", header.trim_end(), err)))
+            .collect::<Result<Vec<f32>, String>>()?;
+
+        if values.len() != 3
         {
-            panic!("Failed to send the result of reading the initial point cloud file: {}", err.to_string());
+            return Err(format!("Lidar position {} does not have 3 components", header.trim_end()));
         }
 
-        self.file_index = (self.file_index + 1) % self.files.len();
+        Ok((Some(up_axis.world_from_file([values[0], values[1], values[2]]) * units_scale), point_content))
     }
 
     /// Parses the data file containing the updated point cloud to extract the updated points of the
     /// point cloud
     ///
     /// `read_content` - the file containing updated point cloud data
-    pub fn parse_read_data(read_content: &String) -> Result<Vec<TVec3<f32>>, String>
+    /// `units_scale` - the value each coordinate is multiplied by, such as to convert the point cloud to meters
+    /// `up_axis` - which axis of the file points up
+    /// `parse_mode` - how values that are not numbers are handled
+    pub fn parse_read_data(read_content: &str, units_scale: f32, up_axis: UpAxis, parse_mode: ParseMode) -> Result<Vec<TVec3<f32>>, String>
+    {
+        IPCContributor::parse_read_data_with_progress(read_content, units_scale, up_axis, parse_mode, |_| {})
+    }
+
+    /// Same as parse_read_data, but periodically reports how many points have been parsed so far
+    ///
+    /// `read_content` - the file containing updated point cloud data
+    /// `units_scale` - the value each coordinate is multiplied by, such as to convert the point cloud to meters
+    /// `up_axis` - which axis of the file points up
+    /// `parse_mode` - how values that are not numbers are handled
+    /// `progress` - called with the number of points parsed so far
+    pub fn parse_read_data_with_progress<F: Fn(usize)>(read_content: &str, units_scale: f32, up_axis: UpAxis, parse_mode: ParseMode, progress: F) -> Result<Vec<TVec3<f32>>, String>
     {
-        let handle_parsing = |vertex_number: usize, number: &str|
+        let parse_settings = ParseSettings{ units_scale, up_axis, parse_mode, point_columns: PointColumns::Position };
+        IPCContributor::parse_points_with_progress(read_content, parse_settings, progress).map(|x| x.0)
+    }
+
+    /// Parses the data file containing the updated point cloud to extract the points and, if the file
+    /// holds them, their velocities and scalars. The velocities and scalars are empty otherwise
+    ///
+    /// `read_content` - the file containing updated point cloud data
+    /// `parse_settings` - how the values of the file are read
+    pub fn parse_points(read_content: &str, parse_settings: ParseSettings) -> Result<ParsedPoints, String>
+    {
+        IPCContributor::parse_points_with_progress(read_content, parse_settings, |_| {})
+    }
+
+    /// Same as parse_points, but periodically reports how many points have been parsed so far. Large
+    /// files are divided into chunks of whole points that are parsed on separate threads
+    ///
+    /// `read_content` - the file containing updated point cloud data
+    /// `parse_settings` - how the values of the file are read
+    /// `progress` - called with the number of points parsed so far
+    pub fn parse_points_with_progress<F: Fn(usize)>(read_content: &str, parse_settings: ParseSettings, progress: F) -> Result<ParsedPoints, String>
+    {
+        let num_threads = thread::available_parallelism().map(|x| x.get()).unwrap_or(1);
+        let num_chunks = num_threads.min(read_content.len() / MIN_CHUNK_LENGTH).max(1);
+
+        IPCContributor::parse_points_in_chunks(read_content, parse_settings, num_chunks, progress)
+    }
+
+    /// Parses the point cloud by dividing it into the given number of chunks, each parsed on its own
+    /// thread. The parsed chunks are joined in the order they appear in the file
+    ///
+    /// `read_content` - the file containing updated point cloud data
+    /// `parse_settings` - how the values of the file are read
+    /// `num_chunks` - the maximum number of chunks to divide the file into
+    /// `progress` - called with the number of points parsed so far
+    fn parse_points_in_chunks<F: Fn(usize)>(read_content: &str, parse_settings: ParseSettings, num_chunks: usize, progress: F) -> Result<ParsedPoints, String>
+    {
+        let chunks = IPCContributor::split_into_chunks(read_content, parse_settings.point_columns.num_values(), num_chunks);
+        let last_chunk = chunks.len() - 1;
+
+        let num_parsed = AtomicUsize::new(0);
+        let num_finished = AtomicUsize::new(0);
+        let progress_thread = thread::current();
+
+        thread::scope(|scope|
             {
-                match f32::from_str(number)
-                {
-                    Ok(i) => Ok(i),
-                    Err(err) =>
+                let workers = chunks.iter().enumerate()
+                    .map(|(index, (chunk, first_vertex))|
                         {
-                            let error_result = format!("Failed to parse vertex number {} having value {}. Error: {}", vertex_number, number, err.to_string());
-                            return Err(error_result)
-                        }
+                            let (num_parsed, num_finished, progress_thread) = (&num_parsed, &num_finished, &progress_thread);
+
+                            scope.spawn(move ||
+                                {
+                                    let parsed = IPCContributor::parse_chunk(chunk, *first_vertex, index == last_chunk, parse_settings, num_parsed);
+                                    num_finished.fetch_add(1, Ordering::SeqCst);
+                                    progress_thread.unpark();
+                                    parsed
+                                })
+                        })
+                    .collect::<Vec<_>>();
+
+                // The progress is reported from this thread, as the function reporting it need not be
+                // callable from other threads
+                while num_finished.load(Ordering::SeqCst) < workers.len()
+                {
+                    progress(num_parsed.load(Ordering::SeqCst));
+                    thread::park_timeout(Duration::from_millis(50));
                 }
-            };
 
+                let mut parsed_vertices = Vec::new();
+                let mut parsed_velocities = Vec::new();
+                let mut parsed_scalars = Vec::new();
+
+                for worker in workers
+                {
+                    let (vertices, velocities, scalars) = worker.join().map_err(|_| "Thread parsing the point cloud panicked".to_string())??;
+                    parsed_vertices.extend(vertices);
+                    parsed_velocities.extend(velocities);
+                    parsed_scalars.extend(scalars);
+                }
+
+                Ok((parsed_vertices, parsed_velocities, parsed_scalars))
+            })
+    }
+
+    /// Divides the file into chunks of roughly equal length. Chunks only end after the last value of
+    /// a point, and the separator between chunks is not part of either chunk. Each chunk is returned
+    /// with the index of its first point within the file
+    ///
+    /// `read_content` - the file containing updated point cloud data
+    /// `num_values` - the number of values written for each point
+    /// `num_chunks` - the maximum number of chunks to divide the file into
+    fn split_into_chunks(read_content: &str, num_values: usize, num_chunks: usize) -> Vec<(&str, usize)>
+    {
+        let target_length = read_content.len() / num_chunks.max(1) + 1;
+
+        let mut chunks = Vec::new();
+        let mut chunk_start = 0;
+        let mut first_vertex = 0;
+        let mut num_separators = 0;
+
+        for (index, _) in read_content.bytes().enumerate().filter(|(_, x)| *x == b'|')
+        {
+            num_separators += 1;
+
+            if num_separators % num_values == 0 && index - chunk_start >= target_length && chunks.len() + 1 < num_chunks
+            {
+                chunks.push((&read_content[chunk_start..index], first_vertex));
+                chunk_start = index + 1;
+                first_vertex = num_separators / num_values;
+            }
+        }
+
+        chunks.push((&read_content[chunk_start..], first_vertex));
+        chunks
+    }
+
+    /// Parses the points of a chunk of the file
+    ///
+    /// `chunk` - the part of the file to parse
+    /// `first_vertex` - the index of the first point of the chunk within the file
+    /// `is_last_chunk` - true if the chunk is at the end of the file, where it may end with a separator
+    ///                   or an incomplete point
+    /// `parse_settings` - how the values of the file are read
+    /// `num_parsed` - incremented periodically by the number of points parsed
+    fn parse_chunk(chunk: &str, first_vertex: usize, is_last_chunk: bool, parse_settings: ParseSettings, num_parsed: &AtomicUsize) -> Result<ParsedPoints, String>
+    {
         let pos_component_separator = "|";
 
-        let mut split_content = Vec::from_iter(read_content.split(pos_component_separator));
+        let mut split_content = Vec::from_iter(chunk.split(pos_component_separator));
 
         // In case the last character is the separator itself, remove it so that it is not interpreted
         // as part of a position
         if let Some(last_char) = split_content.last()
         {
-            if *last_char == pos_component_separator || *last_char == ""
+            if is_last_chunk && (*last_char == pos_component_separator || last_char.is_empty())
             {
                 split_content.pop();
             }
         }
 
-        let number_vertices = IPCContributor::round_number_down(split_content.len(), 3);
-
-        if number_vertices != split_content.len()
-        {
-            eprintln!("Incomplete last vertex, did not receive three components to form a vertex. New vertex count: {}", number_vertices);
-        }
+        // An incomplete last vertex is dropped; see incomplete_vertex_warning for reporting it
+        let ParseSettings{ units_scale, up_axis, parse_mode, point_columns } = parse_settings;
+        let num_values = point_columns.num_values();
+        let number_vertices = IPCContributor::round_number_down(split_content.len(), num_values);
 
         let mut parsed_vertices = Vec::new();
+        let mut parsed_velocities = Vec::new();
+        let mut parsed_scalars = Vec::new();
 
-        for v in 0..number_vertices / 3
+        for v in 0..number_vertices / num_values
         {
-            let x_coord = handle_parsing(v, split_content[v * 3])?;
-            let y_coord = handle_parsing(v, split_content[v * 3 + 1])?;
-            let z_coord = handle_parsing(v, split_content[v * 3 + 2])?;
+            if v % 10_000 == 0 && v != 0
+            {
+                num_parsed.fetch_add(10_000, Ordering::SeqCst);
+            }
+
+            let mut values = [0.0_f32; 7];
+            let vertex = split_content[v * num_values..(v + 1) * num_values].iter()
+                .zip(values.iter_mut())
+                .try_for_each(|(number, value)| IPCContributor::parse_value(first_vertex + v, number).map(|x| *value = x));
+
+            // The velocity is written in the same coordinate system as the position. The scalar is not
+            // a length, so it is not scaled
+            match (vertex, parse_mode)
+            {
+                (Ok(_), _) =>
+                    {
+                        parsed_vertices.push(up_axis.world_from_file([values[0], values[1], values[2]]) * units_scale);
+
+                        if point_columns.has_velocity()
+                        {
+                            parsed_velocities.push(up_axis.world_from_file([values[3], values[4], values[5]]) * units_scale);
+                        }
 
-            parsed_vertices.push(vec3(x_coord, z_coord, y_coord));
+                        if point_columns.has_scalar()
+                        {
+                            parsed_scalars.push(values[num_values - 1]);
+                        }
+                    },
+                (Err(err), ParseMode::Lenient) => eprintln!("Skipping vertex: {}", err),
+                (Err(err), ParseMode::Strict) => return Err(err)
+            }
         }
 
-        Ok(parsed_vertices)
+        Ok((parsed_vertices, parsed_velocities, parsed_scalars))
+    }
+
+    /// Parses a single value of a point
+    ///
+    /// `vertex_number` - the index of the point the value belongs to, used in the error message
+    /// `number` - the text of the value
+    fn parse_value(vertex_number: usize, number: &str) -> Result<f32, String>
+    {
+        match f32::from_str(number)
+        {
+            Ok(i) => Ok(i),
+            Err(err) => Err(format!("Failed to parse vertex number {} having value {}. Error: {}", vertex_number, number, err))
+        }
     }
 
     /// Rounds the given number to the next lowest multiple provided
@@ -180,26 +603,32 @@ impl IPCContributor
             return number_to_round;
         }
 
-        let remainder = number_to_round % multiple;
-        if remainder == 0
-        {
-            return number_to_round;
-        }
-
-        (number_to_round + multiple - remainder) - multiple
+        number_to_round - number_to_round % multiple
     }
 }
 
 #[cfg(test)]
 mod tests
 {
-    use crate::ipc_logic::ipc_receiver::IPCContributor;
+    use std::fs;
+    use std::sync::Mutex;
+    use std::sync::mpsc::sync_channel;
+    use std::time::Instant;
+    use nalgebra_glm::vec3;
+    use crate::args_parser::{IPCFiles, IPCHandshake};
+    use crate::ipc_logic::ipc_receiver::{IPCContributor, ParseMode, ParseSettings, PointColumns, UpAxis};
+
+    /// Settings that reject malformed points
+    fn strict_settings(units_scale: f32, up_axis: UpAxis, point_columns: PointColumns) -> ParseSettings
+    {
+        ParseSettings{ units_scale, up_axis, parse_mode: ParseMode::Strict, point_columns }
+    }
 
     #[test]
     fn parse_correct_num_vertices()
     {
         let string = "1|2|3|4|5|6";
-        match IPCContributor::parse_read_data(&string.to_string())
+        match IPCContributor::parse_read_data(string, 1.0, UpAxis::Z, ParseMode::Strict)
         {
             Ok(i) =>
                 {
@@ -221,7 +650,7 @@ mod tests
     fn parse_correct_num_vertices_trailing_separator()
     {
         let string = "1|2|3|";
-        match IPCContributor::parse_read_data(&string.to_string())
+        match IPCContributor::parse_read_data(string, 1.0, UpAxis::Z, ParseMode::Strict)
         {
             Ok(i) =>
                 {
@@ -239,7 +668,7 @@ mod tests
     fn parse_incorrect_num_vertices()
     {
         let string = "2|4|3|4";
-        match IPCContributor::parse_read_data(&string.to_string())
+        match IPCContributor::parse_read_data(string, 1.0, UpAxis::Z, ParseMode::Strict)
         {
             Ok(i) =>
                 {
@@ -252,4 +681,235 @@ mod tests
             Err(_) => assert!(false, "Failed to parse vertices")
         }
     }
+
+    #[test]
+    fn parse_scaled_vertices()
+    {
+        let string = "2|4|-6";
+        match IPCContributor::parse_read_data(string, 0.5, UpAxis::Z, ParseMode::Strict)
+        {
+            Ok(i) =>
+                {
+                    assert_eq!(1, i.len(), "Incorrect number of parsed vertices");
+
+                    assert_eq!(1 as f32, i[0].x);
+                    assert_eq!(-3.0, i[0].y);
+                    assert_eq!(2 as f32, i[0].z);
+                },
+            Err(_) => assert!(false, "Failed to parse vertices")
+        }
+    }
+    #[test]
+    fn parse_velocities()
+    {
+        let string = "1|2|3|4|5|6|7|8|9|10|11|12|13|14";
+        match IPCContributor::parse_points(string, strict_settings(0.5, UpAxis::Z, PointColumns::PositionVelocity))
+        {
+            Ok((points, velocities, _)) =>
+                {
+                    assert_eq!(2, points.len(), "Incorrect number of parsed vertices");
+                    assert_eq!(2, velocities.len(), "Incorrect number of parsed velocities");
+
+                    assert_eq!(vec3(3.5, 4.5, 4.0), points[1]);
+                    assert_eq!(vec3(2.0, 3.0, 2.5), velocities[0]);
+                    assert_eq!(vec3(5.0, 6.0, 5.5), velocities[1]);
+                },
+            Err(_) => assert!(false, "Failed to parse vertices")
+        }
+    }
+
+    #[test]
+    fn parse_scalars()
+    {
+        // The scalar is the last value of each point and is not affected by the units scale
+        let (points, velocities, scalars) = IPCContributor::parse_points("1|2|3|0.25|4|5|6|8|", strict_settings(0.5, UpAxis::Z, PointColumns::PositionScalar)).unwrap();
+        assert_eq!(vec![vec3(0.5, 1.5, 1.0), vec3(2.0, 3.0, 2.5)], points);
+        assert!(velocities.is_empty());
+        assert_eq!(vec![0.25, 8.0], scalars);
+
+        let (points, velocities, scalars) = IPCContributor::parse_points("1|2|3|4|5|6|7|", strict_settings(1.0, UpAxis::Y, PointColumns::PositionVelocityScalar)).unwrap();
+        assert_eq!(vec![vec3(1.0, 2.0, 3.0)], points);
+        assert_eq!(vec![vec3(4.0, 5.0, 6.0)], velocities);
+        assert_eq!(vec![7.0], scalars);
+    }
+
+    #[test]
+    fn parse_lidar_header()
+    {
+        let string = "LIDAR 2 4 6\n1|2|3|4|5|6|";
+        let contents = IPCContributor::parse_contents(string, "cloud.txt".to_string(), strict_settings(0.5, UpAxis::Z, PointColumns::Position)).unwrap();
+
+        assert_eq!(Some(vec3(1.0, 3.0, 2.0)), contents.lidar_pos);
+        assert_eq!(vec![vec3(0.5, 1.5, 1.0), vec3(2.0, 3.0, 2.5)], contents.points);
+
+        // Files without the header are read as before
+        let contents = IPCContributor::parse_contents("1|2|3|", "cloud.txt".to_string(), strict_settings(1.0, UpAxis::Z, PointColumns::Position)).unwrap();
+        assert_eq!(None, contents.lidar_pos);
+        assert_eq!(1, contents.points.len());
+
+        assert!(IPCContributor::parse_contents("LIDAR 2 4\n1|2|3|", "cloud.txt".to_string(), strict_settings(1.0, UpAxis::Z, PointColumns::Position)).is_err());
+    }
+
+    #[test]
+    fn parse_up_axis_conventions()
+    {
+        let string = "LIDAR 2 4 6\n1|2|3|4|5|6|7|8|9|10|11|12|";
+
+        // Z-up files have their y and z values swapped into the y-up coordinates of the program
+        let (points, velocities, _) = IPCContributor::parse_points(string.lines().last().unwrap(), strict_settings(1.0, UpAxis::Z, PointColumns::PositionVelocity)).unwrap();
+        assert_eq!(vec![vec3(1.0, 3.0, 2.0), vec3(7.0, 9.0, 8.0)], points);
+        assert_eq!(vec![vec3(4.0, 6.0, 5.0), vec3(10.0, 12.0, 11.0)], velocities);
+
+        // Y-up files are already in the coordinates of the program
+        let (points, velocities, _) = IPCContributor::parse_points(string.lines().last().unwrap(), strict_settings(1.0, UpAxis::Y, PointColumns::PositionVelocity)).unwrap();
+        assert_eq!(vec![vec3(1.0, 2.0, 3.0), vec3(7.0, 8.0, 9.0)], points);
+        assert_eq!(vec![vec3(4.0, 5.0, 6.0), vec3(10.0, 11.0, 12.0)], velocities);
+
+        let contents = IPCContributor::parse_contents(string, "cloud.txt".to_string(), strict_settings(0.5, UpAxis::Y, PointColumns::Position)).unwrap();
+        assert_eq!(Some(vec3(1.0, 2.0, 3.0)), contents.lidar_pos);
+        assert_eq!(vec3(0.5, 1.0, 1.5), contents.points[0]);
+
+        for up_axis in &[UpAxis::Z, UpAxis::Y]
+        {
+            let position = up_axis.world_from_file([1.0, 2.0, 3.0]);
+            assert_eq!(vec3(1.0, 2.0, 3.0), up_axis.file_from_world(&position));
+        }
+    }
+
+    #[test]
+    fn parse_malformed_vertex_strict()
+    {
+        let string = "1|2|3|4|x|6|7|8|9";
+        assert!(IPCContributor::parse_read_data(string, 1.0, UpAxis::Z, ParseMode::Strict).is_err());
+    }
+
+    #[test]
+    fn parse_malformed_vertex_lenient()
+    {
+        let string = "1|2|3|4|x|6|7|8|9";
+        match IPCContributor::parse_read_data(string, 1.0, UpAxis::Z, ParseMode::Lenient)
+        {
+            Ok(i) =>
+                {
+                    assert_eq!(2, i.len(), "Incorrect number of parsed vertices");
+
+                    assert_eq!(1 as f32, i[0].x);
+                    assert_eq!(3 as f32, i[0].y);
+                    assert_eq!(2 as f32, i[0].z);
+
+                    assert_eq!(7.0, i[1].x);
+                    assert_eq!(9.0, i[1].y);
+                    assert_eq!(8.0, i[1].z);
+                },
+            Err(_) => assert!(false, "Failed to parse vertices")
+        }
+    }
+
+    #[test]
+    fn parse_chunks_matches_single_chunk()
+    {
+        let positions = (0..200).map(|x| format!("{}|{}|{}", x, x + 1, x + 2)).collect::<Vec<_>>().join("|");
+        let velocities = (0..200).map(|x| format!("{}|{}|{}|{}|{}|{}", x, x + 1, x + 2, -x, 0, 1)).collect::<Vec<_>>().join("|");
+
+        let files =
+        [
+            (positions.clone(), PointColumns::Position),
+            (format!("{}|", positions), PointColumns::Position),
+            (format!("{}|7|8", positions), PointColumns::Position),
+            (velocities.clone(), PointColumns::PositionVelocity),
+            (format!("{}|1|2|3|4|", velocities), PointColumns::PositionVelocity),
+        ];
+
+        for (file, point_columns) in &files
+        {
+            let expected = IPCContributor::parse_points_in_chunks(file, strict_settings(1.0, UpAxis::Z, *point_columns), 1, |_| {}).unwrap();
+            assert_eq!(200, expected.0.len());
+
+            for num_chunks in &[2, 3, 7, 64]
+            {
+                let chunks = IPCContributor::split_into_chunks(file, point_columns.num_values(), *num_chunks);
+                assert!(chunks.len() > 1 && chunks.len() <= *num_chunks);
+
+                let parsed = IPCContributor::parse_points_in_chunks(file, strict_settings(1.0, UpAxis::Z, *point_columns), *num_chunks, |_| {}).unwrap();
+                assert_eq!(expected, parsed, "Differs when parsed in {} chunks", num_chunks);
+            }
+        }
+
+        // Errors name the vertex by its position in the whole file
+        let malformed = format!("{}|x|1|2", positions);
+        match IPCContributor::parse_points_in_chunks(&malformed, strict_settings(1.0, UpAxis::Z, PointColumns::Position), 8, |_| {})
+        {
+            Ok(_) => panic!("Malformed vertex was parsed"),
+            Err(err) => assert!(err.starts_with("Failed to parse vertex number 200 "), "Unexpected error: {}", err)
+        }
+    }
+
+    #[test]
+    fn incomplete_last_vertex_dropped()
+    {
+        for (count, rounded) in &[(0, 0), (1, 0), (2, 0), (3, 3), (4, 3), (7, 6)]
+        {
+            assert_eq!(*rounded, IPCContributor::round_number_down(*count, 3), "Rounding {}", count);
+        }
+
+        assert_eq!(None, IPCContributor::incomplete_vertex_warning("", PointColumns::Position));
+        assert_eq!(None, IPCContributor::incomplete_vertex_warning("LIDAR 1 2 3\n1|2|3|4|5|6|\n", PointColumns::Position));
+        assert_eq!(Some("Incomplete last vertex, did not receive 3 components to form a vertex. New vertex count: 2".to_string()),
+                   IPCContributor::incomplete_vertex_warning("LIDAR 1 2 3\n1|2|3|4|5|6|7|", PointColumns::Position));
+        assert!(IPCContributor::incomplete_vertex_warning("1|2|3|4|5|6", PointColumns::PositionVelocity).is_none());
+        assert!(IPCContributor::incomplete_vertex_warning("1|2|3|4|5|6|1|2|3", PointColumns::PositionVelocity).is_some());
+
+        assert_eq!(2, IPCContributor::parse_read_data("1|2|3|4|5|6|7|8", 1.0, UpAxis::Z, ParseMode::Strict).unwrap().len());
+    }
+
+    /// Run with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn parse_chunks_faster_than_single_chunk()
+    {
+        let file = (0..5_000_000).map(|x| format!("{:.3}|{:.3}|{:.3}", x as f32 * 0.001, -(x as f32) * 0.002, (x % 1000) as f32 * 0.5)).collect::<Vec<_>>().join("|");
+        let num_threads = std::thread::available_parallelism().map(|x| x.get()).unwrap_or(1);
+
+        let single_start = Instant::now();
+        let expected = IPCContributor::parse_points_in_chunks(&file, strict_settings(1.0, UpAxis::Z, PointColumns::Position), 1, |_| {}).unwrap();
+        let single_time = single_start.elapsed();
+
+        let chunks_start = Instant::now();
+        let parsed = IPCContributor::parse_points_with_progress(&file, strict_settings(1.0, UpAxis::Z, PointColumns::Position), |_| {}).unwrap();
+        let chunks_time = chunks_start.elapsed();
+
+        assert_eq!(5_000_000, parsed.0.len());
+        assert_eq!(expected, parsed);
+
+        if num_threads > 1
+        {
+            assert!(chunks_time < single_time);
+        }
+    }
+
+    #[test]
+    fn missing_data_file_sends_error()
+    {
+        let mutex_file = std::env::temp_dir().join("ipc_receiver_missing_data_mutex.txt");
+        let data_file = std::env::temp_dir().join("ipc_receiver_missing_data.txt");
+        let _ = fs::remove_file(&data_file);
+        fs::write(&mutex_file, "taken").unwrap();
+
+        let ipc_files = vec![IPCFiles{ mutex_file_names: mutex_file.to_string_lossy().into_owned(), data_file_names: data_file.to_string_lossy().into_owned() }];
+        let handshake = IPCHandshake{ taken_sentinel: "taken".to_string(), clear_sentinel: "clear".to_string() };
+        let (sender, receiver) = sync_channel(1);
+
+        let mut ipc_contributor = IPCContributor::new(ipc_files, sender, 0, strict_settings(1.0, UpAxis::Z, PointColumns::Position), handshake);
+        ipc_contributor.read_rendering_data(&Mutex::new(false));
+
+        match receiver.try_recv().unwrap()
+        {
+            Ok(_) => panic!("Missing data file was read"),
+            Err(err) => assert!(err.starts_with("Failed to read point cloud file"), "Unexpected error: {}", err)
+        }
+
+        // The producer is allowed to write the data file again
+        assert_eq!("clear", fs::read_to_string(&mutex_file).unwrap());
+        let _ = fs::remove_file(&mutex_file);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,183 @@
+use std::io::BufRead;
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use nalgebra_glm::{TVec3, vec3};
+use serde_json::Value;
+
+/// The commands that can be given to the visualizer over stdin. Each command is a JSON object on its
+/// own line, with the name of the command under "command", such as:
+/// {"command": "set_epsilon", "epsilon": 0.5}
+#[derive(Clone, Debug, PartialEq)]
+pub enum ControlCommand
+{
+    /// Moves the main camera to the given "position", looking along the given "direction"
+    SetCamera{ position: TVec3<f32>, direction: TVec3<f32> },
+    /// Changes the epsilon of the cluster detection to the given "epsilon"
+    SetEpsilon{ epsilon: f32 },
+    /// Finds the clusters of the displayed point cloud
+    FindClusters,
+    /// Replaces the displayed point cloud with the one in the given "file"
+    LoadFile{ file: String },
+}
+
+impl ControlCommand
+{
+    /// Get the name of the command, as written under "command"
+    pub fn get_name(&self) -> &'static str
+    {
+        match self
+        {
+            ControlCommand::SetCamera{ .. } => "set_camera",
+            ControlCommand::SetEpsilon{ .. } => "set_epsilon",
+            ControlCommand::FindClusters => "find_clusters",
+            ControlCommand::LoadFile{ .. } => "load_file",
+        }
+    }
+
+    /// Parses a line holding a command
+    ///
+    /// `line` - the JSON object holding the command
+    pub fn parse(line: &str) -> Result<ControlCommand, String>
+    {
+        let fields = match serde_json::from_str::<Value>(line).map_err(|x| format!("Invalid command: {}", x))?
+        {
+            Value::Object(i) => i,
+            _ => return Err("The command has to be a JSON object".to_string())
+        };
+        let field = |name: &str| fields.get(name);
+
+        let command = match field("command")
+        {
+            Some(Value::String(i)) => i.as_str(),
+            Some(_) => return Err("The command has to be a string".to_string()),
+            None => return Err("Missing the command".to_string())
+        };
+
+        let number = |name: &str| match field(name).and_then(Value::as_f64)
+        {
+            Some(i) => Ok(i as f32),
+            None => Err(format!("The {} command requires a number for {}", command, name))
+        };
+
+        let vector = |name: &str| match field(name)
+        {
+            Some(Value::Array(values)) if values.len() == 3 =>
+                {
+                    let mut components = [0.0_f32; 3];
+                    for (index, value) in values.iter().enumerate()
+                    {
+                        match value.as_f64()
+                        {
+                            Some(i) => components[index] = i as f32,
+                            None => return Err(format!("The {} of the {} command has to hold numbers", name, command))
+                        }
+                    }
+                    Ok(vec3(components[0], components[1], components[2]))
+                },
+            _ => Err(format!("The {} command requires an array of three numbers for {}", command, name))
+        };
+
+        match command
+        {
+            "set_camera" =>
+                {
+                    let direction = vector("direction")?;
+                    if direction == vec3(0.0, 0.0, 0.0)
+                    {
+                        return Err("The direction of the set_camera command cannot be zero".to_string());
+                    }
+
+                    Ok(ControlCommand::SetCamera{ position: vector("position")?, direction })
+                },
+            "set_epsilon" => Ok(ControlCommand::SetEpsilon{ epsilon: number("epsilon")? }),
+            "find_clusters" => Ok(ControlCommand::FindClusters),
+            "load_file" => match field("file")
+            {
+                Some(Value::String(i)) => Ok(ControlCommand::LoadFile{ file: i.clone() }),
+                _ => Err("The load_file command requires a string for file".to_string())
+            },
+            _ => Err(format!("Unknown command: {}", command))
+        }
+    }
+}
+
+/// Launches the thread that reads commands from stdin, one per line, until stdin is closed. Lines
+/// that are not valid commands are sent as errors so that they can be acknowledged as such
+pub fn launch_json_control_reader() -> Receiver<Result<ControlCommand, String>>
+{
+    let (sender, receiver) = channel();
+
+    thread::spawn(move ||
+        {
+            for line in std::io::stdin().lock().lines()
+            {
+                let command = match line
+                {
+                    Ok(i) if i.trim().is_empty() => continue,
+                    Ok(i) => ControlCommand::parse(&i),
+                    Err(err) => Err(format!("Failed to read the command: {}", err))
+                };
+
+                if sender.send(command).is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+    receiver
+}
+
+/// Creates the line written to stdout in response to a command, as a JSON object holding the name of
+/// the command (null if it could not be read), whether it succeeded and, if not, why
+///
+/// `command_name` - the name of the command being acknowledged
+/// `result` - the result of applying the command
+pub fn acknowledgement(command_name: Option<&str>, result: &Result<(), String>) -> String
+{
+    // Written by hand rather than as a serde_json object, so that the fields keep their order
+    let command_name = Value::from(command_name);
+
+    match result
+    {
+        Ok(_) => format!("{{\"command\":{},\"ok\":true}}", command_name),
+        Err(err) => format!("{{\"command\":{},\"ok\":false,\"error\":{}}}", command_name, Value::from(err.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::ipc_logic::json_control::{ControlCommand, acknowledgement};
+
+    #[test]
+    fn parse_commands()
+    {
+        assert_eq!(Ok(ControlCommand::SetEpsilon{ epsilon: 0.5 }), ControlCommand::parse(r#"{"command": "set_epsilon", "epsilon": 5e-1}"#));
+        assert_eq!(Ok(ControlCommand::FindClusters), ControlCommand::parse(r#" {"id": null, "command":"find_clusters"} "#));
+        assert_eq!(Ok(ControlCommand::LoadFile{ file: "a \"b\"\\c.txt".to_string() }), ControlCommand::parse(r#"{"command": "load_file", "file": "a \"b\"\\c.txt"}"#));
+        assert_eq!(Ok(ControlCommand::SetCamera{ position: vec3(1.0, -2.0, 3.5), direction: vec3(0.0, 0.0, -1.0) }),
+                   ControlCommand::parse(r#"{"command": "set_camera", "position": [1, -2, 3.5], "direction": [0, 0, -1], "smooth": false}"#));
+
+        assert!(ControlCommand::parse(r#"{"command": "set_epsilon"}"#).is_err());
+        assert!(ControlCommand::parse(r#"{"command": "set_camera", "position": [1, 2], "direction": [0, 0, -1]}"#).is_err());
+        assert!(ControlCommand::parse(r#"{"command": "set_camera", "position": [1, 2, 3], "direction": [0, 0, 0]}"#).is_err());
+        assert!(ControlCommand::parse(r#"{"command": "fly"}"#).is_err());
+        assert!(ControlCommand::parse(r#"{"command": "find_clusters""#).is_err());
+        assert!(ControlCommand::parse(r#"{"command": "find_clusters"} x"#).is_err());
+        assert!(ControlCommand::parse("find_clusters").is_err());
+        assert!(ControlCommand::parse(r#"{"command": "set_epsilon", "epsilon": 1-2}"#).is_err());
+        assert!(ControlCommand::parse(r#"["find_clusters"]"#).is_err());
+
+        // Characters outside of the basic multilingual plane are escaped as surrogate pairs
+        assert_eq!(Ok(ControlCommand::LoadFile{ file: "\u{1F600}.txt".to_string() }), ControlCommand::parse(r#"{"command": "load_file", "file": "\ud83d\ude00.txt"}"#));
+    }
+
+    #[test]
+    fn acknowledgements_are_json()
+    {
+        assert_eq!(r#"{"command":"find_clusters","ok":true}"#, acknowledgement(Some("find_clusters"), &Ok(())));
+        assert_eq!(r#"{"command":null,"ok":false,"error":"Bad \"line\"\n"}"#, acknowledgement(None, &Err("Bad \"line\"\n".to_string())));
+    }
+}
\ No newline at end of file
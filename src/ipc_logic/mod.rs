@@ -1,2 +1,4 @@
 pub mod ipc_content_logic;
 pub mod ipc_receiver;
+pub mod json_control;
+pub mod playback;
@@ -0,0 +1,244 @@
+use std::fs;
+use std::sync::mpsc::{channel, Receiver, Sender, SyncSender};
+use std::thread;
+use std::time::{Duration, Instant};
+use glfw::{Action, Key};
+use crate::ipc_logic::ipc_receiver::{IPCContributor, ParseSettings, SendContents};
+use crate::window::RenderWindow;
+
+/// Plays back a recorded sequence of point cloud files, one file per frame. The frames are read on
+/// a separate thread and sent through the same channel as the point clouds received through IPC
+pub struct Playback
+{
+    frame_files: Vec<String>,
+    current_frame: usize,
+    playing: bool,
+    frame_interval: Duration,
+    last_frame_time: Instant,
+    frame_requests: Sender<usize>,
+}
+
+impl Playback
+{
+    /// Starts the playback of the frames in the given directory, beginning with the first frame
+    ///
+    /// `playback_dir` - the directory holding a point cloud file for each frame
+    /// `frames_per_second` - how many frames are shown each second while playing
+    /// `sender` - the variable used to send the read frames to the rest of the program
+    /// `parse_settings` - how the frame files are read
+    pub fn new(playback_dir: &String, frames_per_second: f32, sender: SyncSender<Result<SendContents, String>>,
+               parse_settings: ParseSettings) -> Result<Playback, String>
+    {
+        let frame_files = Playback::list_frame_files(playback_dir)?;
+        if frame_files.is_empty()
+        {
+            return Err(format!("No frame files found in {}", playback_dir));
+        }
+
+        let (frame_requests, frame_request_receiver) = channel();
+        Playback::launch_frame_reader(frame_files.clone(), frame_request_receiver, sender, parse_settings);
+
+        let mut playback = Playback
+        {
+            frame_files,
+            current_frame: 0,
+            playing: true,
+            frame_interval: Duration::from_secs_f32(1.0 / frames_per_second),
+            last_frame_time: Instant::now(),
+            frame_requests,
+        };
+
+        playback.show_frame(0);
+        Ok(playback)
+    }
+
+    /// Lists the files in the given directory, sorted by name
+    ///
+    /// `playback_dir` - the directory holding a point cloud file for each frame
+    pub fn list_frame_files(playback_dir: &String) -> Result<Vec<String>, String>
+    {
+        let mut frame_files = fs::read_dir(playback_dir).map_err(|x| x.to_string())?
+            .filter_map(|x| x.ok())
+            .map(|x| x.path())
+            .filter(|x| x.is_file())
+            .map(|x| x.to_string_lossy().into_owned())
+            .collect::<Vec<String>>();
+
+        frame_files.sort();
+        Ok(frame_files)
+    }
+
+    /// Get the file of the frame currently shown
+    pub fn get_current_frame_file(&self) -> &String
+    {
+        &self.frame_files[self.current_frame]
+    }
+
+    /// Responds to the playback keys, and advances to the next frame when playing and enough time
+    /// has passed since the last frame. Playing stops at the last frame
+    ///
+    /// `render_window` - the window that holds all user input
+    pub fn update(&mut self, render_window: &RenderWindow)
+    {
+        let key_pressed = |key: Key| render_window.get_key_input().iter()
+            .find(|x| **x == (key, Action::Press) || **x == (key, Action::Repeat)).is_some();
+
+        if render_window.get_key_input().iter().find(|x| **x == (Key::Space, Action::Press)).is_some()
+        {
+            // Playing again from the last frame starts over
+            if !self.playing && self.current_frame == self.frame_files.len() - 1
+            {
+                self.show_frame(0);
+            }
+
+            self.playing = !self.playing;
+            self.last_frame_time = Instant::now();
+        }
+
+        // Seeking jumps a tenth of the recording at a time
+        let seek_amount = (self.frame_files.len() / 10).max(1) as isize;
+
+        for (key, amount) in &[(Key::Right, 1), (Key::Left, -1), (Key::Up, seek_amount), (Key::Down, -seek_amount)]
+        {
+            if key_pressed(*key)
+            {
+                self.step(*amount);
+            }
+        }
+
+        if self.playing && self.last_frame_time.elapsed() >= self.frame_interval
+        {
+            if self.current_frame == self.frame_files.len() - 1
+            {
+                self.playing = false;
+            }
+            else
+            {
+                self.step(1);
+            }
+        }
+    }
+
+    /// Get the text representation of the playback position, including a bar showing how far into
+    /// the recording the current frame is
+    pub fn to_string_position(&self) -> String
+    {
+        let bar_length = 20;
+        let filled_length = if self.frame_files.len() > 1
+        {
+            self.current_frame * bar_length / (self.frame_files.len() - 1)
+        }
+        else
+        {
+            bar_length
+        };
+
+        format!("{} {}/{} [{}{}]", if self.playing { "Playing" } else { "Paused" }, self.current_frame + 1, self.frame_files.len(),
+                "=".repeat(filled_length), "-".repeat(bar_length - filled_length))
+    }
+
+    /// Moves the given number of frames forwards (positive) or backwards (negative), stopping at
+    /// the first and last frames
+    ///
+    /// `amount` - the number of frames to move
+    fn step(&mut self, amount: isize)
+    {
+        let frame = (self.current_frame as isize + amount).max(0).min(self.frame_files.len() as isize - 1) as usize;
+        if frame != self.current_frame
+        {
+            self.show_frame(frame);
+        }
+    }
+
+    /// Requests the given frame to be read and sent to the rest of the program
+    ///
+    /// `frame` - the index of the frame to show
+    fn show_frame(&mut self, frame: usize)
+    {
+        self.current_frame = frame;
+        self.last_frame_time = Instant::now();
+
+        if let Err(err) = self.frame_requests.send(frame)
+        {
+            eprintln!("Failed to request playback frame {}: {}", frame, err);
+        }
+    }
+
+    /// Reads the requested frames on a separate thread, so that large frames do not stall rendering
+    ///
+    /// `frame_files` - the file of each frame
+    /// `frame_requests` - the indexes of the frames to read
+    /// `sender` - the variable used to send the read frames to the rest of the program
+    /// `parse_settings` - how the frame files are read
+    fn launch_frame_reader(frame_files: Vec<String>, frame_requests: Receiver<usize>, sender: SyncSender<Result<SendContents, String>>,
+                           parse_settings: ParseSettings)
+    {
+        thread::spawn(move ||
+            {
+                // Stops once the playback is dropped
+                while let Ok(mut frame) = frame_requests.recv()
+                {
+                    // Only the latest frame matters if frames were requested faster than they could be read
+                    while let Ok(i) = frame_requests.try_recv()
+                    {
+                        frame = i;
+                    }
+
+                    let result = fs::read_to_string(&frame_files[frame])
+                        .map_err(|x| format!("Failed to read playback frame {}: {}", frame_files[frame], x))
+                        .and_then(|x|
+                            {
+                                if let Some(warning) = IPCContributor::incomplete_vertex_warning(&x, parse_settings.point_columns)
+                                {
+                                    eprintln!("{}: {}", frame_files[frame], warning);
+                                }
+
+                                IPCContributor::parse_contents(&x, frame_files[frame].clone(), parse_settings)
+                            });
+
+                    if sender.send(result).is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::fs;
+    use std::sync::mpsc::sync_channel;
+    use crate::args_parser::Args;
+    use crate::ipc_logic::playback::Playback;
+
+    #[test]
+    fn frames_sorted_and_stepped_within_bounds()
+    {
+        let playback_dir = std::env::temp_dir().join("playback_frames_test");
+        let _ = fs::remove_dir_all(&playback_dir);
+        fs::create_dir_all(&playback_dir).unwrap();
+
+        for frame in &["frame_2.txt", "frame_0.txt", "frame_1.txt"]
+        {
+            fs::write(playback_dir.join(frame), "1|2|3|").unwrap();
+        }
+
+        let (sender, receiver) = sync_channel(1);
+        let mut playback = Playback::new(&playback_dir.to_string_lossy().into_owned(), 10.0, sender, Args::default().parse_settings()).unwrap();
+
+        assert!(playback.get_current_frame_file().ends_with("frame_0.txt"));
+        assert_eq!(1, receiver.recv().unwrap().unwrap().points.len());
+
+        playback.step(5);
+        assert!(playback.get_current_frame_file().ends_with("frame_2.txt"));
+        assert_eq!("Playing 3/3 [====================]", playback.to_string_position());
+
+        playback.step(-1);
+        assert!(playback.get_current_frame_file().ends_with("frame_1.txt"));
+        assert_eq!("Playing 2/3 [==========----------]", playback.to_string_position());
+
+        let _ = fs::remove_dir_all(&playback_dir);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+pub mod args_parser;
+mod cluster_logic;
+mod geometry;
+mod gl_wrappers;
+mod helper_logic;
+mod ipc_logic;
+mod rendering;
+mod view_logic;
+mod view_port_constants;
+mod visualizer;
+mod window;
+
+pub use glfw::{Action, Key, MouseButton};
+pub use ipc_logic::ipc_content_logic::PointFilter;
+pub use visualizer::Visualizer;
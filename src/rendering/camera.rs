@@ -1,9 +1,12 @@
 use glfw::{Action, MouseButton};
-use nalgebra_glm::{cross, normalize, TMat4, TVec3};
+use nalgebra_glm::{cross, normalize, TMat4, TVec2, TVec3, TVec4, vec2, vec3, vec4};
 use crate::window::RenderWindow;
+use crate::view_logic::cursor_smoothing::smooth_cursor_positions;
+use crate::view_logic::pose_history::CameraPose;
 use glfw::Key;
 
 /// Representation of a camera through which the world is seen through
+#[derive(Clone)]
 pub struct Camera
 {
     view_matrix: TMat4<f32>,
@@ -20,8 +23,33 @@ pub struct Camera
     last_x: i32,
     last_y: i32,
     first_mouse: bool,
+
+    orbit_pivot: Option<TVec3<f32>>,
+
+    smooth_movement: bool,
+    velocity: TVec3<f32>,
+
+    invert_look_y: bool,
+    cursor_smoothing: usize,
+
+    near_plane: f32,
+    far_plane: f32,
+    aspect_ratio: f32,
+    is_perspective: bool,
 }
 
+/// Speed in units per second that a camera with smooth movement reaches while a movement key is
+/// held. Matches the speed of the instantaneous movement at 60 frames per second
+const SMOOTH_MAX_SPEED: f32 = 3.0;
+
+/// How quickly a camera with smooth movement reaches its speed, and stops once keys are released.
+/// Higher is quicker
+const SMOOTH_ACCELERATION_RATE: f32 = 6.0;
+
+/// Fraction of the usual speed that cameras move and rotate at while Control is held, so that they
+/// can be positioned precisely
+const PRECISION_SCALE: f32 = 0.1;
+
 /// The direction that a camera should move in
 #[repr(usize)]
 pub enum MovementKeys
@@ -97,6 +125,10 @@ impl Camera
         let direction;
         let position;
         let up;
+        let near_plane;
+        let far_plane;
+        let aspect_ratio;
+        let is_perspective;
 
         match camera_type
         {
@@ -108,19 +140,23 @@ impl Camera
                     direction = i.direction;
                     position = i.position;
                     up = i.up;
+                    near_plane = i.near_plane;
+                    far_plane = i.far_plane;
+                    aspect_ratio = (i.right - i.left) / (i.top - i.bottom);
+                    is_perspective = false;
                 },
             CameraType::Perspective(i) =>
                 {
                     view_matrix = nalgebra_glm::look_at(&i.position, &(i.position + i.direction), &i.up);
-                    perspective_matrix = nalgebra_glm::perspective
-                        (
-                            (i.window_dimensions.0 as f32) / (i.window_dimensions.1 as f32),
-                            45.0, i.near_plane, i.far_plane
-                        );
+                    aspect_ratio = (i.window_dimensions.0 as f32) / (i.window_dimensions.1 as f32);
+                    perspective_matrix = perspective_projection(aspect_ratio, i.near_plane, i.far_plane);
 
                     direction = i.direction;
                     position = i.position;
                     up = i.up;
+                    near_plane = i.near_plane;
+                    far_plane = i.far_plane;
+                    is_perspective = true;
                 }
         }
 
@@ -156,6 +192,15 @@ impl Camera
             last_x: 0,
             last_y: 0,
             first_mouse: true,
+            orbit_pivot: None,
+            smooth_movement: false,
+            velocity: vec3(0.0, 0.0, 0.0),
+            invert_look_y: false,
+            cursor_smoothing: 1,
+            near_plane,
+            far_plane,
+            aspect_ratio,
+            is_perspective,
         }
     }
 
@@ -173,12 +218,112 @@ impl Camera
             );
     }
 
+    /// Get a copy of the camera moved sideways by the given distance, looking in the same direction,
+    /// such as to see the scene from one of a pair of eyes
+    ///
+    /// `offset` - the distance to move the camera to its right; negative moves it to its left
+    pub fn eye_camera(&self, offset: f32) -> Camera
+    {
+        let mut eye_camera = self.clone();
+        eye_camera.set_camera_pos(self.position + normalize(&cross(&self.direction, &self.up)) * offset);
+        eye_camera
+    }
+
     /// Return the projection * view matrix
     pub fn get_projection_view_matrix(&self) -> TMat4<f32>
     {
         self.perspective_matrix * self.view_matrix
     }
 
+    /// Projects the given world position onto the camera's view, returning the Normalized Device
+    /// Coordinates of the position. None is returned if the position is behind the camera or is
+    /// outside of what the camera can see
+    ///
+    /// `world_pos` - the position to project
+    pub fn world_to_screen(&self, world_pos: &TVec3<f32>) -> Option<TVec2<f32>>
+    {
+        let clip_pos = self.get_projection_view_matrix() * vec4(world_pos.x, world_pos.y, world_pos.z, 1.0);
+
+        // A non-positive w means the position is behind the camera; dividing by it would
+        // mirror the position onto the screen
+        if clip_pos.w <= 0.0
+        {
+            return None;
+        }
+
+        let ndc = vec2(clip_pos.x / clip_pos.w, clip_pos.y / clip_pos.w);
+        if ndc.x.abs() > 1.0 || ndc.y.abs() > 1.0
+        {
+            return None;
+        }
+
+        Some(ndc)
+    }
+
+    /// Get the planes bounding the camera's view frustum: left, right, bottom, top, near and far. Each
+    /// plane is stored as (a, b, c, d) with ax + by + cz + d being the distance of a position from
+    /// the plane, positive inside the frustum
+    pub fn extract_frustum_planes(&self) -> [TVec4<f32>; 6]
+    {
+        // Frustum planes are extracted from the rows of the projection * view matrix
+        // (Gribb and Hartmann); each plane's normal points into the frustum
+        let matrix = self.get_projection_view_matrix();
+        let row = |index: usize| vec4(matrix[(index, 0)], matrix[(index, 1)], matrix[(index, 2)], matrix[(index, 3)]);
+
+        let mut planes =
+        [
+            row(3) + row(0), row(3) - row(0),
+            row(3) + row(1), row(3) - row(1),
+            row(3) + row(2), row(3) - row(2),
+        ];
+
+        for plane in planes.iter_mut()
+        {
+            *plane /= vec3(plane.x, plane.y, plane.z).magnitude();
+        }
+
+        planes
+    }
+
+    /// Get the corners of the camera's view frustum. The first four corners are on the near plane and
+    /// the last four on the far plane, both going around their plane in the same order
+    pub fn get_frustum_corners(&self) -> [TVec3<f32>; 8]
+    {
+        let inverse_projection_view = nalgebra_glm::inverse(&self.get_projection_view_matrix());
+        let mut corners = [vec3(0.0, 0.0, 0.0); 8];
+
+        for (index, corner) in corners.iter_mut().enumerate()
+        {
+            // The frustum is the cube from -1 to 1 in normalized device coordinates
+            let x = if index % 4 == 1 || index % 4 == 2 { 1.0 } else { -1.0 };
+            let y = if index % 4 >= 2 { 1.0 } else { -1.0 };
+            let z = if index < 4 { -1.0 } else { 1.0 };
+
+            let world_corner = inverse_projection_view * vec4(x, y, z, 1.0);
+            *corner = vec3(world_corner.x, world_corner.y, world_corner.z) / world_corner.w;
+        }
+
+        corners
+    }
+
+    /// Checks if any part of the given sphere is within the camera's view frustum
+    ///
+    /// `centre` - the centre of the sphere
+    /// `radius` - the radius of the sphere
+    pub fn sphere_in_frustum(&self, centre: &TVec3<f32>, radius: f32) -> bool
+    {
+        self.extract_frustum_planes().iter()
+            .all(|plane| plane.x * centre.x + plane.y * centre.y + plane.z * centre.z + plane.w >= -radius)
+    }
+
+    /// Checks if any of the given positions can be seen by the camera
+    ///
+    /// `positions` - the world positions to check
+    pub fn any_position_in_view(&self, mut positions: impl Iterator<Item=TVec3<f32>>) -> bool
+    {
+        positions.any(|x| self.world_to_screen(&x).is_some())
+    }
+
     pub fn get_position(&self) -> TVec3<f32>
     {
         self.position
@@ -193,12 +338,40 @@ impl Camera
     /// Clears all movement keys, making the camera stop moving in all directions
     pub fn clear_movement_key(&mut self)
     {
+        self.velocity = vec3(0.0, 0.0, 0.0);
+
         self.movement_keys[0] = false;
         self.movement_keys[1] = false;
         self.movement_keys[2] = false;
         self.movement_keys[3] = false;
     }
 
+    /// Makes the camera speed up and slow down when moved, rather than starting and stopping instantly
+    ///
+    /// `smooth_movement` - true if the camera should move smoothly
+    pub fn set_smooth_movement(&mut self, smooth_movement: bool)
+    {
+        self.smooth_movement = smooth_movement;
+    }
+
+    /// Makes moving the cursor up pitch the camera down, and the other way around
+    ///
+    /// `invert_look_y` - true if the vertical rotation should be inverted
+    pub fn set_invert_look_y(&mut self, invert_look_y: bool)
+    {
+        self.invert_look_y = invert_look_y;
+    }
+
+    /// Makes the camera rotate by the average of groups of cursor positions instead of by every
+    /// cursor position, reducing jitter from high polling rate mice
+    ///
+    /// `cursor_smoothing` - the number of consecutive cursor positions that are averaged together.
+    ///                      One or less uses every cursor position
+    pub fn set_cursor_smoothing(&mut self, cursor_smoothing: usize)
+    {
+        self.cursor_smoothing = cursor_smoothing;
+    }
+
     /// Indicate that cursor movement should affect camera rotation
     pub fn set_rotation_button_status(&mut self, status: bool)
     {
@@ -218,11 +391,166 @@ impl Camera
         self.direction
     }
 
-    /// Moves the camera in the given direction
+    /// Get the distance from the camera past which nothing is rendered
+    pub fn get_far_plane(&self) -> f32
+    {
+        self.far_plane
+    }
+
+    /// Changes the distance from the camera past which nothing is rendered. Only perspective cameras
+    /// can be changed; orthographic cameras are left as they are
+    ///
+    /// `far_plane` - the new distance of the far plane from the camera
+    pub fn set_far_plane(&mut self, far_plane: f32)
+    {
+        if !self.is_perspective
+        {
+            return;
+        }
+
+        // Only these terms of a perspective matrix depend on the far plane
+        let near_plane = self.near_plane;
+        self.perspective_matrix[(2, 2)] = -(far_plane + near_plane) / (far_plane - near_plane);
+        self.perspective_matrix[(2, 3)] = -2.0 * far_plane * near_plane / (far_plane - near_plane);
+        self.far_plane = far_plane;
+    }
+
+    /// Check if the camera has a perspective projection, rather than an orthographic one
+    pub fn is_perspective(&self) -> bool
+    {
+        self.is_perspective
+    }
+
+    /// Replaces the projection of the camera with a perspective projection, keeping its position,
+    /// direction, aspect ratio and near and far planes
+    pub fn set_perspective(&mut self)
+    {
+        self.perspective_matrix = perspective_projection(self.aspect_ratio, self.near_plane, self.far_plane);
+        self.is_perspective = true;
+    }
+
+    /// Replaces the projection of the camera with an orthographic projection, keeping its position,
+    /// direction, aspect ratio and near and far planes. The orthographic projection is sized to show
+    /// as much as a perspective projection does at the given distance, so that what is being looked
+    /// at stays about the same size
+    ///
+    /// `focus_distance` - the distance from the camera to what is being looked at
+    pub fn set_orthographic(&mut self, focus_distance: f32)
+    {
+        // The second diagonal term of a perspective matrix is one over the tangent of half the FOV
+        let half_height = focus_distance / perspective_projection(self.aspect_ratio, self.near_plane, self.far_plane)[(1, 1)];
+        let half_width = half_height * self.aspect_ratio;
+
+        self.perspective_matrix = nalgebra_glm::ortho(-half_width, half_width, -half_height, half_height, self.near_plane, self.far_plane);
+        self.is_perspective = false;
+    }
+
+    /// Get the horizontal rotation of the camera in degrees, starting from the positive x-axis
+    pub fn get_yaw(&self) -> f32
+    {
+        self.yaw
+    }
+
+    /// Returns true if the camera is currently being moved or rotated by the user
+    pub fn is_moving(&self) -> bool
+    {
+        self.middle_key_down || self.movement_keys.iter().any(|x| *x) || self.velocity != vec3(0.0, 0.0, 0.0)
+    }
+
+    /// Get the current position and direction of the camera
+    pub fn get_pose(&self) -> CameraPose
+    {
+        CameraPose{ position: self.position, direction: self.direction }
+    }
+
+    /// Makes the camera see exactly what the given camera sees. The up vector of this camera is kept,
+    /// so that the camera can be moved back to its previous pose with set_pose
+    ///
+    /// `camera` - the camera to copy the view of
+    pub fn mirror_camera(&mut self, camera: &Camera)
+    {
+        self.position = camera.position;
+        self.direction = camera.direction;
+        self.view_matrix = camera.view_matrix;
+    }
+
+    /// Moves the camera to the given pose and updates the view matrix
+    ///
+    /// `pose` - the position and direction the camera should have
+    pub fn set_pose(&mut self, pose: CameraPose)
+    {
+        self.position = pose.position;
+        self.set_direction(pose.direction);
+
+        self.view_matrix = nalgebra_glm::look_at
+            (
+                &self.position,
+                &(self.position + self.direction),
+                &self.up,
+            );
+    }
+
+    /// Makes rotating the camera orbit it around the given pivot, keeping the pivot in view. Without
+    /// a pivot the camera rotates in place
+    ///
+    /// `pivot` - the point to orbit around, if any
+    pub fn set_orbit_pivot(&mut self, pivot: Option<TVec3<f32>>)
+    {
+        self.orbit_pivot = pivot;
+        self.reset_first_mouse();
+    }
+
+    /// Get the point the camera orbits around when rotated, if any
+    pub fn get_orbit_pivot(&self) -> Option<TVec3<f32>>
+    {
+        self.orbit_pivot
+    }
+
+    /// Moves the camera horizontally around the given pivot by the given angle, turning the camera
+    /// to look at the pivot
+    ///
+    /// `pivot` - the point to move the camera around
+    /// `degrees` - the angle to move the camera by
+    pub fn orbit_around(&mut self, pivot: TVec3<f32>, degrees: f32)
+    {
+        if pivot != self.position
+        {
+            self.set_direction(pivot - self.position);
+        }
+
+        self.rotate(degrees, 0.0, Some(pivot));
+
+        self.view_matrix = nalgebra_glm::look_at
+            (
+                &self.position,
+                &(self.position + self.direction),
+                &self.up,
+            );
+    }
+
+    /// Get the ray starting at the camera that passes through the given position on the screen,
+    /// as the origin and normalized direction of the ray
+    ///
+    /// `ndc` - the position on the screen, as normalized device coordinates of the camera's viewport
+    pub fn screen_ray(&self, ndc: TVec2<f32>) -> (TVec3<f32>, TVec3<f32>)
+    {
+        let inverse_matrix = nalgebra_glm::inverse(&self.get_projection_view_matrix());
+        let unproject = |depth: f32|
+            {
+                let world_pos = inverse_matrix * vec4(ndc.x, ndc.y, depth, 1.0);
+                vec3(world_pos.x, world_pos.y, world_pos.z) / world_pos.w
+            };
+
+        let near_pos = unproject(-1.0);
+        (near_pos, normalize(&(unproject(1.0) - near_pos)))
+    }
+
+    /// Moves the camera in the given direction, slower while Control is held
     ///
     /// `render_window` - window that holds all user input
     /// `camera` - the instance of the camera that should have its position updated
-    pub fn update_camera_movement(render_window: &RenderWindow, camera: &mut Camera)
+    /// `delta_time` - the seconds since the previous frame
+    pub fn update_camera_movement(render_window: &RenderWindow, camera: &mut Camera, delta_time: f32)
     {
         camera_movement!(render_window, camera, Key::W, MovementKeys::Forward);
         camera_movement!(render_window, camera, Key::A, MovementKeys::Left);
@@ -233,10 +561,10 @@ impl Camera
 
         // Above macros set the movement flag. Below function actually moves the camera based off of
         // those flags. This split into two functions is for readability
-        camera.update_camera_position();
+        camera.update_camera_position(delta_time, precision_scale(render_window));
     }
 
-    /// Updates the rotation of the camera
+    /// Updates the rotation of the camera, slower while Control is held
     ///
     /// `render_window` - window that holds all user input
     /// `camera` - the instance of the camera that should be rotated
@@ -254,7 +582,7 @@ impl Camera
         }
 
         // Actual rotation happens here. Another function does the actual rotation for readability
-        camera.update_camera_rotate(render_window.get_cursor_history());
+        camera.update_camera_rotate(render_window.get_cursor_history(), precision_scale(render_window));
     }
 
     /// Get the string representation of the camera position
@@ -288,47 +616,91 @@ impl Camera
             self.direction = direction;
         }
 
+        // Looking along the up vector leaves the orientation of the view undefined, in which case
+        // another axis is used as the up vector
+        let up = if cross(&direction, &self.up).norm() < 0.0001
+        {
+            vec3(self.up.z, self.up.x, self.up.y)
+        }
+        else
+        {
+            self.up
+        };
+
         self.view_matrix = nalgebra_glm::look_at
             (
                 &self.position,
                 &(self.position + direction),
-                &self.up,
+                &up,
             );
     }
 
+    /// Sets the direction the camera looks in, keeping the rotation angles consistent with it so
+    /// that the next rotation continues from it rather than jumping back to the previous direction
+    ///
+    /// `direction` - the new direction of the camera
+    fn set_direction(&mut self, direction: TVec3<f32>)
+    {
+        self.direction = normalize(&direction);
+        self.pitch = self.direction.y.asin().to_degrees();
+        self.yaw = self.direction.z.atan2(self.direction.x).to_degrees();
+    }
+
     /// Updates the camera position based off of the directions camera was specified to move in
-    fn update_camera_position(&mut self)
+    ///
+    /// `delta_time` - the seconds since the previous frame; only used for smooth movement
+    /// `speed_scale` - the fraction of the usual speed to move at
+    fn update_camera_position(&mut self, delta_time: f32, speed_scale: f32)
     {
-        let movement_scale = 0.05;
+        let mut movement = vec3(0.0, 0.0, 0.0);
 
         if self.movement_keys[MovementKeys::Forward as usize]
         {
-            self.position += self.direction * movement_scale;
+            movement += self.direction;
         }
 
         if self.movement_keys[MovementKeys::Backward as usize]
         {
-            self.position -= self.direction * movement_scale;
+            movement -= self.direction;
         }
 
         if self.movement_keys[MovementKeys::Left as usize]
         {
-            self.position -= normalize(&cross(&self.direction, &self.up)) * movement_scale;
+            movement -= normalize(&cross(&self.direction, &self.up));
         }
 
         if self.movement_keys[MovementKeys::Right as usize]
         {
-            self.position += normalize(&cross(&self.direction, &self.up)) * movement_scale;
+            movement += normalize(&cross(&self.direction, &self.up));
         }
 
         if self.movement_keys[MovementKeys::UpBackwards as usize]
         {
-            self.position -= self.up * movement_scale;
+            movement -= self.up;
         }
 
         if self.movement_keys[MovementKeys::UpForward as usize]
         {
-            self.position += self.up * movement_scale;
+            movement += self.up;
+        }
+
+        if self.smooth_movement
+        {
+            // The velocity approaches the velocity of the held keys, so it speeds up while keys are
+            // held and slows down once they are released
+            let target_velocity = movement * SMOOTH_MAX_SPEED * speed_scale;
+            self.velocity += (target_velocity - self.velocity) * (1.0 - (-SMOOTH_ACCELERATION_RATE * delta_time).exp());
+
+            if movement == vec3(0.0, 0.0, 0.0) && self.velocity.magnitude() < 0.001
+            {
+                self.velocity = vec3(0.0, 0.0, 0.0);
+            }
+
+            self.position += self.velocity * delta_time;
+        }
+        else
+        {
+            self.position += movement * 0.05 * speed_scale;
         }
 
         self.view_matrix = nalgebra_glm::look_at
@@ -340,50 +712,103 @@ impl Camera
     }
 
     /// Rotate camera based off of cursor movement. If the camera's rotation button status is set
-    /// to false (middle key is not pressed), this function has no effect
+    /// to false (middle key is not pressed), this function has no effect. With an orbit pivot, the
+    /// camera is moved around the pivot instead of rotating in place. With cursor smoothing, groups of
+    /// cursor positions are averaged before rotating
     ///
     /// `cursor_pos_history` - the locations of the cursor (typically of a single frame)
-    fn update_camera_rotate(&mut self, cursor_pos_history: &Vec<(i32, i32)>)
+    /// `sensitivity_scale` - the fraction of the usual sensitivity to rotate with
+    fn update_camera_rotate(&mut self, cursor_pos_history: &[(i32, i32)], sensitivity_scale: f32)
     {
         if !self.middle_key_down
         {
             return;
         }
 
-        for (x, y) in cursor_pos_history
+        for (x, y) in &smooth_cursor_positions(cursor_pos_history, self.cursor_smoothing)
         {
             if self.first_mouse
             {
                 self.last_x = *x;
                 self.last_y = *y;
                 self.first_mouse = false;
+
+                // The camera may have been moved away from looking at the pivot since the last rotation
+                if let Some(pivot) = self.orbit_pivot
+                {
+                    if pivot != self.position
+                    {
+                        self.set_direction(pivot - self.position);
+                    }
+                }
             }
 
             let mut x_offset = (*x - self.last_x) as f32;
             let mut y_offset = (self.last_y - *y) as f32;
+            if self.invert_look_y
+            {
+                y_offset = -y_offset;
+            }
 
-            x_offset *= 0.1;
-            y_offset *= 0.1;
+            x_offset *= 0.1 * sensitivity_scale;
+            y_offset *= 0.1 * sensitivity_scale;
 
             self.last_x = *x;
             self.last_y = *y;
 
-            self.yaw += x_offset;
-            self.pitch += y_offset;
+            self.rotate(x_offset, y_offset, self.orbit_pivot);
+        }
+    }
 
-            if self.pitch > 89.0
-            {
-                self.pitch = 89.0;
-            } else if self.pitch < -89.0
-            {
-                self.pitch = -89.0;
-            }
+    /// Rotates the camera by the given angles. With a pivot the camera is moved around the pivot,
+    /// staying the same distance from it, instead of rotating in place
+    ///
+    /// `yaw_offset` - the degrees to rotate by horizontally
+    /// `pitch_offset` - the degrees to rotate by vertically
+    /// `pivot` - the point to move the camera around, if any
+    fn rotate(&mut self, yaw_offset: f32, pitch_offset: f32, pivot: Option<TVec3<f32>>)
+    {
+        self.yaw += yaw_offset;
+        self.pitch += pitch_offset;
+
+        self.pitch = self.pitch.clamp(-89.0, 89.0);
+
+        self.direction.x = self.yaw.to_radians().cos() * self.pitch.to_radians().cos();
+        self.direction.y = self.pitch.to_radians().sin();
+        self.direction.z = self.yaw.to_radians().sin() * self.pitch.to_radians().cos();
 
-            self.direction.x = self.yaw.to_radians().cos() * self.pitch.to_radians().cos();
-            self.direction.y = self.pitch.to_radians().sin();
-            self.direction.z = self.yaw.to_radians().sin() * self.pitch.to_radians().cos();
+        self.direction = normalize(&self.direction);
 
-            self.direction = normalize(&self.direction);
+        if let Some(pivot) = pivot
+        {
+            let distance = nalgebra_glm::distance(&pivot, &self.position);
+            self.position = pivot - self.direction * distance;
         }
     }
+}
+
+/// Get the fraction of the usual speed cameras move and rotate at, which is lowered while Control is
+/// held for precise positioning
+///
+/// `render_window` - window that holds all user input
+fn precision_scale(render_window: &RenderWindow) -> f32
+{
+    if render_window.is_control_down()
+    {
+        PRECISION_SCALE
+    }
+    else
+    {
+        1.0
+    }
+}
+
+/// Creates the perspective matrix used by perspective cameras. The FOV is hard-coded to 45 degrees
+///
+/// `aspect_ratio` - the width of the view divided by its height
+/// `near_plane` - the distance from the camera before which nothing is rendered
+/// `far_plane` - the distance from the camera past which nothing is rendered
+fn perspective_projection(aspect_ratio: f32, near_plane: f32, far_plane: f32) -> TMat4<f32>
+{
+    nalgebra_glm::perspective(aspect_ratio, 45.0, near_plane, far_plane)
 }
\ No newline at end of file
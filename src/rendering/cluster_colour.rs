@@ -1,6 +1,19 @@
 use nalgebra_glm::{TVec3, vec3};
 
+/// The sets of colours that clusters can be drawn with
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClusterPalette
+{
+    /// Combinations of red, green and blue at decreasing intensities
+    Default,
+    /// The colour blind safe palette by Okabe and Ito
+    OkabeIto,
+    /// The colour blind safe muted palette by Paul Tol
+    Tol,
+}
+
 /// Holds the colours that a cluster is to have
+#[derive(Clone)]
 pub struct ClusterColour
 {
     colours: Vec<TVec3<f32>>
@@ -8,8 +21,43 @@ pub struct ClusterColour
 
 impl ClusterColour
 {
-    /// Creates the colours that a cluster can have
-    pub fn new() -> ClusterColour
+    /// Creates the colours that a cluster can have. The first colour is given to points that are
+    /// not part of any cluster
+    ///
+    /// `palette` - the set of colours to give clusters
+    pub fn new(palette: ClusterPalette) -> ClusterColour
+    {
+        let colours = match palette
+        {
+            ClusterPalette::Default => ClusterColour::default_colours(),
+            // Grey is used for the noise instead of the palette's black, which is hard to see against
+            // the dark background
+            ClusterPalette::OkabeIto => ClusterColour::from_hex(&[0x999999, 0xE69F00, 0x56B4E9, 0x009E73, 0xF0E442, 0x0072B2, 0xD55E00, 0xCC79A7]),
+            ClusterPalette::Tol => ClusterColour::from_hex(&[0xDDDDDD, 0xCC6677, 0x88CCEE, 0x117733, 0xDDCC77, 0x332288, 0x44AA99, 0x882255, 0x999933, 0xAA4499]),
+        };
+
+        ClusterColour { colours }
+    }
+
+    /// Get the cluster colour given its index (as defined in the DBCluster scan). If the index
+    /// is greater than the amount of colours prepared, then the cluster colours are repeated
+    ///
+    /// `cluster-index` - the index of the cluster according to the DBScan results
+    pub fn get_colour(&self, cluster_index: usize) -> TVec3<f32>
+    {
+        if cluster_index >= self.colours.len()
+        {
+            // The noise colour is never repeated, so that clusters are not mistaken for noise
+            self.colours[1 + (cluster_index - 1) % (self.colours.len() - 1)]
+        }
+        else
+        {
+            self.colours[cluster_index]
+        }
+    }
+
+    /// Creates the colours of the default palette
+    fn default_colours() -> Vec<TVec3<f32>>
     {
         let mut colours = Vec::new();
 
@@ -34,22 +82,66 @@ impl ClusterColour
             colour_intensity -= 0.1;
         }
 
-        ClusterColour { colours }
+        colours
     }
 
-    /// Get the cluster colour given its index (as defined in the DBCluster scan). If the index
-    /// is greater than the amount of colours prepared, then a non-unique colour is returned
+    /// Converts colours written as 0xRRGGBB into colours with components between 0 and 1
     ///
-    /// `cluster-index` - the index of the cluster according to the DBScan results
-    pub fn get_colour(&self, cluster_index: usize) -> TVec3<f32>
+    /// `hex_colours` - the colours to convert
+    fn from_hex(hex_colours: &[u32]) -> Vec<TVec3<f32>>
     {
-        if cluster_index >= self.colours.len()
-        {
-            vec3(1.0, 0.75, 0.5)
-        }
-        else
+        hex_colours.iter()
+            .map(|x| vec3((x >> 16 & 0xFF) as f32 / 255.0, (x >> 8 & 0xFF) as f32 / 255.0, (x & 0xFF) as f32 / 255.0))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::{TVec3, vec3};
+    use crate::rendering::cluster_colour::{ClusterColour, ClusterPalette};
+
+    /// Converts a colour to the CIELAB colour space, where the distance between colours roughly
+    /// matches how different they look
+    fn to_lab(colour: &TVec3<f32>) -> TVec3<f32>
+    {
+        let linear = colour.map(|x| if x <= 0.04045 { x / 12.92 } else { ((x + 0.055) / 1.055).powf(2.4) });
+
+        // Relative to the D65 white point
+        let xyz = vec3
+        (
+            (0.4124 * linear.x + 0.3576 * linear.y + 0.1805 * linear.z) / 0.9505,
+            0.2126 * linear.x + 0.7152 * linear.y + 0.0722 * linear.z,
+            (0.0193 * linear.x + 0.1192 * linear.y + 0.9505 * linear.z) / 1.089
+        );
+
+        let f = xyz.map(|x| if x > 0.008856 { x.cbrt() } else { 7.787 * x + 16.0 / 116.0 });
+        vec3(116.0 * f.y - 16.0, 500.0 * (f.x - f.y), 200.0 * (f.y - f.z))
+    }
+
+    #[test]
+    fn palettes_have_distinct_colours()
+    {
+        for palette in &[ClusterPalette::Default, ClusterPalette::OkabeIto, ClusterPalette::Tol]
         {
-            self.colours[cluster_index]
+            let cluster_colour = ClusterColour::new(*palette);
+
+            // Colours that differ by at least this much in CIELAB are easily told apart
+            let mut distinct_colours: Vec<TVec3<f32>> = Vec::new();
+            for colour in cluster_colour.colours.iter().map(to_lab)
+            {
+                if distinct_colours.iter().all(|x| nalgebra_glm::distance(x, &colour) > 20.0)
+                {
+                    distinct_colours.push(colour);
+                }
+            }
+
+            assert!(distinct_colours.len() >= 8, "{:?} has {} distinct colours", palette, distinct_colours.len());
+
+            // Clusters beyond the palette reuse cluster colours rather than the noise colour
+            let num_colours = cluster_colour.colours.len();
+            assert_eq!(cluster_colour.get_colour(1), cluster_colour.get_colour(num_colours));
         }
     }
 }
\ No newline at end of file
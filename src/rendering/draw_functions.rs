@@ -1,9 +1,12 @@
 use std::ffi::c_void;
-use nalgebra_glm::{TMat4, TVec3, vec3};
+use nalgebra_glm::{TMat4, TVec2, TVec3, vec3};
 use crate::rendering::camera::Camera;
+use crate::rendering::gpu_profiler::{GpuProfiler, ProfileSection, profile_section};
+use crate::rendering::position_capture::POINT_LIFT;
+use crate::helper_logic::point_cloud_analyzer::BoundingSphere;
 use crate::gl_wrappers::shader_program_creation::ShaderProgram;
 use crate::rendering::view_fbo::ViewFBO;
-use crate::view_port_constants::{SCENE_VIEWPORT_HEIGHT_OFFSET, SCENE_VIEWPORT_WIDTH};
+use crate::view_port_constants::{SCENE_VIEWPORT_HEIGHT_OFFSET, ViewLayout};
 use crate::view_logic::view_selection::ViewSelection;
 use crate::view_logic::view_transform::ViewTransformation;
 
@@ -16,8 +19,35 @@ pub struct OutsideParam<'a>
     pub window_resolution: (i32, i32),
     pub scene_matrix: &'a TMat4<f32>,
     pub camera: &'a Camera,
+    pub second_camera: Option<&'a Camera>,
     pub cloud_translation: TVec3<f32>,
-    pub reflect_vertical: i32
+    pub reflect_vertical: i32,
+    pub cloud_bounding_sphere: Option<BoundingSphere>,
+    /// The centre of the point cloud, before the cloud translation is applied, and its largest extent
+    pub cloud_extent: (TVec3<f32>, f32),
+    pub grid_height: f32,
+    pub shadows_enabled: bool,
+    pub alpha_to_coverage: bool,
+    pub log_depth: bool,
+    pub x_ray: bool,
+    pub flat_shading: bool,
+    pub transparent_background: bool,
+    pub near_fade: (f32, f32),
+    pub point_stride: u32,
+    /// The height below which points are hidden, if the ground is removed
+    pub ground_threshold: Option<f32>,
+    pub show_sun_frustum: bool,
+    /// The slope scaled and constant depth offsets of the shadow map, as given to glPolygonOffset
+    pub shadow_offset: (f32, f32),
+    /// How far the grid is pushed behind points at the same depth, as a fraction of the depth range
+    pub grid_depth_offset: f32,
+    pub dither: bool,
+    /// The distance between the eyes the scene is seen from in red and cyan, if shown in anaglyph 3D
+    pub anaglyph_eye_separation: Option<f32>,
+    pub gpu_profiler: Option<&'a GpuProfiler>,
+    /// Opposite corners of the rectangle being dragged out to select points, in Normalized Device
+    /// Coordinates of the whole scene
+    pub selection_rect: Option<(TVec2<f32>, TVec2<f32>)>
 }
 
 /// Provides information about what buffer ranges are needed to model a model
@@ -33,12 +63,51 @@ pub struct DrawCallInfo
 
 pub type RenderFunction = fn(&ShaderProgram, &DrawCallInfo, OutsideParam);
 
+/// The region of the window a camera renders to, as (x, y, width, height)
+pub type Viewport = (i32, i32, i32, i32);
+
+/// Finds the cameras that render the main scene along with the viewport (x, y, width, height) each
+/// camera renders to. If there is a second camera, the scene region is split in half between the cameras
+///
+/// `outside_param` - the variables holding the cameras and window resolution
+pub fn scene_viewports<'a>(outside_param: &OutsideParam<'a>) -> Vec<(&'a Camera, Viewport)>
+{
+    let viewport_x = ((outside_param.window_resolution.0 as f32) * ViewLayout::new(outside_param.window_resolution).scene_width) as i32;
+    let viewport_y = outside_param.window_resolution.1;
+    let viewport_offset_y = (outside_param.window_resolution.1 as f32 * SCENE_VIEWPORT_HEIGHT_OFFSET) as i32;
+
+    match outside_param.second_camera
+    {
+        Some(second_camera) =>
+            {
+                vec!
+                [
+                    (outside_param.camera, (0, viewport_offset_y, viewport_x / 2, viewport_y)),
+                    (second_camera, (viewport_x / 2, viewport_offset_y, viewport_x / 2, viewport_y))
+                ]
+            },
+        None => vec![(outside_param.camera, (0, viewport_offset_y, viewport_x, viewport_y))]
+    }
+}
+
 /// Renders the cube model, which is used to represent points in the point cloud
 pub fn cube_draw_function(shader_program: &ShaderProgram, draw_call_info: &DrawCallInfo, outside_param: OutsideParam)
 {
-    create_shadow_map(shader_program, draw_call_info, outside_param);
-    create_scene_side_views(shader_program, draw_call_info, outside_param);
-    render_scene(shader_program, draw_call_info, outside_param);
+    // Applies to the shadow map and side views as well, as they draw every point too
+    shader_program.write_uint("pointStride", outside_param.point_stride);
+    shader_program.write_uint("groundRemoval", outside_param.ground_threshold.is_some() as u32);
+    shader_program.write_float("groundThreshold", outside_param.ground_threshold.unwrap_or(0.0));
+
+    if outside_param.shadows_enabled
+    {
+        profile_section(outside_param.gpu_profiler, ProfileSection::Shadows, || create_shadow_map(shader_program, draw_call_info, outside_param));
+    }
+
+    profile_section(outside_param.gpu_profiler, ProfileSection::SideViews, || create_scene_side_views(shader_program, draw_call_info, outside_param));
+    profile_section(outside_param.gpu_profiler, ProfileSection::Scene, || render_scene(shader_program, draw_call_info, outside_param));
+
+    shader_program.write_uint("pointStride", 1);
+    shader_program.write_uint("groundRemoval", 0);
 }
 
 /// Renders the plane model, which is used to represent the scene views
@@ -56,29 +125,70 @@ pub fn draw_sun(shader_program: &ShaderProgram, draw_call_info: &DrawCallInfo, o
 {
     shader_program.write_uint("drawingSun", 1);
     shader_program.write_vec3("sunPosition", &outside_param.view_fbos.get_sun_fbo().get_sun_position());
-    shader_program.write_mat4("projViewMatrix", &outside_param.camera.get_projection_view_matrix());
-    unsafe
-        {
-            gl::DrawElementsBaseVertex(gl::TRIANGLES, draw_call_info.indice_count, gl::UNSIGNED_INT, draw_call_info.indice_offset, draw_call_info.vertex_offset);
-        }
+
+    for (camera, (x, y, width, height)) in scene_viewports(&outside_param)
+    {
+        shader_program.write_mat4("projViewMatrix", &camera.get_projection_view_matrix());
+        unsafe
+            {
+                gl::Viewport(x, y, width, height);
+                gl::DrawElementsBaseVertex(gl::TRIANGLES, draw_call_info.indice_count, gl::UNSIGNED_INT, draw_call_info.indice_offset, draw_call_info.vertex_offset);
+            }
+    }
 
     shader_program.write_uint("drawingSun", 0);
 }
 
+/// Renders the translucent ground plane, which covers the point cloud along the x and z axes at the
+/// height of the grid. Points are drawn before it, so that points below the ground are seen through it
+pub fn draw_ground_plane(shader_program: &ShaderProgram, draw_call_info: &DrawCallInfo, outside_param: OutsideParam)
+{
+    let sun = outside_param.view_fbos.get_sun_fbo();
+    let (cloud_centre, cloud_max_length) = outside_param.cloud_extent;
+    let centre = cloud_centre + outside_param.cloud_translation;
+
+    // The shadow map is sampled the same way as for the points, whose uniforms are still set
+    sun.bind_draw_result();
+    shader_program.write_uint("drawingGroundPlane", 1);
+    shader_program.write_vec3("groundPlaneCentre", &vec3(centre.x, outside_param.grid_height, centre.z));
+    shader_program.write_float("groundPlaneHalfSize", cloud_max_length * 0.6);
+
+    // Being translucent, the ground plane does not hide the grid lines drawn after it
+    unsafe{ gl::DepthMask(gl::FALSE) }
+
+    for (camera, (x, y, width, height)) in scene_viewports(&outside_param)
+    {
+        shader_program.write_mat4("projViewMatrix", &camera.get_projection_view_matrix());
+        unsafe
+            {
+                gl::Viewport(x, y, width, height);
+                gl::DrawElementsBaseVertex(gl::TRIANGLES, draw_call_info.indice_count, gl::UNSIGNED_INT, draw_call_info.indice_offset, draw_call_info.vertex_offset);
+            }
+    }
+
+    unsafe{ gl::DepthMask(gl::TRUE) }
+    shader_program.write_uint("drawingGroundPlane", 0);
+}
+
 /// Renders the arrow used to represent the point that the sun is looking at
 pub fn draw_sun_arrow(shader_program: &ShaderProgram, draw_call_info: &DrawCallInfo, outside_param: OutsideParam)
 {
     shader_program.write_uint("drawingSunArrow", 1);
-    shader_program.write_vec3("sunArrowPosition", &outside_param.view_fbos.get_sun_fbo().look_at_position());
-    shader_program.write_float("sunArrowScale", 0.25); // Seemed like nice value
-    shader_program.write_mat4("projViewMatrix", &outside_param.camera.get_projection_view_matrix());
-    unsafe
-        {
-            gl::DrawElementsBaseVertex(gl::TRIANGLES, draw_call_info.indice_count, gl::UNSIGNED_INT, draw_call_info.indice_offset, draw_call_info.vertex_offset);
-            shader_program.write_vec3("sunArrowPosition", &vec3(0.0, 0.0, 0.0));
-            shader_program.write_float("sunArrowScale", 1.0);
-            gl::DrawArrays(gl::LINES, 0, 2);
-        }
+
+    for (camera, (x, y, width, height)) in scene_viewports(&outside_param)
+    {
+        shader_program.write_vec3("sunArrowPosition", &outside_param.view_fbos.get_sun_fbo().look_at_position());
+        shader_program.write_float("sunArrowScale", 0.25); // Seemed like nice value
+        shader_program.write_mat4("projViewMatrix", &camera.get_projection_view_matrix());
+        unsafe
+            {
+                gl::Viewport(x, y, width, height);
+                gl::DrawElementsBaseVertex(gl::TRIANGLES, draw_call_info.indice_count, gl::UNSIGNED_INT, draw_call_info.indice_offset, draw_call_info.vertex_offset);
+                shader_program.write_vec3("sunArrowPosition", &vec3(0.0, 0.0, 0.0));
+                shader_program.write_float("sunArrowScale", 1.0);
+                gl::DrawArrays(gl::LINES, 0, 2);
+            }
+    }
 
     shader_program.write_uint("drawingSunArrow", 0);
 }
@@ -86,9 +196,6 @@ pub fn draw_sun_arrow(shader_program: &ShaderProgram, draw_call_info: &DrawCallI
 /// Renders the scene onto the window. Assumes the shadow map has been created
 fn render_scene(shader_program: &ShaderProgram, draw_call_info: &DrawCallInfo, outside_param: OutsideParam)
 {
-    let reset_viewport_x = ((outside_param.window_resolution.0 as f32) * SCENE_VIEWPORT_WIDTH) as i32;
-    let reset_viewport_y = outside_param.window_resolution.1 as i32;
-
     let sun = outside_param.view_fbos.get_sun_fbo();
 
     sun.bind_draw_result();
@@ -96,23 +203,114 @@ fn render_scene(shader_program: &ShaderProgram, draw_call_info: &DrawCallInfo, o
     shader_program.write_vec3("cloudTranslation", &outside_param.cloud_translation);
     shader_program.write_uint("drawingScene", 1);
     shader_program.write_mat4("lightPerspectiveMatrix", &sun.get_light_matrix());
-    shader_program.write_mat4("projViewMatrix", &outside_param.camera.get_projection_view_matrix());
-    shader_program.write_vec3("cameraPos", &outside_param.camera.get_position());
     shader_program.write_vec3("sunLightColour", &vec3(1.0, 1.0, 1.0));
     shader_program.write_vec3("sunDirection", &sun.get_sun_direction());
+    shader_program.write_uint("shadowsEnabled", outside_param.shadows_enabled as u32);
+    shader_program.write_uint("flatShading", outside_param.flat_shading as u32);
+    // Stays set for the sun and the grid, which are drawn into the scene afterwards
+    shader_program.write_uint("logDepth", outside_param.log_depth as u32);
+    shader_program.write_float("logDepthFarPlane", outside_param.camera.get_far_plane());
+    shader_program.write_float("nearFadeStart", outside_param.near_fade.0);
+    shader_program.write_float("nearFadeEnd", outside_param.near_fade.1);
+    // Stays set for everything drawn afterwards, including the side views of the next frame
+    shader_program.write_uint("dither", outside_param.dither as u32);
 
     unsafe
         {
             gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
-            gl::ClearColor(0.15, 0.15, 0.15, 1.0);
+            gl::ClearColor(0.15, 0.15, 0.15, if outside_param.transparent_background { 0.0 } else { 1.0 });
             gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
-            gl::Viewport(0, ((outside_param.window_resolution.1 as f32 * SCENE_VIEWPORT_HEIGHT_OFFSET)) as i32, reset_viewport_x, reset_viewport_y);
-            gl::DrawElementsInstancedBaseVertexBaseInstance(gl::TRIANGLES, draw_call_info.indice_count, gl::UNSIGNED_INT, draw_call_info.indice_offset, draw_call_info.instance_count, draw_call_info.vertex_offset, draw_call_info.instance_offset);
+
+            // Points are not sorted by depth, so blended points can hide points behind them depending on
+            // the order they are drawn in. Alpha to coverage does not depend on the order, at the cost
+            // of only having as many levels of transparency as there are samples
+            //
+            // The alpha of what is drawn is added to the alpha of the window rather than blended with it,
+            // so that the points, the grid and the sun stay opaque over a transparent background
+            if outside_param.x_ray
+            {
+                // Every point is drawn regardless of what is in front of it. Adding a fraction of each
+                // point's colour makes areas with many overlapping points brighter
+                gl::Disable(gl::DEPTH_TEST);
+                gl::Enable(gl::BLEND);
+                gl::BlendColor(0.0, 0.0, 0.0, 0.35);
+                gl::BlendFuncSeparate(gl::CONSTANT_ALPHA, gl::ONE, gl::ONE, gl::ONE);
+            }
+            else if outside_param.alpha_to_coverage
+            {
+                gl::Disable(gl::BLEND);
+                gl::Enable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+                gl::Enable(gl::SAMPLE_ALPHA_TO_ONE);
+            }
+            else
+            {
+                gl::Enable(gl::BLEND);
+                gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE);
+            }
+        }
+
+    for (camera, (x, y, width, height)) in scene_viewports(&outside_param)
+    {
+        if !cloud_in_view(camera, outside_param)
+        {
+            continue;
         }
 
+        shader_program.write_mat4("projViewMatrix", &camera.get_projection_view_matrix());
+        shader_program.write_vec3("cameraPos", &camera.get_position());
+
+        unsafe
+            {
+                gl::Viewport(x, y, width, height);
+                gl::DrawElementsInstancedBaseVertexBaseInstance(gl::TRIANGLES, draw_call_info.indice_count, gl::UNSIGNED_INT, draw_call_info.indice_offset, draw_call_info.instance_count, draw_call_info.vertex_offset, draw_call_info.instance_offset);
+            }
+    }
+
+    if outside_param.x_ray
+    {
+        // The sun, grid and views drawn afterwards are depth tested and blended as usual
+        unsafe
+            {
+                gl::Enable(gl::DEPTH_TEST);
+                gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE);
+            }
+    }
+    else if outside_param.alpha_to_coverage
+    {
+        // The text rendering relies on blending
+        unsafe
+            {
+                gl::Disable(gl::SAMPLE_ALPHA_TO_COVERAGE);
+                gl::Disable(gl::SAMPLE_ALPHA_TO_ONE);
+                gl::Enable(gl::BLEND);
+                gl::BlendFuncSeparate(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA, gl::ONE, gl::ONE);
+            }
+    }
+
     shader_program.write_uint("drawingScene", 0);
 }
 
+/// Checks if the point cloud can be seen by the given camera. Without a bounding sphere of the point
+/// cloud this cannot be determined, in which case the point cloud is assumed to be visible
+///
+/// `camera` - the camera to check if it sees the point cloud
+/// `outside_param` - the variables holding the bounding sphere and the transformations of the point cloud
+fn cloud_in_view(camera: &Camera, outside_param: OutsideParam) -> bool
+{
+    let bounding_sphere = match outside_param.cloud_bounding_sphere
+    {
+        Some(i) => i,
+        None => return true
+    };
+
+    // Same transformation done to the points in the scene vertex shader. The radius is increased
+    // by the size of a point's cube, as the sphere only encloses the points' centres
+    let mut centre = bounding_sphere.centre + outside_param.cloud_translation + vec3(0.0, POINT_LIFT, 0.0);
+    centre.y *= outside_param.reflect_vertical as f32;
+
+    camera.sphere_in_frustum(&centre, bounding_sphere.radius + 0.1)
+}
+
 /// Creates the shadow map for the scene, which is only comprised of the point cloud points
 fn create_shadow_map(shader_program: &ShaderProgram, draw_call_info: &DrawCallInfo, outside_param: OutsideParam)
 {
@@ -120,7 +318,12 @@ fn create_shadow_map(shader_program: &ShaderProgram, draw_call_info: &DrawCallIn
     sun.prepare_for_drawing(shader_program, &outside_param.scene_matrix, &outside_param.cloud_translation);
     unsafe
         {
+            // Pushing the depths of the shadow map away from the sun stops faces from shadowing
+            // themselves (shadow acne), especially faces at a steep angle to the sun
+            gl::Enable(gl::POLYGON_OFFSET_FILL);
+            gl::PolygonOffset(outside_param.shadow_offset.0, outside_param.shadow_offset.1);
             gl::DrawElementsInstancedBaseVertexBaseInstance(gl::TRIANGLES, draw_call_info.indice_count, gl::UNSIGNED_INT, draw_call_info.indice_offset, draw_call_info.instance_count, draw_call_info.vertex_offset, draw_call_info.instance_offset);
+            gl::Disable(gl::POLYGON_OFFSET_FILL);
         }
     sun.done_drawing(shader_program);
 }
@@ -133,6 +336,8 @@ fn create_scene_side_views(shader_program: &ShaderProgram, draw_call_info: &Draw
 
     shader_program.write_int("reflectVertically", outside_param.reflect_vertical);
     shader_program.write_uint("drawingFromSideView", 1);
+    shader_program.write_uint("logDepth", outside_param.log_depth as u32);
+    shader_program.write_float("logDepthFarPlane", top_view.get_camera().get_far_plane());
     shader_program.write_mat4("rotationMatrix", &outside_param.scene_matrix);
     shader_program.write_mat4("projViewMatrix", &top_view.get_camera().get_projection_view_matrix());
     shader_program.write_vec3("cameraPos", &top_view.get_camera().get_position());
@@ -0,0 +1,203 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+/// The parts of a frame that are timed
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub enum ProfileSection
+{
+    /// Copying new point clouds into the buffers. The copies are done by the CPU into mapped
+    /// buffers, so this is timed on the CPU
+    Upload,
+    Shadows,
+    SideViews,
+    Scene,
+    Lines,
+    Text,
+}
+
+const SECTIONS: [(ProfileSection, &str); 6] =
+[
+    (ProfileSection::Upload, "Upload (CPU)"),
+    (ProfileSection::Shadows, "Shadow pass"),
+    (ProfileSection::SideViews, "Side views"),
+    (ProfileSection::Scene, "Scene pass"),
+    (ProfileSection::Lines, "Grid and edges"),
+    (ProfileSection::Text, "Text"),
+];
+
+/// The number of frames the queries of a frame are kept for before their results are read. The GPU
+/// runs behind the CPU, so reading the results of the frame just rendered would stall until the GPU
+/// catches up
+const NUM_FRAMES_IN_FLIGHT: usize = 3;
+
+/// How much of a new time is blended into the displayed time of a section, so that the displayed
+/// times are steady enough to read
+const SMOOTHING_FACTOR: f32 = 0.1;
+
+/// Times how long the GPU spends on each part of a frame using timestamp queries. A timestamp is
+/// written before and after each section; the results are read a couple of frames later, once the
+/// GPU has executed them
+pub struct GpuProfiler
+{
+    // Two queries, the start and the end, per section for each frame in flight
+    queries: Vec<Vec<u32>>,
+    timed_sections: Vec<Vec<Cell<bool>>>,
+    frame_index: usize,
+    section_times_ms: Vec<Option<f32>>,
+}
+
+impl GpuProfiler
+{
+    /// Creates a new profiler with no section timed yet
+    pub fn new() -> GpuProfiler
+    {
+        let queries = (0..NUM_FRAMES_IN_FLIGHT)
+            .map(|_|
+                {
+                    let mut frame_queries = vec![0_u32; SECTIONS.len() * 2];
+                    unsafe{ gl::CreateQueries(gl::TIMESTAMP, frame_queries.len() as i32, frame_queries.as_mut_ptr()) }
+                    frame_queries
+                })
+            .collect();
+
+        GpuProfiler
+        {
+            queries,
+            timed_sections: (0..NUM_FRAMES_IN_FLIGHT).map(|_| SECTIONS.iter().map(|_| Cell::new(false)).collect()).collect(),
+            frame_index: 0,
+            section_times_ms: vec![None; SECTIONS.len()],
+        }
+    }
+
+    /// Writes the timestamp the given section starts at once the GPU reaches this point
+    ///
+    /// `section` - the section that starts
+    pub fn begin(&self, section: ProfileSection)
+    {
+        let index = GpuProfiler::section_index(section);
+        unsafe{ gl::QueryCounter(self.queries[self.frame_index][index * 2], gl::TIMESTAMP) }
+    }
+
+    /// Writes the timestamp the given section ends at once the GPU reaches this point. Must be called
+    /// after the section was begun in the same frame
+    ///
+    /// `section` - the section that ends
+    pub fn end(&self, section: ProfileSection)
+    {
+        let index = GpuProfiler::section_index(section);
+        unsafe{ gl::QueryCounter(self.queries[self.frame_index][index * 2 + 1], gl::TIMESTAMP) }
+        self.timed_sections[self.frame_index][index].set(true);
+    }
+
+    /// Records the time a section took on the CPU, for sections that do not involve the GPU
+    ///
+    /// `section` - the section that was timed
+    /// `duration` - how long the section took
+    pub fn record_cpu_time(&mut self, section: ProfileSection, duration: Duration)
+    {
+        self.record_time(GpuProfiler::section_index(section), duration.as_secs_f32() * 1000.0);
+    }
+
+    /// Moves on to the next frame, reading the results of the oldest frame in flight before its
+    /// queries are reused. Results that are still not available are dropped
+    pub fn end_frame(&mut self)
+    {
+        self.frame_index = (self.frame_index + 1) % NUM_FRAMES_IN_FLIGHT;
+
+        for index in 0..SECTIONS.len()
+        {
+            if !self.timed_sections[self.frame_index][index].replace(false)
+            {
+                continue;
+            }
+
+            let start_query = self.queries[self.frame_index][index * 2];
+            let end_query = self.queries[self.frame_index][index * 2 + 1];
+
+            let mut available = 0_u64;
+            unsafe{ gl::GetQueryObjectui64v(end_query, gl::QUERY_RESULT_AVAILABLE, &mut available) }
+            if available == 0
+            {
+                continue;
+            }
+
+            let (mut start_ns, mut end_ns) = (0_u64, 0_u64);
+            unsafe
+                {
+                    gl::GetQueryObjectui64v(start_query, gl::QUERY_RESULT, &mut start_ns);
+                    gl::GetQueryObjectui64v(end_query, gl::QUERY_RESULT, &mut end_ns);
+                }
+
+            self.record_time(index, end_ns.saturating_sub(start_ns) as f32 / 1_000_000.0);
+        }
+    }
+
+    /// Get the text representation of the time of each section that has been timed, followed by
+    /// their total
+    pub fn to_string_rows(&self) -> Vec<String>
+    {
+        let mut rows: Vec<String> = SECTIONS.iter().zip(&self.section_times_ms)
+            .filter_map(|((_, name), time)| time.map(|x| format!("{}: {:.2} ms", name, x)))
+            .collect();
+
+        let total = self.section_times_ms.iter().flatten().sum::<f32>();
+        rows.push(format!("Total: {:.2} ms", total));
+        rows
+    }
+
+    /// Blends a new time of a section into the displayed time of the section
+    ///
+    /// `index` - the index of the section
+    /// `time_ms` - the new time of the section, in milliseconds
+    fn record_time(&mut self, index: usize, time_ms: f32)
+    {
+        let section_time = &mut self.section_times_ms[index];
+        *section_time = Some(match *section_time
+        {
+            Some(i) => i + (time_ms - i) * SMOOTHING_FACTOR,
+            None => time_ms
+        });
+    }
+
+    /// Get the index of the given section within the sections timed
+    ///
+    /// `section` - the section to find the index of
+    fn section_index(section: ProfileSection) -> usize
+    {
+        SECTIONS.iter().position(|(x, _)| *x == section).unwrap()
+    }
+}
+
+/// Runs the given function as a section of the frame, timing it if there is a profiler
+///
+/// `gpu_profiler` - the profiler timing the frame, if the frame is being profiled
+/// `section` - the section the function does
+/// `function` - the function doing the section
+pub fn profile_section<F: FnOnce()>(gpu_profiler: Option<&GpuProfiler>, section: ProfileSection, function: F)
+{
+    if let Some(gpu_profiler) = gpu_profiler
+    {
+        gpu_profiler.begin(section);
+    }
+
+    function();
+
+    if let Some(gpu_profiler) = gpu_profiler
+    {
+        gpu_profiler.end(section);
+    }
+}
+
+impl Drop for GpuProfiler
+{
+    fn drop(&mut self)
+    {
+        for frame_queries in &self.queries
+        {
+            unsafe
+                {
+                    gl::DeleteQueries(frame_queries.len() as i32, frame_queries.as_ptr());
+                }
+        }
+    }
+}
\ No newline at end of file
@@ -1,6 +1,8 @@
 pub mod camera;
 pub mod cluster_colour;
 pub mod draw_functions;
+pub mod gpu_profiler;
+pub mod position_capture;
 pub mod scene_renderer;
 mod sunlight;
 pub mod text_rendering;
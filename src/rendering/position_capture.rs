@@ -0,0 +1,130 @@
+use nalgebra_glm::{TVec3, vec3};
+
+/// Height points are lifted by in the passes drawing them in colour, so that points resting on the
+/// y = 0 plane are drawn above the grid. Written to the "pointLift" uniform of sceneVertexShader.glsl
+pub const POINT_LIFT: f32 = 0.995;
+
+/// The passes that draw the points of the point cloud, each of which places the points in the world
+/// with its own uniforms
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CapturePass
+{
+    ShadowMap,
+    SideViews,
+    Scene,
+}
+
+impl CapturePass
+{
+    /// All of the passes, in the order they are drawn in
+    pub const ALL: [CapturePass; 3] = [CapturePass::ShadowMap, CapturePass::SideViews, CapturePass::Scene];
+
+    /// Get the name of the pass, as used in the names of the files the captured positions are written to
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            CapturePass::ShadowMap => "shadow",
+            CapturePass::SideViews => "side_views",
+            CapturePass::Scene => "scene",
+        }
+    }
+
+    /// Get the uniform that makes the vertex shader draw the points the way the pass does
+    pub fn uniform_name(&self) -> &'static str
+    {
+        match self
+        {
+            CapturePass::ShadowMap => "drawingSceneLightPerspective",
+            CapturePass::SideViews => "drawingFromSideView",
+            CapturePass::Scene => "drawingScene",
+        }
+    }
+}
+
+/// Computes where the vertex shader is expected to place the centre of each point in the given pass,
+/// before the scene rotation is applied
+///
+/// `pass` - the pass the points are drawn in
+/// `translations` - the positions of the points, as uploaded
+/// `cloud_translation` - the translation applied to the whole point cloud
+/// `reflect_vertical` - -1 if the point cloud is reflected vertically, otherwise 1
+pub fn expected_point_positions(pass: CapturePass, translations: &[TVec3<f32>], cloud_translation: TVec3<f32>, reflect_vertical: i32) -> Vec<TVec3<f32>>
+{
+    // The shadow map is drawn without the lift; the shadows are looked up with the lift instead
+    let lift = if pass == CapturePass::ShadowMap { 0.0 } else { POINT_LIFT };
+
+    translations.iter()
+        .map(|x|
+            {
+                let world_pos = x + cloud_translation + vec3(0.0, lift, 0.0);
+                vec3(world_pos.x, world_pos.y * reflect_vertical as f32, world_pos.z)
+            })
+        .collect()
+}
+
+/// Get the largest distance between a captured position and the position it was expected to be at,
+/// or None if there is not one captured position for each expected position
+///
+/// `captured` - the positions read back from the vertex shader
+/// `expected` - the positions computed on the CPU
+pub fn max_deviation(captured: &[TVec3<f32>], expected: &[TVec3<f32>]) -> Option<f32>
+{
+    if captured.len() != expected.len()
+    {
+        return None;
+    }
+
+    Some(captured.iter().zip(expected).map(|(x, y)| nalgebra_glm::distance(x, y)).fold(0.0, f32::max))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::rendering::position_capture::{CapturePass, expected_point_positions, max_deviation, POINT_LIFT};
+
+    const VERTEX_SHADER: &str = include_str!("../../shaders/sceneVertexShader.glsl");
+
+    #[test]
+    fn expected_positions_follow_pass_uniforms()
+    {
+        let translations = [vec3(1.0, 2.0, 3.0)];
+        let cloud_translation = vec3(0.5, -1.0, 0.0);
+
+        assert_eq!(vec![vec3(1.5, -1.0, 3.0)], expected_point_positions(CapturePass::ShadowMap, &translations, cloud_translation, -1));
+
+        let scene = expected_point_positions(CapturePass::Scene, &translations, cloud_translation, -1);
+        assert_eq!(scene, expected_point_positions(CapturePass::SideViews, &translations, cloud_translation, -1));
+        assert!((scene[0].y + 1.995).abs() < 1e-6);
+
+        assert_eq!(Some(0.0), max_deviation(&scene, &scene));
+        assert_eq!(Some(1.0), max_deviation(&[vec3(1.5, 0.5, 3.0)], &[vec3(1.5, -0.5, 3.0)]));
+        assert_eq!(None, max_deviation(&scene, &[]));
+    }
+
+    #[test]
+    fn expected_positions_mirror_vertex_shader()
+    {
+        // The expected positions add the same offsets, and reflect the same component, as the shader does
+        assert!(VERTEX_SHADER.contains("vec4(modelVertex + translation + cloudTranslation + vec3(0.0, lift, 0.0), 1.0);\n    worldSpaceVertex.y *= reflectVertically;"));
+        assert!(VERTEX_SHADER.contains("uniform float pointLift;"));
+
+        for pass in CapturePass::ALL.iter()
+        {
+            let branch = VERTEX_SHADER.find(&format!("if({} == 1)", pass.uniform_name())).unwrap();
+            let capture = "capturedPosition = pointWorldPosition(vec3(0.0), ";
+            let lift_start = branch + VERTEX_SHADER[branch..].find(capture).unwrap() + capture.len();
+            let lift_end = lift_start + VERTEX_SHADER[lift_start..].find(')').unwrap();
+
+            let shader_lift = match &VERTEX_SHADER[lift_start..lift_end]
+            {
+                "pointLift" => POINT_LIFT,
+                lift => lift.parse::<f32>().unwrap()
+            };
+
+            let expected = expected_point_positions(*pass, &[vec3(0.0, 0.0, 0.0)], vec3(0.0, 0.0, 0.0), 1);
+            assert_eq!(vec![vec3(0.0, shader_lift, 0.0)], expected, "{} pass", pass.name());
+        }
+    }
+}
\ No newline at end of file
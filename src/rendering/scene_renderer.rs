@@ -1,28 +1,172 @@
 use std::ffi::c_void;
 use std::mem::size_of;
-use nalgebra_glm::{TVec2, TVec3, vec3};
-use crate::gl_wrappers::buffer::{Buffer, BufferType};
+use std::process::exit;
+use nalgebra_glm::{TMat3, TVec2, TVec3, vec3};
+use crate::gl_wrappers::buffer::{Buffer, BufferType, UploadMode};
+use crate::gl_wrappers::transform_feedback::TransformFeedback;
 use crate::geometry;
 use crate::rendering::draw_functions::{DrawCallInfo, OutsideParam, RenderFunction};
 use crate::geometry::geometry_trait::RenderableGeometry;
-use crate::geometry::grid::Grid;
+use crate::geometry::grid::{Grid, GridColours};
 use crate::helper_logic::folder_location_functions::{get_point_models_folder, get_shaders_folder};
 use crate::helper_logic::point_cloud_analyzer::InitialCloudAnalyzer;
 use crate::gl_wrappers::shader_program_creation::{ShaderInitInfo, ShaderProgram, ShaderType};
 use crate::gl_wrappers::vao::VAO;
 use crate::rendering::draw_functions;
-use crate::view_port_constants::{SCENE_VIEWPORT_HEIGHT_OFFSET, SCENE_VIEWPORT_WIDTH};
+use crate::rendering::gpu_profiler::{ProfileSection, profile_section};
+use crate::rendering::position_capture::{CapturePass, POINT_LIFT};
+use crate::view_port_constants::{SCENE_VIEWPORT_HEIGHT_OFFSET, ViewLayout};
 
 pub fn default_point_colour() -> TVec3<f32>
 {
     vec3(0.0_f32, 0.7, 0.0)
 }
 
+/// The colour of the lines drawn between points given as edges
+pub fn edge_colour() -> TVec3<f32>
+{
+    vec3(0.9_f32, 0.75, 0.2)
+}
+
+/// The colour of points selected with a selection rectangle, and of the rectangle itself
+pub fn selection_colour() -> TVec3<f32>
+{
+    vec3(1.0_f32, 0.3, 1.0)
+}
+
+/// The transformation applied to the cube model of points that are not given a shape
+pub fn default_point_shape() -> TMat3<f32>
+{
+    TMat3::from_diagonal_element(0.05)
+}
+
+/// Maps the scalar of each point to the scale of its model, linearly from the smallest scalar at the
+/// smallest scale to the largest scalar at the largest scale. If all scalars are equal, every point
+/// is given the smallest scale
+///
+/// `scalars` - the scalar of each point
+/// `scale_range` - the scales given to the points with the smallest and largest scalars
+pub fn scalar_point_scales(scalars: &[f32], scale_range: (f32, f32)) -> Vec<f32>
+{
+    let min_scalar = scalars.iter().copied().fold(f32::MAX, f32::min);
+    let max_scalar = scalars.iter().copied().fold(f32::MIN, f32::max);
+    let scalar_difference = max_scalar - min_scalar;
+
+    scalars.iter()
+        .map(|x| if scalar_difference > f32::EPSILON { (x - min_scalar) / scalar_difference } else { 0.0 })
+        .map(|x| scale_range.0 + x * (scale_range.1 - scale_range.0))
+        .collect()
+}
+
+/// Maps the activity of each point to a colour, from blue for no activity to red for the most activity
+///
+/// `activities` - the activity of each point, such as how often the region around it changed
+/// `max_activity` - the activity given the colour red
+pub fn activity_colours(activities: &[f32], max_activity: f32) -> Vec<TVec3<f32>>
+{
+    activities.iter()
+        .map(|x| if max_activity > f32::EPSILON { (x / max_activity).min(1.0) } else { 0.0 })
+        .map(|x| VelocityColours::hue_to_rgb(240.0 * (1.0 - x)))
+        .collect()
+}
+
+/// What property of the velocity of the points determines their colour
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VelocityColourMode
+{
+    /// The magnitude of the velocity, from blue for the slowest point to red for the fastest
+    Speed,
+    /// The heading of the velocity in the xz-plane, as a hue that starts with red along +x
+    Direction,
+}
+
+/// The colours given to points based off of their velocities
+pub struct VelocityColours
+{
+    pub colours: Vec<TVec3<f32>>,
+    pub mode: VelocityColourMode,
+    /// The speed of the slowest and fastest points
+    pub speed_range: (f32, f32),
+}
+
+impl VelocityColours
+{
+    /// Colours each point based off of its velocity. Points that are not moving have no heading, and
+    /// are given the default point colour when colouring by direction
+    ///
+    /// `velocities` - the velocity of each point
+    /// `mode` - what property of the velocities determines the colours
+    pub fn new(velocities: &[TVec3<f32>], mode: VelocityColourMode) -> VelocityColours
+    {
+        let speeds = velocities.iter().map(nalgebra_glm::length).collect::<Vec<f32>>();
+        let speed_range = if speeds.is_empty()
+        {
+            (0.0, 0.0)
+        }
+        else
+        {
+            (speeds.iter().copied().fold(f32::MAX, f32::min), speeds.iter().copied().fold(f32::MIN, f32::max))
+        };
+
+        let colours = match mode
+        {
+            VelocityColourMode::Speed =>
+                {
+                    let speed_difference = speed_range.1 - speed_range.0;
+                    speeds.iter()
+                        .map(|x| if speed_difference > f32::EPSILON { (x - speed_range.0) / speed_difference } else { 0.0 })
+                        .map(|x| VelocityColours::hue_to_rgb(240.0 * (1.0 - x)))
+                        .collect()
+                },
+            VelocityColourMode::Direction =>
+                {
+                    velocities.iter()
+                        .zip(speeds.iter())
+                        .map(|(velocity, speed)| if *speed > f32::EPSILON { VelocityColours::hue_to_rgb(velocity.z.atan2(velocity.x).to_degrees()) } else { default_point_colour() })
+                        .collect()
+                }
+        };
+
+        VelocityColours { colours, mode, speed_range }
+    }
+
+    /// Get the text describing what the colours of the points mean
+    pub fn to_string_legend(&self) -> String
+    {
+        match self.mode
+        {
+            VelocityColourMode::Speed => format!("Speed: {:.2} (blue) - {:.2} (red)", self.speed_range.0, self.speed_range.1),
+            VelocityColourMode::Direction => "Direction: +X red, +Z lime, -X cyan, -Z violet".to_string(),
+        }
+    }
+
+    /// Converts a hue to a fully saturated and bright colour
+    ///
+    /// `hue_degrees` - the hue, where 0 is red, 120 is green and 240 is blue
+    fn hue_to_rgb(hue_degrees: f32) -> TVec3<f32>
+    {
+        let hue = hue_degrees.rem_euclid(360.0) / 60.0;
+        let rising = 1.0 - (hue % 2.0 - 1.0).abs();
+
+        match hue as u32
+        {
+            0 => vec3(1.0, rising, 0.0),
+            1 => vec3(rising, 1.0, 0.0),
+            2 => vec3(0.0, 1.0, rising),
+            3 => vec3(0.0, rising, 1.0),
+            4 => vec3(rising, 0.0, 1.0),
+            _ => vec3(1.0, 0.0, rising),
+        }
+    }
+}
+
 /// Specifies how the geometrical information that makes up a model and how to render it
 pub struct RenderInformation
 {
     pub geometry: Box<dyn RenderableGeometry>,
-    pub command: RenderFunction
+    pub command: RenderFunction,
+    /// If false the command is not called, so the model is not drawn
+    pub visible: bool
 }
 
 /// Holds the requires elements needed to render the scene
@@ -38,6 +182,9 @@ pub struct SceneRenderer
 
     instanced_translations: Buffer,
     instanced_colours: Buffer,
+    instanced_alphas: Buffer,
+    instanced_shapes: Buffer,
+    instanced_scales: Buffer,
 
     indices: Buffer,
 
@@ -48,7 +195,39 @@ pub struct SceneRenderer
 
     models: Vec<RenderInformation>,
     model_render_info: Vec<DrawCallInfo>,
+    instance_data: Vec<InstanceData>,
+
+    edges: Option<Edges>,
+    /// Where the lines of the sun frustum are written in the vertex buffer
+    sun_frustum_bytes_offset: isize,
+    /// Where the lines of the selection rectangle are written in the vertex buffer
+    selection_rect_bytes_offset: isize,
+    /// Reads back the positions the vertex shader places points at
+    position_capture: TransformFeedback,
+}
 
+/// The number of vertices of the lines making up the twelve edges of the sun frustum
+const SUN_FRUSTUM_NUM_VERTICES: usize = 24;
+
+/// The number of vertices of the lines making up the four sides of the selection rectangle
+const SELECTION_RECT_NUM_VERTICES: usize = 8;
+
+/// Lines drawn between pairs of instances of a model, such as to show a skeleton or trajectory
+struct Edges
+{
+    model_id: ModelId,
+    /// The instance indices of the ends of each edge, sorted by the larger index of each edge
+    instance_indices: Vec<[u32; 2]>,
+    indices_offset_bytes: isize,
+}
+
+/// Copy of the instance information most recently uploaded for a model, so that it can be read
+/// without reading back the GPU buffers
+#[derive(Default)]
+struct InstanceData
+{
+    translations: Vec<TVec3<f32>>,
+    colours: Vec<TVec3<f32>>,
 }
 
 /// Specifies the instance information for a model
@@ -56,7 +235,15 @@ pub struct UploadInformation<'a>
 {
     pub model_id: ModelId,
     pub instance_translations: Option<&'a [TVec3<f32>]>,
-    pub instance_colours: Option<&'a Vec<TVec3<f32>>>,
+    pub instance_colours: Option<&'a [TVec3<f32>]>,
+    /// If not given while translations are, every instance is fully opaque
+    pub instance_alphas: Option<&'a [f32]>,
+    /// Transformation of the model of each instance. If not given while translations are, every
+    /// instance uses the default point shape
+    pub instance_shapes: Option<&'a [TMat3<f32>]>,
+    /// Scale applied to the model of each instance on top of its shape. If not given while translations
+    /// are, every instance keeps the size of its shape
+    pub instance_scales: Option<&'a [f32]>,
 }
 
 /// Unique identifier for a model
@@ -66,10 +253,41 @@ pub struct ModelId
     id: usize
 }
 
+/// The identifiers of the models added by the scene renderer setup
+#[derive(Copy, Clone)]
+pub struct SceneModelIds
+{
+    pub cube: ModelId,
+    pub sun: ModelId,
+    pub sun_arrow: ModelId,
+    pub plane: ModelId,
+    pub ground_plane: ModelId,
+}
+
+/// The optional attributes of each point of the initial point cloud. An attribute that is not given
+/// takes its default value for every point
+pub struct InitialPointAttributes<'a>
+{
+    /// The colour of each point, if not the default colour
+    pub colours: Option<&'a [TVec3<f32>]>,
+    pub alphas: Option<&'a [f32]>,
+    /// The transformation of the model of each point
+    pub shapes: Option<&'a [TMat3<f32>]>,
+    /// The scale of the model of each point
+    pub scales: Option<&'a [f32]>,
+}
+
 impl SceneRenderer
 {
     /// Specifies all of the models and associated information needed to render a scene
-    pub fn setup_scene_renderer(point_analyzer: &InitialCloudAnalyzer) -> (SceneRenderer, ModelId)
+    ///
+    /// `point_analyzer` - information about the initial point cloud
+    /// `initial_attributes` - the colours, alphas, shapes and scales of the points of the initial point cloud, if any
+    /// `edges` - pairs of indices of points to draw lines between
+    /// `upload_mode` - how data is uploaded to the buffers of the scene
+    /// `grid_colours` - the colours of the world grid
+    pub fn setup_scene_renderer(point_analyzer: &InitialCloudAnalyzer, initial_attributes: InitialPointAttributes,
+                                edges: &[[u32; 2]], upload_mode: UploadMode, grid_colours: GridColours) -> (SceneRenderer, SceneModelIds)
     {
         let mut scene_renderer_builder = SceneRendererBuilder::new();
 
@@ -77,65 +295,115 @@ impl SceneRenderer
         {
             geometry: Box::new( geometry::model::Model::from_file(get_point_models_folder().join("cube.obj"))),
             command: draw_functions::cube_draw_function,
+            visible: true,
         });
 
-        scene_renderer_builder.add_model(RenderInformation
+        let sun_model_index = scene_renderer_builder.add_model(RenderInformation
         {
             geometry: Box::new( geometry::model::Model::from_file(get_point_models_folder().join("sun.obj"))),
             command: draw_functions::draw_sun,
+            visible: true,
         });
 
-        scene_renderer_builder.add_model(RenderInformation
+        let sun_arrow_model_index = scene_renderer_builder.add_model(RenderInformation
         {
             geometry: Box::new( geometry::model::Model::from_file(get_point_models_folder().join("sunArrow.obj"))),
             command: draw_functions::draw_sun_arrow,
+            visible: true,
         });
 
-        scene_renderer_builder.add_model(RenderInformation
+        let plane_model_index = scene_renderer_builder.add_model(RenderInformation
         {
             geometry: Box::new( geometry::model::Model::from_file(get_point_models_folder().join("plane2.obj"))),
             command: draw_functions::plane_draw_function,
+            visible: true,
+        });
+
+        // Hidden until toggled, as the grid already shows where the ground is
+        let ground_plane_model_index = scene_renderer_builder.add_model(RenderInformation
+        {
+            geometry: Box::new( geometry::model::Model::from_file(get_point_models_folder().join("groundPlane.obj"))),
+            command: draw_functions::draw_ground_plane,
+            visible: false,
         });
 
-        let mut scene_renderer = scene_renderer_builder.build(50_000);
+        if !edges.is_empty()
+        {
+            scene_renderer_builder.set_edges(cube_model_index, edges);
+        }
+
+        let mut scene_renderer = scene_renderer_builder.build(50_000, upload_mode, grid_colours);
+
+        // By default the points in a scene will be a shade of green; personal preference
+        let default_colours = vec![default_point_colour(); point_analyzer.get_initial_points().len()];
 
-        scene_renderer.upload_instance_information(vec!
+        let upload_result = scene_renderer.upload_instance_information(vec!
         [
             UploadInformation
             {
                 model_id: cube_model_index,
                 instance_translations: Some(&point_analyzer.get_initial_points()),
-                // By default the points in a scene will be a shade of green; personal preference
-                instance_colours: Some(&vec![default_point_colour(); point_analyzer.get_initial_points().len()])
+                instance_colours: Some(initial_attributes.colours.unwrap_or(&default_colours)),
+                instance_alphas: initial_attributes.alphas,
+                instance_shapes: initial_attributes.shapes,
+                instance_scales: initial_attributes.scales
             }]);
 
-        (scene_renderer, cube_model_index)
+        if let Err(err) = upload_result
+        {
+            eprintln!("Failed to upload the initial point cloud: {}", err);
+        }
+
+        (scene_renderer, SceneModelIds{ cube: cube_model_index, sun: sun_model_index, sun_arrow: sun_arrow_model_index, plane: plane_model_index, ground_plane: ground_plane_model_index })
     }
 
     /// Creates a new scene renderer that with the ability to store and render instances that constitute a scene
     ///
     /// `models` - the models that make up a scene
+    /// `edges` - the model whose instances have lines drawn between them, and the pairs of instances to draw lines between
     /// `max_number_instances` - maximum number of instances of all models combined in the scene
-    fn new(models: Vec<RenderInformation>, max_number_instances: u32) -> SceneRenderer
+    /// `upload_mode` - how data is uploaded to the buffers of the scene
+    /// `grid_colours` - the colours of the world grid
+    fn new(models: Vec<RenderInformation>, edges: Option<(ModelId, Vec<[u32; 2]>)>, max_number_instances: u32, upload_mode: UploadMode, grid_colours: GridColours) -> SceneRenderer
     {
         let shader_program = create_shader_program();
+        shader_program.write_float("pointLift", POINT_LIFT);
 
         // 500 length is chosen as it is unlikely a point cloud will extend beyond this amount,
         // and at this length the edges of the grid are not visible
-        let grid = Grid::new(500);
-        let vertices_buffer_bytes = models.iter().map(|x| x.geometry.len_vertices_bytes()).sum::<isize>()
+        let grid = Grid::new(500, grid_colours);
+        // The lines of the sun frustum and the selection rectangle are written every frame after all
+        // of the other vertices
+        let sun_frustum_bytes_offset = models.iter().map(|x| x.geometry.len_vertices_bytes()).sum::<isize>()
             + grid.len_vertices_bytes()
             + SceneRenderer::size_sun_arrow_bytes();
+        let selection_rect_bytes_offset = sun_frustum_bytes_offset + SceneRenderer::size_sun_frustum_bytes();
+        let vertices_buffer_bytes = selection_rect_bytes_offset + SceneRenderer::size_selection_rect_bytes();
 
         let tex_coords_size_bytes = models.iter().map(|x|  x.geometry.len_tex_coords_bytes()).sum::<isize>()
             + grid.len_tex_coords_bytes()
-            + SceneRenderer::size_sun_arrow_tex_bytes();
+            + SceneRenderer::size_sun_arrow_tex_bytes()
+            + SceneRenderer::size_sun_frustum_tex_bytes()
+            + SceneRenderer::size_selection_rect_tex_bytes();
 
         let normals_buffer_bytes = models.iter().map(|x|  x.geometry.len_normals_bytes()).sum::<isize>()
             + grid.len_normals_bytes()
-            + SceneRenderer::size_sun_arrow_bytes();
+            + SceneRenderer::size_sun_arrow_bytes()
+            + SceneRenderer::size_sun_frustum_bytes()
+            + SceneRenderer::size_selection_rect_bytes();
 
-        let indices_buffer_bytes = models.iter().map(|x|  x.geometry.len_indices_bytes()).sum::<isize>() + grid.len_indices_bytes();
+        let models_indices_bytes = models.iter().map(|x|  x.geometry.len_indices_bytes()).sum::<isize>() + grid.len_indices_bytes();
+
+        // Only the edges between instances that exist are drawn. Sorting the edges by their larger
+        // index makes those edges the first ones, however many instances there are
+        let edges = edges.map(|(model_id, mut instance_indices)|
+            {
+                instance_indices.sort_by_key(|x| x[0].max(x[1]));
+                Edges{ model_id, instance_indices, indices_offset_bytes: models_indices_bytes }
+            });
+
+        let edges_indices_bytes = edges.as_ref().map_or(0, |x| (x.instance_indices.len() * size_of::<[u32; 2]>()) as isize);
+        let indices_buffer_bytes = models_indices_bytes + edges_indices_bytes;
 
         let vao = VAO::new();
         vao.bind_vao();
@@ -145,38 +413,66 @@ impl SceneRenderer
         vao.specify_index_layout(2, 3, gl::FLOAT, false, 0);
         vao.specify_index_layout(3, 3, gl::FLOAT, false, 0);
         vao.specify_index_layout(4, 3, gl::FLOAT, false, 0);
+        vao.specify_index_layout(5, 1, gl::FLOAT, false, 0);
+
+        // The columns of the shape matrix are interleaved in a single buffer
+        for column in 0..3
+        {
+            vao.specify_index_layout(6 + column, 3, gl::FLOAT, false, column * size_of::<TVec3<f32>>() as u32);
+            vao.specify_index_binding(6 + column, 6);
+        }
+
+        vao.specify_index_layout(9, 1, gl::FLOAT, false, 0);
 
         vao.specify_divisor(3, 1);
         vao.specify_divisor(4, 1);
+        vao.specify_divisor(5, 1);
+        vao.specify_divisor(6, 1);
+        vao.specify_divisor(9, 1);
 
         let size_instance_buffer_bytes = (size_of::<TVec3<f32>>() * max_number_instances as usize) as isize;
+        let size_instance_alpha_buffer_bytes = (size_of::<f32>() * max_number_instances as usize) as isize;
+        let size_instance_shape_buffer_bytes = (size_of::<TMat3<f32>>() * max_number_instances as usize) as isize;
+        let size_instance_scale_buffer_bytes = (size_of::<f32>() * max_number_instances as usize) as isize;
 
         let mut buffer_group = SceneRenderer
         {
             shader_program,
             grid,
-            vertices: Buffer::new(&vao, vertices_buffer_bytes, 1, BufferType::Array(0, 12)),
-            tex_coords: Buffer::new(&vao, tex_coords_size_bytes, 1,BufferType::Array(1, 8)),
-            normals: Buffer::new(&vao, normals_buffer_bytes, 1,BufferType::Array(2, 12)),
-            instanced_translations: Buffer::new(&vao, size_instance_buffer_bytes, 1, BufferType::Array(4, 12)),
-            instanced_colours: Buffer::new(&vao, size_instance_buffer_bytes, 1, BufferType::Array(3, 12)),
-            indices: Buffer::new(&vao, indices_buffer_bytes, 1, BufferType::Indice),
+            vertices: Buffer::new(&vao, vertices_buffer_bytes, 1, BufferType::Array(0, 12), upload_mode),
+            tex_coords: Buffer::new(&vao, tex_coords_size_bytes, 1,BufferType::Array(1, 8), upload_mode),
+            normals: Buffer::new(&vao, normals_buffer_bytes, 1,BufferType::Array(2, 12), upload_mode),
+            instanced_translations: Buffer::new(&vao, size_instance_buffer_bytes, 1, BufferType::Array(4, 12), upload_mode),
+            instanced_colours: Buffer::new(&vao, size_instance_buffer_bytes, 1, BufferType::Array(3, 12), upload_mode),
+            instanced_alphas: Buffer::new(&vao, size_instance_alpha_buffer_bytes, 1, BufferType::Array(5, 4), upload_mode),
+            instanced_shapes: Buffer::new(&vao, size_instance_shape_buffer_bytes, 1, BufferType::Array(6, 36), upload_mode),
+            instanced_scales: Buffer::new(&vao, size_instance_scale_buffer_bytes, 1, BufferType::Array(9, 4), upload_mode),
+            indices: Buffer::new(&vao, indices_buffer_bytes, 1, BufferType::Indice, upload_mode),
+            instance_data: (0..models.len()).map(|_| InstanceData::default()).collect(),
             models,
+            edges,
             model_render_info: Vec::new(),
+            sun_frustum_bytes_offset,
+            selection_rect_bytes_offset,
+            position_capture: TransformFeedback::new(max_number_instances as usize),
             max_number_instances,
             base_number_instances: 0,
             current_instance_upload_index: 0,
             vao,
         };
 
-        buffer_group.upload_model_geometry();
+        if let Err(err) = buffer_group.upload_model_geometry()
+        {
+            eprintln!("Failed to upload model geometry: {}", err);
+            exit(-1);
+        }
 
         buffer_group
     }
 
     /// Uploads the model geometry into buffers and keeps track of the required indexing information
     /// into these buffers in order to render the uploaded geometry
-    fn upload_model_geometry(&mut self)
+    fn upload_model_geometry(&mut self) -> Result<(), String>
     {
         /* All instanced layouts (translation and colours) are matched up to the per-vertex layouts.
           In other words:
@@ -188,31 +484,38 @@ impl SceneRenderer
           is defined. This is done as it is not sure if doing otherwise is against OpenGL rules
         */
 
-        let timeout = 5_000_000;
-
         let mut bytes_vertices_written = SceneRenderer::size_sun_arrow_bytes();
         let mut bytes_tex_coords_written = SceneRenderer::size_sun_arrow_tex_bytes();
         let mut bytes_normals_written = SceneRenderer::size_sun_arrow_bytes();
         let mut bytes_instanced_translations_written = (size_of::<TVec3<f32>>() * 2) as isize;
         let mut bytes_instanced_colours_written = (size_of::<TVec3<f32>>() * 2) as isize;
+        let mut bytes_instanced_alphas_written = (size_of::<f32>() * 2) as isize;
+        let mut bytes_instanced_shapes_written = (size_of::<TMat3<f32>>() * 2) as isize;
+        let mut bytes_instanced_scales_written = (size_of::<f32>() * 2) as isize;
         let mut bytes_indices_written = 0;
 
         let num_vertices = self.grid.get_vertices().len();
-        self.vertices.write_data_offset(self.grid.get_vertices(), &self.vao, timeout, bytes_vertices_written);
-        self.tex_coords.write_data_offset( self.grid.get_tex_coords(), &self.vao, timeout, bytes_tex_coords_written);
-        self.normals.write_data_offset( self.grid.get_normals(), &self.vao, timeout, bytes_normals_written);
-        self.indices.write_data_offset( self.grid.get_indices(), &self.vao, timeout, bytes_indices_written);
+        self.vertices.write_data_offset(self.grid.get_vertices(), &self.vao, bytes_vertices_written)?;
+        self.tex_coords.write_data_offset( self.grid.get_tex_coords(), &self.vao, bytes_tex_coords_written)?;
+        self.normals.write_data_offset( self.grid.get_normals(), &self.vao, bytes_normals_written)?;
+        self.indices.write_data_offset( self.grid.get_indices(), &self.vao, bytes_indices_written)?;
         self.base_number_instances += num_vertices as u32;
         // By default no "effective" (0 values are considered to have no effect)
         // translations nor colours are given; any other values doesn't make sense
-        self.instanced_translations.write_data_offset(&vec![vec3(0.0, 0.0, 0.0); num_vertices], &self.vao, timeout, bytes_instanced_translations_written);
-        self.instanced_colours.write_data_offset(&vec![vec3(0.0, 0.0, 0.0); num_vertices], &self.vao, timeout, bytes_instanced_colours_written);
+        self.instanced_translations.write_data_offset(&vec![vec3(0.0, 0.0, 0.0); num_vertices], &self.vao, bytes_instanced_translations_written)?;
+        self.instanced_colours.write_data_offset(&vec![vec3(0.0, 0.0, 0.0); num_vertices], &self.vao, bytes_instanced_colours_written)?;
+        self.instanced_alphas.write_data_offset(&vec![1.0_f32; num_vertices], &self.vao, bytes_instanced_alphas_written)?;
+        self.instanced_shapes.write_data_offset(&vec![default_point_shape(); num_vertices], &self.vao, bytes_instanced_shapes_written)?;
+        self.instanced_scales.write_data_offset(&vec![1.0_f32; num_vertices], &self.vao, bytes_instanced_scales_written)?;
 
         bytes_vertices_written += self.grid.len_vertices_bytes();
         bytes_tex_coords_written += self.grid.len_tex_coords_bytes();
         bytes_normals_written += self.grid.len_normals_bytes();
         bytes_instanced_translations_written += (size_of::<TVec3<f32>>() * num_vertices) as isize;
         bytes_instanced_colours_written += (size_of::<TVec3<f32>>() * num_vertices) as isize;
+        bytes_instanced_alphas_written += (size_of::<f32>() * num_vertices) as isize;
+        bytes_instanced_shapes_written += (size_of::<TMat3<f32>>() * num_vertices) as isize;
+        bytes_instanced_scales_written += (size_of::<f32>() * num_vertices) as isize;
         bytes_indices_written += self.grid.len_indices_bytes();
 
         let mut model_render_info = Vec::new();
@@ -221,15 +524,21 @@ impl SceneRenderer
         {
             let num_vertices = render_info.geometry.get_vertices().len();
 
-            self.vertices.write_data_offset(render_info.geometry.get_vertices(), &self.vao, timeout, bytes_vertices_written);
-            self.tex_coords.write_data_offset( render_info.geometry.get_tex_coords(), &self.vao, timeout, bytes_tex_coords_written);
-            self.normals.write_data_offset( render_info.geometry.get_normals(), &self.vao, timeout, bytes_normals_written);
-            self.indices.write_data_offset( render_info.geometry.get_indices(), &self.vao, timeout, bytes_indices_written);
+            self.vertices.write_data_offset(render_info.geometry.get_vertices(), &self.vao, bytes_vertices_written)?;
+            self.tex_coords.write_data_offset( render_info.geometry.get_tex_coords(), &self.vao, bytes_tex_coords_written)?;
+            self.normals.write_data_offset( render_info.geometry.get_normals(), &self.vao, bytes_normals_written)?;
+            self.indices.write_data_offset( render_info.geometry.get_indices(), &self.vao, bytes_indices_written)?;
 
             self.instanced_translations.write_data_offset
-            (&vec![vec3(0.0, 0.0, 0.0); num_vertices], &self.vao, timeout, bytes_instanced_translations_written);
+            (&vec![vec3(0.0, 0.0, 0.0); num_vertices], &self.vao, bytes_instanced_translations_written)?;
             self.instanced_colours.write_data_offset
-            (&vec![vec3(0.0, 0.0, 0.0); num_vertices], &self.vao, timeout, bytes_instanced_colours_written);
+            (&vec![vec3(0.0, 0.0, 0.0); num_vertices], &self.vao, bytes_instanced_colours_written)?;
+            self.instanced_alphas.write_data_offset
+            (&vec![1.0_f32; num_vertices], &self.vao, bytes_instanced_alphas_written)?;
+            self.instanced_shapes.write_data_offset
+            (&vec![default_point_shape(); num_vertices], &self.vao, bytes_instanced_shapes_written)?;
+            self.instanced_scales.write_data_offset
+            (&vec![1.0_f32; num_vertices], &self.vao, bytes_instanced_scales_written)?;
 
             let draw_call_info = DrawCallInfo
             {
@@ -250,20 +559,34 @@ impl SceneRenderer
 
             bytes_instanced_translations_written += (size_of::<TVec3<f32>>() * num_vertices) as isize;
             bytes_instanced_colours_written += (size_of::<TVec3<f32>>() * num_vertices) as isize;
+            bytes_instanced_alphas_written += (size_of::<f32>() * num_vertices) as isize;
+            bytes_instanced_shapes_written += (size_of::<TMat3<f32>>() * num_vertices) as isize;
+            bytes_instanced_scales_written += (size_of::<f32>() * num_vertices) as isize;
 
             self.base_number_instances += num_vertices as u32;
         }
 
         self.model_render_info = model_render_info;
 
+        // The edges are placed after the indices of all of the models
+        if let Some(edges) = &self.edges
+        {
+            let edge_indices = edges.instance_indices.iter().flatten().copied().collect::<Vec<u32>>();
+            self.indices.write_data_offset(&edge_indices, &self.vao, edges.indices_offset_bytes)?;
+        }
+
         self.current_instance_upload_index = self.base_number_instances;
+
+        Ok(())
     }
 
     /// Uploads the instance model of the specified models into GPU memory. If the sum of all instances
-    /// exceeds the maximum specified in the scene renderer constructor, then excess instances will be discarded
-    pub fn upload_instance_information(&mut self, info: Vec<UploadInformation>)
+    /// exceeds the maximum specified in the scene renderer constructor, then excess instances will be discarded.
+    /// If the GPU buffers do not become free in time, an error is returned and the upload should be
+    /// attempted again later
+    pub fn upload_instance_information(&mut self, info: Vec<UploadInformation>) -> Result<(), String>
     {
-        let timeout = 5_000_000;
+        self.wait_for_instance_buffers()?;
         self.current_instance_upload_index = self.base_number_instances;
 
         let num_instances = self.grid.get_translations().len();
@@ -279,8 +602,11 @@ impl SceneRenderer
         };
 
         let bytes_offset = (self.current_instance_upload_index as usize * size_of::<TVec3<f32>>()) as isize;
-        self.instanced_colours.write_data_offset(self.grid.get_colours(), &self.vao, timeout, bytes_offset);
-        self.instanced_translations.write_data_offset(self.grid.get_translations(), &self.vao, timeout, bytes_offset);
+        self.instanced_colours.write_data_offset(self.grid.get_colours(), &self.vao, bytes_offset)?;
+        self.instanced_translations.write_data_offset(self.grid.get_translations(), &self.vao, bytes_offset)?;
+        self.instanced_alphas.write_data_offset(&vec![1.0_f32; num_instances], &self.vao, SceneRenderer::alpha_bytes_offset(self.current_instance_upload_index))?;
+        self.instanced_shapes.write_data_offset(&vec![default_point_shape(); num_instances], &self.vao, SceneRenderer::shape_bytes_offset(self.current_instance_upload_index))?;
+        self.instanced_scales.write_data_offset(&vec![1.0_f32; num_instances], &self.vao, SceneRenderer::alpha_bytes_offset(self.current_instance_upload_index))?;
         self.current_instance_upload_index += max_upload_amount;
 
         for x in info
@@ -306,22 +632,230 @@ impl SceneRenderer
                 num_instances as u32
             };
 
+            let bytes_offset = (self.current_instance_upload_index as usize * size_of::<TVec3<f32>>()) as isize;
+            if let Some(colours) = x.instance_colours
+            {
+                self.instanced_colours.write_data_offset(colours, &self.vao, bytes_offset)?;
+            }
+
+            if let Some(translations) = x.instance_translations
+            {
+                self.instanced_translations.write_data_offset(translations, &self.vao, bytes_offset)?;
+            }
+
+            // New points without alphas should not keep the alphas of the points previously in their place
+            let alpha_bytes_offset = SceneRenderer::alpha_bytes_offset(self.current_instance_upload_index);
+            match (x.instance_alphas, x.instance_translations)
+            {
+                (Some(alphas), _) => self.instanced_alphas.write_data_offset(alphas, &self.vao, alpha_bytes_offset)?,
+                (None, Some(_)) => self.instanced_alphas.write_data_offset(&vec![1.0_f32; num_instances], &self.vao, alpha_bytes_offset)?,
+                (None, None) => {}
+            }
+
+            let shape_bytes_offset = SceneRenderer::shape_bytes_offset(self.current_instance_upload_index);
+            match (x.instance_shapes, x.instance_translations)
+            {
+                (Some(shapes), _) => self.instanced_shapes.write_data_offset(shapes, &self.vao, shape_bytes_offset)?,
+                (None, Some(_)) => self.instanced_shapes.write_data_offset(&vec![default_point_shape(); num_instances], &self.vao, shape_bytes_offset)?,
+                (None, None) => {}
+            }
+
+            // Scales are a single float per instance, the same as alphas
+            match (x.instance_scales, x.instance_translations)
+            {
+                (Some(scales), _) => self.instanced_scales.write_data_offset(scales, &self.vao, alpha_bytes_offset)?,
+                (None, Some(_)) => self.instanced_scales.write_data_offset(&vec![1.0_f32; num_instances], &self.vao, alpha_bytes_offset)?,
+                (None, None) => {}
+            }
+
+            // Only done once the upload succeeded so that a failed upload keeps rendering the previous instances
             self.model_render_info[x.model_id.id].instance_count = max_upload_amount as i32;
             self.model_render_info[x.model_id.id].instance_offset = self.current_instance_upload_index;
 
-            let bytes_offset = (self.current_instance_upload_index as usize * size_of::<TVec3<f32>>()) as isize;
+            let instance_data = &mut self.instance_data[x.model_id.id];
             if let Some(colours) = x.instance_colours
             {
-                self.instanced_colours.write_data_offset(colours, &self.vao, timeout, bytes_offset);
+                instance_data.colours = colours.iter().take(max_upload_amount as usize).cloned().collect();
             }
 
             if let Some(translations) = x.instance_translations
             {
-                self.instanced_translations.write_data_offset(translations, &self.vao, timeout, bytes_offset);
+                instance_data.translations = translations.iter().take(max_upload_amount as usize).cloned().collect();
             }
 
             self.current_instance_upload_index += max_upload_amount;
         }
+
+        Ok(())
+    }
+
+    /// Uploads instances added to the end of those of the given model, leaving the instances already
+    /// uploaded untouched. Much less is written than when uploading every instance again, which suits
+    /// point clouds that only ever gain points, such as those accumulated from a sensor. Only the model
+    /// uploaded last can be appended to, as its instances are the ones at the end of the buffers. If the
+    /// new instances exceed the maximum specified in the scene renderer constructor, then excess instances
+    /// will be discarded. If the GPU buffers do not become free in time, an error is returned and nothing
+    /// is appended
+    ///
+    /// `model_id` - the model to add instances to
+    /// `new_points` - the translation of each new instance
+    /// `new_colours` - the colour of each new instance
+    pub fn append_instances(&mut self, model_id: ModelId, new_points: &[TVec3<f32>], new_colours: &[TVec3<f32>]) -> Result<(), String>
+    {
+        if new_points.len() != new_colours.len()
+        {
+            return Err(format!("Got {} colours for {} appended instances", new_colours.len(), new_points.len()));
+        }
+
+        let render_info = &self.model_render_info[model_id.id];
+
+        // A model without instances can start wherever the instances uploaded last end
+        let append_index = if render_info.instance_count == 0
+        {
+            self.current_instance_upload_index
+        }
+        else
+        {
+            render_info.instance_offset + render_info.instance_count as u32
+        };
+
+        if append_index != self.current_instance_upload_index
+        {
+            return Err("Instances can only be appended to the model uploaded last".to_string());
+        }
+
+        let num_instances = new_points.len();
+        let max_upload_amount = if append_index + num_instances as u32 > self.max_number_instances
+        {
+            let upload_amount = self.max_number_instances - append_index;
+            eprintln!("Not enough VRam reserved to append {} instances. Appending: {}", num_instances, upload_amount);
+            upload_amount
+        }
+        else
+        {
+            num_instances as u32
+        };
+
+        let new_points = &new_points[..max_upload_amount as usize];
+        let new_colours = &new_colours[..max_upload_amount as usize];
+
+        // New instances should not keep the alphas, shapes and scales of instances previously in their place
+        self.wait_for_instance_buffers()?;
+        let bytes_offset = (append_index as usize * size_of::<TVec3<f32>>()) as isize;
+        self.instanced_translations.write_data_offset(new_points, &self.vao, bytes_offset)?;
+        self.instanced_colours.write_data_offset(new_colours, &self.vao, bytes_offset)?;
+        self.instanced_alphas.write_data_offset(&vec![1.0_f32; new_points.len()], &self.vao, SceneRenderer::alpha_bytes_offset(append_index))?;
+        self.instanced_shapes.write_data_offset(&vec![default_point_shape(); new_points.len()], &self.vao, SceneRenderer::shape_bytes_offset(append_index))?;
+        self.instanced_scales.write_data_offset(&vec![1.0_f32; new_points.len()], &self.vao, SceneRenderer::alpha_bytes_offset(append_index))?;
+
+        let render_info = &mut self.model_render_info[model_id.id];
+        if render_info.instance_count == 0
+        {
+            render_info.instance_offset = append_index;
+        }
+        render_info.instance_count += max_upload_amount as i32;
+        self.current_instance_upload_index += max_upload_amount;
+
+        let instance_data = &mut self.instance_data[model_id.id];
+        instance_data.translations.extend_from_slice(new_points);
+        instance_data.colours.extend_from_slice(new_colours);
+
+        Ok(())
+    }
+
+    /// Get the instance translations and colours that were most recently uploaded for the given model
+    ///
+    /// `model_id` - the model to get the instance information of
+    pub fn get_instance_information(&self, model_id: ModelId) -> (&[TVec3<f32>], &[TVec3<f32>])
+    {
+        let instance_data = &self.instance_data[model_id.id];
+        (&instance_data.translations, &instance_data.colours)
+    }
+
+    /// Changes the colours of the world grid and uploads them so that the change is seen immediately.
+    /// If the upload fails, the new colours are still used the next time instance information is uploaded
+    ///
+    /// `grid_colours` - the new colours of the grid
+    pub fn set_grid_colours(&mut self, grid_colours: GridColours) -> Result<(), String>
+    {
+        self.grid.set_colours(grid_colours);
+
+        // The grid instances are always the first ones after the base instances
+        let bytes_offset = (self.base_number_instances as usize * size_of::<TVec3<f32>>()) as isize;
+        self.instanced_colours.write_data_offset(self.grid.get_colours(), &self.vao, bytes_offset)
+    }
+
+    /// Moves the world grid vertically and uploads it so that the change is seen immediately. If the
+    /// upload fails, the new height is still used the next time instance information is uploaded
+    ///
+    /// `height` - the height of the plane the grid lies in
+    pub fn set_grid_height(&mut self, height: f32) -> Result<(), String>
+    {
+        self.grid.set_height(height);
+
+        // The grid instances are always the first ones after the base instances
+        let bytes_offset = (self.base_number_instances as usize * size_of::<TVec3<f32>>()) as isize;
+        self.instanced_translations.write_data_offset(self.grid.get_translations(), &self.vao, bytes_offset)
+    }
+
+    /// Get the height of the plane the world grid lies in
+    pub fn get_grid_height(&self) -> f32
+    {
+        self.grid.get_height()
+    }
+
+    /// Shows the given model if it is hidden, or hides it if it is shown
+    ///
+    /// `model_id` - the model to show or hide
+    pub fn toggle_model_visibility(&mut self, model_id: ModelId)
+    {
+        self.models[model_id.id].visible = !self.models[model_id.id].visible;
+    }
+
+    /// Captures where the vertex shader places the centre of each instance of the given model in each
+    /// of the passes that draw the point cloud, such as to check that the point cloud translation and
+    /// reflection are applied the same way as expected. The positions are before the scene rotation
+    ///
+    /// `model_id` - the model whose instances to capture the positions of
+    /// `outside_param` - the variables holding the transformations of the point cloud
+    pub fn capture_point_positions(&mut self, model_id: ModelId, outside_param: &OutsideParam) -> Vec<(CapturePass, Vec<TVec3<f32>>)>
+    {
+        self.shader_program.use_program();
+        self.vao.bind_vao();
+
+        self.shader_program.write_int("reflectVertically", outside_param.reflect_vertical);
+        self.shader_program.write_vec3("cloudTranslation", &outside_param.cloud_translation);
+        // Every point is captured, including those hidden to keep the frame rate up or as part of the ground
+        self.shader_program.write_uint("pointStride", 1);
+        self.shader_program.write_uint("groundRemoval", 0);
+
+        let draw_call_info = &self.model_render_info[model_id.id];
+
+        // The capture buffer is sized when the renderer is created; every instance drawn has to fit in
+        // it, or the instances past its end would be left out of the capture
+        let num_instances = draw_call_info.instance_count.max(0) as usize;
+        if num_instances > self.position_capture.max_num_vertices()
+        {
+            self.position_capture = TransformFeedback::new(num_instances);
+        }
+
+        CapturePass::ALL.iter()
+            .map(|pass|
+                {
+                    self.shader_program.write_uint(pass.uniform_name(), 1);
+
+                    // A single vertex per instance is enough, as the captured position is the centre of the instance
+                    let positions = self.position_capture.capture_points(||
+                        unsafe
+                            {
+                                gl::DrawArraysInstancedBaseInstance(gl::POINTS, draw_call_info.vertex_offset, 1, draw_call_info.instance_count,
+                                                                    draw_call_info.instance_offset);
+                            });
+
+                    self.shader_program.write_uint(pass.uniform_name(), 0);
+                    (*pass, positions)
+                })
+            .collect()
     }
 
     /// Renders the required scene onto the currently active frame buffer
@@ -336,35 +870,251 @@ impl SceneRenderer
                   outside_param.view_fbos.get_sun_fbo().look_at_position()], 0
         );
 
+        match outside_param.anaglyph_eye_separation
+        {
+            Some(eye_separation) =>
+                {
+                    // Each eye only writes to its colour channels, which includes the clearing done by the
+                    // scene, so the second eye does not erase the first. The left eye is seen through red
+                    // and the right eye through cyan
+                    let eyes = [(-0.5, (gl::TRUE, gl::FALSE, gl::FALSE)), (0.5, (gl::FALSE, gl::TRUE, gl::TRUE))];
+                    self.shader_program.write_uint("anaglyph", 1);
+
+                    for (side, (red, green, blue)) in eyes.iter()
+                    {
+                        let camera = outside_param.camera.eye_camera(side * eye_separation);
+                        let second_camera = outside_param.second_camera.map(|x| x.eye_camera(side * eye_separation));
+
+                        unsafe{ gl::ColorMask(*red, *green, *blue, gl::TRUE) }
+                        self.render_models(OutsideParam{ camera: &camera, second_camera: second_camera.as_ref(), ..outside_param });
+                    }
+
+                    unsafe{ gl::ColorMask(gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE) }
+                    self.shader_program.write_uint("anaglyph", 0);
+                },
+            None => self.render_models(outside_param)
+        }
+
+        self.instanced_translations.update_fence();
+        self.instanced_colours.update_fence();
+        self.instanced_alphas.update_fence();
+        self.instanced_shapes.update_fence();
+        self.instanced_scales.update_fence();
+    }
+
+    /// Renders every visible model, followed by the lines of the scene, as seen by the cameras given
+    ///
+    /// `outside_param` - the variables holding the cameras and the transformations of the point cloud
+    fn render_models(&mut self, outside_param: OutsideParam)
+    {
         // Models are rendered in the same order as specified in the constructor
-        for (index, x) in self.models.iter().enumerate()
+        for (index, x) in self.models.iter().enumerate().filter(|(_, x)| x.visible)
         {
             (x.command)(&self.shader_program, &self.model_render_info[index], outside_param)
         }
 
+        profile_section(outside_param.gpu_profiler, ProfileSection::Lines, || self.render_lines(&outside_param));
+    }
+
+    /// Draws the grid, the edges between points and the volume the sun casts shadows in
+    ///
+    /// `outside_param` - the variables holding the cameras and the transformations of the point cloud
+    fn render_lines(&mut self, outside_param: &OutsideParam)
+    {
         self.shader_program.write_uint("drawingGrid", 1);
-        self.shader_program.write_mat4("projViewMatrix", &outside_param.camera.get_projection_view_matrix());
-        let reset_viewport_x = ((outside_param.window_resolution.0 as f32) * SCENE_VIEWPORT_WIDTH) as i32;
-        let reset_viewport_y = outside_param.window_resolution.1 as i32;
+        self.shader_program.write_float("gridDepthOffset", outside_param.grid_depth_offset);
+
+        for (camera, (x, y, width, height)) in draw_functions::scene_viewports(outside_param)
+        {
+            self.shader_program.write_mat4("projViewMatrix", &camera.get_projection_view_matrix());
+
+            unsafe
+                {
+                    gl::Viewport(x, y, width, height);
+
+                    let mut instance_offset: u32 = self.base_number_instances;
+                    gl::DrawArraysInstancedBaseInstance(gl::LINES, 2, 2, self.grid.get_num_instances(), instance_offset);
+                    instance_offset += self.grid.get_num_instances() as u32;
+                    gl::DrawArraysInstancedBaseInstance(gl::LINES, 4, 2, self.grid.get_num_instances(), instance_offset);
+                    instance_offset += self.grid.get_num_instances() as u32;
+                    gl::DrawArraysInstancedBaseInstance(gl::LINES, 6, 2, self.grid.get_num_instances(), instance_offset);
+                    instance_offset += self.grid.get_num_instances() as u32;
+                    gl::DrawArraysInstancedBaseInstance(gl::LINES, 8, 2, self.grid.get_num_instances(), instance_offset);
+                }
+        }
+
+        self.shader_program.write_uint("drawingGrid", 0);
+
+        self.render_edges(outside_param);
+
+        self.render_sun_frustum(outside_param);
+
+        self.render_selection_rect(outside_param);
+    }
+
+    /// Draws lines between the instances given as edges. The instance translations are read as if
+    /// they were the vertices of the lines, with the edges as the indices of those vertices
+    ///
+    /// `outside_param` - the variables holding the cameras and the transformations of the point cloud
+    fn render_edges(&self, outside_param: &OutsideParam)
+    {
+        let edges = match &self.edges
+        {
+            Some(i) => i,
+            None => return
+        };
+
+        let draw_call_info = &self.model_render_info[edges.model_id.id];
+        let num_edges = edges.instance_indices.partition_point(|x| x[0].max(x[1]) < draw_call_info.instance_count as u32);
+        if num_edges == 0
+        {
+            return;
+        }
+
+        self.shader_program.write_uint("drawingEdges", 1);
+        self.shader_program.write_vec3("edgeColour", &edge_colour());
+        self.shader_program.write_vec3("cloudTranslation", &outside_param.cloud_translation);
+        self.shader_program.write_int("reflectVertically", outside_param.reflect_vertical);
+
+        // The per-vertex buffers are far smaller than the number of instances, so they are not read
+        for index in 0..3
+        {
+            self.vao.set_index_enabled(index, false);
+        }
+        self.vao.specify_divisor(4, 0);
+
+        for (camera, (x, y, width, height)) in draw_functions::scene_viewports(outside_param)
+        {
+            self.shader_program.write_mat4("projViewMatrix", &camera.get_projection_view_matrix());
+
+            unsafe
+                {
+                    gl::Viewport(x, y, width, height);
+                    gl::DrawElementsBaseVertex(gl::LINES, (num_edges * 2) as i32, gl::UNSIGNED_INT, edges.indices_offset_bytes as *const c_void,
+                                               draw_call_info.instance_offset as i32);
+                }
+        }
+
+        self.vao.specify_divisor(4, 1);
+        for index in 0..3
+        {
+            self.vao.set_index_enabled(index, true);
+        }
+
+        self.shader_program.write_uint("drawingEdges", 0);
+    }
+
+    /// Draws the edges of the view frustum of the sun, which is the volume that shadows are found in.
+    /// The lines are drawn the same way as the line to the point the sun looks at
+    ///
+    /// `outside_param` - the variables holding the cameras and the sun
+    fn render_sun_frustum(&mut self, outside_param: &OutsideParam)
+    {
+        if !outside_param.show_sun_frustum
+        {
+            return;
+        }
+
+        let corners = outside_param.view_fbos.get_sun_fbo().get_fbo().get_camera().get_frustum_corners();
+        let mut lines = Vec::with_capacity(SUN_FRUSTUM_NUM_VERTICES);
+
+        // Around the near plane, around the far plane, and from the near plane to the far plane
+        for index in 0..4
+        {
+            lines.extend_from_slice(&[corners[index], corners[(index + 1) % 4]]);
+            lines.extend_from_slice(&[corners[index + 4], corners[(index + 1) % 4 + 4]]);
+            lines.extend_from_slice(&[corners[index], corners[index + 4]]);
+        }
+
+        self.vertices.write_data_no_wait_no_binding(&lines, self.sun_frustum_bytes_offset);
+
+        self.shader_program.write_uint("drawingSunArrow", 1);
+        self.shader_program.write_vec3("sunArrowPosition", &vec3(0.0, 0.0, 0.0));
+        self.shader_program.write_float("sunArrowScale", 1.0);
+
+        let first_vertex = (self.sun_frustum_bytes_offset as usize / size_of::<TVec3<f32>>()) as i32;
+
+        for (camera, (x, y, width, height)) in draw_functions::scene_viewports(outside_param)
+        {
+            self.shader_program.write_mat4("projViewMatrix", &camera.get_projection_view_matrix());
+
+            unsafe
+                {
+                    gl::Viewport(x, y, width, height);
+                    gl::DrawArrays(gl::LINES, first_vertex, SUN_FRUSTUM_NUM_VERTICES as i32);
+                }
+        }
+
+        self.shader_program.write_uint("drawingSunArrow", 0);
+    }
+
+    /// Draws the outline of the rectangle being dragged out to select points, on top of the scene
+    ///
+    /// `outside_param` - the variables holding the selection rectangle and the window resolution
+    fn render_selection_rect(&mut self, outside_param: &OutsideParam)
+    {
+        let (corner, opposite_corner) = match outside_param.selection_rect
+        {
+            Some(i) => i,
+            None => return
+        };
+
+        let corners = [vec3(corner.x, corner.y, 0.0), vec3(opposite_corner.x, corner.y, 0.0),
+                       vec3(opposite_corner.x, opposite_corner.y, 0.0), vec3(corner.x, opposite_corner.y, 0.0)];
+        let mut lines = Vec::with_capacity(SELECTION_RECT_NUM_VERTICES);
+
+        for index in 0..4
+        {
+            lines.extend_from_slice(&[corners[index], corners[(index + 1) % 4]]);
+        }
+
+        self.vertices.write_data_no_wait_no_binding(&lines, self.selection_rect_bytes_offset);
 
+        self.shader_program.write_uint("drawingSelectionRect", 1);
+        self.shader_program.write_vec3("selectionColour", &selection_colour());
+
+        let first_vertex = (self.selection_rect_bytes_offset as usize / size_of::<TVec3<f32>>()) as i32;
+        let viewport_x = ((outside_param.window_resolution.0 as f32) * ViewLayout::new(outside_param.window_resolution).scene_width) as i32;
+        let viewport_offset_y = (outside_param.window_resolution.1 as f32 * SCENE_VIEWPORT_HEIGHT_OFFSET) as i32;
+
+        // The corners are relative to the whole scene, even if it is split between two cameras
         unsafe
             {
-                gl::Viewport(0, (outside_param.window_resolution.1 as f32 * SCENE_VIEWPORT_HEIGHT_OFFSET) as i32, reset_viewport_x, reset_viewport_y);
-
-                let mut instance_offset: u32 = self.base_number_instances;
-                gl::DrawArraysInstancedBaseInstance(gl::LINES, 2, 2, self.grid.get_num_instances(), instance_offset);
-                instance_offset += self.grid.get_num_instances() as u32;
-                gl::DrawArraysInstancedBaseInstance(gl::LINES, 4, 2, self.grid.get_num_instances(), instance_offset);
-                instance_offset += self.grid.get_num_instances() as u32;
-                gl::DrawArraysInstancedBaseInstance(gl::LINES, 6, 2, self.grid.get_num_instances(), instance_offset);
-                instance_offset += self.grid.get_num_instances() as u32;
-                gl::DrawArraysInstancedBaseInstance(gl::LINES, 8, 2, self.grid.get_num_instances(), instance_offset);
+                gl::Disable(gl::DEPTH_TEST);
+                gl::Viewport(0, viewport_offset_y, viewport_x, outside_param.window_resolution.1);
+                gl::DrawArrays(gl::LINES, first_vertex, SELECTION_RECT_NUM_VERTICES as i32);
+                gl::Enable(gl::DEPTH_TEST);
             }
 
-        self.shader_program.write_uint("drawingGrid", 0);
+        self.shader_program.write_uint("drawingSelectionRect", 0);
+    }
 
-        self.instanced_translations.update_fence();
-        self.instanced_colours.update_fence();
+    /// Number of bytes before the alpha of the instance with the given index
+    ///
+    /// `instance_index` - the index of the instance
+    fn alpha_bytes_offset(instance_index: u32) -> isize
+    {
+        (instance_index as usize * size_of::<f32>()) as isize
+    }
+
+    /// Number of bytes before the shape of the instance with the given index
+    ///
+    /// `instance_index` - the index of the instance
+    fn shape_bytes_offset(instance_index: u32) -> isize
+    {
+        (instance_index as usize * size_of::<TMat3<f32>>()) as isize
+    }
+
+    /// Waits for every instance buffer to become free, so that an upload either writes to all of them
+    /// or to none of them, instead of leaving them holding the instances of different uploads
+    fn wait_for_instance_buffers(&self) -> Result<(), String>
+    {
+        self.instanced_translations.wait_for_next_buffer()?;
+        self.instanced_colours.wait_for_next_buffer()?;
+        self.instanced_alphas.wait_for_next_buffer()?;
+        self.instanced_shapes.wait_for_next_buffer()?;
+        self.instanced_scales.wait_for_next_buffer()?;
+        Ok(())
     }
 
     /// Number of bytes required to store the sun arrow
@@ -373,6 +1123,30 @@ impl SceneRenderer
         (size_of::<TVec3<f32>>() * 2) as isize
     }
 
+    /// Number of bytes required to store the lines of the sun frustum
+    fn size_sun_frustum_bytes() -> isize
+    {
+        (size_of::<TVec3<f32>>() * SUN_FRUSTUM_NUM_VERTICES) as isize
+    }
+
+    /// Number of bytes required to store the sun frustum texture coordinates; see size_sun_arrow_tex_bytes
+    fn size_sun_frustum_tex_bytes() -> isize
+    {
+        (size_of::<TVec2<f32>>() * SUN_FRUSTUM_NUM_VERTICES) as isize
+    }
+
+    /// Number of bytes required to store the lines of the selection rectangle
+    fn size_selection_rect_bytes() -> isize
+    {
+        (size_of::<TVec3<f32>>() * SELECTION_RECT_NUM_VERTICES) as isize
+    }
+
+    /// Number of bytes required to store the selection rectangle texture coordinates; see size_sun_arrow_tex_bytes
+    fn size_selection_rect_tex_bytes() -> isize
+    {
+        (size_of::<TVec2<f32>>() * SELECTION_RECT_NUM_VERTICES) as isize
+    }
+
     /// Number of bytes required to store the sun arrow texture coordinates
     fn size_sun_arrow_tex_bytes() -> isize
     {
@@ -388,7 +1162,8 @@ impl SceneRenderer
 /// Helps with the constructor of the scene renderer
 struct SceneRendererBuilder
 {
-    models: Vec<RenderInformation>
+    models: Vec<RenderInformation>,
+    edges: Option<(ModelId, Vec<[u32; 2]>)>
 }
 
 impl SceneRendererBuilder
@@ -396,7 +1171,7 @@ impl SceneRendererBuilder
     /// Constructs a new empty scene renderer builder
     pub fn new() -> SceneRendererBuilder
     {
-        SceneRendererBuilder { models: Vec::new() }
+        SceneRendererBuilder { models: Vec::new(), edges: None }
     }
 
     /// Adds a model to add to the scene renderer and gives a back a unique model id
@@ -408,23 +1183,37 @@ impl SceneRendererBuilder
         ModelId{ id: self.models.len() - 1 }
     }
 
+    /// Specifies lines to draw between instances of a model
+    ///
+    /// `model_id` - the model whose instances the lines are drawn between
+    /// `edges` - the pairs of instances to draw lines between
+    pub fn set_edges(&mut self, model_id: ModelId, edges: &[[u32; 2]])
+    {
+        self.edges = Some((model_id, edges.to_vec()));
+    }
+
     /// Creates a new scene renderer with the provided models
-    pub fn build(self, max_number_instances: u32) -> SceneRenderer
+    ///
+    /// `max_number_instances` - maximum number of instances of all models combined in the scene
+    /// `upload_mode` - how data is uploaded to the buffers of the scene
+    /// `grid_colours` - the colours of the world grid
+    pub fn build(self, max_number_instances: u32, upload_mode: UploadMode, grid_colours: GridColours) -> SceneRenderer
     {
-        SceneRenderer::new(self.models, max_number_instances)
+        SceneRenderer::new(self.models, self.edges, max_number_instances, upload_mode, grid_colours)
     }
 }
 
 /// Creates a shader program that renderers the scene
 fn create_shader_program() -> ShaderProgram
 {
-    let shader_program = ShaderProgram::new
+    let shader_program = ShaderProgram::with_feedback_varyings
         (
             vec!
             [
                 ShaderInitInfo{ shader_type: ShaderType::Vertex, shader_location: get_shaders_folder().join("sceneVertexShader.glsl") },
                 ShaderInitInfo{ shader_type: ShaderType::Fragment, shader_location: get_shaders_folder().join("sceneFragmentShader.glsl") },
-            ]
+            ],
+            &["capturedPosition"]
         );
     shader_program.use_program();
     shader_program
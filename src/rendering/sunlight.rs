@@ -4,6 +4,7 @@ use nalgebra_glm::{TMat4, TVec3, vec3};
 use crate::rendering::camera::{Camera, CameraType, OrthographicParam};
 use crate::gl_wrappers::fbo::{FBO, TextureType};
 use crate::gl_wrappers::shader_program_creation::ShaderProgram;
+use crate::view_logic::sun_arc::SunArc;
 use crate::window::RenderWindow;
 
 /// Represents a "logical" (as in the model is separate from this class) sun shining light onto the scene
@@ -11,7 +12,8 @@ pub struct SunLight
 {
     fbo: FBO,
     look_at_position: TVec3<f32>,
-    current_scroll_direction: ScrollDirection
+    current_scroll_direction: ScrollDirection,
+    arc: SunArc
 }
 
 enum ScrollDirection
@@ -27,7 +29,8 @@ impl SunLight
     ///
     /// `window_dimensions` - the dimensions of the window being rendered to
     /// `binding_point` - texture unit to bind the sun's rendered perspective to
-    pub fn new(window_dimensions: (i32, i32), binding_point: u32) -> SunLight
+    /// `arc` - the arc the sun can be moved along around the point cloud
+    pub fn new(window_dimensions: (i32, i32), binding_point: u32, arc: SunArc) -> SunLight
     {
         let camera_type = CameraType::Orthographic(OrthographicParam
         {
@@ -44,7 +47,7 @@ impl SunLight
 
         let fbo = FBO::new(window_dimensions, binding_point, camera_type, TextureType::DepthComponent).unwrap();
 
-        SunLight{ fbo, look_at_position: vec3(0.0, 0.0, 0.0), current_scroll_direction: ScrollDirection::X }
+        SunLight{ fbo, look_at_position: vec3(0.0, 0.0, 0.0), current_scroll_direction: ScrollDirection::X, arc }
     }
 
     /// Sets the appropriate uniforms so that the sun's perspective can be rendered
@@ -81,6 +84,26 @@ impl SunLight
         self.fbo.bind_draw_result();
     }
 
+    /// Get the FBO holding the rendered sun's perspective of the scene
+    pub fn get_fbo(&self) -> &FBO
+    {
+        &self.fbo
+    }
+
+    /// Get the mutable camera the sun's perspective of the scene is rendered with
+    pub fn get_mut_camera(&mut self) -> &mut Camera
+    {
+        self.fbo.get_mut_camera()
+    }
+
+    /// Makes moving the cursor up pitch the sun camera down, and the other way around
+    ///
+    /// `invert_look_y` - true if the vertical rotation should be inverted
+    pub fn set_invert_look_y(&mut self, invert_look_y: bool)
+    {
+        self.fbo.get_mut_camera().set_invert_look_y(invert_look_y);
+    }
+
     /// Get the position of the sun
     pub fn get_sun_position(&self) -> TVec3<f32>
     {
@@ -104,6 +127,27 @@ impl SunLight
         self.fbo.get_mut_camera().point_camera_in_direction(normalize(&(self.look_at_position - pos)), false);
     }
 
+    /// Get the arc the sun can be moved along
+    pub fn get_arc(&self) -> &SunArc
+    {
+        &self.arc
+    }
+
+    /// Get the mutable arc the sun can be moved along
+    pub fn get_mut_arc(&mut self) -> &mut SunArc
+    {
+        &mut self.arc
+    }
+
+    /// Places the sun where it is along its arc, looking at the centre of the arc
+    ///
+    /// `centre_scene` - the centre of the scene that the sun orbits around
+    /// `distance` - how far the sun is from the centre of the scene
+    pub fn place_on_arc(&mut self, centre_scene: TVec3<f32>, distance: f32)
+    {
+        self.hard_set_sun_pos(self.arc.position(centre_scene, distance), centre_scene);
+    }
+
     /// Get the string representation of the sun's position
     pub fn to_string_sun_position(&self, lidar_pos: TVec3<f32>) -> String
     {
@@ -119,12 +163,26 @@ impl SunLight
                 self.look_at_position.z + lidar_pos.z)
     }
 
+    /// Get the text describing what scrolling does to the position the sun is looking at
+    pub fn to_string_scroll_axis(&self) -> String
+    {
+        let axis = match self.current_scroll_direction
+        {
+            ScrollDirection::X => "X",
+            ScrollDirection::Y => "Y",
+            ScrollDirection::Z => "Z",
+        };
+
+        format!("Scroll: {} axis (Shift: sun direction)", axis)
+    }
+
     /// Move the sun according to key input
     ///
     /// `render_window` - the structure representing the window being rendered to
-    pub fn move_sun(&mut self, render_window: &RenderWindow)
+    /// `delta_time` - the seconds since the previous frame
+    pub fn move_sun(&mut self, render_window: &RenderWindow, delta_time: f32)
     {
-        Camera::update_camera_movement(&render_window,self.fbo.get_mut_camera());
+        Camera::update_camera_movement(render_window,self.fbo.get_mut_camera(), delta_time);
         let sun_pos = self.get_sun_position();
         self.fbo.get_mut_camera().point_camera_in_direction(normalize(&(self.look_at_position - sun_pos)), false);
     }
@@ -140,8 +198,18 @@ impl SunLight
     /// `render_window` - the structure representing the window being rendered to
     pub fn move_look_at_position(&mut self, render_window: &RenderWindow)
     {
+        // Holding shift moves towards or away from the sun, regardless of the selected axis
+        let scroll_along_sun = render_window.is_shift_down();
+        let sun_direction = self.get_sun_direction();
+
         for x in render_window.get_scroll_history()
         {
+            if scroll_along_sun
+            {
+                self.look_at_position += sun_direction * (*x / 10.0);
+                continue;
+            }
+
             match self.current_scroll_direction
             {
                 ScrollDirection::X => self.look_at_position.x += *x / 10.0,
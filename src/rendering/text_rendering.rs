@@ -1,16 +1,19 @@
-use std::ffi::c_void;
+use std::ffi::{c_void, CStr};
 use std::mem::size_of;
 use std::ptr::null;
 use angel_font_file_parser::{AtlasDimensions, CharacterInfo};
 use angel_font_file_parser::extract_characters;
-use nalgebra_glm::{TMat4, TVec2, vec2};
+use nalgebra_glm::{TMat4, TVec2, TVec3, vec2, vec3};
 use stb_image::stb_image::bindgen::stbi_set_flip_vertically_on_load;
 use stb_image::image::LoadResult;
-use crate::gl_wrappers::buffer::{Buffer, BufferType};
+use crate::gl_wrappers::buffer::{Buffer, BufferType, UploadMode};
 use crate::helper_logic::folder_location_functions::{get_shaders_folder, get_text_folder};
 use crate::gl_wrappers::shader_program_creation::{ShaderInitInfo, ShaderProgram, ShaderType};
 use crate::gl_wrappers::vao::VAO;
-use crate::view_port_constants::TEXT_AREA_VIEWPORT_WIDTH;
+use crate::view_port_constants::ViewLayout;
+
+// Enough for the help overlay, which writes the most text at once
+const MAX_NUMBER_CHARACTERS: i32 = 4000;
 
 /// Logic and components required to render text
 pub struct TextRendering
@@ -20,6 +23,7 @@ pub struct TextRendering
     vao: VAO,
     plane_buffer: Buffer,
     tex_coords_buffer: Buffer,
+    placement_buffer: Buffer,
     // This variable is kept to logically show that the VBO it is representing is kept alive for the
     // duration of the program. However, it is never modified after the the TextRendering constructor
     // has run. To silence a compiler warning, the underscore is used
@@ -30,18 +34,20 @@ pub struct TextRendering
 
     character_vertices: Vec<TVec2<f32>>,
     character_tex_coords: Vec<[(f32, f32); 4]>,
+    character_placements: Vec<CharacterPlacement>,
     num_characters: i32,
-    sentence_positions: Vec<SentenceIndex>,
 
-    default_window_width: f32,
-    default_window_height: f32,
+    dim_background: bool,
 }
 
-/// Represents a single line of text to buffer
-struct SentenceIndex
+/// Where a buffered character is placed and its colour, taken from the line of text it is part of.
+/// Every character carries these so that all of the buffered text is rendered in a single draw call
+#[derive(Debug)]
+#[repr(C)]
+struct CharacterPlacement
 {
-    starting_index: i32, // Out of all the characters buffered
-    starting_position: TVec2<f32>, // In pixels
+    starting_position: TVec2<f32>, // Of the line of text, in pixels
+    colour: TVec3<f32>,
 }
 
 /// Reduce the boilerplate to check if all information required to render a character is available
@@ -66,13 +72,24 @@ impl TextRendering
     /// Creates a new TextRendering structure capable of rendering text to a window of the given size
     ///
     /// `window_dimensions` - the dimensions of the window being rendered to
-    pub fn new(window_dimensions: (i32, i32)) -> TextRendering
+    /// `upload_mode` - how data is uploaded to the buffers holding the text
+    pub fn new(window_dimensions: (i32, i32), upload_mode: UploadMode) -> TextRendering
     {
         unsafe{ stbi_set_flip_vertically_on_load(1); }
-        let texture_load = match stb_image::image::load(get_text_folder().join("robotoFont.png"))
+        let atlas_location = get_text_folder().join("robotoFont.png");
+        let texture_load = match stb_image::image::load(&atlas_location)
+        {
+            LoadResult::Error(err) => panic!("Could not load file: {:?}. Error: {}", atlas_location, err),
+            LoadResult::ImageU8(i) => i,
+            // High dynamic range atlases are reduced to the same range as other atlases
+            LoadResult::ImageF32(i) => stb_image::image::Image::new(i.width, i.height, i.depth,
+                                                                   i.data.iter().map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8).collect())
+        };
+
+        let atlas_pixels = match TextRendering::atlas_to_rgba(texture_load.depth, &texture_load.data)
         {
-            LoadResult::Error(_) | LoadResult::ImageF32(_) => panic!("Could not file: {:?}", get_text_folder().join("robotoFont.png")),
-            LoadResult::ImageU8(i) => i
+            Ok(i) => i,
+            Err(err) => panic!("Could not use file: {:?}. Error: {}", atlas_location, err)
         };
 
         let mut texture: u32 = 0;
@@ -84,33 +101,69 @@ impl TextRendering
                 gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
                 gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
 
+                // Enough levels to reduce the atlas down to a single pixel
+                let num_mipmap_levels = (texture_load.width.max(texture_load.height) as f32).log2().floor() as i32 + 1;
+
                 gl::CreateTextures(gl::TEXTURE_2D, 1, &mut texture);
-                gl::TextureStorage2D(texture, 1, gl::RGBA8, texture_load.width as i32, texture_load.height as i32);
+                gl::TextureStorage2D(texture, num_mipmap_levels, gl::RGBA8, texture_load.width as i32, texture_load.height as i32);
 
-                gl::TextureParameteri(texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+                // Mipmaps keep the text sharp when it is rendered smaller than the atlas, such as at small window sizes
+                gl::TextureParameteri(texture, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32);
                 gl::TextureParameteri(texture, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
                 gl::TextureParameteri(texture, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_BORDER as i32);
                 gl::TextureParameteri(texture, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_BORDER as i32);
 
+                if TextRendering::anisotropic_filtering_supported()
+                {
+                    // Values of GL_EXT_texture_filter_anisotropic, which is not part of the core OpenGL bindings
+                    const TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FE;
+                    const MAX_TEXTURE_MAX_ANISOTROPY_EXT: u32 = 0x84FF;
+
+                    let mut max_anisotropy = 1.0_f32;
+                    gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut max_anisotropy);
+                    gl::TextureParameterf(texture, TEXTURE_MAX_ANISOTROPY_EXT, max_anisotropy);
+                }
+
                 gl::TextureSubImage2D(texture, 0, 0, 0, texture_load.width as i32, texture_load.height as i32,
-                                        gl::RGBA, gl::UNSIGNED_BYTE, texture_load.data.as_ptr() as *const c_void);
+                                        gl::RGBA, gl::UNSIGNED_BYTE, atlas_pixels.as_ptr() as *const c_void);
+                gl::GenerateTextureMipmap(texture);
             }
 
         let vao = VAO::new();
         vao.bind_vao();
-        // These correspond to "textVertexShader.glsl" in the shaders folder
-        vao.specify_index_layout(0, 2, gl::FLOAT, false, 0);
-        vao.specify_index_layout(1, 2, gl::FLOAT, false, 0);
+        // These correspond to "textVertexShader.glsl" in the shaders folder. Each character is an
+        // instance, with the four corners of its plane and texture coordinates interleaved per instance
+        for corner in 0..4
+        {
+            vao.specify_index_layout(corner, 2, gl::FLOAT, false, corner * size_of::<TVec2<f32>>() as u32);
+            vao.specify_index_binding(corner, 0);
+            vao.specify_index_layout(4 + corner, 2, gl::FLOAT, false, corner * size_of::<TVec2<f32>>() as u32);
+            vao.specify_index_binding(4 + corner, 1);
+        }
+
+        vao.specify_index_layout(8, 2, gl::FLOAT, false, 0);
+        vao.specify_index_binding(8, 2);
+        vao.specify_index_layout(9, 3, gl::FLOAT, false, size_of::<TVec2<f32>>() as u32);
+        vao.specify_index_binding(9, 2);
 
-        // More than enough as of time of writing
-        let max_number_characters = 1000;
+        vao.specify_divisor(0, 1);
+        vao.specify_divisor(1, 1);
+        vao.specify_divisor(2, 1);
 
-        let plane_buffer = Buffer::new(&vao, max_number_characters * (size_of::<TVec2<f32>>() * 4) as isize, 3, BufferType::Array(0, 8));
-        let tex_coords_buffer = Buffer::new(&vao, max_number_characters * (size_of::<TVec2<f32>>() * 4) as isize, 3, BufferType::Array(1, 8));
-        let mut indice_buffer = Buffer::new(&vao, (size_of::<u32>() * 6) as isize, 1, BufferType::Indice);
+        let max_number_characters = MAX_NUMBER_CHARACTERS as isize;
+        let corners_stride = (size_of::<TVec2<f32>>() * 4) as i32;
+        let placement_stride = size_of::<CharacterPlacement>() as i32;
+
+        let plane_buffer = Buffer::new(&vao, max_number_characters * corners_stride as isize, 3, BufferType::Array(0, corners_stride), upload_mode);
+        let tex_coords_buffer = Buffer::new(&vao, max_number_characters * corners_stride as isize, 3, BufferType::Array(1, corners_stride), upload_mode);
+        let placement_buffer = Buffer::new(&vao, max_number_characters * placement_stride as isize, 3, BufferType::Array(2, placement_stride), upload_mode);
+        let mut indice_buffer = Buffer::new(&vao, (size_of::<u32>() * 6) as isize, 1, BufferType::Indice, upload_mode);
 
         // Indices to render a rectangle. Vertices to render a character rectangle are done later
-        indice_buffer.write_data(&vec![0_u32, 1, 2, 2, 0, 3], &vao, 5_000_000);
+        if let Err(err) = indice_buffer.write_data(&[0_u32, 1, 2, 2, 0, 3], &vao)
+        {
+            panic!("Failed to upload text rendering indices: {}", err);
+        }
 
         let shader_program = ShaderProgram::new
             (
@@ -134,16 +187,16 @@ impl TextRendering
             vao,
             plane_buffer,
             tex_coords_buffer,
+            placement_buffer,
             char_info,
             window_dimensions,
             // The location of the characters are specified in pixels due to this
             camera_matrix: nalgebra_glm::ortho(0.0, window_dimensions.0 as f32, 0.0, window_dimensions.1 as f32, 0.0, 1.0),
             character_vertices: vec![],
             character_tex_coords: vec![],
+            character_placements: vec![],
             num_characters: 0,
-            sentence_positions: vec![],
-            default_window_width: 1280.0,
-            default_window_height: 720.0
+            dim_background: false
         }
     }
 
@@ -162,12 +215,24 @@ impl TextRendering
     /// `starting_position` - the position to start rendering the text, specified as Normalized Device Coordinates
     ///                       with the viewport of the most recent window dimensions given to the text renderer
     /// `max_num_char` - maximum number of char of the provided text to render. Any excess characters are not rendered
-    pub fn buffer_text_for_rendering<A: AsRef<str>>(&mut self, text: A, mut starting_position: TVec2<f32>, max_num_char: usize)
+    pub fn buffer_text_for_rendering<A: AsRef<str>>(&mut self, text: A, starting_position: TVec2<f32>, max_num_char: usize)
+    {
+        self.buffer_coloured_text_for_rendering(text, starting_position, max_num_char, vec3(1.0, 1.0, 1.0));
+    }
+
+    /// Prepares the required rendering information to render the given text in the given colour,
+    /// rather than in white
+    ///
+    /// `text` - the text to render
+    /// `starting_position` - the position to start rendering the text, specified as Normalized Device Coordinates
+    ///                       with the viewport of the most recent window dimensions given to the text renderer
+    /// `max_num_char` - maximum number of char of the provided text to render. Any excess characters are not rendered
+    /// `colour` - the colour of the text, with each component between 0 and 1
+    pub fn buffer_coloured_text_for_rendering<A: AsRef<str>>(&mut self, text: A, mut starting_position: TVec2<f32>, max_num_char: usize, colour: TVec3<f32>)
     {
         // Convert the starting position from NDC to pixels
         starting_position.x *= self.window_dimensions.0 as f32;
         starting_position.y *= self.window_dimensions.1 as f32;
-        self.sentence_positions.push(SentenceIndex{starting_index: self.num_characters, starting_position});
 
         // This is relative to the starting point
         let mut total_offset_x = 0.0_f32;
@@ -215,10 +280,11 @@ impl TextRendering
                 self.character_vertices.push(vec2(local_offset_x + char_width, local_offset_y));
 
                 self.character_tex_coords.push(char_info.texture_coordinates);
+                self.character_placements.push(CharacterPlacement{ starting_position, colour });
 
                 self.num_characters += 1;
-                // Only enough space reserved to render 1000 characters
-                if self.num_characters >= 1000
+                // Only enough space reserved to render a limited number of characters
+                if self.num_characters >= MAX_NUMBER_CHARACTERS
                 {
                     break;
                 }
@@ -229,51 +295,120 @@ impl TextRendering
         }
     }
 
+    /// Darkens everything behind the text the next time the buffered text is rendered, so that the
+    /// text stays readable regardless of what was rendered before it
+    pub fn dim_background(&mut self)
+    {
+        self.dim_background = true;
+    }
+
     /// Render the buffered text
     pub fn render_buffered_text(&mut self)
     {
-        // This is required to handle the last buffer character due to the loop logic below. No
-        // character will be rendered
-        self.buffer_text_for_rendering("", vec2(0.0, 0.0), 0);
+        let layout = ViewLayout::new(self.window_dimensions);
 
         unsafe
             {
                 gl::Disable(gl::DEPTH_TEST);
-                gl::Viewport(0, 0, (self.window_dimensions.0 as f32 * TEXT_AREA_VIEWPORT_WIDTH) as i32, self.window_dimensions.1);
+                gl::Viewport(0, 0, (self.window_dimensions.0 as f32 * layout.scene_width) as i32, self.window_dimensions.1);
                 gl::BindTextureUnit(0, self.texture);
             }
 
-        self.plane_buffer.write_data(&self.character_vertices, &self.vao, 5_000_000);
-        self.tex_coords_buffer.write_data(&self.character_tex_coords, &self.vao, 5_000_000);
+        // If the text cannot be uploaded in time, the text is skipped for this frame rather than waiting further
+        let upload_result = self.plane_buffer.write_data(&self.character_vertices, &self.vao)
+            .and_then(|_| self.tex_coords_buffer.write_data(&self.character_tex_coords, &self.vao))
+            .and_then(|_| self.placement_buffer.write_data(&self.character_placements, &self.vao));
+
+        if let Err(err) = upload_result
+        {
+            if cfg!(debug_assertions)
+            {
+                eprintln!("Skipping text rendering for this frame: {}", err);
+            }
+
+            self.clear_buffered_text();
+            unsafe{ gl::Enable(gl::DEPTH_TEST) }
+            return;
+        }
 
         self.shader_program.use_program();
         self.vao.bind_vao();
         self.shader_program.write_mat4("projectionViewMatrix", &self.camera_matrix);
-        self.shader_program.write_float("textScaleX", self.window_dimensions.0 as f32 / self.default_window_width);
-        self.shader_program.write_float("textScaleY", self.window_dimensions.1 as f32 / self.default_window_height);
+        let text_scale = layout.text_scale(self.window_dimensions);
+        self.shader_program.write_float("textScaleX", text_scale.x);
+        self.shader_program.write_float("textScaleY", text_scale.y);
 
-        for x in 0..self.sentence_positions.len() - 1
+        if self.dim_background
         {
-            self.shader_program.write_vec2("translation", &self.sentence_positions[x].starting_position);
-
-            // Number of characters in the current sentence
-            let number_characters = self.sentence_positions[x + 1].starting_index - self.sentence_positions[x].starting_index;
-            for i in 0..number_characters
-            {
-                let char_index = i + self.sentence_positions[x].starting_index;
-                unsafe{ gl::DrawElementsBaseVertex(gl::TRIANGLES, 6, gl::UNSIGNED_INT, null(), (char_index * 4) as i32) }
-            }
+            // A single triangle covering the viewport is generated in the vertex shader
+            self.shader_program.write_uint("drawingBackground", 1);
+            unsafe{ gl::DrawArrays(gl::TRIANGLES, 0, 3) }
+            self.shader_program.write_uint("drawingBackground", 0);
         }
 
+        // Every character is drawn at once, each placed by the line of text it was buffered with
+        unsafe{ gl::DrawElementsInstanced(gl::TRIANGLES, 6, gl::UNSIGNED_INT, null(), self.num_characters) }
+
         self.plane_buffer.update_fence();
         self.tex_coords_buffer.update_fence();
+        self.placement_buffer.update_fence();
 
+        self.clear_buffered_text();
+
+        unsafe{ gl::Enable(gl::DEPTH_TEST) }
+    }
+
+    /// Removes all of the text buffered for rendering
+    fn clear_buffered_text(&mut self)
+    {
         self.num_characters = 0;
-        self.sentence_positions.clear();
         self.character_vertices.clear();
         self.character_tex_coords.clear();
+        self.character_placements.clear();
+        self.dim_background = false;
+    }
 
-        unsafe{ gl::Enable(gl::DEPTH_TEST) }
+    /// Checks if the OpenGL context supports anisotropic filtering. It is only part of the core
+    /// profile from OpenGL 4.6 onwards, so with earlier versions it is provided through an extension
+    fn anisotropic_filtering_supported() -> bool
+    {
+        let mut num_extensions = 0;
+        unsafe{ gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut num_extensions); }
+
+        (0..num_extensions).any(|x|
+            {
+                let extension = unsafe{ gl::GetStringi(gl::EXTENSIONS, x as u32) };
+                if extension.is_null()
+                {
+                    return false;
+                }
+
+                let extension = unsafe{ CStr::from_ptr(extension as *const _) }.to_string_lossy();
+                extension == "GL_ARB_texture_filter_anisotropic" || extension == "GL_EXT_texture_filter_anisotropic"
+            })
+    }
+
+    /// Expands the pixels of a texture atlas to the four channels the atlas texture is created with.
+    /// Grayscale channels are copied to red, green and blue; missing alpha is fully opaque
+    ///
+    /// `num_channels` - the number of channels of each pixel in the atlas file
+    /// `pixels` - the pixels of the atlas file
+    fn atlas_to_rgba(num_channels: usize, pixels: &[u8]) -> Result<Vec<u8>, String>
+    {
+        if num_channels == 4
+        {
+            return Ok(pixels.to_vec());
+        }
+
+        let expand: fn(&[u8]) -> [u8; 4] = match num_channels
+        {
+            1 => |x| [x[0], x[0], x[0], 255],
+            2 => |x| [x[0], x[0], x[0], x[1]],
+            3 => |x| [x[0], x[1], x[2], 255],
+            _ => return Err(format!("Unsupported number of channels: {}", num_channels))
+        };
+
+        Ok(pixels.chunks(num_channels).flat_map(|x| expand(x).to_vec()).collect())
     }
 
     /// Check that the given texture coordinates are valid (as in will result in a recognizable
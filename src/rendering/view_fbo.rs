@@ -1,8 +1,11 @@
 use nalgebra_glm::{TVec3, vec2, vec3};
 use crate::rendering::camera::{Camera, CameraType, PerspectiveParam};
 use crate::gl_wrappers::fbo::{FBO, TextureType};
+use crate::helper_logic::image_export::write_png;
 use crate::rendering::sunlight::SunLight;
 use crate::rendering::text_rendering::TextRendering;
+use crate::view_logic::pose_history::CameraPose;
+use crate::view_logic::sun_arc::SunArc;
 use crate::view_logic::view_selection::ViewSelection;
 use crate::window::RenderWindow;
 
@@ -12,6 +15,9 @@ pub struct ViewFBO
     right: FBO,
     top: FBO,
     sun: SunLight,
+    /// The poses the right and top view cameras had before mirroring the main camera, if they mirror it
+    right_pose_before_mirror: Option<CameraPose>,
+    top_pose_before_mirror: Option<CameraPose>,
 }
 
 impl ViewFBO
@@ -19,22 +25,26 @@ impl ViewFBO
     /// Creates a new top view, right view and sun view
     ///
     /// `render_window` - the window the views are going to be rendered to
-    pub fn new(render_window: &RenderWindow) -> ViewFBO
+    /// `sun_arc` - the arc the sun can be moved along around the point cloud
+    pub fn new(render_window: &RenderWindow, sun_arc: SunArc) -> ViewFBO
     {
         ViewFBO
         {
             right: create_right_view_fbo(&render_window),
             top: create_top_view_fbo(&render_window),
-            sun:  SunLight::new(render_window.get_window_dimensions(), 0)
+            sun:  SunLight::new(render_window.get_window_dimensions(), 0, sun_arc),
+            right_pose_before_mirror: None,
+            top_pose_before_mirror: None,
         }
     }
 
-    /// Move the position of the sun without regard to key input
+    /// Move the position of the sun without regard to key input, taking it off its arc
     ///
     /// `centre_scene` - the centre of the scene (ie centre of the point cloud)
     pub fn hard_set_light_pos(&mut self, pos: TVec3<f32>, centre_scene: TVec3<f32>)
     {
         self.sun.hard_set_sun_pos(pos, centre_scene);
+        self.sun.get_mut_arc().stop();
     }
 
     /// Move the position of the right view camera without regard to the key input
@@ -53,23 +63,62 @@ impl ViewFBO
         self.top.get_mut_camera().set_camera_pos(pos);
     }
 
+    /// Moves the right view camera to the given pose
+    ///
+    /// `pose` - the position and direction the camera should have
+    pub fn set_right_view_pose(&mut self, pose: CameraPose)
+    {
+        self.right.get_mut_camera().set_pose(pose);
+    }
+
+    /// Moves the top view camera to the given pose
+    ///
+    /// `pose` - the position and direction the camera should have
+    pub fn set_top_view_pose(&mut self, pose: CameraPose)
+    {
+        self.top.get_mut_camera().set_pose(pose);
+    }
+
+    /// Moves the right view camera back from the given centre along its view direction, so that the
+    /// centre is in the middle of the view
+    ///
+    /// `centre_scene` - the centre of the scene (ie centre of the point cloud)
+    /// `distance` - how far away from the centre the camera is placed
+    pub fn centre_right_view(&mut self, centre_scene: TVec3<f32>, distance: f32)
+    {
+        let pos = centre_scene - self.right.get_camera().get_direction() * distance;
+        self.hard_set_right_view_pos(pos);
+    }
+
+    /// Moves the top view camera back from the given centre along its view direction, so that the
+    /// centre is in the middle of the view
+    ///
+    /// `centre_scene` - the centre of the scene (ie centre of the point cloud)
+    /// `distance` - how far away from the centre the camera is placed
+    pub fn centre_top_view(&mut self, centre_scene: TVec3<f32>, distance: f32)
+    {
+        let pos = centre_scene - self.top.get_camera().get_direction() * distance;
+        self.hard_set_top_view_pos(pos);
+    }
+
     /// Update the position of the selected view, if any
     ///
     /// `view_selection` - structure holding the state of what view is selected
     /// `render_window` - the render window being rendered to
-    pub fn update_camera_movement(&mut self, view_selection: &ViewSelection, render_window: &RenderWindow)
+    /// `delta_time` - the seconds since the previous frame
+    pub fn update_camera_movement(&mut self, view_selection: &ViewSelection, render_window: &RenderWindow, delta_time: f32)
     {
         if view_selection.get_top_view_selected()
         {
-            Camera::update_camera_movement(&render_window, &mut self.top.get_mut_camera());
+            Camera::update_camera_movement(render_window, self.top.get_mut_camera(), delta_time);
         }
         else if view_selection.get_right_view_selected()
         {
-            Camera::update_camera_movement(&render_window, &mut self.right.get_mut_camera());
+            Camera::update_camera_movement(render_window, self.right.get_mut_camera(), delta_time);
         }
         else if view_selection.get_shadow_camera_view_selected()
         {
-            self.sun.move_sun(&render_window);
+            self.sun.move_sun(render_window, delta_time);
         }
         else if view_selection.get_shadow_lookat_view_selected()
         {
@@ -77,6 +126,84 @@ impl ViewFBO
         }
     }
 
+    /// Makes the selected top or right view show what the main camera sees, or returns it to the pose
+    /// it had before if it already does
+    ///
+    /// `view_selection` - structure holding the state of what view is selected
+    pub fn toggle_mirror_main_camera(&mut self, view_selection: &ViewSelection)
+    {
+        let (fbo, pose_before_mirror) = if view_selection.get_top_view_selected()
+        {
+            (&mut self.top, &mut self.top_pose_before_mirror)
+        }
+        else if view_selection.get_right_view_selected()
+        {
+            (&mut self.right, &mut self.right_pose_before_mirror)
+        }
+        else
+        {
+            return;
+        };
+
+        match pose_before_mirror.take()
+        {
+            Some(pose) => fbo.get_mut_camera().set_pose(pose),
+            None => *pose_before_mirror = Some(fbo.get_camera().get_pose())
+        }
+    }
+
+    /// Get the camera of the selected view, such as to change its projection. The sun look at view
+    /// shares the sun camera. Returns None if no view is selected
+    ///
+    /// `view_selection` - structure holding the state of what view is selected
+    pub fn get_mut_selected_camera(&mut self, view_selection: &ViewSelection) -> Option<&mut Camera>
+    {
+        if view_selection.get_top_view_selected()
+        {
+            Some(self.top.get_mut_camera())
+        }
+        else if view_selection.get_right_view_selected()
+        {
+            Some(self.right.get_mut_camera())
+        }
+        else if view_selection.get_shadow_camera_view_selected() || view_selection.get_shadow_lookat_view_selected()
+        {
+            Some(self.sun.get_mut_camera())
+        }
+        else
+        {
+            None
+        }
+    }
+
+    /// Copies the view of the main camera into the top and right views that mirror it
+    ///
+    /// `camera` - the main camera
+    pub fn mirror_main_camera(&mut self, camera: &Camera)
+    {
+        if self.top_pose_before_mirror.is_some()
+        {
+            self.top.get_mut_camera().mirror_camera(camera);
+        }
+
+        if self.right_pose_before_mirror.is_some()
+        {
+            self.right.get_mut_camera().mirror_camera(camera);
+        }
+    }
+
+    /// Check if the right view shows what the main camera sees
+    pub fn is_right_view_mirrored(&self) -> bool
+    {
+        self.right_pose_before_mirror.is_some()
+    }
+
+    /// Check if the top view shows what the main camera sees
+    pub fn is_top_view_mirrored(&self) -> bool
+    {
+        self.top_pose_before_mirror.is_some()
+    }
+
     /// Buffers the held view information to be rendered (view positions, and for the sun, the direction
     /// of the camera in the sun view
     pub fn buffer_write_fbo_information(&self, text_renderer: &mut TextRendering, lidar_pos: TVec3<f32>)
@@ -88,6 +215,34 @@ impl ViewFBO
         text_renderer.buffer_text_for_rendering("SD:  " .to_string() + &self.sun.to_string_lookat_pos(lidar_pos), vec2(0.75, 0.1), 30);
     }
 
+    /// Writes the rendered top, right and sun views to separate PNG files, named by appending the view
+    /// to the given prefix. The sun view holds depth, so it is written as grayscale
+    ///
+    /// `file_prefix` - the start of the name of each written file
+    pub fn export_views(&self, file_prefix: &String) -> Result<Vec<String>, String>
+    {
+        let mut written_files = Vec::new();
+
+        for (view, fbo) in &[("top", &self.top), ("right", &self.right), ("sun", self.sun.get_fbo())]
+        {
+            let file_name = format!("{}_{}.png", file_prefix, view);
+            write_png(&file_name, fbo.get_texture_dimensions(), &fbo.read_texture())?;
+            written_files.push(file_name);
+        }
+
+        Ok(written_files)
+    }
+
+    /// Makes moving the cursor up pitch the cameras of all the views down, and the other way around
+    ///
+    /// `invert_look_y` - true if the vertical rotation should be inverted
+    pub fn set_invert_look_y(&mut self, invert_look_y: bool)
+    {
+        self.top.get_mut_camera().set_invert_look_y(invert_look_y);
+        self.right.get_mut_camera().set_invert_look_y(invert_look_y);
+        self.sun.set_invert_look_y(invert_look_y);
+    }
+
     /// Reset the camera movement keys of all the views. All camera movements for the view will stop
     pub fn reset_movement_key_status(&mut self)
     {
@@ -104,6 +259,9 @@ impl ViewFBO
 
     /// Get the reference to the sun view FBO
     pub fn get_sun_fbo(&self) -> &SunLight { &self.sun }
+
+    /// Get the mutable reference to the sun view FBO
+    pub fn get_mut_sun_fbo(&mut self) -> &mut SunLight { &mut self.sun }
 }
 
 /// Creates the top view
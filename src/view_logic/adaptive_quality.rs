@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+/// Keeps the frame rate interactive on slow hardware by drawing fewer points of the point cloud when
+/// frames take longer than a target time, and drawing more again once there is room to spare
+pub struct AdaptiveQuality
+{
+    frame_times: VecDeque<f32>,
+    target_frame_time: f32,
+    point_stride: u32,
+}
+
+/// The number of frames the frame time is averaged over before the quality is changed
+const NUM_AVERAGED_FRAMES: usize = 30;
+
+/// The largest number of points that only one is drawn out of
+const MAX_POINT_STRIDE: u32 = 16;
+
+/// The fraction of the target frame time the average has to be below before more points are drawn.
+/// Drawing twice as many points roughly doubles the frame time, so this leaves room for that
+const RELAX_FRACTION: f32 = 0.4;
+
+impl AdaptiveQuality
+{
+    /// Creates a new adaptive quality that starts by drawing every point
+    ///
+    /// `target_frame_time` - the longest a frame should take, in seconds
+    pub fn new(target_frame_time: f32) -> AdaptiveQuality
+    {
+        AdaptiveQuality{ frame_times: VecDeque::with_capacity(NUM_AVERAGED_FRAMES), target_frame_time, point_stride: 1 }
+    }
+
+    /// Records how long the last frame took, and halves or doubles the number of points drawn once
+    /// enough frames have been recorded. Frames recorded before a change do not count afterwards, as
+    /// they were drawn with a different number of points
+    ///
+    /// `frame_time` - how long the last frame took, in seconds
+    pub fn record_frame_time(&mut self, frame_time: f32)
+    {
+        if self.frame_times.len() == NUM_AVERAGED_FRAMES
+        {
+            self.frame_times.pop_front();
+        }
+
+        self.frame_times.push_back(frame_time);
+
+        if self.frame_times.len() < NUM_AVERAGED_FRAMES
+        {
+            return;
+        }
+
+        let average_frame_time = self.average_frame_time();
+
+        if average_frame_time > self.target_frame_time && self.point_stride < MAX_POINT_STRIDE
+        {
+            self.point_stride *= 2;
+            self.frame_times.clear();
+        }
+        else if average_frame_time < self.target_frame_time * RELAX_FRACTION && self.point_stride > 1
+        {
+            self.point_stride /= 2;
+            self.frame_times.clear();
+        }
+    }
+
+    /// Get how many points only one is drawn out of
+    pub fn get_point_stride(&self) -> u32
+    {
+        self.point_stride
+    }
+
+    /// Check if the recorded frames take longer than the target time even though as few points as
+    /// possible are drawn
+    pub fn over_budget(&self) -> bool
+    {
+        self.point_stride == MAX_POINT_STRIDE && self.frame_times.len() == NUM_AVERAGED_FRAMES && self.average_frame_time() > self.target_frame_time
+    }
+
+    /// Get the text representation of the fraction of points drawn and of the average frame time
+    pub fn to_string_quality(&self) -> String
+    {
+        let mut string = format!("Quality: {}%", 100 / self.point_stride);
+
+        if !self.frame_times.is_empty()
+        {
+            string += &format!(" ({:.1} ms)", self.average_frame_time() * 1000.0);
+        }
+
+        if self.over_budget()
+        {
+            string += &format!(" over the {:.0} ms budget", self.target_frame_time * 1000.0);
+        }
+
+        string
+    }
+
+    /// Get the average time of the recorded frames, in seconds
+    fn average_frame_time(&self) -> f32
+    {
+        self.frame_times.iter().sum::<f32>() / self.frame_times.len().max(1) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::view_logic::adaptive_quality::{AdaptiveQuality, MAX_POINT_STRIDE, NUM_AVERAGED_FRAMES};
+
+    #[test]
+    fn quality_follows_frame_time()
+    {
+        let mut adaptive_quality = AdaptiveQuality::new(0.033);
+
+        // A single slow frame is not enough to lower the quality
+        adaptive_quality.record_frame_time(0.1);
+        assert_eq!(1, adaptive_quality.get_point_stride());
+
+        for _ in 0..NUM_AVERAGED_FRAMES
+        {
+            adaptive_quality.record_frame_time(0.05);
+        }
+        assert_eq!(2, adaptive_quality.get_point_stride());
+
+        for _ in 0..NUM_AVERAGED_FRAMES * 10
+        {
+            adaptive_quality.record_frame_time(0.05);
+        }
+        assert_eq!(MAX_POINT_STRIDE, adaptive_quality.get_point_stride());
+        assert!(adaptive_quality.over_budget());
+        assert_eq!("Quality: 6% (50.0 ms) over the 33 ms budget", adaptive_quality.to_string_quality());
+
+        // Frame times between the relax fraction and the target keep the quality where it is
+        for _ in 0..NUM_AVERAGED_FRAMES * 2
+        {
+            adaptive_quality.record_frame_time(0.02);
+        }
+        assert_eq!(MAX_POINT_STRIDE, adaptive_quality.get_point_stride());
+
+        for _ in 0..NUM_AVERAGED_FRAMES
+        {
+            adaptive_quality.record_frame_time(0.005);
+        }
+        assert_eq!(MAX_POINT_STRIDE / 2, adaptive_quality.get_point_stride());
+        assert!(!adaptive_quality.over_budget());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+use nalgebra_glm::{TVec2, TVec3};
+
+/// Text annotations pinned to positions in the scene, such as to mark features of a point cloud while
+/// inspecting it. Each annotation stays at its position as the camera moves
+pub struct Annotations
+{
+    annotations: Vec<(TVec3<f32>, String)>,
+    labels: Vec<String>,
+    num_added: usize,
+}
+
+impl Annotations
+{
+    /// Creates a new annotation store with no annotations
+    ///
+    /// `labels` - the text given to annotations in the order they are added. Once all of them are used,
+    ///            annotations are numbered instead
+    pub fn new(labels: Vec<String>) -> Annotations
+    {
+        Annotations{ annotations: Vec::new(), labels, num_added: 0 }
+    }
+
+    /// Pins an annotation to the given position, returning the text it was given
+    ///
+    /// `world_pos` - the position to pin the annotation to
+    pub fn add(&mut self, world_pos: TVec3<f32>) -> &str
+    {
+        let label = match self.labels.get(self.num_added)
+        {
+            Some(i) => i.clone(),
+            None => format!("#{}", self.num_added + 1)
+        };
+
+        self.num_added += 1;
+        self.annotations.push((world_pos, label));
+        &self.annotations.last().unwrap().1
+    }
+
+    /// Removes all of the annotations. Annotations added afterwards start from the first label again
+    pub fn clear(&mut self)
+    {
+        self.annotations.clear();
+        self.num_added = 0;
+    }
+
+    /// Check if there are any annotations
+    pub fn is_empty(&self) -> bool
+    {
+        self.annotations.is_empty()
+    }
+
+    /// Get where each annotation is on the screen along with its text. Annotations the projection does
+    /// not place on the screen, such as those behind the camera, are skipped
+    ///
+    /// `world_to_screen` - projects a position onto the screen, or returns None if it is not visible
+    pub fn on_screen<F: Fn(&TVec3<f32>) -> Option<TVec2<f32>>>(&self, world_to_screen: F) -> Vec<(TVec2<f32>, &str)>
+    {
+        self.annotations.iter()
+            .filter_map(|(world_pos, label)| world_to_screen(world_pos).map(|x| (x, label.as_str())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::{vec2, vec3};
+    use crate::view_logic::annotations::Annotations;
+
+    #[test]
+    fn annotations_follow_projection()
+    {
+        let mut annotations = Annotations::new(vec!["inlet".to_string()]);
+        assert!(annotations.is_empty());
+
+        assert_eq!("inlet", annotations.add(vec3(1.0, 0.0, -2.0)));
+        assert_eq!("#2", annotations.add(vec3(0.0, 0.0, 3.0)));
+
+        // Only positions in front of the camera, looking down -z, are projected
+        let on_screen = annotations.on_screen(|x| if x.z < 0.0 { Some(vec2(x.x / -x.z, x.y / -x.z)) } else { None });
+        assert_eq!(vec![(vec2(0.5, 0.0), "inlet")], on_screen);
+
+        annotations.clear();
+        assert!(annotations.is_empty());
+        assert_eq!("inlet", annotations.add(vec3(0.0, 0.0, 0.0)));
+    }
+}
\ No newline at end of file
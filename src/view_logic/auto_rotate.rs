@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+/// Keeps track of slowly orbiting the camera around the point cloud without user input, such as
+/// for unattended displays. Any user input suspends the rotation for a short time
+pub struct AutoRotate
+{
+    enabled: bool,
+    degrees_per_second: f32,
+    last_update: Instant,
+    suspended_until: Instant,
+}
+
+/// How long the rotation stays suspended after the last user input
+const SUSPEND_DURATION: Duration = Duration::from_secs(3);
+
+impl AutoRotate
+{
+    /// Creates a new auto rotation
+    ///
+    /// `enabled` - true if the camera should start rotating immediately
+    /// `degrees_per_second` - how fast the camera rotates around the point cloud
+    pub fn new(enabled: bool, degrees_per_second: f32) -> AutoRotate
+    {
+        let now = Instant::now();
+        AutoRotate{ enabled, degrees_per_second, last_update: now, suspended_until: now }
+    }
+
+    /// Turns the rotation on or off
+    pub fn toggle(&mut self)
+    {
+        self.enabled = !self.enabled;
+    }
+
+    /// Pauses the rotation because of user input. It resumes once there has been no input for a
+    /// few seconds
+    ///
+    /// `now` - the time the input happened
+    pub fn suspend(&mut self, now: Instant)
+    {
+        self.suspended_until = now + SUSPEND_DURATION;
+    }
+
+    /// Get the angle in degrees the camera should be rotated by since the last call, or None if
+    /// the camera should not be rotated
+    ///
+    /// `now` - the current time
+    pub fn angle_since_last_update(&mut self, now: Instant) -> Option<f32>
+    {
+        let elapsed = now.saturating_duration_since(self.last_update);
+        self.last_update = now;
+
+        if !self.enabled || now < self.suspended_until
+        {
+            return None;
+        }
+
+        Some(elapsed.as_secs_f32() * self.degrees_per_second)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::{Duration, Instant};
+    use crate::view_logic::auto_rotate::{AutoRotate, SUSPEND_DURATION};
+
+    #[test]
+    fn input_suspends_rotation()
+    {
+        let start = Instant::now();
+        let mut auto_rotate = AutoRotate::new(true, 10.0);
+        auto_rotate.last_update = start;
+        auto_rotate.suspended_until = start;
+
+        assert_eq!(Some(10.0), auto_rotate.angle_since_last_update(start + Duration::from_secs(1)));
+
+        auto_rotate.suspend(start + Duration::from_secs(1));
+        assert_eq!(None, auto_rotate.angle_since_last_update(start + Duration::from_secs(2)));
+
+        // Time spent suspended is not made up for once the rotation resumes
+        let resumed = start + Duration::from_secs(1) + SUSPEND_DURATION;
+        assert_eq!(None, auto_rotate.angle_since_last_update(resumed - Duration::from_millis(1)));
+        assert!(auto_rotate.angle_since_last_update(resumed).unwrap() < 0.1);
+
+        auto_rotate.toggle();
+        assert_eq!(None, auto_rotate.angle_since_last_update(resumed + Duration::from_secs(1)));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,177 @@
+use nalgebra_glm::{TVec2, TVec3, vec2};
+
+/// How far the cursor has to move while the left button is held for it to count as dragging out a
+/// rectangle, rather than a click
+const DRAG_THRESHOLD_PIXELS: f32 = 4.0;
+
+/// Keeps track of the points selected by dragging a rectangle over the scene. Selected points are
+/// highlighted by changing their colour; their colours from before the selection are kept so that
+/// they can be restored once the selection is cleared
+pub struct BoxSelection
+{
+    drag_start: Option<TVec2<f32>>,
+    selected: Vec<usize>,
+    original_colours: Vec<TVec3<f32>>,
+    highlighted_colours: Vec<TVec3<f32>>,
+}
+
+impl BoxSelection
+{
+    /// Creates a new box selection with no points selected
+    pub fn new() -> BoxSelection
+    {
+        BoxSelection{ drag_start: None, selected: Vec::new(), original_colours: Vec::new(), highlighted_colours: Vec::new() }
+    }
+
+    /// Starts dragging out a rectangle from the given cursor position
+    ///
+    /// `cursor_pos` - where the left button was pressed, in window coordinates
+    pub fn start_drag(&mut self, cursor_pos: TVec2<f32>)
+    {
+        self.drag_start = Some(cursor_pos);
+    }
+
+    /// Get the opposite corners of the rectangle being dragged out, if the cursor has moved far
+    /// enough from where the drag started
+    ///
+    /// `cursor_pos` - the current cursor position, in window coordinates
+    pub fn drag_rect(&self, cursor_pos: TVec2<f32>) -> Option<(TVec2<f32>, TVec2<f32>)>
+    {
+        self.drag_start
+            .filter(|x| nalgebra_glm::distance(x, &cursor_pos) >= DRAG_THRESHOLD_PIXELS)
+            .map(|x| (x, cursor_pos))
+    }
+
+    /// Stops dragging, returning the rectangle that was dragged out. None is returned if nothing
+    /// was being dragged, or if the cursor did not move far enough to be more than a click
+    ///
+    /// `cursor_pos` - where the left button was released, in window coordinates
+    pub fn finish_drag(&mut self, cursor_pos: TVec2<f32>) -> Option<(TVec2<f32>, TVec2<f32>)>
+    {
+        let rect = self.drag_rect(cursor_pos);
+        self.drag_start = None;
+        rect
+    }
+
+    /// Check if a rectangle is being dragged out
+    pub fn is_dragging(&self) -> bool
+    {
+        self.drag_start.is_some()
+    }
+
+    /// Get the number of points that are selected
+    pub fn num_selected(&self) -> usize
+    {
+        self.selected.len()
+    }
+
+    /// Selects the given points, replacing any previous selection. Returns the colours of the points
+    /// with the selected points highlighted
+    ///
+    /// `selected` - the indices of the points to select
+    /// `colours` - the colours the points are currently displayed with
+    /// `highlight_colour` - the colour to display selected points with
+    pub fn select(&mut self, selected: Vec<usize>, colours: &[TVec3<f32>], highlight_colour: TVec3<f32>) -> Vec<TVec3<f32>>
+    {
+        // Highlighting is not part of the colour of a point, so a previous selection must not carry over
+        if self.selected.is_empty() || colours != self.highlighted_colours.as_slice()
+        {
+            self.original_colours = colours.to_vec();
+        }
+
+        self.highlighted_colours = self.original_colours.clone();
+        for index in &selected
+        {
+            if let Some(colour) = self.highlighted_colours.get_mut(*index)
+            {
+                *colour = highlight_colour;
+            }
+        }
+
+        self.selected = selected;
+        self.highlighted_colours.clone()
+    }
+
+    /// Deselects all points, returning the colours to restore if any points were selected
+    pub fn clear(&mut self) -> Option<Vec<TVec3<f32>>>
+    {
+        if self.selected.is_empty()
+        {
+            return None;
+        }
+
+        self.selected.clear();
+        self.highlighted_colours.clear();
+        Some(std::mem::take(&mut self.original_colours))
+    }
+
+    /// Forgets the selection if the displayed colours are no longer the highlighted ones, such as
+    /// when an updated point cloud is displayed. The points of the selection may no longer exist
+    ///
+    /// `colours` - the colours the points are currently displayed with
+    pub fn discard_if_replaced(&mut self, colours: &[TVec3<f32>])
+    {
+        if !self.selected.is_empty() && colours != self.highlighted_colours.as_slice()
+        {
+            self.clear();
+        }
+    }
+
+    /// Get the text representation of the number of selected points
+    pub fn to_string_selected(&self) -> String
+    {
+        format!("Selected points: {}", self.selected.len())
+    }
+}
+
+/// Finds the points whose projection onto the screen is inside the given rectangle
+///
+/// `corner` - a corner of the rectangle, in Normalized Device Coordinates
+/// `opposite_corner` - the corner of the rectangle opposite the first one
+/// `screen_positions` - the projection of each point, or None for points that are not on the screen
+pub fn points_in_rect<I: Iterator<Item=Option<TVec2<f32>>>>(corner: TVec2<f32>, opposite_corner: TVec2<f32>, screen_positions: I) -> Vec<usize>
+{
+    let min = vec2(corner.x.min(opposite_corner.x), corner.y.min(opposite_corner.y));
+    let max = vec2(corner.x.max(opposite_corner.x), corner.y.max(opposite_corner.y));
+
+    screen_positions
+        .enumerate()
+        .filter(|(_, x)| x.is_some_and(|i| i.x >= min.x && i.x <= max.x && i.y >= min.y && i.y <= max.y))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::{vec2, vec3};
+    use crate::view_logic::box_selection::{BoxSelection, points_in_rect};
+
+    #[test]
+    fn selects_points_inside_rect()
+    {
+        let screen_positions = vec![Some(vec2(0.0, 0.0)), Some(vec2(0.6, 0.0)), None, Some(vec2(-0.4, 0.4))];
+        let selected = points_in_rect(vec2(0.5, -0.5), vec2(-0.5, 0.5), screen_positions.into_iter());
+        assert_eq!(vec![0, 3], selected);
+
+        let mut box_selection = BoxSelection::new();
+        box_selection.start_drag(vec2(100.0, 100.0));
+        assert_eq!(None, box_selection.finish_drag(vec2(101.0, 100.0)));
+
+        let colours = vec![vec3(0.0, 0.0, 1.0); 4];
+        let highlight = vec3(1.0, 1.0, 0.0);
+        let highlighted = box_selection.select(selected, &colours, highlight);
+        assert_eq!(vec![highlight, colours[1], colours[2], highlight], highlighted);
+        assert_eq!(2, box_selection.num_selected());
+
+        // Selecting again starts from the colours from before the first selection
+        let highlighted = box_selection.select(vec![1], &highlighted, highlight);
+        assert_eq!(vec![colours[0], highlight, colours[2], colours[3]], highlighted);
+        assert_eq!(Some(colours.clone()), box_selection.clear());
+        assert_eq!(None, box_selection.clear());
+
+        box_selection.select(vec![0], &colours, highlight);
+        box_selection.discard_if_replaced(&colours);
+        assert_eq!(0, box_selection.num_selected());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,233 @@
+use std::f32::consts::PI;
+use std::fs;
+use nalgebra_glm::{TVec3, vec3};
+use crate::view_logic::pose_history::CameraPose;
+
+/// A pose the camera passes through at a given time along a camera path
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraKeyframe
+{
+    /// Seconds from the start of the path
+    pub time: f32,
+    pub pose: CameraPose,
+}
+
+/// Moves the camera through keyframed poses, such as for recording reproducible flythroughs. Positions
+/// follow a Catmull-Rom spline through the keyframes and directions turn at a constant rate between them
+pub struct CameraPath
+{
+    keyframes: Vec<CameraKeyframe>,
+    playing: bool,
+    time: f32,
+    moved_camera: bool,
+}
+
+impl CameraPath
+{
+    /// Reads a camera path file. Each line is a keyframe written as its time in seconds followed by the
+    /// position and the direction of the camera, such as "2.5 0 10 -20 0 0 1". Times must increase from
+    /// one keyframe to the next. Empty lines and lines starting with '#' are ignored
+    ///
+    /// `path_file` - the file containing the camera path
+    pub fn from_file(path_file: &String) -> Result<CameraPath, String>
+    {
+        let content = fs::read_to_string(path_file).map_err(|x| format!("Failed to read camera path {}: {}", path_file, x))?;
+        CameraPath::parse(&content).map_err(|x| format!("Invalid camera path {}: {}", path_file, x))
+    }
+
+    /// Starts moving the camera along the path, or stops it where it is. Starting once the end of the
+    /// path has been reached starts from the beginning again
+    pub fn toggle(&mut self)
+    {
+        if !self.playing && self.time >= self.duration()
+        {
+            self.time = 0.0;
+        }
+
+        self.playing = !self.playing;
+    }
+
+    /// Check if the last call to advance moved the camera
+    pub fn moved_camera(&self) -> bool
+    {
+        self.moved_camera
+    }
+
+    /// Get the time from the start of the path to the last keyframe, in seconds
+    pub fn duration(&self) -> f32
+    {
+        self.keyframes.last().map_or(0.0, |x| x.time)
+    }
+
+    /// Get how far along the path the camera is, in seconds
+    pub fn get_time(&self) -> f32
+    {
+        self.time
+    }
+
+    /// Moves along the path if it is playing, returning the pose the camera should have. Playing stops
+    /// at the end of the path
+    ///
+    /// `seconds` - how far to move along the path
+    pub fn advance(&mut self, seconds: f32) -> Option<CameraPose>
+    {
+        self.moved_camera = self.playing;
+
+        if !self.playing
+        {
+            return None;
+        }
+
+        self.time = (self.time + seconds).min(self.duration());
+        if self.time >= self.duration()
+        {
+            self.playing = false;
+        }
+
+        Some(self.pose_at(self.time))
+    }
+
+    /// Get the text representation of how far along the path the camera is
+    pub fn to_string_progress(&self) -> String
+    {
+        format!("Camera path: {:.1} / {:.1}s{}", self.time, self.duration(), if self.playing { "" } else { " (stopped)" })
+    }
+
+    /// Get the pose of the camera at the given time along the path. Times outside of the path give the
+    /// pose of the closest end of the path
+    ///
+    /// `time` - seconds from the start of the path
+    pub fn pose_at(&self, time: f32) -> CameraPose
+    {
+        let last = self.keyframes.len() - 1;
+        let segment = self.keyframes[1..].iter().position(|x| time < x.time).unwrap_or(last - 1);
+
+        let (start, end) = (&self.keyframes[segment], &self.keyframes[segment + 1]);
+        let t = ((time - start.time) / (end.time - start.time)).clamp(0.0, 1.0);
+
+        // Keyframes past the ends of the path are mirrored, so that the path keeps its direction at the ends
+        let before = if segment == 0 { start.pose.position * 2.0 - end.pose.position } else { self.keyframes[segment - 1].pose.position };
+        let after = if segment + 1 == last { end.pose.position * 2.0 - start.pose.position } else { self.keyframes[segment + 2].pose.position };
+
+        CameraPose
+        {
+            position: catmull_rom(&before, &start.pose.position, &end.pose.position, &after, t),
+            direction: slerp_direction(&start.pose.direction, &end.pose.direction, t),
+        }
+    }
+
+    /// Parses the text of a camera path file
+    ///
+    /// `content` - the contents of the camera path file
+    fn parse(content: &str) -> Result<CameraPath, String>
+    {
+        let mut keyframes: Vec<CameraKeyframe> = Vec::new();
+
+        for line in content.lines().map(|x| x.trim()).filter(|x| !x.is_empty() && !x.starts_with('#'))
+        {
+            let values = line.split_whitespace().map(|x| x.parse::<f32>()).collect::<Result<Vec<f32>, _>>()
+                .map_err(|_| format!("Keyframe \"{}\" has a value that is not a number", line))?;
+
+            if values.len() != 7
+            {
+                return Err(format!("Keyframe \"{}\" does not have a time, position and direction", line));
+            }
+
+            let direction = vec3(values[4], values[5], values[6]);
+            if nalgebra_glm::length(&direction) == 0.0
+            {
+                return Err(format!("Keyframe \"{}\" has no direction", line));
+            }
+
+            if keyframes.last().is_some_and(|x| values[0] <= x.time)
+            {
+                return Err(format!("Keyframe \"{}\" is not after the keyframe before it", line));
+            }
+
+            keyframes.push(CameraKeyframe
+            {
+                time: values[0],
+                pose: CameraPose{ position: vec3(values[1], values[2], values[3]), direction: nalgebra_glm::normalize(&direction) }
+            });
+        }
+
+        if keyframes.len() < 2
+        {
+            return Err("At least two keyframes are required".to_string());
+        }
+
+        Ok(CameraPath{ keyframes, playing: false, time: 0.0, moved_camera: false })
+    }
+}
+
+/// Interpolates between the middle two of four points along a Catmull-Rom spline, which passes through
+/// every point
+///
+/// `p0` - the point before the start of the section
+/// `p1` - the start of the section
+/// `p2` - the end of the section
+/// `p3` - the point after the end of the section
+/// `t` - how far along the section to interpolate, between 0 and 1
+fn catmull_rom(p0: &TVec3<f32>, p1: &TVec3<f32>, p2: &TVec3<f32>, p3: &TVec3<f32>, t: f32) -> TVec3<f32>
+{
+    let (t2, t3) = (t * t, t * t * t);
+
+    (p1 * 2.0 + (p2 - p0) * t + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2 + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3) * 0.5
+}
+
+/// Turns from one direction to another at a constant rate
+///
+/// `from` - the direction at the start, of unit length
+/// `to` - the direction at the end, of unit length
+/// `t` - how far to turn, between 0 and 1
+fn slerp_direction(from: &TVec3<f32>, to: &TVec3<f32>, t: f32) -> TVec3<f32>
+{
+    let angle = nalgebra_glm::dot(from, to).clamp(-1.0, 1.0).acos();
+
+    // Nearly equal directions are too close to tell the way to turn apart, and opposite directions can
+    // be turned between in any way
+    if angle < 0.0001 || PI - angle < 0.0001
+    {
+        return if t < 0.5 { *from } else { *to };
+    }
+
+    (from * ((1.0 - t) * angle).sin() + to * (t * angle).sin()) / angle.sin()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::view_logic::camera_path::CameraPath;
+
+    #[test]
+    fn poses_interpolated_between_keyframes()
+    {
+        let content = "# time position direction\n0 0 0 0 1 0 0\n\n2 10 0 0 0 0 1\n4 20 0 0 0 0 1\n";
+        let mut camera_path = CameraPath::parse(content).unwrap();
+        assert_eq!(4.0, camera_path.duration());
+
+        // Evenly spaced keyframes along a line are moved between at a constant speed
+        let close = |a: nalgebra_glm::TVec3<f32>, b: nalgebra_glm::TVec3<f32>| nalgebra_glm::distance(&a, &b) < 0.0001;
+        assert!(close(vec3(10.0, 0.0, 0.0), camera_path.pose_at(2.0).position));
+        assert!(close(vec3(5.0, 0.0, 0.0), camera_path.pose_at(1.0).position));
+        assert!(close(vec3(15.0, 0.0, 0.0), camera_path.pose_at(3.0).position));
+
+        let halfway = camera_path.pose_at(1.0).direction;
+        assert!(close(vec3(1.0, 0.0, 1.0).normalize(), halfway));
+
+        assert_eq!(None, camera_path.advance(1.0));
+        camera_path.toggle();
+        assert!(close(vec3(5.0, 0.0, 0.0), camera_path.advance(1.0).unwrap().position));
+
+        // Stops at the end, and starts over when started again
+        assert!(close(vec3(20.0, 0.0, 0.0), camera_path.advance(10.0).unwrap().position));
+        assert!(!camera_path.playing);
+        camera_path.toggle();
+        assert!(close(vec3(5.0, 0.0, 0.0), camera_path.advance(1.0).unwrap().position));
+
+        assert!(CameraPath::parse("0 0 0 0 1 0 0\n").is_err());
+        assert!(CameraPath::parse("1 0 0 0 1 0 0\n1 0 0 0 1 0 0\n").is_err());
+        assert!(CameraPath::parse("0 0 0 0 0 0 0\n1 0 0 0 1 0 0\n").is_err());
+    }
+}
\ No newline at end of file
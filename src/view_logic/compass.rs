@@ -0,0 +1,50 @@
+use nalgebra_glm::{TVec2, vec2};
+
+/// Get where the labels of the world axes are placed on a top-down compass, relative to its centre.
+/// The direction the camera looks in always points up on the compass, so the labels move around
+/// the centre as the camera turns. Each offset is a unit length
+///
+/// `yaw_degrees` - the horizontal rotation of the camera, starting from the positive x-axis
+pub fn compass_labels(yaw_degrees: f32) -> [(&'static str, TVec2<f32>); 4]
+{
+    let (sin, cos) = yaw_degrees.to_radians().sin_cos();
+
+    // Components of each axis along the camera's right and forward directions
+    let positive_x = vec2(-sin, cos);
+    let positive_z = vec2(cos, sin);
+
+    [("+X", positive_x), ("+Z", positive_z), ("-X", -positive_x), ("-Z", -positive_z)]
+}
+
+/// Get the world axis closest to the direction the camera is looking in, ignoring height
+///
+/// `yaw_degrees` - the horizontal rotation of the camera, starting from the positive x-axis
+pub fn facing_axis(yaw_degrees: f32) -> &'static str
+{
+    let quadrant = (yaw_degrees / 90.0).round().rem_euclid(4.0) as usize;
+    ["+X", "+Z", "-X", "-Z"][quadrant]
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::view_logic::compass::{compass_labels, facing_axis};
+
+    #[test]
+    fn facing_axis_points_up()
+    {
+        for yaw in &[0.0, 90.0, -90.0, 180.0, 450.0]
+        {
+            let (label, offset) = compass_labels(*yaw).iter()
+                .max_by(|a, b| a.1.y.partial_cmp(&b.1.y).unwrap())
+                .copied()
+                .unwrap();
+
+            assert_eq!(facing_axis(*yaw), label);
+            assert!((offset.y - 1.0).abs() < 0.001);
+        }
+
+        assert_eq!("-Z", facing_axis(-100.0));
+        assert_eq!("+X", facing_axis(-20.0));
+    }
+}
\ No newline at end of file
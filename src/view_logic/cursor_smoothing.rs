@@ -0,0 +1,42 @@
+/// Reduces the cursor positions captured during a frame by replacing each group of consecutive
+/// positions with their average. High polling rate mice report many small, noisy movements per
+/// frame; rotating the camera by their average movement is steadier. With a group size of one or
+/// less the positions are returned unchanged
+///
+/// `cursor_pos_history` - the locations of the cursor, in the order they were captured
+/// `group_size` - the number of consecutive positions that are averaged together
+pub fn smooth_cursor_positions(cursor_pos_history: &[(i32, i32)], group_size: usize) -> Vec<(i32, i32)>
+{
+    if group_size <= 1
+    {
+        return cursor_pos_history.to_vec();
+    }
+
+    cursor_pos_history.chunks(group_size)
+        .map(|group|
+            {
+                let (sum_x, sum_y) = group.iter().fold((0_i64, 0_i64), |sum, (x, y)| (sum.0 + *x as i64, sum.1 + *y as i64));
+                ((sum_x as f64 / group.len() as f64).round() as i32, (sum_y as f64 / group.len() as f64).round() as i32)
+            })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::view_logic::cursor_smoothing::smooth_cursor_positions;
+
+    #[test]
+    fn positions_averaged_in_groups()
+    {
+        let history = vec![(0, 0), (2, 1), (4, 2), (6, 3), (9, 4)];
+
+        // Without smoothing every position is used, as before smoothing was added
+        assert_eq!(history, smooth_cursor_positions(&history, 0));
+        assert_eq!(history, smooth_cursor_positions(&history, 1));
+
+        // The last group holds the remaining positions
+        assert_eq!(vec![(2, 1), (8, 4)], smooth_cursor_positions(&history, 3));
+        assert_eq!(vec![(4, 2)], smooth_cursor_positions(&history, 10));
+    }
+}
\ No newline at end of file
@@ -1,2 +1,14 @@
+pub mod adaptive_quality;
+pub mod annotations;
+pub mod auto_rotate;
+pub mod box_selection;
+pub mod camera_path;
+pub mod compass;
+pub mod cursor_smoothing;
+pub mod point_picking;
+pub mod point_smoothing;
+pub mod pose_history;
+pub mod sun_arc;
+pub mod update_heatmap;
 pub mod view_selection;
 pub mod view_transform;
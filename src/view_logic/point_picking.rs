@@ -0,0 +1,54 @@
+use nalgebra_glm::{cross, dot, TVec3};
+
+/// Finds the point closest to the given ray, measured as the angle between the ray and the direction
+/// from the ray origin to the point. Only points in front of the origin and within a small angle of
+/// the ray can be picked, so that clicking on empty space does not pick a point far from the cursor
+///
+/// `origin` - the start of the ray
+/// `direction` - the normalized direction of the ray
+/// `points` - the points that can be picked
+pub fn pick_point<I: Iterator<Item=TVec3<f32>>>(origin: TVec3<f32>, direction: TVec3<f32>, points: I) -> Option<TVec3<f32>>
+{
+    // Tangent of roughly one degree
+    let max_angle_tan = 0.0175;
+
+    points
+        .filter_map(|point|
+            {
+                let to_point = point - origin;
+                let depth = dot(&to_point, &direction);
+                if depth <= 0.0
+                {
+                    return None;
+                }
+
+                // Distance from the ray divided by the distance along the ray
+                let angle_tan = cross(&to_point, &direction).magnitude() / depth;
+                if angle_tan <= max_angle_tan { Some((point, angle_tan)) } else { None }
+            })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(point, _)| point)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::view_logic::point_picking::pick_point;
+
+    #[test]
+    fn picks_point_closest_to_ray()
+    {
+        let points = vec![vec3(0.0, 0.5, 10.0), vec3(0.0, 0.05, 5.0), vec3(0.0, 0.0, -5.0)];
+
+        assert_eq!(Some(vec3(0.0, 0.05, 5.0)), pick_point(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), points.into_iter()));
+    }
+
+    #[test]
+    fn ignores_points_far_from_ray()
+    {
+        let points = vec![vec3(0.0, 1.0, 5.0), vec3(0.0, 0.0, -5.0)];
+
+        assert_eq!(None, pick_point(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), points.into_iter()));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,96 @@
+use nalgebra_glm::TVec3;
+use crate::cluster_logic::octree::Octree;
+
+/// Reduces the jitter of noisy point clouds by displaying an exponential moving average of the
+/// position of each point over the point clouds received. Points are matched to the closest point of
+/// the previous point cloud; points without one close enough are shown where they are
+pub struct PointSmoothing
+{
+    enabled: bool,
+    blend_factor: f32,
+    match_distance: f32,
+    previous_points: Vec<TVec3<f32>>,
+}
+
+impl PointSmoothing
+{
+    /// Creates a new, disabled, point smoothing
+    ///
+    /// `blend_factor` - how much of a new position is blended into the average, between 0 and 1.
+    ///                  Smaller values are smoother but lag further behind moving points
+    /// `match_distance` - the furthest a point can be from the closest previous point to be matched to it
+    pub fn new(blend_factor: f32, match_distance: f32) -> PointSmoothing
+    {
+        PointSmoothing{ enabled: false, blend_factor, match_distance, previous_points: Vec::new() }
+    }
+
+    /// Switches between displaying the smoothed and the received positions of points
+    pub fn toggle(&mut self)
+    {
+        self.enabled = !self.enabled;
+    }
+
+    /// Checks if the positions of points are smoothed
+    pub fn is_enabled(&self) -> bool
+    {
+        self.enabled
+    }
+
+    /// Determines the positions to display for an updated point cloud. If enabled, each point is moved
+    /// from its received position towards the displayed position of the closest previous point, if
+    /// that point is within the match distance; otherwise the received positions are used
+    ///
+    /// `points` - the received points of the updated point cloud
+    pub fn smooth(&mut self, points: &[TVec3<f32>]) -> Vec<TVec3<f32>>
+    {
+        let smoothed_points = if self.enabled && !self.previous_points.is_empty()
+        {
+            let octree = Octree::new(&self.previous_points);
+            points.iter()
+                .map(|x|
+                    {
+                        match octree.find_nearest(x).map(|i| self.previous_points[i])
+                        {
+                            Some(previous) if nalgebra_glm::distance(&previous, x) <= self.match_distance => previous + (x - previous) * self.blend_factor,
+                            _ => *x
+                        }
+                    })
+                .collect()
+        }
+        else
+        {
+            points.to_vec()
+        };
+
+        self.previous_points = smoothed_points.clone();
+        smoothed_points
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::view_logic::point_smoothing::PointSmoothing;
+
+    #[test]
+    fn matched_points_are_averaged()
+    {
+        let mut point_smoothing = PointSmoothing::new(0.25, 1.0);
+
+        // Disabled smoothing still records the points, so that smoothing starts from them once enabled
+        assert_eq!(vec![vec3(0.0, 0.0, 0.0), vec3(10.0, 0.0, 0.0)], point_smoothing.smooth(&[vec3(0.0, 0.0, 0.0), vec3(10.0, 0.0, 0.0)]));
+        point_smoothing.toggle();
+
+        // The second point moved further than the match distance, so it is shown where it was received
+        let smoothed = point_smoothing.smooth(&[vec3(0.8, 0.0, 0.0), vec3(15.0, 0.0, 0.0)]);
+        assert_eq!(vec![vec3(0.2, 0.0, 0.0), vec3(15.0, 0.0, 0.0)], smoothed);
+
+        // Averages continue from the displayed positions
+        let smoothed = point_smoothing.smooth(&[vec3(1.0, 0.0, 0.0)]);
+        assert!(nalgebra_glm::distance(&vec3(0.4, 0.0, 0.0), &smoothed[0]) < 0.0001);
+
+        point_smoothing.toggle();
+        assert_eq!(vec![vec3(1.0, 0.0, 0.0)], point_smoothing.smooth(&[vec3(1.0, 0.0, 0.0)]));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use nalgebra_glm::TVec3;
+
+/// The location and orientation of a camera
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CameraPose
+{
+    pub position: TVec3<f32>,
+    pub direction: TVec3<f32>,
+}
+
+/// Keeps a bounded history of where the camera was before each of its movements, so that
+/// movements can be undone
+pub struct PoseHistory
+{
+    poses: VecDeque<CameraPose>,
+    max_poses: usize,
+    last_still_pose: Option<CameraPose>,
+}
+
+impl PoseHistory
+{
+    /// Creates an empty pose history
+    ///
+    /// `max_poses` - the maximum number of poses remembered. Once reached, the oldest pose is forgotten
+    pub fn new(max_poses: usize) -> PoseHistory
+    {
+        PoseHistory{ poses: VecDeque::with_capacity(max_poses), max_poses, last_still_pose: None }
+    }
+
+    /// Records the current pose of the camera. This should be called every frame so that the pose
+    /// before a movement begins can be remembered
+    ///
+    /// `pose` - the current pose of the camera
+    /// `moving` - true if the camera is currently being moved or rotated
+    pub fn record(&mut self, pose: CameraPose, moving: bool)
+    {
+        if !moving
+        {
+            self.last_still_pose = Some(pose);
+            return;
+        }
+
+        // A movement has just started; the pose the camera had before it is what an undo returns to
+        if let Some(still_pose) = self.last_still_pose.take()
+        {
+            if self.poses.back() != Some(&still_pose)
+            {
+                if self.poses.len() == self.max_poses
+                {
+                    self.poses.pop_front();
+                }
+
+                self.poses.push_back(still_pose);
+            }
+        }
+    }
+
+    /// Removes the most recently remembered pose and returns it, if there is one
+    pub fn undo(&mut self) -> Option<CameraPose>
+    {
+        self.poses.pop_back()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::view_logic::pose_history::{CameraPose, PoseHistory};
+
+    fn pose(x: f32) -> CameraPose
+    {
+        CameraPose{ position: vec3(x, 0.0, 0.0), direction: vec3(1.0, 0.0, 0.0) }
+    }
+
+    #[test]
+    fn undo_returns_pose_before_movement()
+    {
+        let mut history = PoseHistory::new(20);
+
+        history.record(pose(0.0), false);
+        history.record(pose(0.5), true);
+        history.record(pose(1.0), false);
+        history.record(pose(1.5), true);
+        history.record(pose(2.0), false);
+
+        assert_eq!(Some(pose(1.0)), history.undo());
+        assert_eq!(Some(pose(0.0)), history.undo());
+        assert_eq!(None, history.undo());
+    }
+
+    #[test]
+    fn history_is_bounded()
+    {
+        let mut history = PoseHistory::new(2);
+
+        for x in 0..5
+        {
+            history.record(pose(x as f32), false);
+            history.record(pose(x as f32 + 0.5), true);
+        }
+
+        assert_eq!(Some(pose(4.0)), history.undo());
+        assert_eq!(Some(pose(3.0)), history.undo());
+        assert_eq!(None, history.undo());
+    }
+}
\ No newline at end of file
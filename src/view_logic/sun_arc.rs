@@ -0,0 +1,121 @@
+use std::time::Instant;
+use nalgebra_glm::{TVec3, vec3};
+
+/// Keeps track of the sun moving along an arc around the point cloud at a fixed elevation, like the sun
+/// over a day, so that the way shadows change with the direction of the light can be studied. The arc
+/// is stepped along by key input, or swept along over time
+pub struct SunArc
+{
+    azimuth: f32,
+    elevation: f32,
+    degrees_per_second: f32,
+    active: bool,
+    sweeping: bool,
+    last_update: Instant,
+}
+
+impl SunArc
+{
+    /// Creates a new sun arc. The sun is not placed on the arc until the arc is stepped along or swept
+    ///
+    /// `elevation` - the angle of the sun above the horizon, in degrees
+    /// `degrees_per_second` - how fast the sun moves along the arc while sweeping
+    pub fn new(elevation: f32, degrees_per_second: f32) -> SunArc
+    {
+        SunArc{ azimuth: 0.0, elevation, degrees_per_second, active: false, sweeping: false, last_update: Instant::now() }
+    }
+
+    /// Moves the sun along the arc, placing it on the arc if it was not already
+    ///
+    /// `degrees` - how far to move around the arc; negative values move the other way
+    pub fn step(&mut self, degrees: f32)
+    {
+        self.active = true;
+        self.azimuth = (self.azimuth + degrees).rem_euclid(360.0);
+    }
+
+    /// Starts or stops sweeping the sun along the arc. Starting places the sun on the arc
+    pub fn toggle_sweep(&mut self)
+    {
+        self.sweeping = !self.sweeping;
+        self.active |= self.sweeping;
+    }
+
+    /// Takes the sun off the arc, such as when it is placed somewhere else
+    pub fn stop(&mut self)
+    {
+        self.active = false;
+        self.sweeping = false;
+    }
+
+    /// Check if the sun is placed on the arc
+    pub fn is_active(&self) -> bool
+    {
+        self.active
+    }
+
+    /// Moves the sun along the arc by the time since the last call if it is sweeping. Returns true if
+    /// the sun was moved
+    ///
+    /// `now` - the current time
+    pub fn advance(&mut self, now: Instant) -> bool
+    {
+        let elapsed = now.saturating_duration_since(self.last_update);
+        self.last_update = now;
+
+        if self.sweeping
+        {
+            self.step(elapsed.as_secs_f32() * self.degrees_per_second);
+        }
+
+        self.sweeping
+    }
+
+    /// Get the position of the sun on the arc
+    ///
+    /// `centre` - the centre of the arc, which the sun looks at
+    /// `distance` - how far the sun is from the centre
+    pub fn position(&self, centre: TVec3<f32>, distance: f32) -> TVec3<f32>
+    {
+        let (azimuth, elevation) = (self.azimuth.to_radians(), self.elevation.to_radians());
+        centre + vec3(elevation.cos() * azimuth.cos(), elevation.sin(), elevation.cos() * azimuth.sin()) * distance
+    }
+
+    /// Get the text representation of where the sun is on the arc
+    pub fn to_string_angles(&self) -> String
+    {
+        format!("Sun azimuth: {:.0}  elevation: {:.0}{}", self.azimuth, self.elevation, if self.sweeping { " (sweeping)" } else { "" })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::{Duration, Instant};
+    use nalgebra_glm::vec3;
+    use crate::view_logic::sun_arc::SunArc;
+
+    #[test]
+    fn sun_moves_along_arc()
+    {
+        let start = Instant::now();
+        let mut sun_arc = SunArc::new(30.0, 10.0);
+        sun_arc.last_update = start;
+        assert!(!sun_arc.is_active());
+
+        // Stepping back from the start wraps around the arc
+        sun_arc.step(-90.0);
+        assert!(sun_arc.is_active());
+        let position = sun_arc.position(vec3(1.0, 0.0, 0.0), 2.0);
+        assert!(nalgebra_glm::distance(&vec3(1.0, 1.0, -3.0_f32.sqrt()), &position) < 0.0001);
+        assert_eq!("Sun azimuth: 270  elevation: 30", sun_arc.to_string_angles());
+
+        assert!(!sun_arc.advance(start + Duration::from_secs(1)));
+        sun_arc.toggle_sweep();
+        assert!(sun_arc.advance(start + Duration::from_secs(10)));
+        assert_eq!("Sun azimuth: 0  elevation: 30 (sweeping)", sun_arc.to_string_angles());
+
+        sun_arc.stop();
+        assert!(!sun_arc.is_active() && !sun_arc.advance(start + Duration::from_secs(11)));
+    }
+}
\ No newline at end of file
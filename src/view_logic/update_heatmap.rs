@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::time::Instant;
+use nalgebra_glm::TVec3;
+use crate::rendering::scene_renderer::activity_colours;
+
+/// Activity below this is forgotten, so that regions that stopped changing do not stay in the map
+const MIN_ACTIVITY: f32 = 0.01;
+
+/// Shows which regions of a live point cloud change the most by colouring points by the recent activity
+/// of the voxel they are in. A voxel is active when its number of points differs from the previous
+/// point cloud, such as when an object moves through it; the activity decays over time
+pub struct UpdateHeatmap
+{
+    enabled: bool,
+    voxel_size: f32,
+    decay_rate: f32,
+    activity: HashMap<(i32, i32, i32), f32>,
+    previous_counts: HashMap<(i32, i32, i32), u32>,
+    max_activity: f32,
+    last_update: Instant,
+}
+
+impl UpdateHeatmap
+{
+    /// Creates a new, disabled, heatmap
+    ///
+    /// `voxel_size` - the length of the sides of the voxels the points are counted in
+    /// `decay_rate` - how quickly the activity fades; the activity is multiplied by e^(-rate) every second
+    pub fn new(voxel_size: f32, decay_rate: f32) -> UpdateHeatmap
+    {
+        UpdateHeatmap
+        {
+            enabled: false,
+            voxel_size,
+            decay_rate,
+            activity: HashMap::new(),
+            previous_counts: HashMap::new(),
+            max_activity: 0.0,
+            last_update: Instant::now()
+        }
+    }
+
+    /// Switches between colouring points by the activity of their voxels and their regular colours.
+    /// The activity is only recorded while enabled, and starts over when enabled again
+    pub fn toggle(&mut self)
+    {
+        self.enabled = !self.enabled;
+        self.activity.clear();
+        self.previous_counts.clear();
+        self.max_activity = 0.0;
+        self.last_update = Instant::now();
+    }
+
+    /// Checks if points are coloured by the activity of their voxels
+    pub fn is_enabled(&self) -> bool
+    {
+        self.enabled
+    }
+
+    /// Records the changes of an updated point cloud, returning the colour of each point if enabled
+    ///
+    /// `points` - the displayed points of the updated point cloud
+    pub fn record_update(&mut self, points: &[TVec3<f32>]) -> Option<Vec<TVec3<f32>>>
+    {
+        let elapsed_seconds = self.last_update.elapsed().as_secs_f32();
+        self.last_update = Instant::now();
+        self.record_update_after(points, elapsed_seconds)
+    }
+
+    /// Get the text describing what the colours of the points mean
+    pub fn to_string_legend(&self) -> String
+    {
+        format!("Changes: 0 (blue) - {:.1} (red)", self.max_activity)
+    }
+
+    /// Records the changes of an updated point cloud received the given time after the previous one
+    ///
+    /// `points` - the displayed points of the updated point cloud
+    /// `elapsed_seconds` - the time since the previous point cloud
+    fn record_update_after(&mut self, points: &[TVec3<f32>], elapsed_seconds: f32) -> Option<Vec<TVec3<f32>>>
+    {
+        if !self.enabled
+        {
+            return None;
+        }
+
+        let voxels = points.iter().map(|x| self.voxel_of(x)).collect::<Vec<(i32, i32, i32)>>();
+
+        let mut counts = HashMap::with_capacity(self.previous_counts.len());
+        for voxel in &voxels
+        {
+            *counts.entry(*voxel).or_insert(0) += 1;
+        }
+
+        let decay = (-self.decay_rate * elapsed_seconds).exp();
+        self.activity.values_mut().for_each(|x| *x *= decay);
+
+        // The first point cloud has nothing to be compared against
+        if !self.previous_counts.is_empty()
+        {
+            let changed_voxels = counts.iter()
+                .filter(|(voxel, count)| self.previous_counts.get(voxel) != Some(count))
+                .map(|(voxel, _)| *voxel)
+                .chain(self.previous_counts.keys().filter(|x| !counts.contains_key(x)).copied())
+                .collect::<Vec<(i32, i32, i32)>>();
+
+            for voxel in changed_voxels
+            {
+                *self.activity.entry(voxel).or_insert(0.0) += 1.0;
+            }
+        }
+
+        self.activity.retain(|_, x| *x >= MIN_ACTIVITY);
+        self.previous_counts = counts;
+        self.max_activity = self.activity.values().copied().fold(0.0, f32::max);
+
+        let activities = voxels.iter().map(|x| self.activity.get(x).copied().unwrap_or(0.0)).collect::<Vec<f32>>();
+        Some(activity_colours(&activities, self.max_activity))
+    }
+
+    /// Get the voxel the given point is in
+    ///
+    /// `point` - the point to find the voxel of
+    fn voxel_of(&self, point: &TVec3<f32>) -> (i32, i32, i32)
+    {
+        ((point.x / self.voxel_size).floor() as i32, (point.y / self.voxel_size).floor() as i32, (point.z / self.voxel_size).floor() as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use nalgebra_glm::vec3;
+    use crate::rendering::scene_renderer::activity_colours;
+    use crate::view_logic::update_heatmap::UpdateHeatmap;
+
+    #[test]
+    fn changing_voxels_are_most_active()
+    {
+        let mut update_heatmap = UpdateHeatmap::new(1.0, 1.0);
+        let background = vec3(5.5, 0.5, 0.5);
+
+        assert!(update_heatmap.record_update_after(&[background, vec3(0.5, 0.5, 0.5)], 0.1).is_none());
+
+        update_heatmap.toggle();
+        update_heatmap.record_update_after(&[background, vec3(0.5, 0.5, 0.5)], 0.0);
+        update_heatmap.record_update_after(&[background, vec3(1.5, 0.5, 0.5)], 0.0);
+        let colours = update_heatmap.record_update_after(&[background, vec3(2.5, 0.5, 0.5)], 0.0).unwrap();
+
+        // The voxel the moving point passed through changed twice, as the point entered and left it
+        assert_eq!(activity_colours(&[0.0, 1.0], 2.0), colours);
+        assert!((update_heatmap.max_activity - 2.0).abs() < 1e-6);
+
+        // Without changes, the activity decays while the colours stay relative to the most active voxel
+        let colours = update_heatmap.record_update_after(&[background, vec3(2.5, 0.5, 0.5)], 1.0).unwrap();
+        let expected_colours = activity_colours(&[0.0, 1.0], 2.0);
+        assert!(colours.iter().zip(&expected_colours).all(|(x, y)| nalgebra_glm::distance(x, y) < 1e-5));
+        assert!((update_heatmap.max_activity - 2.0 / std::f32::consts::E).abs() < 1e-5);
+    }
+}
\ No newline at end of file
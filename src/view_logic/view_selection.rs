@@ -1,6 +1,7 @@
 use glfw::{Action, MouseButton};
 use nalgebra_glm::{TVec3, vec3};
 use crate::view_logic::view_transform::ViewTransformation;
+use crate::view_port_constants::{DESIGN_WINDOW_DIMENSIONS, ViewLayout};
 use crate::window::RenderWindow;
 
 /// Handles the logic of determining if a view is selected or not
@@ -36,6 +37,8 @@ impl ViewSelection
     /// Creates a new ViewSelection where all views are not selected
     pub fn new() -> ViewSelection
     {
+        let layout = ViewLayout::new(DESIGN_WINDOW_DIMENSIONS);
+
         ViewSelection
         {
             right_view: false,
@@ -44,14 +47,22 @@ impl ViewSelection
             top_view: false,
             border_colour: green_colour(),
 
-            // These values were based on trial and error; maybe something more formal could be done to get numbers,
-            // but these work
-            top_view_transformation:  ViewTransformation::new(vec3(0.675, 0.65, 0.0), vec3(0.3, 0.3, 0.0)),
-            right_view_transformation: ViewTransformation::new(vec3(0.675, 0.0, 0.0), vec3(0.3, 0.3, 0.0)),
-            shadow_map_view_transformation: ViewTransformation::new(vec3(0.675, -0.65, 0.0), vec3(0.3, 0.3, 0.0)),
+            top_view_transformation: ViewSelection::view_transformation(&layout, 0),
+            right_view_transformation: ViewSelection::view_transformation(&layout, 1),
+            shadow_map_view_transformation: ViewSelection::view_transformation(&layout, 2),
         }
     }
 
+    /// Places the views where the given layout puts them, such as after the window was resized
+    ///
+    /// `layout` - the layout of the window the views are drawn in
+    pub fn update_layout(&mut self, layout: &ViewLayout)
+    {
+        self.top_view_transformation = ViewSelection::view_transformation(layout, 0);
+        self.right_view_transformation = ViewSelection::view_transformation(layout, 1);
+        self.shadow_map_view_transformation = ViewSelection::view_transformation(layout, 2);
+    }
+
     /// Determines if any of the views are selected
     pub fn is_any_view_selected(&self) -> bool
     {
@@ -92,6 +103,30 @@ impl ViewSelection
         }
     }
 
+    /// Moves the selection to the next view, in the order of the main scene (no view selected), the
+    /// top view, the right view, the sun and where the sun is looking at. This allows the views to
+    /// be selected without a mouse
+    pub fn select_next_view(&mut self)
+    {
+        if self.top_view
+        {
+            self.change_view_selection(Some(ViewSelected::Right));
+        }
+        else if self.right_view || self.shadow_map_camera
+        {
+            // Selecting the shadow view while moving the sun moves to where the sun is looking at
+            self.change_view_selection(Some(ViewSelected::Shadow));
+        }
+        else if self.shadow_map_lookat
+        {
+            self.change_view_selection(None);
+        }
+        else
+        {
+            self.change_view_selection(Some(ViewSelected::Top));
+        }
+    }
+
     /// Applies the logic of selecting or deselecting a view
     ///
     /// `view` - the view that was clicked on, if any
@@ -176,6 +211,16 @@ impl ViewSelection
     /// Get the transformation for the top view
     pub fn get_top_view_transformation(&self) -> &ViewTransformation { &self.top_view_transformation }
 
+    /// Creates the transformation placing a view where the layout puts it
+    ///
+    /// `layout` - the layout of the window the views are drawn in
+    /// `view_index` - the position of the view from the top: the top view, the right view and the shadow map
+    fn view_transformation(layout: &ViewLayout, view_index: usize) -> ViewTransformation
+    {
+        let centre = layout.side_view_centres[view_index];
+        ViewTransformation::new(vec3(centre.x, centre.y, 0.0), vec3(layout.side_view_scale.x, layout.side_view_scale.y, 0.0))
+    }
+
     /// Get the border colour to use for the selected view
     pub fn get_border_colour(&self) -> TVec3<f32>
     {
@@ -270,4 +315,27 @@ mod tests
         check_selected_invariants(&view_selection, false, false, false, false);
         check_border_colour(green_colour(), view_selection.border_colour);
     }
+
+    #[test]
+    fn check_next_view_cycles_all_views()
+    {
+        let mut view_selection = ViewSelection::new();
+
+        view_selection.select_next_view();
+        check_selected_invariants(&view_selection, false, false, false, true);
+
+        view_selection.select_next_view();
+        check_selected_invariants(&view_selection, true, false, false, false);
+
+        view_selection.select_next_view();
+        check_selected_invariants(&view_selection, false, true, false, false);
+        check_border_colour(green_colour(), view_selection.border_colour);
+
+        view_selection.select_next_view();
+        check_selected_invariants(&view_selection, false, false, true, false);
+        check_border_colour(blue_colour(), view_selection.border_colour);
+
+        view_selection.select_next_view();
+        check_selected_invariants(&view_selection, false, false, false, false);
+    }
 }
\ No newline at end of file
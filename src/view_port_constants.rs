@@ -1,5 +1,140 @@
-// These are hard coded as the values were specifically adjusted for a specific program look
+use nalgebra_glm::{TVec2, vec2};
 
-pub const SCENE_VIEWPORT_WIDTH: f32 = 0.675;
+// The layout was adjusted for a specific program look in a 16:9 window. Other aspect ratios keep the
+// side views at the aspect ratio they have in such a window, with the rest of the width given to the scene
+
+/// The window dimensions the layout and the size of the text were designed for
+pub const DESIGN_WINDOW_DIMENSIONS: (i32, i32) = (1280, 720);
+
+/// Fraction of the window height below the scene, where the information text is written
 pub const SCENE_VIEWPORT_HEIGHT_OFFSET: f32 = 0.25;
-pub const TEXT_AREA_VIEWPORT_WIDTH: f32 = SCENE_VIEWPORT_WIDTH;
+
+/// The aspect ratio of the side views in pixels
+const SIDE_VIEW_ASPECT_RATIO: f32 = 16.0 / 9.0;
+
+/// The largest scale of a side view in Normalized Device Coordinates, in either dimension
+const MAX_SIDE_VIEW_SCALE: f32 = 0.3;
+
+/// Where the right side of the side views is, in Normalized Device Coordinates
+const SIDE_VIEW_RIGHT_EDGE: f32 = 0.975;
+
+/// The vertical gap between side views, in Normalized Device Coordinates
+const SIDE_VIEW_GAP: f32 = 0.05;
+
+/// The gap between the scene and the side views, as a fraction of the window width
+const SCENE_SIDE_VIEW_GAP: f32 = 0.0125;
+
+/// Where the scene, the side views and the information text are placed in a window
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ViewLayout
+{
+    /// Fraction of the window width taken up by the scene and the information text, starting from
+    /// the left of the window
+    pub scene_width: f32,
+    /// The centre of the top, right and shadow map side views, in Normalized Device Coordinates
+    pub side_view_centres: [TVec2<f32>; 3],
+    /// The scale of each side view, in Normalized Device Coordinates
+    pub side_view_scale: TVec2<f32>,
+}
+
+impl ViewLayout
+{
+    /// Finds the layout of a window with the given dimensions. The side views are placed in a column
+    /// on the right of the window, as large as they can be while keeping their aspect ratio
+    ///
+    /// `window_dimensions` - the dimensions of the window being rendered to
+    pub fn new(window_dimensions: (i32, i32)) -> ViewLayout
+    {
+        // A minimised window has no size
+        let aspect_ratio = if window_dimensions.0 > 0 && window_dimensions.1 > 0
+        {
+            window_dimensions.0 as f32 / window_dimensions.1 as f32
+        }
+        else
+        {
+            SIDE_VIEW_ASPECT_RATIO
+        };
+
+        // A scale in NDC covers a fraction of the window's width or height, so keeping the aspect ratio of
+        // a view means the horizontal scale times the window width is the vertical scale times the window
+        // height times the aspect ratio of a view
+        let scale_x = (MAX_SIDE_VIEW_SCALE * SIDE_VIEW_ASPECT_RATIO / aspect_ratio).min(MAX_SIDE_VIEW_SCALE);
+        let scale_y = scale_x * aspect_ratio / SIDE_VIEW_ASPECT_RATIO;
+
+        let centre_x = SIDE_VIEW_RIGHT_EDGE - scale_x;
+        let centre_spacing_y = scale_y * 2.0 + SIDE_VIEW_GAP;
+
+        ViewLayout
+        {
+            scene_width: (centre_x - scale_x + 1.0) * 0.5 - SCENE_SIDE_VIEW_GAP,
+            side_view_centres: [vec2(centre_x, centre_spacing_y), vec2(centre_x, 0.0), vec2(centre_x, -centre_spacing_y)],
+            side_view_scale: vec2(scale_x, scale_y),
+        }
+    }
+
+    /// Get how much text is scaled horizontally and vertically so that it keeps the proportions it
+    /// has in a window of the design dimensions, while fitting in the window
+    ///
+    /// `window_dimensions` - the dimensions of the window being rendered to
+    pub fn text_scale(&self, window_dimensions: (i32, i32)) -> TVec2<f32>
+    {
+        let scale = (window_dimensions.0 as f32 / DESIGN_WINDOW_DIMENSIONS.0 as f32).min(window_dimensions.1 as f32 / DESIGN_WINDOW_DIMENSIONS.1 as f32);
+
+        // Text is drawn into the viewport of the text area, which squeezes it horizontally by the width of the area
+        let design_scene_width = ViewLayout::new(DESIGN_WINDOW_DIMENSIONS).scene_width;
+        vec2(scale * design_scene_width / self.scene_width, scale)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::view_port_constants::{DESIGN_WINDOW_DIMENSIONS, ViewLayout};
+
+    fn close(a: f32, b: f32) -> bool
+    {
+        (a - b).abs() < 0.0001
+    }
+
+    #[test]
+    fn design_dimensions_keep_original_layout()
+    {
+        let layout = ViewLayout::new(DESIGN_WINDOW_DIMENSIONS);
+
+        assert!(close(0.675, layout.scene_width));
+        assert!(close(0.3, layout.side_view_scale.x) && close(0.3, layout.side_view_scale.y));
+        assert!(close(0.675, layout.side_view_centres[0].x) && close(0.65, layout.side_view_centres[0].y));
+        assert!(close(-0.65, layout.side_view_centres[2].y));
+
+        let text_scale = layout.text_scale((1920, 1080));
+        assert!(close(1.5, text_scale.x) && close(1.5, text_scale.y));
+    }
+
+    #[test]
+    fn extreme_aspect_ratios_fit_in_window()
+    {
+        for window_dimensions in &[(3840, 1080), (720, 1280), (5120, 1440), (1080, 1920)]
+        {
+            let layout = ViewLayout::new(*window_dimensions);
+            let scale = layout.side_view_scale;
+            let (width, height) = (window_dimensions.0 as f32, window_dimensions.1 as f32);
+
+            // Side views keep their aspect ratio in pixels, and stay inside the window
+            assert!(close(16.0 / 9.0, scale.x * width / (scale.y * height)));
+            assert!(layout.side_view_centres[0].y + scale.y <= 1.0 && layout.side_view_centres[2].y - scale.y >= -1.0);
+            assert!(layout.side_view_centres[0].x + scale.x <= 1.0);
+
+            // The scene ends before the side views start
+            let side_views_start = (layout.side_view_centres[0].x - scale.x + 1.0) * 0.5;
+            assert!(layout.scene_width > 0.5 && layout.scene_width < side_views_start);
+
+            // Text keeps its proportions; the text area squeezes text horizontally by its width
+            let text_scale = layout.text_scale(*window_dimensions);
+            let design_scene_width = ViewLayout::new(DESIGN_WINDOW_DIMENSIONS).scene_width;
+            assert!(close(text_scale.y * design_scene_width, text_scale.x * layout.scene_width));
+        }
+
+        // Wide windows give the extra width to the scene
+        assert!(ViewLayout::new((3840, 1080)).scene_width > ViewLayout::new(DESIGN_WINDOW_DIMENSIONS).scene_width);
+    }
+}
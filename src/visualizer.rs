@@ -0,0 +1,713 @@
+use std::fs;
+use std::time::{Duration, Instant};
+use glfw::{Action, Key, MouseButton, Modifiers, WindowEvent};
+use nalgebra_glm::TVec3;
+use crate::args_parser::Args;
+use crate::rendering::draw_functions::OutsideParam;
+use crate::rendering::gpu_profiler::ProfileSection;
+use crate::rendering::scene_renderer::{UploadInformation, default_point_colour};
+use crate::helper_logic::initialization_functions::ProgramVariables;
+use crate::helper_logic::point_cloud_analyzer::{ROBUST_PERCENTILES, STDIN_FILE};
+use crate::helper_logic::status_output::print_status;
+use crate::ipc_logic::ipc_content_logic::{IPCProcessingArgs, PointFilter, filter_points};
+use crate::ipc_logic::ipc_receiver::IPCContributor;
+use crate::ipc_logic::json_control::{ControlCommand, acknowledgement};
+use crate::view_logic::pose_history::CameraPose;
+use crate::helper_logic::main_loop_functions::*;
+
+/// The visualizer as a whole: the window, the rendering of the point cloud and the response to input.
+/// The program is a thin wrapper around this, and other programs can embed it by creating one,
+/// optionally giving it points with set_points, and calling render_frame in their own loop
+pub struct Visualizer
+{
+    // This variable is never directly passed into the other functions, as that would allow unused members
+    // to be modified. Hence verbose code below. All program variables condensed into one to reduce
+    // function length
+    program_variables: ProgramVariables,
+}
+
+impl Visualizer
+{
+    /// Creates the window and everything required to render into it
+    ///
+    /// `config` - the arguments controlling the visualizer, either parsed from the command line or
+    ///            created from Args::default
+    pub fn new(config: Args) -> Visualizer
+    {
+        Visualizer{ program_variables: ProgramVariables::new(config) }
+    }
+
+    /// Replaces the rendered point cloud with the given points. The cameras are centred on the points
+    /// the first time points are given. Cluster detection reads the point cloud from its file, so it is
+    /// not available for points given this way
+    ///
+    /// `points` - the positions of the points, which are filtered by the point filter, if any, and
+    ///            smoothed if point smoothing is enabled
+    /// `colours` - the colour of each point. Without colours the points use the default colour, or
+    ///             the colours of the frozen clusters if they are frozen. While the update heatmap is
+    ///             shown, the points are coloured by it instead
+    pub fn set_points(&mut self, points: &[TVec3<f32>], colours: Option<&[TVec3<f32>]>) -> Result<(), String>
+    {
+        let program_variables = &mut self.program_variables;
+
+        if let Some(i) = colours.filter(|x| x.len() != points.len())
+        {
+            return Err(format!("Got {} colours for {} points", i.len(), points.len()));
+        }
+
+        let (points, colours) = filter_points(points, colours.unwrap_or(&[]), program_variables.point_cloud_data.point_filter.as_deref());
+        let points = &program_variables.point_cloud_data.point_smoothing.smooth(&points);
+
+        let colours = if let Some(heatmap_colours) = program_variables.point_cloud_data.update_heatmap.record_update(points)
+        {
+            heatmap_colours
+        }
+        else if colours.is_empty()
+        {
+            program_variables.point_cloud_data.frozen_clusters.colours_for_update(points, default_point_colour())
+        }
+        else
+        {
+            colours
+        };
+
+        program_variables.render_data.buffer_groups.upload_instance_information(vec![UploadInformation
+        {
+            model_id: program_variables.render_data.cube_model_id,
+            instance_colours: Some(&colours),
+            instance_translations: Some(points),
+            instance_alphas: None,
+            instance_shapes: None,
+            instance_scales: None,
+        }])?;
+
+        program_variables.point_cloud_data.num_points_cloud = points.len();
+        program_variables.point_cloud_data.time_since_update = Instant::now();
+        program_variables.point_cloud_update.cluster_for_most_recent = false;
+        program_variables.centre_views_on_points(points);
+        Ok(())
+    }
+
+    /// Adds the given points to the rendered point cloud, keeping the points already displayed. Only the
+    /// new points are uploaded, so this is much cheaper than set_points for point clouds that only gain
+    /// points, such as those accumulated from a sensor. The new points are filtered by the point filter,
+    /// if any, but not smoothed, and the cameras are not recentred
+    ///
+    /// `points` - the positions of the points to add
+    /// `colours` - the colour of each point to add. Without colours the points use the default colour
+    pub fn append_points(&mut self, points: &[TVec3<f32>], colours: Option<&[TVec3<f32>]>) -> Result<(), String>
+    {
+        let program_variables = &mut self.program_variables;
+
+        if let Some(i) = colours.filter(|x| x.len() != points.len())
+        {
+            return Err(format!("Got {} colours for {} points", i.len(), points.len()));
+        }
+
+        let default_colours = vec![default_point_colour(); points.len()];
+        let (points, colours) = filter_points(points, colours.unwrap_or(&default_colours), program_variables.point_cloud_data.point_filter.as_deref());
+
+        let render_data = &mut program_variables.render_data;
+        render_data.buffer_groups.append_instances(render_data.cube_model_id, &points, &colours)?;
+
+        program_variables.point_cloud_data.num_points_cloud = render_data.buffer_groups.get_instance_information(render_data.cube_model_id).0.len();
+        program_variables.point_cloud_data.time_since_update = Instant::now();
+        program_variables.point_cloud_update.cluster_for_most_recent = false;
+        Ok(())
+    }
+
+    /// Sets the filter deciding which points of the point clouds received afterwards are kept, whether
+    /// given with set_points or load_file, or received through IPC or playback. The points already
+    /// displayed are not filtered. With a filter, clusters are found in the displayed points rather
+    /// than in the point cloud file, as the file holds the points that were filtered out as well.
+    /// Without a filter, which is the default, every point is kept
+    ///
+    /// `point_filter` - returns true for the points to keep, if any
+    pub fn set_point_filter(&mut self, point_filter: Option<PointFilter>)
+    {
+        if point_filter.is_some()
+        {
+            self.program_variables.point_cloud_data.cluster_information.cluster_displayed = true;
+        }
+
+        self.program_variables.point_cloud_data.point_filter = point_filter;
+    }
+
+    /// Replaces the rendered point cloud with the one in the given file, read the same way as the point
+    /// cloud files given on the command line. Unlike points given with set_points, clusters can be
+    /// found in the point cloud
+    ///
+    /// `file` - the point cloud file to read
+    pub fn load_file(&mut self, file: &String) -> Result<(), String>
+    {
+        let args = &self.program_variables.args;
+        let modified_time = || fs::metadata(file).and_then(|x| x.modified()).ok();
+
+        let modified_before_read = modified_time();
+        let file_contents = fs::read_to_string(file).map_err(|x| format!("Failed to read {}: {}", file, x))?;
+
+        // A file written to while it is read may have been read only partly
+        if modified_time() != modified_before_read
+        {
+            return Err(format!("{} changed while it was read", file));
+        }
+
+        if let Some(warning) = IPCContributor::incomplete_vertex_warning(&file_contents, args.point_columns())
+        {
+            eprintln!("{}: {}", file, warning);
+        }
+
+        let contents = IPCContributor::parse_contents(&file_contents, file.clone(), args.parse_settings())?;
+
+        // The same conventions for the lidar position as for updated point clouds
+        let (lidar_pos, points) = match contents.lidar_pos
+        {
+            Some(lidar_pos) => (Some(lidar_pos), &contents.points[..]),
+            None if args.display_lidar_pos && !contents.points.is_empty() => (Some(contents.points[0]), &contents.points[1..]),
+            None => (None, &contents.points[..])
+        };
+
+        let points = match args.point_limit()
+        {
+            Some(point_limit) => point_limit.apply(points.to_vec(), Vec::<()>::new())?.0,
+            None => points.to_vec()
+        };
+
+        self.set_points(&points, None)?;
+        self.program_variables.point_cloud_data.position = lidar_pos;
+        self.program_variables.point_cloud_update.current_content_file = file.clone();
+        Ok(())
+    }
+
+    /// Reads the point cloud file being displayed again and recentres the views on it, such as after
+    /// another program changed the file. Point clouds that are updated while running, or that were not
+    /// read from a file, cannot be reloaded
+    pub fn reload_file(&mut self) -> Result<(), String>
+    {
+        if self.program_variables.args.updating_point_cloud()
+        {
+            return Err("The point cloud is updated while running, so it cannot be reloaded".to_string());
+        }
+
+        let file = self.program_variables.point_cloud_update.current_content_file.clone();
+        if file.is_empty() || file == STDIN_FILE
+        {
+            return Err("The point cloud was not read from a file, so it cannot be reloaded".to_string());
+        }
+
+        let initial_files = &self.program_variables.args.initial_data_models;
+        if initial_files.len() > 1 && initial_files.contains(&file)
+        {
+            return Err("The point cloud was read from several files, so it cannot be reloaded".to_string());
+        }
+
+        self.load_file(&file)?;
+
+        let program_variables = &mut self.program_variables;
+        let points = program_variables.render_data.buffer_groups.get_instance_information(program_variables.render_data.cube_model_id).0.to_vec();
+        program_variables.recentre_views_on_points(&points);
+        Ok(())
+    }
+
+    /// Responds to the input since the previous frame, updates the point cloud and renders it.
+    /// Returns false once the window has been requested to close, without rendering
+    pub fn render_frame(&mut self) -> bool
+    {
+        if self.program_variables.render_data.render_window.should_close()
+        {
+            return false;
+        }
+
+        self.program_variables.render_data.render_window.poll_events();
+        let delta_time = self.program_variables.render_data.update_frame_time();
+        update_adaptive_quality(&mut self.program_variables.render_data, delta_time);
+
+        self.respond_to_input(delta_time);
+        self.apply_control_commands();
+        let upload_start = Instant::now();
+        self.update_point_cloud();
+        if let Some(gpu_profiler) = &mut self.program_variables.render_data.gpu_profiler
+        {
+            gpu_profiler.record_cpu_time(ProfileSection::Upload, upload_start.elapsed());
+        }
+
+        self.render();
+
+        if let Some(gpu_profiler) = &mut self.program_variables.render_data.gpu_profiler
+        {
+            gpu_profiler.end_frame();
+        }
+
+        self.program_variables.render_data.render_window.swap_buffers();
+        true
+    }
+
+    /// Presses or releases a key as if it happened in the window. Handled in the next frame
+    ///
+    /// `key` - the key that was pressed or released
+    /// `action` - what happened to the key
+    pub fn inject_key(&mut self, key: Key, action: Action)
+    {
+        self.program_variables.render_data.render_window.inject_event(WindowEvent::Key(key, 0, action, Modifiers::empty()));
+    }
+
+    /// Moves the cursor as if it moved in the window. Handled in the next frame
+    ///
+    /// `position` - the position of the cursor in pixels, from the top left of the window
+    pub fn inject_cursor_pos(&mut self, position: (f64, f64))
+    {
+        self.program_variables.render_data.render_window.inject_event(WindowEvent::CursorPos(position.0, position.1));
+    }
+
+    /// Presses or releases a mouse button as if it happened in the window. Handled in the next frame
+    ///
+    /// `button` - the mouse button that was pressed or released
+    /// `action` - what happened to the button
+    pub fn inject_mouse_button(&mut self, button: MouseButton, action: Action)
+    {
+        self.program_variables.render_data.render_window.inject_event(WindowEvent::MouseButton(button, action, Modifiers::empty()));
+    }
+
+    /// Scrolls as if the mouse wheel was moved in the window. Handled in the next frame
+    ///
+    /// `amount` - how far was scrolled vertically
+    pub fn inject_scroll(&mut self, amount: f32)
+    {
+        self.program_variables.render_data.render_window.inject_event(WindowEvent::Scroll(0.0, amount as f64));
+    }
+
+    /// Stops the threads that update the point cloud. The window is closed once the visualizer is dropped
+    pub fn close(mut self)
+    {
+        self.program_variables.point_cloud_update.notify_cluster_thread_to_quit();
+
+        // It should not take longer than twice the sleep duration for the cluster detection thread to notice
+        // it is requested to quit. If it does take longer, it probably was not responsive anyways
+        std::thread::sleep(Duration::from_millis(self.program_variables.args.sleep_duration_ms * 2));
+    }
+
+    /// Responds to the key and mouse input of the current frame
+    ///
+    /// `delta_time` - the seconds since the previous frame
+    fn respond_to_input(&mut self, delta_time: f32)
+    {
+        let program_variables = &mut self.program_variables;
+
+        check_window_close(&mut program_variables.render_data.render_window, program_variables.args.exit_key);
+
+        update_view_layout(&mut program_variables.render_data);
+
+        check_pause_updates(&mut program_variables.point_cloud_data.pause_updating, &program_variables.render_data.render_window);
+
+        check_freeze_clusters(&mut program_variables.point_cloud_data.frozen_clusters, &program_variables.render_data.render_window);
+
+        check_toggle_point_smoothing(&mut program_variables.point_cloud_data.point_smoothing, &program_variables.render_data.render_window);
+        check_toggle_update_heatmap(&mut program_variables.point_cloud_data.update_heatmap, &program_variables.render_data.render_window);
+
+        check_active_camera(&mut program_variables.render_data);
+
+        update_orbit_pivot(&mut program_variables.render_data, program_variables.point_analyzer.get_centre());
+
+        update_box_selection(&mut program_variables.render_data);
+
+        update_annotations(&mut program_variables.render_data);
+
+        check_side_views_framing(&mut program_variables.render_data, program_variables.point_analyzer.get_centre(),
+                                 program_variables.point_analyzer.get_max_length());
+
+        check_toggle_view_projection(&mut program_variables.render_data, program_variables.point_analyzer.get_centre());
+
+        update_auto_rotate(&mut program_variables.render_data, program_variables.point_analyzer.get_centre());
+
+        snap_sun_position(&mut program_variables.render_data, program_variables.point_analyzer.get_centre());
+
+        update_sun_arc(&mut program_variables.render_data, program_variables.point_analyzer.get_centre());
+
+        let active_camera = select_active_camera(&mut program_variables.render_data.camera, &mut program_variables.render_data.second_camera,
+                                                 program_variables.render_data.second_camera_active);
+
+        check_for_view_selection(&mut program_variables.render_data.view_selection, &mut program_variables.render_data.view_fbos,
+                                 active_camera, &program_variables.render_data.render_window);
+
+        // Only movements of the main camera are remembered
+        let pose_history = if program_variables.render_data.second_camera_active { None } else { Some(&mut program_variables.render_data.pose_history) };
+
+        update_camera_movement(&mut program_variables.render_data.view_selection, &mut program_variables.render_data.view_fbos,
+                               active_camera, pose_history, &program_variables.render_data.render_window, delta_time);
+
+        // After the camera is moved by the user, so that the path is followed regardless
+        let camera_path_fps = program_variables.args.camera_path_fps;
+        update_camera_path(&mut program_variables.render_data, program_variables.args.camera_path_record_prefix.as_ref().map(|_| camera_path_fps), delta_time);
+
+        update_cluster_information(&mut program_variables.point_cloud_data.cluster_information,
+                                   &mut program_variables.point_cloud_update.cluster_for_most_recent,
+                                   &mut program_variables.point_cloud_data.cluster_parameter_note, &program_variables.render_data.render_window);
+
+        check_suggest_epsilon(&program_variables.render_data, &mut program_variables.point_cloud_data.cluster_information,
+                              &mut program_variables.point_cloud_data.epsilon_suggestion,
+                              &mut program_variables.point_cloud_update.cluster_for_most_recent);
+
+        change_point_cloud_position(&mut program_variables.render_data);
+
+        reflect_point_cloud(&mut program_variables.render_data);
+
+        add_lidar_pos(&mut program_variables.render_data);
+
+        toggle_grid_labels(&mut program_variables.render_data);
+
+        toggle_help(&mut program_variables.render_data);
+
+        toggle_overlay(&mut program_variables.render_data);
+
+        toggle_shadows(&mut program_variables.render_data);
+
+        toggle_model_visibility(&mut program_variables.render_data);
+
+        toggle_x_ray(&mut program_variables.render_data);
+
+        toggle_flat_shading(&mut program_variables.render_data);
+
+        toggle_anaglyph(&mut program_variables.render_data);
+
+        update_near_fade(&mut program_variables.render_data);
+        update_ground_removal(&mut program_variables.render_data);
+
+        update_shadow_offset(&mut program_variables.render_data);
+
+        toggle_invert_look_y(&mut program_variables.render_data);
+
+        toggle_spacing_histogram(&mut program_variables.render_data);
+
+        cycle_grid_colours(&mut program_variables.render_data);
+
+        update_grid_height(&mut program_variables.render_data);
+
+        update_playback(&mut program_variables.point_cloud_update.playback, &program_variables.render_data.render_window);
+
+        check_session_keys(program_variables);
+
+        check_export_point_cloud(&program_variables.render_data, &program_variables.args.export_file, program_variables.args.units_scale, program_variables.args.up_axis);
+
+        check_export_frustum_point_cloud(&program_variables.render_data, &program_variables.args.export_file, program_variables.args.units_scale, program_variables.args.up_axis);
+
+        check_print_cluster_summary(&program_variables.point_cloud_data.last_cluster_summary, &program_variables.render_data.render_window);
+
+        check_export_cluster_assignments(&program_variables.point_cloud_data.last_cluster_assignments, &program_variables.args.cluster_export_file,
+                                         program_variables.args.units_scale, program_variables.args.up_axis, &program_variables.render_data.render_window);
+
+        check_cluster_program_retry(&mut program_variables.point_cloud_data.cluster_program_disabled,
+                                    &mut program_variables.point_cloud_data.cluster_result_text,
+                                    &mut program_variables.point_cloud_data.cluster_result_time, &program_variables.render_data.render_window);
+
+        check_clear_cluster_result(&mut program_variables.point_cloud_data.cluster_result_text,
+                                   &mut program_variables.point_cloud_data.cluster_result_time,
+                                   program_variables.point_cloud_data.cluster_program_disabled,
+                                   program_variables.args.cluster_status_timeout_s, &program_variables.render_data.render_window);
+
+        self.check_reload_file();
+        self.check_toggle_robust_bounds();
+    }
+
+    /// Reloads the point cloud file when Control + R is pressed, noting the result in the overlay
+    fn check_reload_file(&mut self)
+    {
+        let render_window = &self.program_variables.render_data.render_window;
+
+        if !render_window.is_control_down() || render_window.get_key_input().iter().find(|x| **x == (Key::R, Action::Press)).is_none()
+        {
+            return;
+        }
+
+        let note = match self.reload_file()
+        {
+            Ok(_) => format!("Reloaded {}", self.program_variables.point_cloud_update.current_content_file),
+            Err(err) => format!("Failed to reload: {}", err)
+        };
+
+        self.program_variables.point_cloud_data.reload_note = Some((note, Instant::now()));
+    }
+
+    /// Switches between centring the views on all of the points and ignoring the outliers when the grave
+    /// accent key is pressed, printing both bounds of the point cloud
+    fn check_toggle_robust_bounds(&mut self)
+    {
+        let program_variables = &mut self.program_variables;
+
+        if program_variables.render_data.render_window.get_key_input().iter().find(|x| **x == (Key::GraveAccent, Action::Press)).is_none()
+        {
+            return;
+        }
+
+        let use_robust_bounds = program_variables.toggle_robust_bounds();
+        let absolute_bounds = program_variables.point_analyzer.get_absolute_bounds();
+        let robust_bounds = program_variables.point_analyzer.get_robust_bounds();
+
+        print_status(format!("Views centred on {} points", if use_robust_bounds { "the non-outlier" } else { "all of the" }));
+        print_status(format!("All points: centre ({:.3}, {:.3}, {:.3}), max length {:.3}", absolute_bounds.centre.x, absolute_bounds.centre.y, absolute_bounds.centre.z, absolute_bounds.max_length));
+        print_status(format!("Between percentiles {} and {}: centre ({:.3}, {:.3}, {:.3}), max length {:.3}", ROBUST_PERCENTILES.0, ROBUST_PERCENTILES.1,
+                             robust_bounds.centre.x, robust_bounds.centre.y, robust_bounds.centre.z, robust_bounds.max_length));
+    }
+
+    /// Updates the point cloud with any new point cloud received, and finds the clusters of the point
+    /// cloud when requested
+    fn update_point_cloud(&mut self)
+    {
+        let program_variables = &mut self.program_variables;
+
+        // ********** Update Clusters on Static Point Cloud **********
+
+        if !program_variables.point_cloud_update.cluster_for_most_recent && program_variables.point_cloud_data.pause_updating
+            && program_variables.render_data.render_window.get_key_input().iter().find(|x| **x == (Key::C, Action::Press)).is_some()
+        {
+            Visualizer::find_clusters(program_variables);
+            program_variables.point_cloud_update.cluster_for_most_recent = true;
+        }
+
+        // ********** Update Point Cloud and Clusters **********
+
+        if program_variables.args.updating_point_cloud() && !program_variables.point_cloud_data.pause_updating
+        {
+            program_variables.point_cloud_update.cluster_for_most_recent = false;
+
+            let ipc_processing_arg = IPCProcessingArgs
+            {
+                receiver: &program_variables.point_cloud_update.receiver,
+                buffer_group: &mut program_variables.render_data.buffer_groups,
+                point_model_id: program_variables.render_data.cube_model_id,
+                cluster_information: &program_variables.point_cloud_data.cluster_information,
+                frozen_clusters: &mut program_variables.point_cloud_data.frozen_clusters,
+                point_smoothing: &mut program_variables.point_cloud_data.point_smoothing,
+                update_heatmap: &mut program_variables.point_cloud_data.update_heatmap,
+                point_filter: program_variables.point_cloud_data.point_filter.as_deref(),
+                display_lidar_pos: program_variables.args.display_lidar_pos,
+                velocity_colour_mode: program_variables.args.velocity_colours,
+                point_limit: program_variables.args.point_limit(),
+                point_scale_range: program_variables.args.point_scale_range()
+            };
+
+            let ipc_update_args = HandleIPCUpdate
+            {
+                ipc_args: ipc_processing_arg,
+                lidar_pos: &mut program_variables.point_cloud_data.position,
+                velocity_legend: &mut program_variables.point_cloud_data.velocity_legend,
+                num_cloud_points: &mut program_variables.point_cloud_data.num_points_cloud,
+                time_since_update: &mut program_variables.point_cloud_data.time_since_update,
+                cluster_result_text: &mut program_variables.point_cloud_data.cluster_result_text,
+                cluster_result_time: &mut program_variables.point_cloud_data.cluster_result_time,
+                current_content_file: &mut program_variables.point_cloud_update.current_content_file
+            };
+
+            update_point_cloud(ipc_update_args);
+            program_variables.centre_views(program_variables.args.display_lidar_pos);
+        }
+        else if program_variables.args.updating_point_cloud() && program_variables.point_cloud_data.pause_updating
+            && program_variables.render_data.render_window.get_key_input().contains(&(Key::C, Action::Press))
+        {
+            Visualizer::find_clusters(program_variables);
+        }
+    }
+
+    /// Finds the clusters of the displayed point cloud, returning if clusters were found
+    ///
+    /// `program_variables` - the variables of the visualizer
+    fn find_clusters(program_variables: &mut ProgramVariables) -> bool
+    {
+        // The ground is only left out of the clusters while it is hidden
+        let ground_threshold = Some(program_variables.render_data.ground_threshold).filter(|_| program_variables.render_data.ground_removal);
+
+        let cluster_update_args = HandleClusterUpdate
+        {
+            buffer_groups: &mut program_variables.render_data.buffer_groups,
+            buffer_update_content: &program_variables.point_cloud_data.cluster_information,
+            cube_model_id: program_variables.render_data.cube_model_id,
+            cluster_result_text: &mut program_variables.point_cloud_data.cluster_result_text,
+            cluster_result_time: &mut program_variables.point_cloud_data.cluster_result_time,
+            current_content_file: &mut program_variables.point_cloud_update.current_content_file,
+            cluster_program_disabled: &mut program_variables.point_cloud_data.cluster_program_disabled,
+            frozen_clusters: &mut program_variables.point_cloud_data.frozen_clusters,
+            last_cluster_summary: &mut program_variables.point_cloud_data.last_cluster_summary,
+            last_cluster_assignments: &mut program_variables.point_cloud_data.last_cluster_assignments,
+            ground_threshold
+        };
+
+        update_point_cloud_clusters(cluster_update_args)
+    }
+
+    /// Applies the commands received over stdin since the previous frame, writing an acknowledgement
+    /// of each to stdout
+    fn apply_control_commands(&mut self)
+    {
+        let commands = match &self.program_variables.point_cloud_update.control_receiver
+        {
+            Some(receiver) => receiver.try_iter().collect::<Vec<_>>(),
+            None => return
+        };
+
+        for command in commands
+        {
+            let (command_name, result) = match command
+            {
+                Ok(command) => (Some(command.get_name()), self.apply_control_command(command)),
+                Err(err) => (None, Err(err))
+            };
+
+            println!("{}", acknowledgement(command_name, &result));
+        }
+    }
+
+    /// Applies a command received over stdin
+    ///
+    /// `command` - the command to apply
+    fn apply_control_command(&mut self, command: ControlCommand) -> Result<(), String>
+    {
+        let program_variables = &mut self.program_variables;
+
+        match command
+        {
+            ControlCommand::SetCamera{ position, direction } =>
+                {
+                    program_variables.render_data.camera.set_pose(CameraPose{ position, direction });
+                    Ok(())
+                },
+            ControlCommand::SetEpsilon{ epsilon } =>
+                {
+                    let cluster_information = &mut program_variables.point_cloud_data.cluster_information;
+                    if epsilon < cluster_information.min_epsilon || epsilon > cluster_information.max_epsilon
+                    {
+                        return Err(format!("Epsilon {} is outside of the range {} - {}", epsilon, cluster_information.min_epsilon, cluster_information.max_epsilon));
+                    }
+
+                    cluster_information.epsilon = epsilon;
+                    program_variables.point_cloud_update.cluster_for_most_recent = false;
+                    Ok(())
+                },
+            ControlCommand::FindClusters =>
+                {
+                    if Visualizer::find_clusters(program_variables)
+                    {
+                        program_variables.point_cloud_update.cluster_for_most_recent = true;
+                        Ok(())
+                    }
+                    else
+                    {
+                        Err(program_variables.point_cloud_data.cluster_result_text.clone())
+                    }
+                },
+            ControlCommand::LoadFile{ file } => self.load_file(&file)
+        }
+    }
+
+    /// Renders the scene, the side views and the information text
+    fn render(&mut self)
+    {
+        let program_variables = &mut self.program_variables;
+
+        // ********** Render Scene + Views **********
+
+        let ground_threshold = Some(program_variables.render_data.ground_threshold).filter(|_| program_variables.render_data.ground_removal);
+
+        let outside_param = OutsideParam
+        {
+            view_selection: &program_variables.render_data.view_selection,
+            view_fbos: &program_variables.render_data.view_fbos,
+            camera: &program_variables.render_data.camera,
+            second_camera: program_variables.render_data.second_camera.as_ref(),
+            window_resolution: program_variables.render_data.render_window.get_window_dimensions(),
+            scene_matrix: &program_variables.render_data.translation_matrix,
+            cloud_translation: program_variables.render_data.cloud_translation,
+            reflect_vertical: program_variables.render_data.get_reflect_vertically(),
+            // The bounding sphere is only calculated for the initial point cloud, so it does not apply
+            // to point clouds that are updated
+            cloud_bounding_sphere: if program_variables.args.sphere_culling && !program_variables.args.updating_point_cloud()
+            {
+                Some(program_variables.point_analyzer.get_bounding_sphere())
+            }
+            else
+            {
+                None
+            },
+            cloud_extent: (program_variables.point_analyzer.get_centre(), program_variables.point_analyzer.get_max_length()),
+            grid_height: program_variables.render_data.buffer_groups.get_grid_height(),
+            shadows_enabled: program_variables.render_data.shadows_enabled,
+            alpha_to_coverage: program_variables.args.alpha_to_coverage,
+            log_depth: program_variables.args.log_depth,
+            x_ray: program_variables.render_data.x_ray,
+            flat_shading: program_variables.render_data.flat_shading,
+            transparent_background: program_variables.args.transparent_background,
+            near_fade: program_variables.render_data.near_fade,
+            point_stride: program_variables.render_data.adaptive_quality.as_ref().map_or(1, |x| x.get_point_stride()),
+            ground_threshold,
+            show_sun_frustum: program_variables.render_data.show_sun_frustum,
+            shadow_offset: program_variables.render_data.shadow_offset,
+            grid_depth_offset: program_variables.args.grid_depth_offset,
+            dither: program_variables.args.dither,
+            anaglyph_eye_separation: Some(program_variables.args.eye_separation).filter(|_| program_variables.render_data.anaglyph),
+            gpu_profiler: program_variables.render_data.gpu_profiler.as_ref(),
+            selection_rect: selection_rect_ndc(&program_variables.render_data)
+        };
+        program_variables.render_data.buffer_groups.render(outside_param);
+
+        check_capture_point_positions(&mut program_variables.render_data.buffer_groups, program_variables.render_data.cube_model_id,
+                                      &program_variables.render_data.render_window, &outside_param, &program_variables.args.view_export_prefix,
+                                      program_variables.args.units_scale, program_variables.args.up_axis);
+
+        // ********** Render Information Text **********
+
+        // Closures inside the text parameters cannot read the program variables they borrow from
+        let launch_shadow_offset = program_variables.args.shadow_offset;
+        let updating_point_cloud = program_variables.args.updating_point_cloud();
+        let num_selected_points = program_variables.render_data.box_selection.num_selected();
+
+        let text_param = TextWriteParam
+        {
+            text_renderer: &mut program_variables.render_data.text_renderer,
+            view_fbos: &program_variables.render_data.view_fbos,
+            camera: &mut program_variables.render_data.camera,
+            second_camera: program_variables.render_data.second_camera.as_ref(),
+            time_update: &program_variables.point_cloud_data.time_since_update,
+            render_window: &program_variables.render_data.render_window,
+            num_points: program_variables.point_cloud_data.num_points_cloud,
+            content_file: &program_variables.point_cloud_update.current_content_file,
+            cluster_result_text: &program_variables.point_cloud_data.cluster_result_text,
+            epsilon: program_variables.point_cloud_data.cluster_information.epsilon,
+            cluster_parameter_note: &program_variables.point_cloud_data.cluster_parameter_note,
+            reload_note: &program_variables.point_cloud_data.reload_note,
+            min_num_points: program_variables.point_cloud_data.cluster_information.min_num_points,
+            lidar_pos: program_variables.point_cloud_data.position,
+            add_lidar_pos: program_variables.render_data.add_lidar_pos,
+            clusters_frozen: program_variables.point_cloud_data.frozen_clusters.is_frozen(),
+            points_smoothed: program_variables.point_cloud_data.point_smoothing.is_enabled(),
+            updates_paused: Some(program_variables.point_cloud_data.pause_updating).filter(|_| updating_point_cloud),
+            show_grid_labels: program_variables.render_data.show_grid_labels,
+            help_page: program_variables.render_data.help_page,
+            overlay_layout: &program_variables.render_data.overlay_layout,
+            units_scale: program_variables.args.units_scale,
+            cloud_warning: program_variables.point_analyzer.get_warning(),
+            playback_position: program_variables.point_cloud_update.playback.as_ref().map(|x| x.to_string_position()),
+            right_view_out_of_frame: program_variables.render_data.right_view_out_of_frame,
+            top_view_out_of_frame: program_variables.render_data.top_view_out_of_frame,
+            sun_look_at_selected: program_variables.render_data.view_selection.get_shadow_lookat_view_selected(),
+            spacing_histogram: program_variables.render_data.spacing_histogram.as_ref(),
+            epsilon_suggestion: program_variables.point_cloud_data.epsilon_suggestion,
+            velocity_legend: program_variables.point_cloud_data.velocity_legend.as_deref(),
+            near_fade: program_variables.render_data.near_fade,
+            quality_text: program_variables.render_data.adaptive_quality.as_ref().map(|x| x.to_string_quality()),
+            grid_height: program_variables.render_data.buffer_groups.get_grid_height(),
+            ground_threshold,
+            shadow_offset: Some(program_variables.render_data.shadow_offset).filter(|x| *x != launch_shadow_offset),
+            gpu_profiler: program_variables.render_data.gpu_profiler.as_ref(),
+            camera_path_progress: program_variables.render_data.camera_path.as_ref().map(|x| x.to_string_progress()),
+            selected_points: Some(program_variables.render_data.box_selection.to_string_selected())
+                .filter(|_| num_selected_points > 0),
+            annotations: &program_variables.render_data.annotations
+        };
+        write_scene_info(text_param);
+
+        check_export_views(&program_variables.render_data, &program_variables.args.view_export_prefix);
+
+        if let Some(file_prefix) = &program_variables.args.camera_path_record_prefix
+        {
+            record_camera_path_frame(&program_variables.render_data, file_prefix, program_variables.args.camera_path_fps);
+        }
+    }
+}
\ No newline at end of file
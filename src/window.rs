@@ -2,7 +2,9 @@ use std::ffi::{c_void, CStr};
 use std::process::exit;
 use std::ptr::null;
 use std::sync::mpsc::Receiver;
-use glfw::{Action, Context, Glfw, Key, MouseButton, Window, WindowEvent, WindowHint};
+use glfw::{Action, Context, Glfw, Key, MouseButton, PixelImage, Window, WindowEvent, WindowHint};
+use stb_image::image::LoadResult;
+use crate::helper_logic::status_output::print_status;
 
 /// Abstraction of the window that is rendered to
 pub struct RenderWindow
@@ -15,6 +17,7 @@ pub struct RenderWindow
     scroll_history: Vec<f32>,
     cursor_button_history: Vec<(MouseButton, Action)>,
     latest_cursor_pos: (i32, i32),
+    injected_events: Vec<WindowEvent>,
 }
 
 impl RenderWindow
@@ -23,10 +26,14 @@ impl RenderWindow
     ///
     /// `window_size` - the dimensions of the window (width, height)
     /// `window_title` - the name of the window
+    /// `window_icon` - the PNG file to use as the icon of the window, instead of the default icon
     /// `window_hints`- additional information about how the window should behave or initialize the
     ///                 OpenGL context. If the window hints contain a DebugContext request, then the
     ///                 context will be in debug mode and all warnings printed to the console
-    pub fn new(window_size: (u32, u32), window_title: String, window_hints: Vec<WindowHint>) -> RenderWindow
+    /// `context_versions` - the OpenGL versions to request, in order of preference. The first version
+    ///                      the driver can create a context for is used
+    pub fn new(window_size: (u32, u32), window_title: String, window_icon: Option<&String>, window_hints: Vec<WindowHint>,
+               context_versions: &[(u32, u32)]) -> RenderWindow
     {
         let debug_mode =
             {
@@ -52,13 +59,19 @@ impl RenderWindow
             glfw.window_hint(x);
         }
 
-        let (mut window, events) = match glfw.create_window
-        (
-            window_size.0,
-            window_size.1,
-            window_title.as_str(),
-            glfw::WindowMode::Windowed
-        )
+        let created_window = context_versions.iter().find_map(|(major, minor)|
+            {
+                glfw.window_hint(glfw::WindowHint::ContextVersion(*major, *minor));
+                glfw.create_window
+                (
+                    window_size.0,
+                    window_size.1,
+                    window_title.as_str(),
+                    glfw::WindowMode::Windowed
+                )
+            });
+
+        let (mut window, events) = match created_window
         {
             Some(i) => i,
             None =>
@@ -68,6 +81,19 @@ impl RenderWindow
                 }
         };
 
+        if let Some(icon_file) = window_icon
+        {
+            match RenderWindow::load_icon(icon_file)
+            {
+                Ok(i) => window.set_icon_from_pixels(vec![i]),
+                Err(err) =>
+                    {
+                        eprintln!("Failed to use the window icon {}: {}", icon_file, err);
+                        exit(-1);
+                    }
+            }
+        }
+
         window.set_key_polling(true);
         window.set_cursor_pos_polling(true);
         window.set_mouse_button_polling(true);
@@ -81,6 +107,8 @@ impl RenderWindow
                 gl::Viewport(0, 0, window_size.0 as i32, window_size.1 as i32);
             }
 
+        RenderWindow::log_gl_info();
+
         if debug_mode
         {
             RenderWindow::setup_debug_context();
@@ -89,7 +117,7 @@ impl RenderWindow
         RenderWindow
         {
             glfw, window, events, key_input: Vec::new(), cursor_pos_history: Vec::new(),
-            cursor_button_history: Vec::new(), latest_cursor_pos: (0, 0), scroll_history: Vec::new()
+            cursor_button_history: Vec::new(), latest_cursor_pos: (0, 0), scroll_history: Vec::new(), injected_events: Vec::new()
         }
     }
 
@@ -129,6 +157,22 @@ impl RenderWindow
         self.window.get_size()
     }
 
+    /// Reads back what has been rendered to the window so far this frame as RGBA bytes, with the rows
+    /// ordered from the bottom of the window to the top
+    pub fn read_pixels(&self) -> ((i32, i32), Vec<u8>)
+    {
+        let dimensions = self.window.get_framebuffer_size();
+        let mut pixels = vec![0_u8; (dimensions.0 * dimensions.1 * 4) as usize];
+
+        unsafe
+            {
+                gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+                gl::ReadPixels(0, 0, dimensions.0, dimensions.1, gl::RGBA, gl::UNSIGNED_BYTE, pixels.as_mut_ptr() as *mut c_void);
+            }
+
+        (dimensions, pixels)
+    }
+
     /// Get all of the history of scrolling for the current frame
     pub fn get_scroll_history(&self) -> &Vec<f32>
     {
@@ -141,12 +185,33 @@ impl RenderWindow
         self.window.set_should_close(close);
     }
 
+    /// Query if either control key is currently held down
+    pub fn is_control_down(&self) -> bool
+    {
+        self.window.get_key(Key::LeftControl) == Action::Press || self.window.get_key(Key::RightControl) == Action::Press
+    }
+
+    /// Query if either shift key is currently held down
+    pub fn is_shift_down(&self) -> bool
+    {
+        self.window.get_key(Key::LeftShift) == Action::Press || self.window.get_key(Key::RightShift) == Action::Press
+    }
+
     /// Get the latest cursor position
     pub fn get_latest_cursor_pos(&self) -> (i32, i32)
     {
         self.latest_cursor_pos
     }
 
+    /// Adds an event that did not come from the window, such as input from a program embedding the
+    /// visualizer. It is handled along with the window's events the next time they are polled
+    ///
+    /// `event` - the event to add
+    pub fn inject_event(&mut self, event: WindowEvent)
+    {
+        self.injected_events.push(event);
+    }
+
     /// Find all events that have occurred for the current frame
     pub fn poll_events(&mut self)
     {
@@ -156,7 +221,12 @@ impl RenderWindow
         self.scroll_history.clear();
         self.cursor_button_history.clear();
 
-        for (_, event) in glfw::flush_messages(&self.events)
+        let events = glfw::flush_messages(&self.events)
+            .map(|(_, event)| event)
+            .chain(std::mem::take(&mut self.injected_events))
+            .collect::<Vec<WindowEvent>>();
+
+        for event in events
         {
             match event
             {
@@ -168,7 +238,7 @@ impl RenderWindow
                     {
                         unsafe
                             {
-                                println!("Resized to: {}, {}", width, height);
+                                print_status(format!("Resized to: {}, {}", width, height));
                                 gl::Viewport(0, 0, width, height);
                             }
                     },
@@ -190,6 +260,93 @@ impl RenderWindow
         }
     }
 
+    /// Loads an image file as a window icon. Icons are given to GLFW as rows of RGBA pixels from
+    /// the top of the image down
+    ///
+    /// `icon_file` - the image file to load
+    fn load_icon(icon_file: &String) -> Result<PixelImage, String>
+    {
+        // The text renderer flips the images it loads, which would make the icon upside down
+        unsafe{ stb_image::stb_image::bindgen::stbi_set_flip_vertically_on_load(0); }
+
+        let image = match stb_image::image::load_with_depth(icon_file, 4, false)
+        {
+            LoadResult::Error(err) => return Err(err),
+            LoadResult::ImageU8(i) => i,
+            LoadResult::ImageF32(_) => return Err("High dynamic range images are not supported".to_string())
+        };
+
+        let pixels = image.data.chunks(4).map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]])).collect();
+        Ok(PixelImage{ width: image.width as u32, height: image.height as u32, pixels })
+    }
+
+    /// Prints the OpenGL vendor, renderer and version, along with the amount of video memory if
+    /// the driver exposes it. Useful to include in bug reports of driver specific issues
+    fn log_gl_info()
+    {
+        // Values of the memory extensions, which are not part of the core OpenGL bindings
+        const GPU_MEMORY_INFO_DEDICATED_VIDMEM_NVX: u32 = 0x9047;
+        const GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX: u32 = 0x9049;
+        const TEXTURE_FREE_MEMORY_ATI: u32 = 0x87FC;
+
+        let get_string = |name: gl::types::GLenum|
+            {
+                let value = unsafe{ gl::GetString(name) };
+                if value.is_null()
+                {
+                    "Unknown".to_string()
+                }
+                else
+                {
+                    unsafe{ CStr::from_ptr(value as *const _) }.to_string_lossy().into_owned()
+                }
+            };
+
+        print_status(format!("OpenGL vendor: {}", get_string(gl::VENDOR)));
+        print_status(format!("OpenGL renderer: {}", get_string(gl::RENDERER)));
+        print_status(format!("OpenGL version: {}", get_string(gl::VERSION)));
+
+        let mut num_extensions = 0;
+        unsafe{ gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut num_extensions); }
+
+        let extensions = (0..num_extensions).filter_map(|x|
+            {
+                let extension = unsafe{ gl::GetStringi(gl::EXTENSIONS, x as u32) };
+                if extension.is_null()
+                {
+                    return None;
+                }
+
+                Some(unsafe{ CStr::from_ptr(extension as *const _) }.to_string_lossy().into_owned())
+            }).collect::<Vec<String>>();
+
+        // Both extensions report the memory in kilobytes
+        if extensions.iter().any(|x| x == "GL_NVX_gpu_memory_info")
+        {
+            let mut total_memory = 0;
+            let mut available_memory = 0;
+            unsafe
+                {
+                    gl::GetIntegerv(GPU_MEMORY_INFO_DEDICATED_VIDMEM_NVX, &mut total_memory);
+                    gl::GetIntegerv(GPU_MEMORY_INFO_CURRENT_AVAILABLE_VIDMEM_NVX, &mut available_memory);
+                }
+
+            print_status(format!("Video memory: {} MB available of {} MB", available_memory / 1024, total_memory / 1024));
+        }
+        else if extensions.iter().any(|x| x == "GL_ATI_meminfo")
+        {
+            // The first of the four values is the total free memory in the texture pool
+            let mut free_memory = [0; 4];
+            unsafe{ gl::GetIntegerv(TEXTURE_FREE_MEMORY_ATI, free_memory.as_mut_ptr()); }
+
+            print_status(format!("Video memory: {} MB available", free_memory[0] / 1024));
+        }
+        else
+        {
+            print_status("Video memory: not reported by the driver");
+        }
+    }
+
     /// Configures the OpenGL context for debugging
     fn setup_debug_context()
     {
@@ -222,5 +379,5 @@ extern "system" fn gl_debug_output(_: gl::types::GLenum,
                                    _user_param: *mut c_void)
 {
     let message = unsafe { CStr::from_ptr(message).to_str().unwrap() };
-    println!("{}", message);
+    print_status(message);
 }
\ No newline at end of file